@@ -12,6 +12,7 @@ use reqwest::Request;
 use tailcall::core::config::Batch;
 use tailcall::core::http::{DataLoaderRequest, HttpDataLoader, Response};
 use tailcall::core::ir::model::IoId;
+use tailcall::core::rate_limit::InMemoryRateLimiter;
 use tailcall::core::runtime::TargetRuntime;
 use tailcall::core::{cache, EnvIO, FileIO, HttpIO};
 
@@ -67,6 +68,23 @@ impl tailcall::core::Cache for Cache {
         unimplemented!("Not needed for this bench")
     }
 
+    async fn tag<'a>(
+        &'a self,
+        _: &'a Self::Key,
+        _: &'a [String],
+        _: NonZeroU64,
+    ) -> Result<(), cache::Error> {
+        unimplemented!("Not needed for this bench")
+    }
+
+    async fn delete<'a>(&'a self, _: &'a Self::Key) -> Result<bool, cache::Error> {
+        unimplemented!("Not needed for this bench")
+    }
+
+    async fn purge<'a>(&'a self, _: &'a [String]) -> Result<usize, cache::Error> {
+        unimplemented!("Not needed for this bench")
+    }
+
     fn hit_rate(&self) -> Option<f64> {
         unimplemented!("Not needed for this bench")
     }
@@ -84,6 +102,7 @@ pub fn benchmark_data_loader(c: &mut Criterion) {
                     env: Arc::new(Env {}),
                     file: Arc::new(File {}),
                     cache: Arc::new(Cache {}),
+                    rate_limiter: Arc::new(InMemoryRateLimiter::default()),
                     extensions: Arc::new(vec![]),
                     cmd_worker: None,
                     worker: None,
@@ -1,15 +1,16 @@
 use criterion::Criterion;
 use hyper::Method;
 use tailcall::cli::runtime::NativeHttp;
-use tailcall::core::blueprint::Blueprint;
+use tailcall::core::blueprint::{Blueprint, HttpCache};
 use tailcall::core::HttpIO;
 
 pub fn benchmark_http_execute_method(c: &mut Criterion) {
     let tokio_runtime = tokio::runtime::Runtime::new().unwrap();
 
     let mut blueprint = Blueprint::default();
-    blueprint.upstream.http_cache = 42; // allow http caching for bench test.
-    let native_http = NativeHttp::init(&blueprint.upstream, &blueprint.telemetry);
+    // allow http caching for bench test.
+    blueprint.upstream.http_cache = Some(HttpCache::InMemory { capacity: 42 });
+    let native_http = NativeHttp::init(&blueprint.upstream, &blueprint.telemetry, None).unwrap();
     let request_url = String::from("http://jsonplaceholder.typicode.com/users");
 
     tokio_runtime.block_on(async {
@@ -14,11 +14,12 @@ use indexmap::IndexMap;
 use once_cell::sync::Lazy;
 use reqwest::{Client, Request};
 use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
-use tailcall::core::blueprint::{Server, Upstream};
+use tailcall::core::blueprint::{self, Server, Upstream};
 use tailcall::core::cache::InMemoryCache;
 use tailcall::core::http::{RequestContext, Response};
 use tailcall::core::ir::{EvalContext, ResolverContextLike, SelectionField};
 use tailcall::core::path::PathString;
+use tailcall::core::rate_limit::InMemoryRateLimiter;
 use tailcall::core::runtime::TargetRuntime;
 use tailcall::core::{EnvIO, FileIO, HttpIO};
 use tailcall_http_cache::HttpCacheManager;
@@ -56,10 +57,10 @@ impl Http {
 
         let mut client = ClientBuilder::new(builder.build().expect("Failed to build client"));
 
-        if upstream.http_cache > 0 {
+        if let Some(blueprint::HttpCache::InMemory { capacity }) = upstream.http_cache {
             client = client.with(Cache(HttpCache {
                 mode: CacheMode::Default,
-                manager: HttpCacheManager::new(upstream.http_cache),
+                manager: HttpCacheManager::new(capacity),
                 options: HttpCacheOptions::default(),
             }))
         }
@@ -248,6 +249,7 @@ fn request_context() -> RequestContext {
         env: Arc::new(Env {}),
         file: Arc::new(File {}),
         cache: Arc::new(InMemoryCache::new()),
+        rate_limiter: Arc::new(InMemoryRateLimiter::default()),
         extensions: Arc::new(vec![]),
         cmd_worker: None,
         worker: None,
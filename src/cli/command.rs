@@ -26,6 +26,10 @@ pub enum Command {
         /// separated by spaces if more than one
         #[arg(required = true)]
         file_paths: Vec<String>,
+
+        /// Reloads the config when one of its local files changes
+        #[arg(short, long)]
+        watch: bool,
     },
 
     /// Validate a composition spec
@@ -61,4 +65,17 @@ pub enum Command {
         #[arg(required = true)]
         file_path: String,
     },
+
+    /// Compares the schemas of two configs and reports the breaking changes
+    Diff {
+        /// Path of the old configuration file
+        old: String,
+
+        /// Path of the new configuration file
+        new: String,
+
+        /// Prints the changes as JSON
+        #[arg(long)]
+        json: bool,
+    },
 }
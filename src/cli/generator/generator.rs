@@ -33,8 +33,9 @@ impl Generator {
     async fn write(self, graphql_config: &ConfigModule, output_path: &str) -> anyhow::Result<()> {
         let output_source = config::Source::detect(output_path)?;
         let config = match output_source {
-            config::Source::Json => graphql_config.to_json(true)?,
+            config::Source::Json | config::Source::Json5 => graphql_config.to_json(true)?,
             config::Source::Yml => graphql_config.to_yaml()?,
+            config::Source::Toml => graphql_config.to_toml()?,
             config::Source::GraphQL => graphql_config.to_sdl(),
         };
 
@@ -322,7 +323,7 @@ mod test {
         }
 
         async fn run_test(path: &str) -> anyhow::Result<()> {
-            let mut runtime = crate::cli::runtime::init(&Blueprint::default());
+            let mut runtime = crate::cli::runtime::init(&Blueprint::default())?;
             runtime.http = Arc::new(NativeHttpTest::default());
 
             let generator = Generator::new(path, runtime);
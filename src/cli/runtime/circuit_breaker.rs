@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use once_cell::sync::Lazy;
+use opentelemetry::metrics::UpDownCounter;
+use opentelemetry::KeyValue;
+use opentelemetry_semantic_conventions::trace::SERVER_ADDRESS;
+
+use crate::core::blueprint::CircuitBreaker;
+
+static HTTP_CLIENT_OPEN_CIRCUITS: Lazy<UpDownCounter<i64>> = Lazy::new(|| {
+    let meter = opentelemetry::global::meter("http_request");
+
+    meter
+        .i64_up_down_counter("http.client.circuit_breaker.open")
+        .with_description("Number of open circuits of the upstream hosts")
+        .init()
+});
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    Closed {
+        failures: usize,
+    },
+    Open {
+        since: Instant,
+    },
+    /// A single request is let through to probe the host.
+    HalfOpen {
+        since: Instant,
+    },
+}
+
+/// Tracks the failures of the requests to every upstream host, and stops
+/// sending requests to a host after too many consecutive failures.
+pub struct CircuitBreakers {
+    config: CircuitBreaker,
+    enable_telemetry: bool,
+    hosts: Mutex<HashMap<String, State>>,
+}
+
+impl CircuitBreakers {
+    pub fn new(config: CircuitBreaker, enable_telemetry: bool) -> Self {
+        Self { config, enable_telemetry, hosts: Mutex::new(HashMap::new()) }
+    }
+
+    /// Checks if a request can be sent to the host. Once the circuit has been
+    /// open for the reset timeout, a single request is let through to probe
+    /// the host.
+    pub fn acquire(&self, host: &str) -> bool {
+        let mut hosts = self.hosts.lock().unwrap();
+        let Some(state) = hosts.get_mut(host) else {
+            return true;
+        };
+
+        match *state {
+            State::Closed { .. } => true,
+            State::Open { since } | State::HalfOpen { since }
+                if since.elapsed() >= self.config.reset_timeout =>
+            {
+                *state = State::HalfOpen { since: Instant::now() };
+                true
+            }
+            State::Open { .. } | State::HalfOpen { .. } => false,
+        }
+    }
+
+    /// Records the outcome of a request to the host.
+    pub fn record(&self, host: &str, success: bool) {
+        let mut hosts = self.hosts.lock().unwrap();
+        let state = hosts
+            .entry(host.to_string())
+            .or_insert(State::Closed { failures: 0 });
+        let was_open = !matches!(state, State::Closed { .. });
+
+        *state = match (*state, success) {
+            (_, true) => State::Closed { failures: 0 },
+            (State::Closed { failures }, false) if failures + 1 < self.config.failure_threshold => {
+                State::Closed { failures: failures + 1 }
+            }
+            (_, false) => State::Open { since: Instant::now() },
+        };
+
+        let is_open = matches!(state, State::Open { .. });
+        if was_open != is_open {
+            if is_open {
+                tracing::warn!("Circuit breaker opened for the upstream {}", host);
+            } else {
+                tracing::info!("Circuit breaker closed for the upstream {}", host);
+            }
+
+            if self.enable_telemetry {
+                let change = if is_open { 1 } else { -1 };
+                let attributes = [KeyValue::new(SERVER_ADDRESS, host.to_string())];
+                HTTP_CLIENT_OPEN_CIRCUITS.add(change, &attributes);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn circuit_breakers(reset_timeout: Duration) -> CircuitBreakers {
+        CircuitBreakers::new(
+            CircuitBreaker { failure_threshold: 2, reset_timeout },
+            false,
+        )
+    }
+
+    #[test]
+    fn test_open_after_failures() {
+        let breakers = circuit_breakers(Duration::from_secs(60));
+
+        breakers.record("a", false);
+        assert!(breakers.acquire("a"));
+        breakers.record("a", false);
+        assert!(!breakers.acquire("a"));
+        assert!(breakers.acquire("b"));
+    }
+
+    #[test]
+    fn test_success_resets_failures() {
+        let breakers = circuit_breakers(Duration::from_secs(60));
+
+        breakers.record("a", false);
+        breakers.record("a", true);
+        breakers.record("a", false);
+        assert!(breakers.acquire("a"));
+    }
+
+    #[test]
+    fn test_half_open() {
+        let breakers = circuit_breakers(Duration::ZERO);
+
+        breakers.record("a", false);
+        breakers.record("a", false);
+
+        // the probe fails and the circuit opens again
+        assert!(breakers.acquire("a"));
+        breakers.record("a", false);
+        assert!(breakers.acquire("a"));
+        breakers.record("a", true);
+        assert!(breakers.acquire("a"));
+    }
+}
@@ -1,8 +1,13 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+use futures_util::StreamExt;
 use http_cache_reqwest::{Cache, CacheMode, HttpCache, HttpCacheOptions};
-use hyper::body::Bytes;
+use hyper::body::{Bytes, HttpBody};
+use hyper::client::HttpConnector;
+use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
 use once_cell::sync::Lazy;
 use opentelemetry::metrics::Counter;
 use opentelemetry::trace::SpanKind;
@@ -11,15 +16,19 @@ use opentelemetry_http::HeaderInjector;
 use opentelemetry_semantic_conventions::trace::{
     HTTP_REQUEST_METHOD, HTTP_RESPONSE_STATUS_CODE, NETWORK_PROTOCOL_VERSION, URL_FULL,
 };
-use reqwest::Client;
+use reqwest::{Client, StatusCode};
 use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
-use tailcall_http_cache::HttpCacheManager;
+use tailcall_http_cache::{DiskCacheManager, HttpCacheManager};
+use tokio_rustls::rustls::{ClientConfig, RootCertStore};
 use tracing_opentelemetry::OpenTelemetrySpanExt;
+use url::Url;
 
+use super::circuit_breaker::CircuitBreakers;
 use super::HttpIO;
 use crate::core::blueprint::telemetry::Telemetry;
-use crate::core::blueprint::Upstream;
+use crate::core::blueprint::{self, Upstream, UpstreamTls};
 use crate::core::http::Response;
+use crate::core::{BodyStream, Frame};
 
 static HTTP_CLIENT_REQUEST_COUNT: Lazy<Counter<u64>> = Lazy::new(|| {
     let meter = opentelemetry::global::meter("http_request");
@@ -50,45 +59,72 @@ impl RequestCounter {
         Self { attributes: Some(attributes) }
     }
 
-    fn update(&mut self, response: &reqwest_middleware::Result<reqwest::Response>) {
+    fn update(&mut self, status: Option<StatusCode>) {
         if let Some(ref mut attributes) = self.attributes {
-            attributes.push(get_response_status(response));
+            attributes.push(get_response_status(status));
 
             HTTP_CLIENT_REQUEST_COUNT.add(1, attributes);
         }
     }
 }
 
-fn get_response_status(response: &reqwest_middleware::Result<reqwest::Response>) -> KeyValue {
-    let status_code = match response {
-        Ok(resp) => resp.status().as_u16(),
-        Err(err) => err.status().map(|code| code.as_u16()).unwrap_or(0),
-    };
+fn get_response_status(status: Option<StatusCode>) -> KeyValue {
+    let status_code = status.map(|code| code.as_u16()).unwrap_or(0);
     KeyValue::new(HTTP_RESPONSE_STATUS_CODE, status_code as i64)
 }
 
+/// Client of the server-streaming gRPC methods, since reqwest doesn't expose
+/// the trailers of the responses, that carry the status of the streams. The
+/// unary calls are sent by the reqwest client with its middlewares, so only
+/// they are cached and retried, like for the other upstreams.
+type GrpcClient = hyper::Client<HttpsConnector<HttpConnector>>;
+
+/// Clients of the upstreams under `base_url`, that have their own TLS
+/// certificates.
+#[derive(Clone)]
+struct TlsClients {
+    base_url: Url,
+    client: ClientWithMiddleware,
+    stream_client: Client,
+    grpc_client: Option<GrpcClient>,
+}
+
 #[derive(Clone)]
 pub struct NativeHttp {
     client: ClientWithMiddleware,
+    // Streamed responses can't go through the http cache middleware
+    // since it buffers the whole body.
+    stream_client: Client,
+    // Only set for the runtime of the gRPC upstreams.
+    grpc_client: Option<GrpcClient>,
+    tls_clients: Vec<TlsClients>,
     http2_only: bool,
     enable_telemetry: bool,
+    circuit_breakers: Option<Arc<CircuitBreakers>>,
 }
 
 impl Default for NativeHttp {
     fn default() -> Self {
+        let client = Client::new();
         Self {
-            client: ClientBuilder::new(Client::new()).build(),
+            client: ClientBuilder::new(client.clone()).build(),
+            stream_client: client,
+            grpc_client: None,
+            tls_clients: Vec::new(),
             http2_only: false,
             enable_telemetry: false,
+            circuit_breakers: None,
         }
     }
 }
 
 impl NativeHttp {
-    pub fn init(upstream: &Upstream, telemetry: &Telemetry) -> Self {
+    fn client_builder(
+        upstream: &Upstream,
+        tls: Option<&UpstreamTls>,
+    ) -> Result<reqwest::ClientBuilder> {
         let mut builder = Client::builder()
             .tcp_keepalive(Some(Duration::from_secs(upstream.tcp_keep_alive)))
-            .timeout(Duration::from_secs(upstream.timeout))
             .connect_timeout(Duration::from_secs(upstream.connect_timeout))
             .http2_keep_alive_interval(Some(Duration::from_secs(upstream.keep_alive_interval)))
             .http2_keep_alive_timeout(Duration::from_secs(upstream.keep_alive_timeout))
@@ -110,44 +146,164 @@ impl NativeHttp {
             );
         }
 
-        let mut client = ClientBuilder::new(builder.build().expect("Failed to build client"));
+        if let Some(tls) = tls {
+            if let Some(ref identity) = tls.identity {
+                builder = builder.identity(reqwest::Identity::from_pem(identity.as_bytes())?);
+            }
+
+            if let Some(ref ca) = tls.ca {
+                for certificate in reqwest::Certificate::from_pem_bundle(ca.as_bytes())? {
+                    builder = builder.add_root_certificate(certificate);
+                }
+            }
+        }
+
+        Ok(builder)
+    }
+
+    fn clients(
+        upstream: &Upstream,
+        tls: Option<&UpstreamTls>,
+        disk_cache: Option<&DiskCacheManager>,
+    ) -> Result<(ClientWithMiddleware, Client)> {
+        let builder =
+            Self::client_builder(upstream, tls)?.timeout(Duration::from_secs(upstream.timeout));
+
+        let mut client = ClientBuilder::new(builder.build()?);
 
-        if upstream.http_cache > 0 {
+        if let Some(manager) = disk_cache {
+            client = client.with(Cache(HttpCache {
+                mode: CacheMode::Default,
+                manager: manager.clone(),
+                options: HttpCacheOptions::default(),
+            }))
+        } else if let Some(blueprint::HttpCache::InMemory { capacity }) = upstream.http_cache {
             client = client.with(Cache(HttpCache {
                 mode: CacheMode::Default,
-                manager: HttpCacheManager::new(upstream.http_cache),
+                manager: HttpCacheManager::new(capacity),
                 options: HttpCacheOptions::default(),
             }))
         }
-        Self {
-            client: client.build(),
-            http2_only: upstream.http2_only,
-            enable_telemetry: telemetry.export.is_some(),
+
+        // Streams are long-lived, so the overall request timeout is not applied
+        let stream_client = Self::client_builder(upstream, tls)?.build()?;
+
+        Ok((client.build(), stream_client))
+    }
+
+    fn grpc_client(upstream: &Upstream, tls: Option<&UpstreamTls>) -> Result<GrpcClient> {
+        let mut roots = RootCertStore::empty();
+        roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+        for certificate in tls
+            .map(UpstreamTls::parse_ca)
+            .transpose()?
+            .unwrap_or_default()
+        {
+            roots.add(certificate)?;
         }
+
+        let builder = ClientConfig::builder().with_root_certificates(roots);
+        let identity = tls.map(UpstreamTls::parse_identity).transpose()?.flatten();
+        let tls_config = match identity {
+            Some((certificates, key)) => builder.with_client_auth_cert(certificates, key)?,
+            None => builder.with_no_client_auth(),
+        };
+
+        let mut connector = HttpConnector::new();
+        connector.enforce_http(false);
+        connector.set_keepalive(Some(Duration::from_secs(upstream.tcp_keep_alive)));
+        connector.set_connect_timeout(Some(Duration::from_secs(upstream.connect_timeout)));
+        let connector = HttpsConnectorBuilder::new()
+            .with_tls_config(tls_config)
+            .https_or_http()
+            .enable_http2()
+            .wrap_connector(connector);
+
+        Ok(hyper::Client::builder()
+            .http2_only(true)
+            .http2_keep_alive_interval(Some(Duration::from_secs(upstream.keep_alive_interval)))
+            .http2_keep_alive_timeout(Duration::from_secs(upstream.keep_alive_timeout))
+            .http2_keep_alive_while_idle(upstream.keep_alive_while_idle)
+            .pool_idle_timeout(Some(Duration::from_secs(upstream.pool_idle_timeout)))
+            .pool_max_idle_per_host(upstream.pool_max_idle_per_host)
+            .build(connector))
     }
-}
 
-#[async_trait::async_trait]
-impl HttpIO for NativeHttp {
-    #[allow(clippy::blocks_in_conditions)]
-    // because of the issue with tracing and clippy - https://github.com/rust-lang/rust-clippy/issues/12281
-    #[tracing::instrument(
-        skip_all,
-        err,
-        fields(
-            otel.name = "upstream_request",
-            otel.kind = ?SpanKind::Client,
-            url.full = %request.url(),
-            http.request.method = %request.method(),
-            network.protocol.version = ?request.version()
-        )
-    )]
-    async fn execute(&self, mut request: reqwest::Request) -> Result<Response<Bytes>> {
+    fn tls_client(
+        upstream: &Upstream,
+        tls: &UpstreamTls,
+        disk_cache: Option<&DiskCacheManager>,
+    ) -> Result<TlsClients> {
+        let (client, stream_client) = Self::clients(upstream, Some(tls), disk_cache)?;
+        let grpc_client = upstream
+            .http2_only
+            .then(|| Self::grpc_client(upstream, Some(tls)))
+            .transpose()?;
+
+        Ok(TlsClients {
+            base_url: tls.base_url.parse()?,
+            client,
+            stream_client,
+            grpc_client,
+        })
+    }
+
+    /// Creates the clients of the upstream, that share the index of the
+    /// responses cached on the disk with the other clients of the runtime.
+    pub fn init(
+        upstream: &Upstream,
+        telemetry: &Telemetry,
+        disk_cache: Option<&DiskCacheManager>,
+    ) -> Result<Self> {
+        let (client, stream_client) = Self::clients(upstream, None, disk_cache)?;
+        let grpc_client = upstream
+            .http2_only
+            .then(|| Self::grpc_client(upstream, None))
+            .transpose()?;
+
+        let mut tls_clients = upstream
+            .tls
+            .iter()
+            .map(|tls| {
+                Self::tls_client(upstream, tls, disk_cache)
+                    .with_context(|| format!("Failed to set the TLS of {}", tls.base_url))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        // the most specific base URL is matched first
+        tls_clients.sort_by(|a, b| b.base_url.path().len().cmp(&a.base_url.path().len()));
+
+        let enable_telemetry = telemetry.export.is_some();
+        let circuit_breakers = upstream
+            .circuit_breaker
+            .clone()
+            .map(|config| Arc::new(CircuitBreakers::new(config, enable_telemetry)));
+
+        Ok(Self {
+            client,
+            stream_client,
+            grpc_client,
+            tls_clients,
+            http2_only: upstream.http2_only,
+            enable_telemetry,
+            circuit_breakers,
+        })
+    }
+
+    /// Checks the circuit breaker of the upstream and propagates the trace of
+    /// the request, returns the host of the upstream.
+    fn before_send(&self, request: &mut reqwest::Request) -> Result<(String, RequestCounter)> {
         if self.http2_only {
             *request.version_mut() = reqwest::Version::HTTP_2;
         }
 
-        let mut req_counter = RequestCounter::new(self.enable_telemetry, &request);
+        let host = request.url().authority().to_string();
+        if let Some(ref circuit_breakers) = self.circuit_breakers {
+            if !circuit_breakers.acquire(&host) {
+                anyhow::bail!("Circuit breaker is open for the upstream {}", host);
+            }
+        }
+
+        let req_counter = RequestCounter::new(self.enable_telemetry, request);
 
         if self.enable_telemetry {
             opentelemetry::global::get_text_map_propagator(|propagator| {
@@ -165,22 +321,215 @@ impl HttpIO for NativeHttp {
             request.version()
         );
         tracing::debug!("request: {:?}", request);
-        let response = self.client.execute(request).await;
-        tracing::debug!("response: {:?}", response);
 
-        req_counter.update(&response);
+        Ok((host, req_counter))
+    }
+
+    /// Records the status of the response, that is `None` when the request
+    /// failed without one.
+    fn after_send(&self, host: &str, mut req_counter: RequestCounter, status: Option<StatusCode>) {
+        req_counter.update(status);
+
+        if let Some(ref circuit_breakers) = self.circuit_breakers {
+            let success = status.is_some_and(|status| !status.is_server_error());
+            circuit_breakers.record(host, success);
+        }
 
         if self.enable_telemetry {
-            let status_code = get_response_status(&response);
+            let status_code = get_response_status(status);
             tracing::Span::current().set_attribute(status_code.key, status_code.value);
         }
+    }
+
+    fn tls_clients(&self, request: &reqwest::Request) -> Option<&TlsClients> {
+        self.tls_clients
+            .iter()
+            .find(|tls| is_under(request.url(), &tls.base_url))
+    }
+
+    async fn send(&self, mut request: reqwest::Request, stream: bool) -> Result<reqwest::Response> {
+        let (host, req_counter) = self.before_send(&mut request)?;
+
+        let (client, stream_client) = self
+            .tls_clients(&request)
+            .map_or((&self.client, &self.stream_client), |tls| {
+                (&tls.client, &tls.stream_client)
+            });
+        let response = if stream {
+            stream_client
+                .execute(request)
+                .await
+                .map_err(reqwest_middleware::Error::from)
+        } else {
+            client.execute(request).await
+        };
+        tracing::debug!("response: {:?}", response);
+
+        let status = match response {
+            Ok(ref response) => Some(response.status()),
+            Err(ref err) => err.status(),
+        };
+        self.after_send(&host, req_counter, status);
+
+        Ok(response?
+            .error_for_status()
+            .map_err(|err| err.without_url())?)
+    }
+
+    async fn send_grpc_stream(
+        &self,
+        client: &GrpcClient,
+        mut request: reqwest::Request,
+    ) -> Result<Response<BodyStream>> {
+        let (host, req_counter) = self.before_send(&mut request)?;
+
+        let response = client.request(to_hyper_request(request)?).await;
+        tracing::debug!("response: {:?}", response);
+
+        self.after_send(
+            &host,
+            req_counter,
+            response.as_ref().ok().map(|response| response.status()),
+        );
+
+        let (parts, body) = response?.into_parts();
+        if parts.status.is_client_error() || parts.status.is_server_error() {
+            anyhow::bail!("HTTP status {} received from the upstream", parts.status);
+        }
+
+        Ok(Response {
+            status: parts.status,
+            headers: parts.headers,
+            body: frames(body),
+        })
+    }
+}
+
+/// The indexes of the responses cached on the disk by directory, along with
+/// their maximum size, that are shared by the runtimes of the reloaded configs.
+static DISK_CACHES: Lazy<Mutex<HashMap<String, (u64, DiskCacheManager)>>> =
+    Lazy::new(Default::default);
+
+/// Returns the index of the responses cached on the disk, when the upstream
+/// caches them on the disk. A single index is kept for each directory, unless
+/// its maximum size changes.
+pub fn init_disk_cache(upstream: &Upstream) -> Option<DiskCacheManager> {
+    let Some(blueprint::HttpCache::Disk { ref path, max_size }) = upstream.http_cache else {
+        return None;
+    };
+
+    let mut disk_caches = DISK_CACHES.lock().unwrap();
+    match disk_caches.get(path) {
+        Some((size, manager)) if *size == max_size => Some(manager.clone()),
+        _ => {
+            let manager = DiskCacheManager::new(path, max_size);
+            disk_caches.insert(path.clone(), (max_size, manager.clone()));
+            Some(manager)
+        }
+    }
+}
+
+/// Checks if the URL has the origin of the base URL, and a path starting with
+/// all the segments of its path.
+fn is_under(url: &Url, base_url: &Url) -> bool {
+    if url.scheme() != base_url.scheme()
+        || url.host_str() != base_url.host_str()
+        || url.port_or_known_default() != base_url.port_or_known_default()
+    {
+        return false;
+    }
+
+    let base_path = base_url.path().trim_end_matches('/');
+    url.path()
+        .strip_prefix(base_path)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+fn to_hyper_request(request: reqwest::Request) -> Result<hyper::Request<hyper::Body>> {
+    let body = request
+        .body()
+        .and_then(|body| body.as_bytes())
+        .map(Bytes::copy_from_slice)
+        .unwrap_or_default();
+
+    let mut hyper_request = hyper::Request::new(hyper::Body::from(body));
+    *hyper_request.method_mut() = request.method().clone();
+    *hyper_request.uri_mut() = request.url().as_str().parse()?;
+    *hyper_request.version_mut() = request.version();
+    *hyper_request.headers_mut() = request.headers().clone();
+
+    Ok(hyper_request)
+}
+
+/// Returns the data of the body as it's received followed by its trailers.
+fn frames(body: hyper::Body) -> BodyStream {
+    futures_util::stream::try_unfold(Some(body), |body| async move {
+        let Some(mut body) = body else {
+            return Ok(None);
+        };
+
+        match body.data().await {
+            Some(data) => Ok(Some((Frame::Data(data?), Some(body)))),
+            None => Ok(body
+                .trailers()
+                .await?
+                .map(|trailers| (Frame::Trailers(trailers), None))),
+        }
+    })
+    .boxed()
+}
 
-        Ok(Response::from_reqwest(
-            response?
-                .error_for_status()
-                .map_err(|err| err.without_url())?,
+#[async_trait::async_trait]
+impl HttpIO for NativeHttp {
+    #[allow(clippy::blocks_in_conditions)]
+    // because of the issue with tracing and clippy - https://github.com/rust-lang/rust-clippy/issues/12281
+    #[tracing::instrument(
+        skip_all,
+        err,
+        fields(
+            otel.name = "upstream_request",
+            otel.kind = ?SpanKind::Client,
+            url.full = %request.url(),
+            http.request.method = %request.method(),
+            network.protocol.version = ?request.version()
         )
-        .await?)
+    )]
+    async fn execute(&self, request: reqwest::Request) -> Result<Response<Bytes>> {
+        Ok(Response::from_reqwest(self.send(request, false).await?).await?)
+    }
+
+    #[allow(clippy::blocks_in_conditions)]
+    #[tracing::instrument(
+        skip_all,
+        err,
+        fields(
+            otel.name = "upstream_stream",
+            otel.kind = ?SpanKind::Client,
+            url.full = %request.url(),
+            http.request.method = %request.method(),
+            network.protocol.version = ?request.version()
+        )
+    )]
+    async fn execute_stream(&self, request: reqwest::Request) -> Result<Response<BodyStream>> {
+        let grpc_client = self
+            .tls_clients(&request)
+            .map_or(self.grpc_client.as_ref(), |tls| tls.grpc_client.as_ref());
+        if let Some(grpc_client) = grpc_client {
+            return self.send_grpc_stream(grpc_client, request).await;
+        }
+
+        let response = self.send(request, true).await?;
+        let status = response.status();
+        let headers = response.headers().to_owned();
+        let body = futures_util::stream::try_unfold(response, |mut response| async move {
+            Ok(response
+                .chunk()
+                .await?
+                .map(|chunk| (Frame::Data(chunk), response)))
+        })
+        .boxed();
+
+        Ok(Response { status, headers, body })
     }
 }
 
@@ -190,6 +539,7 @@ mod tests {
     use tokio;
 
     use super::*;
+    use crate::core::blueprint::CircuitBreaker;
     use crate::core::http::Response;
 
     fn start_mock_server() -> httpmock::MockServer {
@@ -211,7 +561,7 @@ mod tests {
             then.status(200).body("Hello");
         });
 
-        let native_http = NativeHttp::init(&Default::default(), &Default::default());
+        let native_http = NativeHttp::init(&Default::default(), &Default::default(), None).unwrap();
         let port = server.port();
         // Build a GET request to the mock server
         let request_url = format!("http://localhost:{}/test", port);
@@ -252,8 +602,11 @@ mod tests {
             then.status(200).body("Hello");
         });
 
-        let upstream = Upstream { http_cache: 2, ..Default::default() };
-        let native_http = NativeHttp::init(&upstream, &Default::default());
+        let upstream = Upstream {
+            http_cache: Some(blueprint::HttpCache::InMemory { capacity: 2 }),
+            ..Default::default()
+        };
+        let native_http = NativeHttp::init(&upstream, &Default::default(), None).unwrap();
         let port = server.port();
 
         let url1 = format!("http://localhost:{}/test-1", port);
@@ -281,4 +634,124 @@ mod tests {
         let resp = make_request(&url1, &native_http).await;
         assert_eq!(resp.headers.get("x-cache-lookup").unwrap(), "MISS");
     }
+
+    #[tokio::test]
+    async fn test_native_http_get_request_with_disk_cache() {
+        let server = start_mock_server();
+
+        let mock = server.mock(|when, then| {
+            when.method(httpmock::Method::GET).path("/test");
+            then.status(200).body("Hello");
+        });
+
+        let dir = tempfile::tempdir().unwrap();
+        let upstream = Upstream {
+            http_cache: Some(blueprint::HttpCache::Disk {
+                path: dir.path().to_string_lossy().to_string(),
+                max_size: 1024 * 1024,
+            }),
+            ..Default::default()
+        };
+        let url = format!("http://localhost:{}/test", server.port());
+
+        let disk_cache = init_disk_cache(&upstream);
+        let native_http =
+            NativeHttp::init(&upstream, &Default::default(), disk_cache.as_ref()).unwrap();
+        let resp = make_request(&url, &native_http).await;
+        assert_eq!(resp.headers.get("x-cache-lookup").unwrap(), "MISS");
+
+        // the clients of the runtimes share the index
+        let disk_cache = init_disk_cache(&upstream);
+        let native_http =
+            NativeHttp::init(&upstream, &Default::default(), disk_cache.as_ref()).unwrap();
+        let resp = make_request(&url, &native_http).await;
+        assert_eq!(resp.headers.get("x-cache-lookup").unwrap(), "HIT");
+
+        // the responses are still cached after a restart
+        let disk_cache = DiskCacheManager::new(dir.path(), 1024 * 1024);
+        let native_http =
+            NativeHttp::init(&upstream, &Default::default(), Some(&disk_cache)).unwrap();
+        let resp = make_request(&url, &native_http).await;
+        assert_eq!(resp.headers.get("x-cache-lookup").unwrap(), "HIT");
+        assert_eq!(resp.body, Bytes::from("Hello"));
+
+        mock.assert_hits(1);
+    }
+
+    #[tokio::test]
+    async fn test_native_http_circuit_breaker() {
+        let server = start_mock_server();
+
+        let mock = server.mock(|when, then| {
+            when.method(httpmock::Method::GET).path("/test");
+            then.status(503);
+        });
+
+        let circuit_breaker =
+            CircuitBreaker { failure_threshold: 2, reset_timeout: Duration::from_secs(60) };
+        let upstream = Upstream { circuit_breaker: Some(circuit_breaker), ..Default::default() };
+        let native_http = NativeHttp::init(&upstream, &Default::default(), None).unwrap();
+        let request_url = format!("http://localhost:{}/test", server.port());
+
+        for _ in 0..3 {
+            let request = reqwest::Request::new(Method::GET, request_url.parse().unwrap());
+            assert!(native_http.execute(request).await.is_err());
+        }
+
+        let request = reqwest::Request::new(Method::GET, request_url.parse().unwrap());
+        let error = native_http.execute(request).await.unwrap_err();
+        assert!(error.to_string().starts_with("Circuit breaker is open"));
+
+        mock.assert_hits(2);
+    }
+
+    #[test]
+    fn test_is_under() {
+        let base_url = Url::parse("https://payments.example/api").unwrap();
+        let is_under = |url: &str| is_under(&Url::parse(url).unwrap(), &base_url);
+
+        assert!(is_under("https://payments.example/api"));
+        assert!(is_under("https://payments.example:443/api/charges?id=1"));
+        assert!(!is_under("https://payments.example/apiv2"));
+        assert!(!is_under("https://payments.example.attacker.com/api"));
+        assert!(!is_under("https://payments.example:8443/api"));
+        assert!(!is_under("http://payments.example/api"));
+    }
+
+    #[test]
+    fn test_native_http_tls_clients() {
+        let tls = UpstreamTls {
+            base_url: "https://payments.example/".to_string(),
+            identity: Some(format!(
+                "{}\n{}",
+                include_str!("../../../tests/server/config/client.crt"),
+                include_str!("../../../tests/server/config/client.key")
+            )),
+            ca: Some(include_str!("../../../tests/server/config/client-ca.crt").to_string()),
+        };
+        let upstream = Upstream { tls: vec![tls], ..Default::default() };
+        let native_http = NativeHttp::init(&upstream, &Default::default(), None).unwrap();
+
+        let request = reqwest::Request::new(
+            Method::GET,
+            "https://payments.example/charges".parse().unwrap(),
+        );
+        assert!(native_http.tls_clients(&request).is_some());
+
+        let request = reqwest::Request::new(Method::GET, "https://other.example/".parse().unwrap());
+        assert!(native_http.tls_clients(&request).is_none());
+    }
+
+    #[test]
+    fn test_native_http_invalid_tls() {
+        let tls = UpstreamTls {
+            base_url: "https://payments.example/".to_string(),
+            identity: Some(include_str!("../../../tests/server/config/client.crt").to_string()),
+            ca: None,
+        };
+        let upstream = Upstream { tls: vec![tls], ..Default::default() };
+        let result = NativeHttp::init(&upstream, &Default::default(), None);
+
+        assert!(result.is_err());
+    }
 }
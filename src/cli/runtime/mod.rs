@@ -1,19 +1,22 @@
+mod circuit_breaker;
 mod env;
 mod file;
 mod http;
+mod redis;
 
 use std::fs;
-use std::hash::Hash;
 use std::sync::Arc;
 
 pub use http::NativeHttp;
 use inquire::{Confirm, Select};
+use tailcall_http_cache::DiskCacheManager;
 
-use crate::core::blueprint::Blueprint;
+use crate::core::blueprint::{Blueprint, CacheStore};
 use crate::core::cache::InMemoryCache;
+use crate::core::rate_limit::InMemoryRateLimiter;
 use crate::core::runtime::TargetRuntime;
 use crate::core::worker::{Command, Event};
-use crate::core::{blueprint, EnvIO, FileIO, HttpIO, WorkerIO};
+use crate::core::{blueprint, EntityCache, EnvIO, FileIO, HttpIO, WorkerIO};
 
 // Provides access to env in native rust environment
 fn init_env() -> Arc<dyn EnvIO> {
@@ -50,39 +53,90 @@ fn init_resolver_worker_io(
 }
 
 // Provides access to http in native rust environment
-fn init_http(blueprint: &Blueprint) -> Arc<dyn HttpIO> {
-    Arc::new(http::NativeHttp::init(
+fn init_http(
+    blueprint: &Blueprint,
+    disk_cache: Option<&DiskCacheManager>,
+) -> anyhow::Result<Arc<dyn HttpIO>> {
+    Ok(Arc::new(http::NativeHttp::init(
         &blueprint.upstream,
         &blueprint.telemetry,
-    ))
+        disk_cache,
+    )?))
 }
 
 // Provides access to http in native rust environment
-fn init_http2_only(blueprint: &Blueprint) -> Arc<dyn HttpIO> {
-    Arc::new(http::NativeHttp::init(
+fn init_http2_only(
+    blueprint: &Blueprint,
+    disk_cache: Option<&DiskCacheManager>,
+) -> anyhow::Result<Arc<dyn HttpIO>> {
+    Ok(Arc::new(http::NativeHttp::init(
         &blueprint.upstream.clone().http2_only(true),
         &blueprint.telemetry,
-    ))
+        disk_cache,
+    )?))
 }
 
-fn init_in_memory_cache<K: Hash + Eq, V: Clone>() -> InMemoryCache<K, V> {
-    InMemoryCache::new()
+// Provides the store of the results of `@cache` in native rust environment
+fn init_cache(store: &CacheStore) -> Arc<EntityCache> {
+    match store {
+        CacheStore::InMemory { capacity } => Arc::new(InMemoryCache::with_capacity(capacity.get())),
+        CacheStore::Redis { url, prefix, pool_size } => Arc::new(redis::RedisCache::new(
+            url.clone(),
+            prefix.clone(),
+            *pool_size,
+        )),
+        CacheStore::CloudflareKv { .. } => {
+            tracing::warn!("The Cloudflare KV cache is only available on Cloudflare, using memory");
+            Arc::new(InMemoryCache::new())
+        }
+    }
 }
 
-pub fn init(blueprint: &Blueprint) -> TargetRuntime {
+/// Creates the runtime of the blueprint, that fails when the clients of the
+/// upstream can't be built, like with an invalid certificate.
+pub fn init(blueprint: &Blueprint) -> anyhow::Result<TargetRuntime> {
     #[cfg(not(feature = "js"))]
     tracing::warn!("JS capabilities are disabled in this build");
 
-    TargetRuntime {
-        http: init_http(blueprint),
-        http2_only: init_http2_only(blueprint),
+    // the clients share a single index of the responses cached on the disk
+    let disk_cache = http::init_disk_cache(&blueprint.upstream);
+
+    Ok(TargetRuntime {
+        http: init_http(blueprint, disk_cache.as_ref())?,
+        http2_only: init_http2_only(blueprint, disk_cache.as_ref())?,
         env: init_env(),
         file: init_file(),
-        cache: Arc::new(init_in_memory_cache()),
+        cache: init_cache(&blueprint.server.cache),
+        rate_limiter: Arc::new(InMemoryRateLimiter::default()),
         extensions: Arc::new(vec![]),
         cmd_worker: init_http_worker_io(blueprint.server.script.clone()),
         worker: init_resolver_worker_io(blueprint.server.script.clone()),
+    })
+}
+
+/// Creates the runtime of a reloaded config. The state of the previous
+/// runtime, like the circuit breakers, the token buckets of the rate limits
+/// and the cached values, is kept unless the config of its component changed.
+pub fn reinit(
+    runtime: &TargetRuntime,
+    old: &Blueprint,
+    new: &Blueprint,
+) -> anyhow::Result<TargetRuntime> {
+    let mut runtime = runtime.clone();
+
+    let enable_telemetry = |blueprint: &Blueprint| blueprint.telemetry.export.is_some();
+    if old.upstream != new.upstream || enable_telemetry(old) != enable_telemetry(new) {
+        let disk_cache = http::init_disk_cache(&new.upstream);
+        runtime.http = init_http(new, disk_cache.as_ref())?;
+        runtime.http2_only = init_http2_only(new, disk_cache.as_ref())?;
+    }
+    if old.server.cache != new.server.cache {
+        runtime.cache = init_cache(&new.server.cache);
     }
+    runtime.cmd_worker = init_http_worker_io(new.server.script.clone());
+    runtime.worker = init_resolver_worker_io(new.server.script.clone());
+
+    Ok(runtime)
 }
 
 pub async fn confirm_and_write(
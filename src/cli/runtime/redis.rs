@@ -0,0 +1,305 @@
+use std::num::{NonZeroU64, NonZeroUsize};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use async_graphql_value::ConstValue;
+use redis::aio::ConnectionManager;
+use redis::{Cmd, FromRedisValue};
+use tokio::sync::OnceCell;
+use url::Url;
+
+use crate::core::cache::codec;
+use crate::core::cache::error::{Error, Result};
+use crate::core::ir::model::IoId;
+use crate::core::Cache;
+
+/// Time after which a query is abandoned, so that an unreachable server slows
+/// down the requests without blocking them.
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Cache in a server speaking the Redis protocol, over TLS with a `rediss://`
+/// URL. The queries are spread over a pool of multiplexed connections, that
+/// are opened on their first use and reconnect after a failure.
+pub struct RedisCache {
+    url: Url,
+    prefix: String,
+    connections: Vec<OnceCell<ConnectionManager>>,
+    next: AtomicUsize,
+    hits: AtomicUsize,
+    miss: AtomicUsize,
+}
+
+impl RedisCache {
+    pub fn new(url: Url, prefix: String, pool_size: NonZeroUsize) -> Self {
+        Self {
+            url,
+            prefix,
+            connections: (0..pool_size.get()).map(|_| OnceCell::new()).collect(),
+            next: AtomicUsize::new(0),
+            hits: AtomicUsize::new(0),
+            miss: AtomicUsize::new(0),
+        }
+    }
+
+    async fn connection(&self) -> redis::RedisResult<ConnectionManager> {
+        let index = self.next.fetch_add(1, Ordering::Relaxed) % self.connections.len();
+        // the connection is opened again by the next query when it fails
+        self.connections[index]
+            .get_or_try_init(|| async {
+                redis::Client::open(self.url.as_str())?
+                    .get_connection_manager()
+                    .await
+            })
+            .await
+            .cloned()
+    }
+
+    async fn query<T: FromRedisValue>(&self, cmd: &Cmd) -> Result<T> {
+        let result = tokio::time::timeout(TIMEOUT, async {
+            let mut connection = self.connection().await?;
+            cmd.query_async(&mut connection).await
+        })
+        .await;
+
+        match result {
+            Ok(Ok(value)) => Ok(value),
+            Ok(Err(err)) => Err(Error::Kv(err.to_string())),
+            Err(_) => Err(Error::Kv("The query timed out".to_string())),
+        }
+    }
+
+    /// Key of the set of the keys associated with a tag.
+    fn tag_key(&self, tag: &str) -> String {
+        format!("{}tag:{}", self.prefix, tag)
+    }
+}
+
+#[async_trait::async_trait]
+impl Cache for RedisCache {
+    type Key = IoId;
+    type Value = ConstValue;
+
+    async fn set<'a>(&'a self, key: IoId, value: ConstValue, ttl: NonZeroU64) -> Result<()> {
+        let key = codec::key(&self.prefix, key.as_u64());
+        let value = codec::encode(&value)?;
+
+        self.query(
+            redis::cmd("SET")
+                .arg(key)
+                .arg(value)
+                .arg("PX")
+                .arg(ttl.get()),
+        )
+        .await
+    }
+
+    async fn get<'a>(&'a self, key: &'a IoId) -> Result<Option<ConstValue>> {
+        let key = codec::key(&self.prefix, key.as_u64());
+
+        match self
+            .query::<Option<Vec<u8>>>(redis::cmd("GET").arg(key))
+            .await?
+        {
+            Some(value) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Ok(Some(codec::decode(&value)?))
+            }
+            None => {
+                self.miss.fetch_add(1, Ordering::Relaxed);
+                Ok(None)
+            }
+        }
+    }
+
+    async fn tag<'a>(&'a self, key: &'a IoId, tags: &'a [String], ttl: NonZeroU64) -> Result<()> {
+        let key = codec::key(&self.prefix, key.as_u64());
+
+        for tag in tags {
+            let tag = self.tag_key(tag);
+            self.query::<()>(redis::cmd("SADD").arg(&tag).arg(&key))
+                .await?;
+
+            // the tag lives as long as the longest lived of its keys
+            let current = self.query::<i64>(redis::cmd("PTTL").arg(&tag)).await?;
+            if current >= 0 && current as u64 >= ttl.get() {
+                continue;
+            }
+            self.query::<()>(redis::cmd("PEXPIRE").arg(&tag).arg(ttl.get()))
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn delete<'a>(&'a self, key: &'a IoId) -> Result<bool> {
+        let key = codec::key(&self.prefix, key.as_u64());
+        Ok(self.query::<usize>(redis::cmd("DEL").arg(key)).await? > 0)
+    }
+
+    async fn purge<'a>(&'a self, tags: &'a [String]) -> Result<usize> {
+        let mut purged = 0;
+
+        for tag in tags {
+            let tag = self.tag_key(tag);
+            let keys = self
+                .query::<Vec<Vec<u8>>>(redis::cmd("SMEMBERS").arg(&tag))
+                .await?;
+
+            if !keys.is_empty() {
+                purged += self.query::<usize>(redis::cmd("DEL").arg(keys)).await?;
+            }
+            self.query::<()>(redis::cmd("DEL").arg(&tag)).await?;
+        }
+
+        Ok(purged)
+    }
+
+    fn hit_rate(&self) -> Option<f64> {
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.miss.load(Ordering::Relaxed);
+
+        if hits + misses > 0 {
+            return Some(hits as f64 / (hits + misses) as f64);
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    async fn read_line<R: AsyncBufRead + Unpin>(reader: &mut R) -> Option<String> {
+        let mut line = String::new();
+        reader.read_line(&mut line).await.ok()?;
+        line.strip_suffix("\r\n").map(str::to_string)
+    }
+
+    /// Reads a command, sent by the clients as an array of bulk strings.
+    async fn read_command<R: AsyncBufRead + Unpin>(reader: &mut R) -> Option<Vec<Vec<u8>>> {
+        let len = read_line(reader).await?.strip_prefix('*')?.parse().ok()?;
+        let mut args = Vec::with_capacity(len);
+        for _ in 0..len {
+            let len = read_line(reader)
+                .await?
+                .strip_prefix('$')?
+                .parse::<usize>()
+                .ok()?;
+            let mut arg = vec![0; len + 2];
+            reader.read_exact(&mut arg).await.ok()?;
+            arg.truncate(len);
+            args.push(arg);
+        }
+        Some(args)
+    }
+
+    fn bulk(value: &[u8]) -> Vec<u8> {
+        [format!("${}\r\n", value.len()).as_bytes(), value, b"\r\n"].concat()
+    }
+
+    /// Serves the commands of the cache for a single connection from maps,
+    /// without expiring the keys.
+    async fn serve(listener: TcpListener) {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut stream = BufReader::new(stream);
+        let mut values = HashMap::<Vec<u8>, Vec<u8>>::new();
+        let mut sets = HashMap::<Vec<u8>, HashSet<Vec<u8>>>::new();
+
+        while let Some(args) = read_command(&mut stream).await {
+            let reply = match args[0].to_ascii_uppercase().as_slice() {
+                b"SET" => {
+                    assert_eq!(args[3], b"PX");
+                    values.insert(args[1].clone(), args[2].clone());
+                    b"+OK\r\n".to_vec()
+                }
+                b"GET" => values
+                    .get(&args[1])
+                    .map_or(b"$-1\r\n".to_vec(), |value| bulk(value)),
+                b"SADD" => {
+                    sets.entry(args[1].clone())
+                        .or_default()
+                        .insert(args[2].clone());
+                    b":1\r\n".to_vec()
+                }
+                b"PTTL" => b":-1\r\n".to_vec(),
+                b"PEXPIRE" => b":1\r\n".to_vec(),
+                b"SMEMBERS" => {
+                    let members = sets.get(&args[1]).cloned().unwrap_or_default();
+                    let mut reply = format!("*{}\r\n", members.len()).into_bytes();
+                    members.iter().for_each(|member| reply.extend(bulk(member)));
+                    reply
+                }
+                b"DEL" => {
+                    let deleted = args[1..]
+                        .iter()
+                        .filter(|key| values.remove(*key).is_some() | sets.remove(*key).is_some())
+                        .count();
+                    format!(":{}\r\n", deleted).into_bytes()
+                }
+                // the information sent by the client when it connects
+                b"CLIENT" => b"+OK\r\n".to_vec(),
+                _ => b"-ERR unknown command\r\n".to_vec(),
+            };
+            stream.get_mut().write_all(&reply).await.unwrap();
+        }
+    }
+
+    async fn cache() -> RedisCache {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = Url::parse(&format!("redis://{}", listener.local_addr().unwrap())).unwrap();
+        tokio::spawn(serve(listener));
+
+        RedisCache::new(url, "tailcall:".into(), NonZeroUsize::new(1).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_redis_cache() {
+        let cache = cache().await;
+        let key = IoId::new(1);
+        let value = ConstValue::List(vec![ConstValue::from("a"), ConstValue::from(1)]);
+        let ttl = NonZeroU64::new(1000).unwrap();
+
+        assert_eq!(cache.get(&key).await.unwrap(), None);
+        cache.set(key.clone(), value.clone(), ttl).await.unwrap();
+        assert_eq!(cache.get(&key).await.unwrap(), Some(value));
+        assert_eq!(cache.hit_rate(), Some(0.5));
+    }
+
+    #[tokio::test]
+    async fn test_redis_purge() {
+        let cache = cache().await;
+        let ttl = NonZeroU64::new(1000).unwrap();
+        let tags = vec!["user:1".to_string()];
+
+        for id in 1..=3 {
+            cache
+                .set(IoId::new(id), ConstValue::from(id), ttl)
+                .await
+                .unwrap();
+        }
+        cache.tag(&IoId::new(1), &tags, ttl).await.unwrap();
+        cache.tag(&IoId::new(2), &tags, ttl).await.unwrap();
+
+        assert_eq!(cache.purge(&tags).await.unwrap(), 2);
+        assert_eq!(cache.purge(&tags).await.unwrap(), 0);
+        assert_eq!(cache.get(&IoId::new(1)).await.unwrap(), None);
+        assert!(cache.delete(&IoId::new(3)).await.unwrap());
+        assert_eq!(cache.get(&IoId::new(3)).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = Url::parse(&format!("redis://{}", listener.local_addr().unwrap())).unwrap();
+        drop(listener);
+
+        let cache = RedisCache::new(url, "tailcall:".into(), NonZeroUsize::new(1).unwrap());
+        assert!(cache.get(&IoId::new(1)).await.is_err());
+    }
+}
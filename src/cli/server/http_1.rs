@@ -1,32 +1,54 @@
 use std::sync::Arc;
 
+use hyper::server::conn::AddrStream;
 use hyper::service::{make_service_fn, service_fn};
 use tokio::sync::oneshot;
 
+use super::reload::SharedAppContext;
 use super::server_config::ServerConfig;
+use super::websocket;
 use crate::cli::CLIError;
 use crate::core::async_graphql_hyper::{GraphQLBatchRequest, GraphQLRequest};
 use crate::core::http::handle_request;
 
 pub async fn start_http_1(
     sc: Arc<ServerConfig>,
+    app_ctx: SharedAppContext,
     server_up_sender: Option<oneshot::Sender<()>>,
 ) -> anyhow::Result<()> {
     let addr = sc.addr();
-    let make_svc_single_req = make_service_fn(|_conn| {
-        let state = Arc::clone(&sc);
+    let make_svc_single_req = make_service_fn(|conn: &AddrStream| {
+        let state = app_ctx.clone();
+        let remote_addr = conn.remote_addr();
         async move {
-            Ok::<_, anyhow::Error>(service_fn(move |req| {
-                handle_request::<GraphQLRequest>(req, state.app_ctx.clone())
+            Ok::<_, anyhow::Error>(service_fn(move |mut req| {
+                let app_ctx = state.get();
+                req.extensions_mut().insert(remote_addr);
+                async move {
+                    if websocket::is_upgrade_request(&req) {
+                        websocket::handle_upgrade(req, app_ctx).await
+                    } else {
+                        handle_request::<GraphQLRequest>(req, app_ctx).await
+                    }
+                }
             }))
         }
     });
 
-    let make_svc_batch_req = make_service_fn(|_conn| {
-        let state = Arc::clone(&sc);
+    let make_svc_batch_req = make_service_fn(|conn: &AddrStream| {
+        let state = app_ctx.clone();
+        let remote_addr = conn.remote_addr();
         async move {
-            Ok::<_, anyhow::Error>(service_fn(move |req| {
-                handle_request::<GraphQLBatchRequest>(req, state.app_ctx.clone())
+            Ok::<_, anyhow::Error>(service_fn(move |mut req| {
+                let app_ctx = state.get();
+                req.extensions_mut().insert(remote_addr);
+                async move {
+                    if websocket::is_upgrade_request(&req) {
+                        websocket::handle_upgrade(req, app_ctx).await
+                    } else {
+                        handle_request::<GraphQLBatchRequest>(req, app_ctx).await
+                    }
+                }
             }))
         }
     });
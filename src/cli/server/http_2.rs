@@ -1,49 +1,171 @@
 #![allow(clippy::too_many_arguments)]
+use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 
-use hyper::server::conn::AddrIncoming;
+use futures_channel::mpsc;
+use hyper::server::accept;
 use hyper::service::{make_service_fn, service_fn};
-use hyper::Server;
-use hyper_rustls::TlsAcceptor;
+use hyper::{Body, Request, Server};
 use rustls_pki_types::{CertificateDer, PrivateKeyDer};
-use tokio::sync::oneshot;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{oneshot, Semaphore};
+use tokio_rustls::rustls::server::WebPkiClientVerifier;
+use tokio_rustls::rustls::{RootCertStore, ServerConfig as TlsConfig};
+use tokio_rustls::server::TlsStream;
+use tokio_rustls::TlsAcceptor;
 
+use super::reload::SharedAppContext;
 use super::server_config::ServerConfig;
 use crate::cli::CLIError;
 use crate::core::async_graphql_hyper::{GraphQLBatchRequest, GraphQLRequest};
+use crate::core::blueprint::ClientCert;
 use crate::core::http::handle_request;
 
+/// Maximum number of TLS handshakes in progress, the connections wait to be
+/// accepted beyond it.
+const MAX_PENDING_HANDSHAKES: usize = 1024;
+
+/// Time after which the connections that haven't completed their TLS handshake
+/// are dropped, so that idle clients can't hold the slots of the handshakes.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+fn tls_config(
+    cert: Vec<CertificateDer<'static>>,
+    key: Arc<PrivateKeyDer<'static>>,
+    client_cert: Option<ClientCert>,
+) -> anyhow::Result<TlsConfig> {
+    let builder = TlsConfig::builder();
+    let builder = match client_cert {
+        Some(client_cert) => {
+            let mut roots = RootCertStore::empty();
+            for ca in client_cert.ca {
+                roots.add(ca)?;
+            }
+
+            let verifier = WebPkiClientVerifier::builder(Arc::new(roots));
+            let verifier = if client_cert.required {
+                verifier
+            } else {
+                verifier.allow_unauthenticated()
+            };
+            builder.with_client_cert_verifier(verifier.build()?)
+        }
+        None => builder.with_no_client_auth(),
+    };
+
+    let mut config = builder.with_single_cert(cert, key.clone_key())?;
+    config.alpn_protocols = vec![b"h2".to_vec()];
+
+    Ok(config)
+}
+
+/// Accepts the TLS connections. The handshakes are done concurrently up to
+/// `MAX_PENDING_HANDSHAKES`, and the connections whose handshake fails or
+/// times out are dropped, so that they don't stop the server.
+fn incoming(
+    listener: TcpListener,
+    acceptor: TlsAcceptor,
+) -> mpsc::UnboundedReceiver<std::io::Result<TlsStream<TcpStream>>> {
+    let (sender, receiver) = mpsc::unbounded();
+    let handshakes = Arc::new(Semaphore::new(MAX_PENDING_HANDSHAKES));
+
+    tokio::spawn(async move {
+        // the connections are accepted until the server is dropped
+        while !sender.is_closed() {
+            // the semaphore is never closed
+            let Ok(permit) = handshakes.clone().acquire_owned().await else {
+                break;
+            };
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(err) => {
+                    // errors such as too many open files persist for a while
+                    tracing::warn!("Failed to accept a connection: {}", err);
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                    continue;
+                }
+            };
+            let acceptor = acceptor.clone();
+            let sender = sender.clone();
+
+            tokio::spawn(async move {
+                let handshake = tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream));
+                match handshake.await {
+                    Ok(Ok(stream)) => {
+                        let _ = sender.unbounded_send(Ok(stream));
+                    }
+                    Ok(Err(err)) => tracing::debug!("TLS handshake failed: {}", err),
+                    Err(_) => tracing::debug!("TLS handshake timed out"),
+                }
+                drop(permit);
+            });
+        }
+    });
+
+    receiver
+}
+
+/// Sets the address and the verified certificate of the client in the
+/// extensions of its requests.
+fn insert_client(
+    req: &mut Request<Body>,
+    remote_addr: Option<SocketAddr>,
+    client_cert: Option<CertificateDer<'static>>,
+) {
+    if let Some(remote_addr) = remote_addr {
+        req.extensions_mut().insert(remote_addr);
+    }
+    if let Some(client_cert) = client_cert {
+        req.extensions_mut().insert(client_cert);
+    }
+}
+
+fn client(conn: &TlsStream<TcpStream>) -> (Option<SocketAddr>, Option<CertificateDer<'static>>) {
+    let (io, session) = conn.get_ref();
+    let client_cert = session
+        .peer_certificates()
+        .and_then(|certs| certs.first())
+        .map(|cert| cert.clone().into_owned());
+
+    (io.peer_addr().ok(), client_cert)
+}
+
 pub async fn start_http_2(
     sc: Arc<ServerConfig>,
+    app_ctx: SharedAppContext,
     cert: Vec<CertificateDer<'static>>,
     key: Arc<PrivateKeyDer<'static>>,
+    client_cert: Option<ClientCert>,
     server_up_sender: Option<oneshot::Sender<()>>,
 ) -> anyhow::Result<()> {
     let addr = sc.addr();
-    let incoming = AddrIncoming::bind(&addr)?;
-    let acceptor = TlsAcceptor::builder()
-        .with_single_cert(cert, key.clone_key())?
-        .with_http2_alpn()
-        .with_incoming(incoming);
-    let make_svc_single_req = make_service_fn(|_conn| {
-        let state = Arc::clone(&sc);
+    let acceptor = TlsAcceptor::from(Arc::new(tls_config(cert, key, client_cert)?));
+    let listener = TcpListener::bind(&addr).await?;
+    let make_svc_single_req = make_service_fn(|conn: &TlsStream<TcpStream>| {
+        let state = app_ctx.clone();
+        let (remote_addr, client_cert) = client(conn);
         async move {
-            Ok::<_, anyhow::Error>(service_fn(move |req| {
-                handle_request::<GraphQLRequest>(req, state.app_ctx.clone())
+            Ok::<_, anyhow::Error>(service_fn(move |mut req: Request<Body>| {
+                insert_client(&mut req, remote_addr, client_cert.clone());
+                handle_request::<GraphQLRequest>(req, state.get())
             }))
         }
     });
 
-    let make_svc_batch_req = make_service_fn(|_conn| {
-        let state = Arc::clone(&sc);
+    let make_svc_batch_req = make_service_fn(|conn: &TlsStream<TcpStream>| {
+        let state = app_ctx.clone();
+        let (remote_addr, client_cert) = client(conn);
         async move {
-            Ok::<_, anyhow::Error>(service_fn(move |req| {
-                handle_request::<GraphQLBatchRequest>(req, state.app_ctx.clone())
+            Ok::<_, anyhow::Error>(service_fn(move |mut req: Request<Body>| {
+                insert_client(&mut req, remote_addr, client_cert.clone());
+                handle_request::<GraphQLBatchRequest>(req, state.get())
             }))
         }
     });
 
-    let builder = Server::builder(acceptor).http2_only(true);
+    let builder =
+        Server::builder(accept::from_stream(incoming(listener, acceptor))).http2_only(true);
 
     super::log_launch(sc.as_ref());
 
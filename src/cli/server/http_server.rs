@@ -6,6 +6,7 @@ use tokio::sync::oneshot::{self};
 
 use super::http_1::start_http_1;
 use super::http_2::start_http_2;
+use super::reload::{Reloader, SharedAppContext};
 use super::server_config::ServerConfig;
 use crate::cli::telemetry::init_opentelemetry;
 use crate::cli::CLIError;
@@ -15,11 +16,18 @@ use crate::core::config::ConfigModule;
 pub struct Server {
     config_module: ConfigModule,
     server_up_sender: Option<oneshot::Sender<()>>,
+    reloader: Option<Reloader>,
 }
 
 impl Server {
     pub fn new(config_module: ConfigModule) -> Self {
-        Self { config_module, server_up_sender: None }
+        Self { config_module, server_up_sender: None, reloader: None }
+    }
+
+    /// Reloads the config with the given reloader while the server runs.
+    pub fn reloader(mut self, reloader: Reloader) -> Self {
+        self.reloader = Some(reloader);
+        self
     }
 
     pub fn server_up_receiver(&mut self) -> oneshot::Receiver<()> {
@@ -38,11 +46,17 @@ impl Server {
 
         init_opentelemetry(blueprint.telemetry.clone(), &server_config.app_ctx.runtime)?;
 
+        let app_ctx = SharedAppContext::new(server_config.app_ctx.clone());
+        if let Some(reloader) = self.reloader {
+            tokio::spawn(reloader.run(server_config.clone(), app_ctx.clone()));
+        }
+
         match blueprint.server.http.clone() {
-            Http::HTTP2 { cert, key } => {
-                start_http_2(server_config, cert, key, self.server_up_sender).await
+            Http::HTTP2 { cert, key, client_cert } => {
+                let sender = self.server_up_sender;
+                start_http_2(server_config, app_ctx, cert, key, client_cert, sender).await
             }
-            Http::HTTP1 => start_http_1(server_config, self.server_up_sender).await,
+            Http::HTTP1 => start_http_1(server_config, app_ctx, self.server_up_sender).await,
         }
     }
 
@@ -2,7 +2,9 @@ pub mod http_1;
 pub mod http_2;
 pub mod http_server;
 pub mod playground;
+pub mod reload;
 pub mod server_config;
+pub mod websocket;
 
 pub use http_server::Server;
 
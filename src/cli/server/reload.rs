@@ -0,0 +1,215 @@
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+use futures_util::future::join_all;
+
+use super::server_config::ServerConfig;
+use crate::cli::CLIError;
+use crate::core::app_context::AppContext;
+use crate::core::blueprint::{Blueprint, ClientCert, Http};
+use crate::core::config::reader::ConfigReader;
+use crate::core::runtime::TargetRuntime;
+
+/// Interval at which the files of the config are checked for changes in the
+/// watch mode.
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The local files of a config, with their modification time.
+type Files = Vec<(String, Option<SystemTime>)>;
+
+/// The `AppContext` of the new requests, that is swapped when the config is
+/// reloaded. The requests in flight keep the context that they started with.
+#[derive(Clone)]
+pub struct SharedAppContext(Arc<RwLock<Arc<AppContext>>>);
+
+impl SharedAppContext {
+    pub fn new(app_ctx: Arc<AppContext>) -> Self {
+        Self(Arc::new(RwLock::new(app_ctx)))
+    }
+
+    pub fn get(&self) -> Arc<AppContext> {
+        self.0.read().unwrap().clone()
+    }
+
+    fn set(&self, app_ctx: Arc<AppContext>) {
+        *self.0.write().unwrap() = app_ctx;
+    }
+}
+
+/// Reloads the config on SIGHUP and, in the watch mode, whenever one of the
+/// local files that it was read from changes. A config that fails validation
+/// is ignored, and the previous one keeps being served.
+pub struct Reloader {
+    file_paths: Vec<String>,
+    runtime: TargetRuntime,
+    watch: bool,
+    files: Files,
+}
+
+impl Reloader {
+    pub fn new(
+        file_paths: Vec<String>,
+        config_reader: &ConfigReader,
+        runtime: TargetRuntime,
+        watch: bool,
+    ) -> Self {
+        let files = config_reader
+            .local_files()
+            .into_iter()
+            .map(|path| (path, None))
+            .collect();
+
+        Self { file_paths, runtime, watch, files }
+    }
+
+    pub async fn run(mut self, mut server_config: Arc<ServerConfig>, app_ctx: SharedAppContext) {
+        let mut hangup = Hangup::listen();
+        self.files = modified(self.files).await;
+
+        loop {
+            let signaled = if self.watch {
+                tokio::time::timeout(WATCH_INTERVAL, hangup.recv())
+                    .await
+                    .is_ok()
+            } else {
+                hangup.recv().await;
+                true
+            };
+
+            if signaled {
+                tracing::info!("Received SIGHUP, reloading the config");
+            } else {
+                let files = modified(self.files.clone()).await;
+                if files == self.files {
+                    continue;
+                }
+                // a config that failed to reload is only retried on the next change
+                self.files = files;
+                tracing::info!("Config files changed, reloading the config");
+            }
+
+            match self.reload(&server_config).await {
+                Ok(reloaded) => {
+                    warn_restart(&server_config.blueprint, &reloaded.blueprint);
+                    app_ctx.set(reloaded.app_ctx.clone());
+                    server_config = Arc::new(reloaded);
+                    tracing::info!("Config reloaded");
+                }
+                Err(err) => {
+                    let err: CLIError = err.into();
+                    tracing::error!(
+                        "Failed to reload the config, the previous config is still served: {}",
+                        err.color(true)
+                    );
+                }
+            }
+        }
+    }
+
+    /// Reads and validates the config again, along with all the files linked
+    /// to it. The state of the previous server is kept where its config
+    /// didn't change.
+    async fn reload(&mut self, previous: &ServerConfig) -> anyhow::Result<ServerConfig> {
+        // the reader caches the files, so a new one is needed to see the changes
+        let config_reader = ConfigReader::init(self.runtime.clone());
+        let config_module = config_reader.read_all(&self.file_paths).await?;
+        let blueprint = Blueprint::try_from(&config_module).map_err(CLIError::from)?;
+        let endpoints = config_module.extensions().endpoint_set.clone();
+        let server_config = ServerConfig::reload(previous, blueprint, endpoints).await?;
+
+        let files = config_reader
+            .local_files()
+            .into_iter()
+            .map(|path| (path, None))
+            .collect();
+        self.files = modified(files).await;
+
+        Ok(server_config)
+    }
+}
+
+/// Sets the modification time of the files, that is `None` when a file can't
+/// be read.
+async fn modified(files: Files) -> Files {
+    join_all(files.into_iter().map(|(path, _)| async move {
+        let modified = tokio::fs::metadata(&path)
+            .await
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        (path, modified)
+    }))
+    .await
+}
+
+/// Logs the changes of the config that only apply once the server restarts.
+fn warn_restart(old: &Blueprint, new: &Blueprint) {
+    let (old, new) = (&old.server, &new.server);
+    if old.hostname != new.hostname
+        || old.port != new.port
+        || http_changed(&old.http, &new.http)
+        || old.enable_batch_requests != new.enable_batch_requests
+        || old.pipeline_flush != new.pipeline_flush
+    {
+        tracing::warn!(
+            "Restart the server to change its address, HTTP version, certificates or batching"
+        );
+    }
+}
+
+/// Checks if the HTTP version changed, or the certificates that the TLS
+/// listener of HTTP/2 was started with.
+fn http_changed(old: &Http, new: &Http) -> bool {
+    match (old, new) {
+        (Http::HTTP1, Http::HTTP1) => false,
+        (
+            Http::HTTP2 { cert, key, client_cert },
+            Http::HTTP2 { cert: new_cert, key: new_key, client_cert: new_client_cert },
+        ) => {
+            let client_ca = |client_cert: &Option<ClientCert>| {
+                client_cert
+                    .as_ref()
+                    .map(|client_cert| (client_cert.ca.clone(), client_cert.required))
+            };
+
+            cert != new_cert
+                || key.secret_der() != new_key.secret_der()
+                || client_ca(client_cert) != client_ca(new_client_cert)
+        }
+        _ => true,
+    }
+}
+
+/// The SIGHUP signals received by the process, that never arrive on the
+/// platforms without them.
+struct Hangup {
+    #[cfg(unix)]
+    signal: Option<tokio::signal::unix::Signal>,
+}
+
+impl Hangup {
+    #[cfg(unix)]
+    fn listen() -> Self {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let signal = signal(SignalKind::hangup())
+            .map_err(|err| tracing::warn!("Failed to listen to SIGHUP: {}", err))
+            .ok();
+        Self { signal }
+    }
+
+    #[cfg(not(unix))]
+    fn listen() -> Self {
+        Self {}
+    }
+
+    async fn recv(&mut self) {
+        #[cfg(unix)]
+        if let Some(signal) = self.signal.as_mut() {
+            if signal.recv().await.is_some() {
+                return;
+            }
+        }
+
+        std::future::pending::<()>().await
+    }
+}
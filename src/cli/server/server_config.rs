@@ -3,16 +3,20 @@ use std::sync::Arc;
 
 use async_graphql_extension_apollo_tracing::ApolloTracing;
 
-use crate::cli::runtime::init;
+use crate::cli::runtime::{init, reinit};
 use crate::core::app_context::AppContext;
 use crate::core::blueprint::telemetry::TelemetryExporter;
 use crate::core::blueprint::{Blueprint, Http};
 use crate::core::rest::{EndpointSet, Unchecked};
+use crate::core::runtime::TargetRuntime;
 use crate::core::schema_extension::SchemaExtension;
 
 pub struct ServerConfig {
     pub blueprint: Blueprint,
     pub app_ctx: Arc<AppContext>,
+    /// The runtime of the config, without the rate limits of the upstream
+    /// that the `AppContext` adds.
+    pub runtime: TargetRuntime,
 }
 
 impl ServerConfig {
@@ -20,8 +24,37 @@ impl ServerConfig {
         blueprint: Blueprint,
         endpoints: EndpointSet<Unchecked>,
     ) -> anyhow::Result<Self> {
-        let mut rt = init(&blueprint);
+        let rt = init(&blueprint)?;
+        let app_ctx = Self::app_ctx(&blueprint, endpoints, rt.clone()).await?;
 
+        Ok(Self { app_ctx: Arc::new(app_ctx), blueprint, runtime: rt })
+    }
+
+    /// Creates the server of a reloaded config, that keeps the state of the
+    /// previous one, like the keys of the identity providers, unless the
+    /// config of the component changed. The cached responses are dropped, as
+    /// the resolvers of the config can change them.
+    pub async fn reload(
+        previous: &ServerConfig,
+        blueprint: Blueprint,
+        endpoints: EndpointSet<Unchecked>,
+    ) -> anyhow::Result<Self> {
+        let rt = reinit(&previous.runtime, &previous.blueprint, &blueprint)?;
+        let mut app_ctx = Self::app_ctx(&blueprint, endpoints, rt.clone()).await?;
+
+        if previous.blueprint.server.auth == blueprint.server.auth {
+            app_ctx.auth_ctx = previous.app_ctx.auth_ctx.clone();
+        }
+        app_ctx.revalidating = previous.app_ctx.revalidating.clone();
+
+        Ok(Self { app_ctx: Arc::new(app_ctx), blueprint, runtime: rt })
+    }
+
+    async fn app_ctx(
+        blueprint: &Blueprint,
+        endpoints: EndpointSet<Unchecked>,
+        mut rt: TargetRuntime,
+    ) -> anyhow::Result<AppContext> {
         let mut extensions = vec![];
 
         if let Some(TelemetryExporter::Apollo(apollo)) = blueprint.telemetry.export.as_ref() {
@@ -36,10 +69,8 @@ impl ServerConfig {
         }
         rt.add_extensions(extensions);
 
-        let endpoints = endpoints.into_checked(&blueprint, rt.clone()).await?;
-        let app_context = Arc::new(AppContext::new(blueprint.clone(), rt, endpoints));
-
-        Ok(Self { app_ctx: app_context, blueprint })
+        let endpoints = endpoints.into_checked(blueprint, rt.clone()).await?;
+        Ok(AppContext::new(blueprint.clone(), rt, endpoints))
     }
 
     pub fn addr(&self) -> SocketAddr {
@@ -48,7 +79,7 @@ impl ServerConfig {
 
     pub fn http_version(&self) -> String {
         match self.blueprint.server.http {
-            Http::HTTP2 { cert: _, key: _ } => "HTTP/2".to_string(),
+            Http::HTTP2 { .. } => "HTTP/2".to_string(),
             _ => "HTTP/1.1".to_string(),
         }
     }
@@ -67,3 +98,61 @@ impl ServerConfig {
         format!("{}://{}", protocol, addr)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use hyper::{Body, Request};
+
+    use super::*;
+    use crate::core::async_graphql_hyper::GraphQLRequest;
+    use crate::core::config::{Config, ConfigModule};
+    use crate::core::http::handle_request;
+    use crate::core::valid::Validator;
+
+    fn blueprint(greeting: &str) -> Blueprint {
+        let sdl = format!(
+            r#"
+            schema @server(responseCache: {{maxAge: 60000}}) {{
+              query: Query
+            }}
+
+            type Query {{
+              greeting: String @expr(body: "{greeting}")
+            }}
+            "#
+        );
+        let config = Config::from_sdl(&sdl).to_result().unwrap();
+        Blueprint::try_from(&ConfigModule::from(config)).unwrap()
+    }
+
+    async fn greeting(server_config: &ServerConfig) -> String {
+        let req = Request::post("http://localhost:8000/graphql")
+            .body(Body::from(r#"{"query": "{ greeting }"}"#))
+            .unwrap();
+        let res = handle_request::<GraphQLRequest>(req, server_config.app_ctx.clone())
+            .await
+            .unwrap();
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_reload_drops_cached_responses() {
+        let server_config = ServerConfig::new(blueprint("hello"), EndpointSet::default())
+            .await
+            .unwrap();
+        assert_eq!(
+            greeting(&server_config).await,
+            r#"{"data":{"greeting":"hello"}}"#
+        );
+
+        let server_config =
+            ServerConfig::reload(&server_config, blueprint("hi"), EndpointSet::default())
+                .await
+                .unwrap();
+        assert_eq!(
+            greeting(&server_config).await,
+            r#"{"data":{"greeting":"hi"}}"#
+        );
+    }
+}
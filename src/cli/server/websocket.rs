@@ -0,0 +1,185 @@
+use std::future::ready;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use async_graphql::http::{WebSocket, WebSocketProtocols, WsMessage};
+use async_graphql::Data;
+use futures_util::{SinkExt, StreamExt};
+use hyper::header::{
+    HeaderValue, CONNECTION, SEC_WEBSOCKET_ACCEPT, SEC_WEBSOCKET_KEY, SEC_WEBSOCKET_PROTOCOL,
+    SEC_WEBSOCKET_VERSION, UPGRADE,
+};
+use hyper::{Body, Request, Response, StatusCode};
+use tokio_tungstenite::tungstenite::handshake::derive_accept_key;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::{CloseFrame, Role};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+
+use super::GRAPHQL_SLUG;
+use crate::core::app_context::AppContext;
+use crate::core::http::{create_request_context, SafelistExecutor};
+
+/// Checks if the request is a websocket upgrade on the graphql endpoint.
+pub fn is_upgrade_request(req: &Request<Body>) -> bool {
+    fn contains(value: Option<&HeaderValue>, token: &str) -> bool {
+        value
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| {
+                value
+                    .split(',')
+                    .any(|part| part.trim().eq_ignore_ascii_case(token))
+            })
+    }
+
+    req.method() == hyper::Method::GET
+        && req.uri().path() == GRAPHQL_SLUG
+        && contains(req.headers().get(CONNECTION), "upgrade")
+        && contains(req.headers().get(UPGRADE), "websocket")
+}
+
+/// Performs the websocket handshake and serves the graphql subscriptions over
+/// the upgraded connection using either `graphql-transport-ws` or the legacy
+/// `graphql-ws` protocol.
+pub async fn handle_upgrade(
+    mut req: Request<Body>,
+    app_ctx: Arc<AppContext>,
+) -> anyhow::Result<Response<Body>> {
+    let headers = req.headers();
+    let Some(key) = headers.get(SEC_WEBSOCKET_KEY) else {
+        return bad_request("missing sec-websocket-key header");
+    };
+    if headers.get(SEC_WEBSOCKET_VERSION).map(|v| v.as_bytes()) != Some(b"13") {
+        return bad_request("unsupported websocket version");
+    }
+
+    let protocol = headers
+        .get(SEC_WEBSOCKET_PROTOCOL)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|protocols| {
+            protocols
+                .split(',')
+                .find_map(|p| WebSocketProtocols::from_str(p.trim()).ok())
+        });
+    let Some(protocol) = protocol else {
+        return bad_request("unsupported websocket sub-protocol");
+    };
+
+    let accept = derive_accept_key(key.as_bytes());
+    let req_ctx = Arc::new(create_request_context(&req, &app_ctx));
+    // the client is verified with the headers of the upgrade request, since
+    // the subscriptions are executed with them
+    req_ctx.auth_ctx.validate(&req_ctx).await;
+    let upgrade = hyper::upgrade::on(&mut req);
+
+    tokio::spawn(async move {
+        let upgraded = match upgrade.await {
+            Ok(upgraded) => upgraded,
+            Err(err) => {
+                tracing::error!("websocket upgrade failed: {}", err);
+                return;
+            }
+        };
+        let socket = WebSocketStream::from_raw_socket(upgraded, Role::Server, None).await;
+        let (sink, stream) = socket.split();
+
+        let input = stream
+            .take_while(|message| ready(message.is_ok()))
+            .filter_map(|message| {
+                ready(match message {
+                    Ok(message @ (Message::Text(_) | Message::Binary(_))) => {
+                        Some(message.into_data())
+                    }
+                    _ => None,
+                })
+            });
+
+        let mut data = Data::default();
+        data.insert(req_ctx);
+
+        let schema = app_ctx.schema.clone();
+        let safelist = app_ctx
+            .blueprint
+            .server
+            .persisted_queries
+            .as_ref()
+            .and_then(|persisted_queries| persisted_queries.safelist.clone());
+        let output = match safelist {
+            Some(safelist) => {
+                let executor = SafelistExecutor::new(schema, safelist);
+                WebSocket::new(executor, input, protocol)
+                    .connection_data(data)
+                    .boxed()
+            }
+            None => WebSocket::new(schema, input, protocol)
+                .connection_data(data)
+                .boxed(),
+        };
+
+        let output = output.map(|message| {
+            Ok(match message {
+                WsMessage::Text(text) => Message::Text(text),
+                WsMessage::Close(code, reason) => Message::Close(Some(CloseFrame {
+                    code: CloseCode::from(code),
+                    reason: reason.into(),
+                })),
+            })
+        });
+
+        if let Err(err) = output.forward(sink.sink_map_err(anyhow::Error::from)).await {
+            tracing::debug!("websocket connection closed: {}", err);
+        }
+    });
+
+    Ok(Response::builder()
+        .status(StatusCode::SWITCHING_PROTOCOLS)
+        .header(CONNECTION, "upgrade")
+        .header(UPGRADE, "websocket")
+        .header(SEC_WEBSOCKET_ACCEPT, accept)
+        .header(SEC_WEBSOCKET_PROTOCOL, protocol.sec_websocket_protocol())
+        .body(Body::empty())?)
+}
+
+fn bad_request(message: &'static str) -> anyhow::Result<Response<Body>> {
+    Ok(Response::builder()
+        .status(StatusCode::BAD_REQUEST)
+        .body(Body::from(message))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(connection: &str, upgrade: &str, path: &str) -> Request<Body> {
+        Request::builder()
+            .uri(path)
+            .header(CONNECTION, connection)
+            .header(UPGRADE, upgrade)
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_is_upgrade_request() {
+        assert!(is_upgrade_request(&request(
+            "Upgrade",
+            "websocket",
+            "/graphql"
+        )));
+        assert!(is_upgrade_request(&request(
+            "keep-alive, Upgrade",
+            "WebSocket",
+            "/graphql"
+        )));
+        assert!(!is_upgrade_request(&request(
+            "Upgrade",
+            "websocket",
+            "/api"
+        )));
+        assert!(!is_upgrade_request(&request(
+            "keep-alive",
+            "websocket",
+            "/graphql"
+        )));
+    }
+}
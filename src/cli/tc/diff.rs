@@ -0,0 +1,134 @@
+use anyhow::{anyhow, Result};
+use colored::*;
+
+use crate::cli::fmt::Fmt;
+use crate::cli::CLIError;
+use crate::core::blueprint::Blueprint;
+use crate::core::config::reader::ConfigReader;
+use crate::core::runtime::TargetRuntime;
+use crate::core::schema_diff::{self, Change, Criticality};
+
+/// Prints the changes between the schemas of the two configs, and fails when
+/// some of them are breaking, so that it can gate the CI.
+pub(super) async fn diff_command(
+    old: &str,
+    new: &str,
+    json: bool,
+    runtime: TargetRuntime,
+) -> Result<()> {
+    let changes = changes(old, new, runtime).await?;
+
+    if json {
+        Fmt::display(serde_json::to_string_pretty(&changes)?);
+    } else if changes.is_empty() {
+        tracing::info!("No changes detected");
+    } else {
+        Fmt::display(
+            changes
+                .iter()
+                .map(format_change)
+                .collect::<Vec<_>>()
+                .join("\n"),
+        );
+    }
+
+    let breaking = changes
+        .iter()
+        .filter(|change| change.criticality == Criticality::Breaking)
+        .count();
+    if breaking > 0 {
+        return Err(anyhow!("{} breaking change(s) detected", breaking));
+    }
+    Ok(())
+}
+
+async fn changes(old: &str, new: &str, runtime: TargetRuntime) -> Result<Vec<Change>> {
+    let old = read_blueprint(old, runtime.clone()).await?;
+    let new = read_blueprint(new, runtime).await?;
+    schema_diff::diff(&old, &new)
+}
+
+async fn read_blueprint(file_path: &str, runtime: TargetRuntime) -> Result<Blueprint> {
+    // the reader caches the linked files, that could differ between the configs
+    let config_reader = ConfigReader::init(runtime);
+    let config_module = config_reader.read(file_path).await?;
+    Ok(Blueprint::try_from(&config_module).map_err(CLIError::from)?)
+}
+
+fn format_change(change: &Change) -> String {
+    let criticality = format!("[{}]", change.criticality);
+    let criticality = match change.criticality {
+        Criticality::Breaking => criticality.red(),
+        Criticality::Dangerous => criticality.yellow(),
+        Criticality::Safe => criticality.green(),
+    };
+    format!("{} {}", criticality.bold(), change.message)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use serde_json::json;
+
+    use super::*;
+
+    const OLD: &str = r#"
+        schema @server @upstream(baseURL: "http://localhost") { query: Query }
+        type Query { user(id: Int!): User @http(path: "/users/{{.args.id}}") }
+        type User { id: Int! name: String }
+    "#;
+
+    fn write_config(dir: &Path, name: &str, content: &str) -> String {
+        let path = dir.join(name);
+        std::fs::write(&path, content).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    fn runtime() -> TargetRuntime {
+        crate::cli::runtime::init(&Blueprint::default()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_safe_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let old = write_config(dir.path(), "old.graphql", OLD);
+        let new = write_config(
+            dir.path(),
+            "new.graphql",
+            &OLD.replace("name: String", "name: String email: String"),
+        );
+
+        let changes = changes(&old, &new, runtime()).await.unwrap();
+        assert_eq!(
+            serde_json::to_value(&changes).unwrap(),
+            json!([{
+                "criticality": "safe",
+                "path": "User.email",
+                "message": "Field `User.email` was added"
+            }])
+        );
+        assert!(diff_command(&old, &new, true, runtime()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_breaking_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let old = write_config(dir.path(), "old.graphql", OLD);
+        let new = write_config(dir.path(), "new.graphql", &OLD.replace(" name: String", ""));
+
+        let changes = changes(&old, &new, runtime()).await.unwrap();
+        assert_eq!(
+            serde_json::to_value(&changes).unwrap(),
+            json!([{
+                "criticality": "breaking",
+                "path": "User.name",
+                "message": "Field `User.name` was removed"
+            }])
+        );
+
+        // the error makes the command exit with a failure
+        let err = diff_command(&old, &new, true, runtime()).await.unwrap_err();
+        assert_eq!(err.to_string(), "1 breaking change(s) detected");
+    }
+}
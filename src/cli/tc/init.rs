@@ -14,7 +14,13 @@ pub(super) async fn init_command(runtime: TargetRuntime, folder_path: &str) -> R
 
     let selection = select_prompt(
         "Please select the format in which you want to generate the config.",
-        vec![Source::GraphQL, Source::Json, Source::Yml],
+        vec![
+            Source::GraphQL,
+            Source::Json,
+            Source::Json5,
+            Source::Yml,
+            Source::Toml,
+        ],
     )?;
 
     let tailcallrc = include_str!("../../../generated/.tailcallrc.graphql");
@@ -102,8 +108,9 @@ async fn create_main(
 
     let content = match source {
         Source::GraphQL => config.to_sdl(),
-        Source::Json => config.to_json(true)?,
+        Source::Json | Source::Json5 => config.to_json(true)?,
         Source::Yml => config.to_yaml()?,
+        Source::Toml => config.to_toml()?,
     };
 
     let path = folder_path
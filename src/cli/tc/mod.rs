@@ -1,4 +1,5 @@
 mod check;
+mod diff;
 mod gen;
 mod helpers;
 mod init;
@@ -4,7 +4,7 @@ use convert_case::{Case, Casing};
 use dotenvy::dotenv;
 
 use super::helpers::TRACKER;
-use super::{check, gen, init, start};
+use super::{check, diff, gen, init, start};
 use crate::cli::command::{Cli, Command};
 use crate::cli::{self, update_checker};
 use crate::core::blueprint::Blueprint;
@@ -17,7 +17,7 @@ pub async fn run() -> Result<()> {
     }
     let cli = Cli::parse();
     update_checker::check_for_update().await;
-    let runtime = cli::runtime::init(&Blueprint::default());
+    let runtime = cli::runtime::init(&Blueprint::default())?;
     let config_reader = ConfigReader::init(runtime.clone());
 
     // Initialize ping event every 60 seconds
@@ -35,8 +35,8 @@ pub async fn run() -> Result<()> {
 
 async fn run_command(cli: Cli, config_reader: ConfigReader, runtime: TargetRuntime) -> Result<()> {
     match cli.command {
-        Command::Start { file_paths } => {
-            start::start_command(file_paths, &config_reader).await?;
+        Command::Start { file_paths, watch } => {
+            start::start_command(file_paths, watch, &config_reader, runtime).await?;
         }
         Command::Check { file_paths, n_plus_one_queries, schema, format } => {
             check::check_command(
@@ -51,6 +51,9 @@ async fn run_command(cli: Cli, config_reader: ConfigReader, runtime: TargetRunti
         Command::Gen { file_path } => {
             gen::gen_command(&file_path, runtime).await?;
         }
+        Command::Diff { old, new, json } => {
+            diff::diff_command(&old, &new, json, runtime).await?;
+        }
     }
     Ok(())
 }
@@ -1,4 +1,5 @@
-use std::sync::Arc;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
 
 use async_graphql::dynamic::{self, DynamicRequest};
 use async_graphql_value::ConstValue;
@@ -7,14 +8,16 @@ use hyper::body::Bytes;
 use crate::core::async_graphql_hyper::OperationId;
 use crate::core::auth::context::GlobalAuthContext;
 use crate::core::blueprint::Type::ListType;
-use crate::core::blueprint::{Blueprint, Definition, SchemaModifiers};
+use crate::core::blueprint::{Blueprint, Definition, Retry, SchemaModifiers};
+use crate::core::cache::InMemoryCache;
 use crate::core::data_loader::{DataLoader, DedupeResult};
-use crate::core::graphql::GraphqlDataLoader;
+use crate::core::graphql::{EntityDataLoader, EntityRequest, GraphqlDataLoader};
 use crate::core::grpc;
 use crate::core::grpc::data_loader::GrpcDataLoader;
 use crate::core::http::{DataLoaderRequest, HttpDataLoader, Response};
 use crate::core::ir::model::{DataLoaderId, IoId, IO, IR};
 use crate::core::ir::Error;
+use crate::core::rate_limit::RateLimitedHttp;
 use crate::core::rest::{Checked, EndpointSet};
 use crate::core::runtime::TargetRuntime;
 
@@ -24,21 +27,39 @@ pub struct AppContext {
     pub blueprint: Blueprint,
     pub http_data_loaders: Arc<Vec<DataLoader<DataLoaderRequest, HttpDataLoader>>>,
     pub gql_data_loaders: Arc<Vec<DataLoader<DataLoaderRequest, GraphqlDataLoader>>>,
+    pub entity_data_loaders: Arc<Vec<DataLoader<EntityRequest, EntityDataLoader>>>,
     pub grpc_data_loaders: Arc<Vec<DataLoader<grpc::DataLoaderRequest, GrpcDataLoader>>>,
     pub endpoints: EndpointSet<Checked>,
     pub auth_ctx: Arc<GlobalAuthContext>,
     pub dedupe_handler: Arc<DedupeResult<IoId, ConstValue, Error>>,
     pub dedupe_operation_handler: DedupeResult<OperationId, Response<Bytes>, Error>,
+    pub revalidating: Arc<Mutex<HashSet<IoId>>>,
+    pub response_cache: Arc<InMemoryCache<OperationId, Response<Bytes>>>,
 }
 
 impl AppContext {
     pub fn new(
         mut blueprint: Blueprint,
-        runtime: TargetRuntime,
+        mut runtime: TargetRuntime,
         endpoints: EndpointSet<Checked>,
     ) -> Self {
+        if let Some(rate) = &blueprint.upstream.rate_limit {
+            let limiter = runtime.rate_limiter.clone();
+            runtime.http = Arc::new(RateLimitedHttp::new(
+                runtime.http.clone(),
+                limiter.clone(),
+                rate.clone(),
+            ));
+            runtime.http2_only = Arc::new(RateLimitedHttp::new(
+                runtime.http2_only.clone(),
+                limiter,
+                rate.clone(),
+            ));
+        }
+
         let mut http_data_loaders = vec![];
         let mut gql_data_loaders = vec![];
+        let mut entity_data_loaders = vec![];
         let mut grpc_data_loaders = vec![];
 
         for def in blueprint.definitions.iter_mut() {
@@ -49,9 +70,16 @@ impl AppContext {
                     field.map_expr(|expr| {
                         expr.modify(|expr| match expr {
                             IR::IO(io) => match io {
-                                IO::Http { req_template, group_by, http_filter, .. } => {
+                                IO::Http {
+                                    req_template,
+                                    group_by,
+                                    http_filter,
+                                    retry,
+                                    oauth2,
+                                    ..
+                                } => {
                                     let data_loader = HttpDataLoader::new(
-                                        runtime.clone(),
+                                        with_retry(&runtime, retry),
                                         group_by.clone(),
                                         matches!(of_type, ListType { .. }),
                                     )
@@ -62,6 +90,8 @@ impl AppContext {
                                         group_by: group_by.clone(),
                                         dl_id: Some(DataLoaderId::new(http_data_loaders.len())),
                                         http_filter: http_filter.clone(),
+                                        retry: retry.clone(),
+                                        oauth2: oauth2.clone(),
                                     }));
 
                                     http_data_loaders.push(data_loader);
@@ -69,9 +99,31 @@ impl AppContext {
                                     result
                                 }
 
-                                IO::GraphQL { req_template, field_name, batch, .. } => {
+                                IO::GraphQL { req_template, field_name, batch, retry, .. }
+                                    if req_template.entity.is_some() =>
+                                {
+                                    let entity_data_loader =
+                                        EntityDataLoader::new(with_retry(&runtime, retry))
+                                            .into_data_loader(
+                                                upstream_batch.clone().unwrap_or_default(),
+                                            );
+
+                                    let result = Some(IR::IO(IO::GraphQL {
+                                        req_template: req_template.clone(),
+                                        field_name: field_name.clone(),
+                                        batch: *batch,
+                                        dl_id: Some(DataLoaderId::new(entity_data_loaders.len())),
+                                        retry: retry.clone(),
+                                    }));
+
+                                    entity_data_loaders.push(entity_data_loader);
+
+                                    result
+                                }
+
+                                IO::GraphQL { req_template, field_name, batch, retry, .. } => {
                                     let graphql_data_loader =
-                                        GraphqlDataLoader::new(runtime.clone(), *batch)
+                                        GraphqlDataLoader::new(with_retry(&runtime, retry), *batch)
                                             .into_data_loader(
                                                 upstream_batch.clone().unwrap_or_default(),
                                             );
@@ -81,6 +133,7 @@ impl AppContext {
                                         field_name: field_name.clone(),
                                         batch: *batch,
                                         dl_id: Some(DataLoaderId::new(gql_data_loaders.len())),
+                                        retry: retry.clone(),
                                     }));
 
                                     gql_data_loaders.push(graphql_data_loader);
@@ -88,9 +141,9 @@ impl AppContext {
                                     result
                                 }
 
-                                IO::Grpc { req_template, group_by, .. } => {
+                                IO::Grpc { req_template, group_by, retry, .. } => {
                                     let data_loader = GrpcDataLoader {
-                                        runtime: runtime.clone(),
+                                        runtime: with_retry(&runtime, retry),
                                         operation: req_template.operation.clone(),
                                         group_by: group_by.clone(),
                                     };
@@ -102,6 +155,7 @@ impl AppContext {
                                         req_template: req_template.clone(),
                                         group_by: group_by.clone(),
                                         dl_id: Some(DataLoaderId::new(grpc_data_loaders.len())),
+                                        retry: retry.clone(),
                                     }));
 
                                     grpc_data_loaders.push(data_loader);
@@ -130,11 +184,14 @@ impl AppContext {
             blueprint,
             http_data_loaders: Arc::new(http_data_loaders),
             gql_data_loaders: Arc::new(gql_data_loaders),
+            entity_data_loaders: Arc::new(entity_data_loaders),
             grpc_data_loaders: Arc::new(grpc_data_loaders),
             endpoints,
             auth_ctx: Arc::new(auth_ctx),
             dedupe_handler: Arc::new(DedupeResult::new(false)),
             dedupe_operation_handler: DedupeResult::new(false),
+            revalidating: Arc::new(Mutex::new(HashSet::new())),
+            response_cache: Arc::new(InMemoryCache::new()),
         }
     }
 
@@ -142,3 +199,10 @@ impl AppContext {
         self.schema.execute(request).await
     }
 }
+
+fn with_retry(runtime: &TargetRuntime, retry: &Option<Retry>) -> TargetRuntime {
+    match retry {
+        Some(retry) => runtime.with_retry(retry),
+        None => runtime.clone(),
+    }
+}
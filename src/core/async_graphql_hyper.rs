@@ -4,6 +4,8 @@ use std::hash::{Hash, Hasher};
 use anyhow::Result;
 use async_graphql::parser::types::{ExecutableDocument, OperationType};
 use async_graphql::{BatchResponse, Executor, Value};
+use futures_util::stream::{self, BoxStream};
+use futures_util::{Stream, StreamExt, TryStreamExt};
 use headers::HeaderMap;
 use hyper::header::{HeaderValue, CACHE_CONTROL, CONTENT_TYPE};
 use hyper::{Body, Response, StatusCode};
@@ -11,9 +13,21 @@ use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use tailcall_hasher::TailcallHasher;
 
+use crate::core::http::sse;
+use crate::core::jit::JITExecutor;
+
+/// Content type of the responses delivered incrementally.
+pub const MULTIPART_MIXED: &str = "multipart/mixed; boundary=\"-\"";
+
 #[derive(PartialEq, Eq, Clone, Hash, Debug)]
 pub struct OperationId(u64);
 
+impl OperationId {
+    pub fn new(id: u64) -> Self {
+        Self(id)
+    }
+}
+
 #[async_trait::async_trait]
 pub trait GraphQLRequestLike: Hash + Send {
     fn data<D: Any + Clone + Send + Sync>(self, data: D) -> Self;
@@ -21,8 +35,21 @@ pub trait GraphQLRequestLike: Hash + Send {
     where
         E: Executor;
 
+    /// Executes the request and returns every incremental result as a
+    /// separate response.
+    fn execute_stream<E>(self, executor: &E) -> GraphQLStreamResponse
+    where
+        E: Executor;
+
+    /// Executes the request on the JIT engine delivering the results of
+    /// `@defer` and `@stream` in subsequent payloads.
+    fn execute_incremental(self, executor: &JITExecutor) -> GraphQLStreamResponse;
+
     fn parse_query(&mut self) -> Option<&ExecutableDocument>;
 
+    /// Every request that is executed as part of this request.
+    fn requests_mut(&mut self) -> Vec<&mut async_graphql::Request>;
+
     fn is_query(&mut self) -> bool {
         self.parse_query()
             .map(|a| {
@@ -81,9 +108,36 @@ impl GraphQLRequestLike for GraphQLBatchRequest {
         GraphQLResponse(executor.execute_batch(self.0).await)
     }
 
+    /// Batches can't be streamed, so all of the responses are sent once the
+    /// whole batch is executed.
+    fn execute_stream<E>(self, executor: &E) -> GraphQLStreamResponse
+    where
+        E: Executor,
+    {
+        let executor = executor.clone();
+        let stream = stream::once(async move { executor.execute_batch(self.0).await }).flat_map(
+            |response| match response {
+                BatchResponse::Single(response) => stream::iter(vec![response]),
+                BatchResponse::Batch(responses) => stream::iter(responses),
+            },
+        );
+
+        GraphQLStreamResponse::new(stream)
+    }
+
+    /// Incremental delivery isn't supported for batches, so every request is
+    /// resolved completely.
+    fn execute_incremental(self, executor: &JITExecutor) -> GraphQLStreamResponse {
+        self.execute_stream(executor)
+    }
+
     fn parse_query(&mut self) -> Option<&ExecutableDocument> {
         None
     }
+
+    fn requests_mut(&mut self) -> Vec<&mut async_graphql::Request> {
+        self.0.iter_mut().collect()
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -115,9 +169,25 @@ impl GraphQLRequestLike for GraphQLRequest {
         GraphQLResponse(executor.execute(self.0).await.into())
     }
 
+    /// Shortcut method to execute the request as a stream on the schema.
+    fn execute_stream<E>(self, executor: &E) -> GraphQLStreamResponse
+    where
+        E: Executor,
+    {
+        GraphQLStreamResponse::new(executor.execute_stream(self.0, None))
+    }
+
+    fn execute_incremental(self, executor: &JITExecutor) -> GraphQLStreamResponse {
+        executor.execute_incremental(self.0)
+    }
+
     fn parse_query(&mut self) -> Option<&ExecutableDocument> {
         self.0.parsed_query().ok()
     }
+
+    fn requests_mut(&mut self) -> Vec<&mut async_graphql::Request> {
+        vec![&mut self.0]
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -267,6 +337,55 @@ impl GraphQLResponse {
     }
 }
 
+/// Stream of serialized responses produced by a subscription or an
+/// incrementally delivered query.
+pub struct GraphQLStreamResponse(pub BoxStream<'static, serde_json::Result<String>>);
+
+impl GraphQLStreamResponse {
+    pub fn new<T: Serialize>(stream: impl Stream<Item = T> + Send + 'static) -> Self {
+        Self(
+            stream
+                .map(|response| serde_json::to_string(&response))
+                .boxed(),
+        )
+    }
+
+    /// Transforms the stream into a `text/event-stream` response following the
+    /// "GraphQL over SSE" protocol: every result is sent as a `next` event and
+    /// the stream is terminated with a `complete` event.
+    pub fn into_sse_response(self) -> Result<Response<hyper::Body>> {
+        let events = self
+            .0
+            .map_ok(sse::Event::next)
+            .chain(stream::once(async { Ok(sse::Event::complete()) }))
+            .map_ok(|event| event.to_string());
+
+        Ok(Response::builder()
+            .status(StatusCode::OK)
+            .header(CONTENT_TYPE, sse::TEXT_EVENT_STREAM)
+            .header(CACHE_CONTROL, "no-cache")
+            .body(Body::wrap_stream(events))?)
+    }
+
+    /// Transforms the stream into a `multipart/mixed` response as expected by
+    /// clients of the incremental delivery protocol: every payload is sent as
+    /// a separate json part and the body is closed with the final boundary.
+    pub fn into_multipart_response(self) -> Result<Response<hyper::Body>> {
+        let parts = self
+            .0
+            .map_ok(|payload| {
+                format!("\r\n---\r\nContent-Type: application/json; charset=utf-8\r\n\r\n{payload}")
+            })
+            .chain(stream::once(async { Ok("\r\n-----\r\n".to_string()) }));
+
+        Ok(Response::builder()
+            .status(StatusCode::OK)
+            .header(CONTENT_TYPE, MULTIPART_MIXED)
+            .header(CACHE_CONTROL, "no-cache")
+            .body(Body::wrap_stream(parts))?)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use async_graphql::{Name, Response, ServerError, Value};
@@ -331,6 +450,55 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_into_sse_response() {
+        let responses = [1, 2].map(|id| {
+            let data = IndexMap::from([(Name::new("id"), Value::from(id))]);
+            Response::new(Value::Object(data))
+        });
+
+        let response = GraphQLStreamResponse::new(stream::iter(responses));
+        let sse_response = response.into_sse_response().unwrap();
+
+        assert_eq!(sse_response.status(), StatusCode::OK);
+        assert_eq!(sse_response.headers()["content-type"], "text/event-stream");
+        assert_eq!(
+            hyper::body::to_bytes(sse_response.into_body())
+                .await
+                .unwrap(),
+            "event: next\ndata: {\"data\":{\"id\":1}}\n\n\
+             event: next\ndata: {\"data\":{\"id\":2}}\n\n\
+             event: complete\ndata: \n\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_into_multipart_response() {
+        let payloads = [
+            json!({"data": {"id": 1}, "hasNext": true}),
+            json!({"hasNext": false}),
+        ];
+
+        let response = GraphQLStreamResponse::new(stream::iter(payloads));
+        let multipart_response = response.into_multipart_response().unwrap();
+
+        assert_eq!(multipart_response.status(), StatusCode::OK);
+        assert_eq!(
+            multipart_response.headers()["content-type"],
+            "multipart/mixed; boundary=\"-\""
+        );
+        assert_eq!(
+            hyper::body::to_bytes(multipart_response.into_body())
+                .await
+                .unwrap(),
+            "\r\n---\r\nContent-Type: application/json; charset=utf-8\r\n\r\n\
+             {\"data\":{\"id\":1},\"hasNext\":true}\
+             \r\n---\r\nContent-Type: application/json; charset=utf-8\r\n\r\n\
+             {\"hasNext\":false}\
+             \r\n-----\r\n"
+        );
+    }
+
     #[tokio::test]
     async fn test_to_rest_response_with_error() {
         let errors = ["Some error", "Another error"];
@@ -0,0 +1,137 @@
+use subtle::ConstantTimeEq;
+
+use super::error::Error;
+use super::verification::Verification;
+use super::verify::Verify;
+use crate::core::blueprint;
+use crate::core::http::RequestContext;
+
+/// Verifies the static API keys of the clients. The keys are compared in
+/// constant time, so that the time of the verification doesn't reveal how
+/// much of a key was guessed.
+pub struct ApiKeyVerifier {
+    options: blueprint::ApiKeys,
+}
+
+impl ApiKeyVerifier {
+    pub fn new(options: blueprint::ApiKeys) -> Self {
+        Self { options }
+    }
+
+    /// Reads the key from the header of the request, or from its query if it
+    /// isn't in the header.
+    fn key(&self, req_ctx: &RequestContext) -> Option<String> {
+        let header = self.options.header.as_ref().and_then(|header| {
+            req_ctx
+                .request_headers
+                .get(header)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.to_string())
+        });
+
+        header.or_else(|| {
+            let name = self.options.query.as_ref()?;
+            let query = req_ctx.request_uri.as_ref()?.query()?;
+
+            url::form_urlencoded::parse(query.as_bytes())
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.into_owned())
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl Verify for ApiKeyVerifier {
+    async fn verify(&self, req_ctx: &RequestContext) -> Verification {
+        let Some(key) = self.key(req_ctx) else {
+            return Verification::fail(Error::Missing);
+        };
+
+        // every key is compared, instead of stopping at the matching one
+        let mut claims = None;
+        for (api_key, metadata) in &self.options.keys {
+            let matches: bool = api_key.as_bytes().ct_eq(key.as_bytes()).into();
+            if matches && claims.is_none() {
+                claims = Some(metadata);
+            }
+        }
+
+        match claims {
+            Some(claims) => Verification::succeed_with(claims.clone()),
+            None => Verification::fail(Error::Invalid),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::HeaderValue;
+    use serde_json::json;
+
+    use super::*;
+
+    fn verifier(header: Option<&str>, query: Option<&str>) -> ApiKeyVerifier {
+        let metadata = json!({"owner": "billing", "roles": ["admin"]});
+
+        ApiKeyVerifier::new(blueprint::ApiKeys {
+            keys: vec![
+                (
+                    "key-1".to_string(),
+                    serde_json::from_value(metadata).unwrap(),
+                ),
+                ("key-2".to_string(), Default::default()),
+            ],
+            header: header.map(|header| header.to_string()),
+            query: query.map(|query| query.to_string()),
+        })
+    }
+
+    fn request(header: Option<&str>, uri: &str) -> RequestContext {
+        let mut req_ctx = RequestContext::default().request_uri(uri.parse().unwrap());
+        if let Some(key) = header {
+            req_ctx
+                .request_headers
+                .insert("x-api-key", HeaderValue::from_str(key).unwrap());
+        }
+
+        req_ctx
+    }
+
+    #[tokio::test]
+    async fn test_header_key() {
+        let verifier = verifier(Some("X-API-Key"), None);
+
+        let actual = verifier.verify(&request(Some("key-1"), "/graphql")).await;
+        let claims = json!({"owner": "billing", "roles": ["admin"]});
+        let expected = Verification::succeed_with(serde_json::from_value(claims).unwrap());
+        assert_eq!(actual, expected);
+
+        let actual = verifier.verify(&request(Some("key-2"), "/graphql")).await;
+        assert_eq!(actual, Verification::succeed());
+    }
+
+    #[tokio::test]
+    async fn test_query_key() {
+        let verifier = verifier(Some("X-API-Key"), Some("api_key"));
+
+        let actual = verifier
+            .verify(&request(None, "/graphql?api_key=key-2"))
+            .await;
+        assert_eq!(actual, Verification::succeed());
+
+        let verifier = self::verifier(None, Some("api_key"));
+        let actual = verifier.verify(&request(Some("key-2"), "/graphql")).await;
+        assert_eq!(actual, Verification::fail(Error::Missing));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_key() {
+        let verifier = verifier(Some("X-API-Key"), Some("api_key"));
+
+        let actual = verifier.verify(&request(Some("key-3"), "/graphql")).await;
+        assert_eq!(actual, Verification::fail(Error::Invalid));
+
+        let actual = verifier.verify(&request(None, "/graphql")).await;
+        assert_eq!(actual, Verification::fail(Error::Missing));
+    }
+}
@@ -0,0 +1,124 @@
+use serde_json::Value;
+
+use super::error::Error;
+use super::verification::Claims;
+use crate::core::blueprint::Protected;
+
+/// Checks that the claims of the client satisfy the requirements of the
+/// protected field.
+pub fn authorize(claims: &Claims, protected: &Protected) -> Result<(), Error> {
+    let roles = values(claims.get("roles"));
+    let has_role = protected.roles.is_empty()
+        || protected
+            .roles
+            .iter()
+            .any(|role| roles.contains(&role.as_str()));
+
+    let scopes = [claims.get("scope"), claims.get("scp")]
+        .into_iter()
+        .flat_map(values)
+        .collect::<Vec<_>>();
+    let has_scopes = protected
+        .scopes
+        .iter()
+        .all(|scope| scopes.contains(&scope.as_str()));
+
+    let has_claims = protected.claims.iter().all(|(name, expected)| {
+        claims
+            .get(name)
+            .is_some_and(|value| matches(value, expected))
+    });
+
+    if has_role && has_scopes && has_claims {
+        Ok(())
+    } else {
+        Err(Error::Forbidden)
+    }
+}
+
+/// Reads the values of a claim that is either a list of strings or a string
+/// of space separated values, as the `scope` claim of OAuth 2.0.
+fn values(claim: Option<&Value>) -> Vec<&str> {
+    match claim {
+        Some(Value::String(value)) => value.split_whitespace().collect(),
+        Some(Value::Array(values)) => values.iter().filter_map(Value::as_str).collect(),
+        _ => vec![],
+    }
+}
+
+fn matches(value: &Value, expected: &Value) -> bool {
+    match value {
+        Value::Array(values) if !expected.is_array() => values.contains(expected),
+        value => value == expected,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn claims(value: Value) -> Claims {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn protected(roles: &[&str], scopes: &[&str], claims: Value) -> Protected {
+        Protected {
+            roles: roles.iter().map(|role| role.to_string()).collect(),
+            scopes: scopes.iter().map(|scope| scope.to_string()).collect(),
+            claims: serde_json::from_value(claims).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_no_requirements() {
+        let actual = authorize(&Claims::default(), &Protected::default());
+        assert_eq!(actual, Ok(()));
+    }
+
+    #[test]
+    fn test_roles() {
+        let protected = protected(&["admin", "editor"], &[], json!({}));
+
+        let actual = authorize(&claims(json!({"roles": ["viewer", "editor"]})), &protected);
+        assert_eq!(actual, Ok(()));
+
+        let actual = authorize(&claims(json!({"roles": "viewer"})), &protected);
+        assert_eq!(actual, Err(Error::Forbidden));
+    }
+
+    #[test]
+    fn test_scopes() {
+        let protected = protected(&[], &["read:users", "write:users"], json!({}));
+
+        let actual = authorize(
+            &claims(json!({"scope": "write:users read:users"})),
+            &protected,
+        );
+        assert_eq!(actual, Ok(()));
+
+        let actual = authorize(&claims(json!({"scp": ["read:users"]})), &protected);
+        assert_eq!(actual, Err(Error::Forbidden));
+    }
+
+    #[test]
+    fn test_claims() {
+        let protected = protected(&[], &[], json!({"tenant": "acme", "groups": "staff"}));
+
+        let actual = authorize(
+            &claims(json!({"tenant": "acme", "groups": ["staff", "ops"]})),
+            &protected,
+        );
+        assert_eq!(actual, Ok(()));
+
+        let actual = authorize(
+            &claims(json!({"tenant": "other", "groups": "staff"})),
+            &protected,
+        );
+        assert_eq!(actual, Err(Error::Forbidden));
+
+        let actual = authorize(&claims(json!({"groups": "staff"})), &protected);
+        assert_eq!(actual, Err(Error::Forbidden));
+    }
+}
@@ -1,15 +1,18 @@
+use std::collections::{BTreeMap, BTreeSet};
+
 use headers::authorization::Basic;
 use headers::{Authorization, HeaderMapExt};
 use htpasswd_verify::Htpasswd;
 
 use super::error::Error;
-use super::verification::Verification;
+use super::verification::{Claims, Verification};
 use super::verify::Verify;
 use crate::core::blueprint;
 use crate::core::http::RequestContext;
 
 pub struct BasicVerifier {
     verifier: Htpasswd<'static>,
+    groups: BTreeMap<String, BTreeSet<String>>,
 }
 
 #[async_trait::async_trait]
@@ -28,7 +31,7 @@ impl Verify for BasicVerifier {
             return Verification::fail(Error::Missing);
         };
         if self.verifier.check(header.username(), header.password()) {
-            Verification::succeed()
+            Verification::succeed_with(self.claims(header.username()))
         } else {
             Verification::fail(Error::Invalid)
         }
@@ -37,7 +40,22 @@ impl Verify for BasicVerifier {
 
 impl BasicVerifier {
     pub fn new(options: blueprint::Basic) -> Self {
-        Self { verifier: Htpasswd::new_owned(&options.htpasswd) }
+        Self {
+            verifier: Htpasswd::new_owned(&options.htpasswd),
+            groups: options.groups,
+        }
+    }
+
+    /// The claims of the user, with its groups as the roles.
+    fn claims(&self, username: &str) -> Claims {
+        let mut claims = Claims::new();
+        claims.insert("sub".to_string(), username.into());
+        if let Some(groups) = self.groups.get(username) {
+            let roles = groups.iter().cloned().collect::<Vec<_>>();
+            claims.insert("roles".to_string(), roles.into());
+        }
+
+        claims
     }
 }
 
@@ -58,10 +76,21 @@ testuser3:{SHA}Y2fEjdGT1W6nsLqtJbGUVeUp9e4=
 
     impl blueprint::Basic {
         pub fn test_value() -> Self {
-            Self { htpasswd: HTPASSWD_TEST.to_owned() }
+            let groups = [("testuser1".to_string(), ["admin".to_string()].into())].into();
+            Self { htpasswd: HTPASSWD_TEST.to_owned(), groups }
         }
     }
 
+    pub fn basic_claims(username: &str) -> Claims {
+        let mut claims = Claims::new();
+        claims.insert("sub".to_string(), username.into());
+        if username == "testuser1" {
+            claims.insert("roles".to_string(), vec!["admin"].into());
+        }
+
+        claims
+    }
+
     pub fn create_basic_auth_request(username: &str, password: &str) -> RequestContext {
         let mut req_context = RequestContext::default();
 
@@ -94,7 +123,10 @@ testuser3:{SHA}Y2fEjdGT1W6nsLqtJbGUVeUp9e4=
         let validation = provider
             .verify(&create_basic_auth_request("testuser1", "password123"))
             .await;
-        assert_eq!(validation, Verification::succeed());
+        assert_eq!(
+            validation,
+            Verification::succeed_with(basic_claims("testuser1"))
+        );
     }
 
     #[tokio::test]
@@ -103,7 +135,10 @@ testuser3:{SHA}Y2fEjdGT1W6nsLqtJbGUVeUp9e4=
         let validation = provider
             .verify(&create_basic_auth_request("testuser2", "mypassword"))
             .await;
-        assert_eq!(validation, Verification::succeed());
+        assert_eq!(
+            validation,
+            Verification::succeed_with(basic_claims("testuser2"))
+        );
     }
 
     #[tokio::test]
@@ -112,7 +147,10 @@ testuser3:{SHA}Y2fEjdGT1W6nsLqtJbGUVeUp9e4=
         let validation = provider
             .verify(&create_basic_auth_request("testuser3", "abc123"))
             .await;
-        assert_eq!(validation, Verification::succeed());
+        assert_eq!(
+            validation,
+            Verification::succeed_with(basic_claims("testuser3"))
+        );
     }
 
     #[tokio::test]
@@ -129,6 +167,6 @@ testuser3:{SHA}Y2fEjdGT1W6nsLqtJbGUVeUp9e4=
 
     // Helper function for setting up the provider
     fn setup_provider() -> BasicVerifier {
-        BasicVerifier::new(blueprint::Basic { htpasswd: HTPASSWD_TEST.to_owned() })
+        BasicVerifier::new(blueprint::Basic::test_value())
     }
 }
@@ -0,0 +1,158 @@
+use simple_asn1::ASN1Block;
+
+use super::error::Error;
+use super::verification::Verification;
+use super::verify::Verify;
+use crate::core::http::RequestContext;
+
+/// Authenticates the clients by the certificates that the server verified
+/// during the TLS handshake. The claims of a client are its subject, as in
+/// `{"subject": "CN=billing,O=Acme", "CN": "billing", "O": "Acme"}`.
+pub struct ClientCertVerifier;
+
+#[async_trait::async_trait]
+impl Verify for ClientCertVerifier {
+    async fn verify(&self, req_ctx: &RequestContext) -> Verification {
+        let Some(cert) = &req_ctx.client_cert else {
+            return Verification::fail(Error::Missing);
+        };
+
+        match subject(cert) {
+            Some(subject) => Verification::succeed_with(claims(&subject)),
+            None => Verification::fail(Error::Invalid),
+        }
+    }
+}
+
+fn claims(subject: &[(String, String)]) -> serde_json::Map<String, serde_json::Value> {
+    let mut claims = serde_json::Map::new();
+    // the distinguished name lists the attributes in the reverse order, as
+    // described by RFC 4514
+    let name = subject
+        .iter()
+        .rev()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect::<Vec<_>>()
+        .join(",");
+    claims.insert("subject".to_string(), name.into());
+
+    for (key, value) in subject {
+        claims
+            .entry(key.clone())
+            .or_insert_with(|| value.clone().into());
+    }
+
+    claims
+}
+
+/// Reads the attributes of the subject of a DER certificate, in their order in
+/// the certificate.
+fn subject(der: &[u8]) -> Option<Vec<(String, String)>> {
+    let blocks = simple_asn1::from_der(der).ok()?;
+    let ASN1Block::Sequence(_, certificate) = blocks.first()? else {
+        return None;
+    };
+    let ASN1Block::Sequence(_, tbs_certificate) = certificate.first()? else {
+        return None;
+    };
+
+    // the version is only present in the certificates after the first version
+    let fields = match tbs_certificate.first()? {
+        ASN1Block::Explicit(..) => &tbs_certificate[1..],
+        _ => &tbs_certificate[..],
+    };
+    // serial number, signature, issuer, validity and subject
+    let ASN1Block::Sequence(_, names) = fields.get(4)? else {
+        return None;
+    };
+
+    let mut subject = Vec::new();
+    for name in names {
+        let ASN1Block::Set(_, attributes) = name else {
+            return None;
+        };
+
+        for attribute in attributes {
+            let ASN1Block::Sequence(_, attribute) = attribute else {
+                return None;
+            };
+            let (Some(ASN1Block::ObjectIdentifier(_, oid)), Some(value)) =
+                (attribute.first(), attribute.get(1))
+            else {
+                return None;
+            };
+            let oid = oid
+                .as_vec::<u64>()
+                .ok()?
+                .iter()
+                .map(|arc| arc.to_string())
+                .collect::<Vec<_>>()
+                .join(".");
+
+            let value = match value {
+                ASN1Block::UTF8String(_, value)
+                | ASN1Block::PrintableString(_, value)
+                | ASN1Block::TeletexString(_, value)
+                | ASN1Block::IA5String(_, value)
+                | ASN1Block::UniversalString(_, value)
+                | ASN1Block::BMPString(_, value) => value.clone(),
+                _ => continue,
+            };
+
+            subject.push((attribute_name(&oid).unwrap_or(&oid).to_string(), value));
+        }
+    }
+
+    Some(subject)
+}
+
+fn attribute_name(oid: &str) -> Option<&'static str> {
+    match oid {
+        "2.5.4.3" => Some("CN"),
+        "2.5.4.6" => Some("C"),
+        "2.5.4.7" => Some("L"),
+        "2.5.4.8" => Some("ST"),
+        "2.5.4.9" => Some("STREET"),
+        "2.5.4.10" => Some("O"),
+        "2.5.4.11" => Some("OU"),
+        "0.9.2342.19200300.100.1.1" => Some("UID"),
+        "0.9.2342.19200300.100.1.25" => Some("DC"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rustls_pki_types::CertificateDer;
+    use serde_json::json;
+
+    use super::*;
+
+    fn client_cert() -> CertificateDer<'static> {
+        let pem = include_str!("../../../tests/server/config/client.crt");
+        let certs = rustls_pemfile::certs(&mut pem.as_bytes()).unwrap();
+
+        CertificateDer::from(certs[0].clone())
+    }
+
+    #[tokio::test]
+    async fn test_verified_client_cert() {
+        let req_ctx = RequestContext::default().client_cert(client_cert());
+
+        let actual = ClientCertVerifier.verify(&req_ctx).await;
+        let claims = json!({
+            "subject": "CN=billing,OU=Payments,O=Acme",
+            "O": "Acme",
+            "OU": "Payments",
+            "CN": "billing"
+        });
+        let expected = Verification::succeed_with(serde_json::from_value(claims).unwrap());
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_missing_client_cert() {
+        let actual = ClientCertVerifier.verify(&RequestContext::default()).await;
+        assert_eq!(actual, Verification::fail(Error::Missing));
+    }
+}
@@ -1,6 +1,6 @@
 use std::sync::{Arc, RwLock};
 
-use super::verification::Verification;
+use super::verification::{Claims, Verification};
 use super::verify::{AuthVerifier, Verify};
 use crate::core::blueprint::Auth;
 use crate::core::http::RequestContext;
@@ -48,6 +48,24 @@ impl AuthContext {
 
         result
     }
+
+    /// Returns the claims of the client, if it was already verified
+    /// successfully.
+    pub fn claims(&self) -> Option<Arc<Claims>> {
+        match self.auth_result.read().unwrap().as_ref() {
+            Some(Verification::Succeed(claims)) => Some(claims.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl Clone for AuthContext {
+    fn clone(&self) -> Self {
+        Self {
+            auth_result: RwLock::new(self.auth_result.read().unwrap().clone()),
+            global_ctx: self.global_ctx.clone(),
+        }
+    }
 }
 
 impl From<&Arc<GlobalAuthContext>> for AuthContext {
@@ -62,11 +80,11 @@ impl From<&Arc<GlobalAuthContext>> for AuthContext {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::core::auth::basic::tests::{create_basic_auth_request, HTPASSWD_TEST};
+    use crate::core::auth::basic::tests::{basic_claims, create_basic_auth_request};
     use crate::core::auth::basic::BasicVerifier;
     use crate::core::auth::error::Error;
     use crate::core::auth::jwt::jwt_verify::tests::{
-        create_jwt_auth_request, JWT_VALID_TOKEN_WITH_KID,
+        create_jwt_auth_request, jwt_claims, JWT_VALID_TOKEN_WITH_KID,
     };
     use crate::core::auth::jwt::jwt_verify::JwtVerifier;
     use crate::core::auth::verify::Verifier;
@@ -94,7 +112,10 @@ mod tests {
         let validation = auth_context
             .validate(&create_basic_auth_request("testuser1", "password123"))
             .await;
-        assert_eq!(validation, Verification::succeed());
+        assert_eq!(
+            validation,
+            Verification::succeed_with(basic_claims("testuser1"))
+        );
     }
 
     #[tokio::test]
@@ -103,13 +124,31 @@ mod tests {
         let validation = auth_context
             .validate(&create_jwt_auth_request(JWT_VALID_TOKEN_WITH_KID))
             .await;
-        assert_eq!(validation, Verification::succeed());
+        assert_eq!(validation, Verification::succeed_with(jwt_claims()));
+    }
+
+    #[tokio::test]
+    async fn claims_of_verified_request() {
+        let global_ctx = Arc::new(setup_auth_context().await);
+        let req_ctx = create_basic_auth_request("testuser1", "password123");
+        let auth_context = AuthContext::from(&global_ctx);
+        assert_eq!(auth_context.claims(), None);
+
+        auth_context.validate(&req_ctx).await;
+        assert_eq!(
+            auth_context.claims(),
+            Some(Arc::new(basic_claims("testuser1")))
+        );
+
+        let req_ctx = create_basic_auth_request("testuser1", "wrong-password");
+        let auth_context = AuthContext::from(&global_ctx);
+        auth_context.validate(&req_ctx).await;
+        assert_eq!(auth_context.claims(), None);
     }
 
     // Helper function for setting up the auth context
     async fn setup_auth_context() -> GlobalAuthContext {
-        let basic_provider =
-            BasicVerifier::new(blueprint::Basic { htpasswd: HTPASSWD_TEST.to_owned() });
+        let basic_provider = BasicVerifier::new(blueprint::Basic::test_value());
         let jwt_options = blueprint::Jwt::test_value();
         let jwt_provider = JwtVerifier::new(jwt_options);
 
@@ -0,0 +1,275 @@
+use std::hash::{Hash, Hasher};
+use std::num::NonZeroU64;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use async_graphql::Value;
+use headers::authorization::Bearer;
+use headers::{Authorization, HeaderMapExt};
+use reqwest::header::{HeaderValue, ACCEPT, CONTENT_TYPE};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tailcall_hasher::TailcallHasher;
+
+use super::error::Error;
+use super::verification::{Claims, Verification};
+use super::verify::Verify;
+use crate::core::blueprint;
+use crate::core::http::RequestContext;
+use crate::core::ir::model::IoId;
+
+/// Response of the introspection endpoint, as described by RFC 7662.
+#[derive(Deserialize)]
+struct IntrospectionResponse {
+    active: bool,
+    #[serde(flatten)]
+    claims: Claims,
+}
+
+/// Claims of an active token, as stored in the cache.
+#[derive(Serialize, Deserialize)]
+struct CachedToken {
+    token_hash: String,
+    claims: Claims,
+}
+
+/// Verifies opaque tokens with an OAuth 2.0 token introspection endpoint. The
+/// active tokens are cached in the cache of the runtime until they expire, or
+/// for the max age of the provider at most.
+pub struct IntrospectionVerifier {
+    options: blueprint::Introspection,
+}
+
+impl IntrospectionVerifier {
+    pub fn new(options: blueprint::Introspection) -> Self {
+        Self { options }
+    }
+
+    fn cache_key(&self, token: &str) -> IoId {
+        let mut hasher = TailcallHasher::default();
+        self.options.url.hash(&mut hasher);
+        token.hash(&mut hasher);
+        IoId::new(hasher.finish())
+    }
+
+    /// Identifies the token in the cached entry, so that a token whose key
+    /// collides with the one of another token never gets its claims.
+    fn token_hash(token: &str) -> String {
+        hex::encode(Sha256::digest(token.as_bytes()))
+    }
+
+    fn request(&self, token: &str) -> anyhow::Result<reqwest::Request> {
+        let mut request = reqwest::Request::new(reqwest::Method::POST, self.options.url.parse()?);
+        let headers = request.headers_mut();
+        headers.insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("application/x-www-form-urlencoded"),
+        );
+        headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
+
+        if let Some(client_id) = &self.options.client_id {
+            let client_secret = self.options.client_secret.as_deref().unwrap_or_default();
+            headers.typed_insert(Authorization::basic(client_id, client_secret));
+        }
+
+        let body = url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("token", token)
+            .append_pair("token_type_hint", "access_token")
+            .finish();
+        *request.body_mut() = Some(body.into());
+
+        Ok(request)
+    }
+
+    /// Returns the claims of the token if it's active.
+    async fn introspect(
+        &self,
+        req_ctx: &RequestContext,
+        token: &str,
+    ) -> anyhow::Result<Option<Claims>> {
+        let key = self.cache_key(token);
+        let token_hash = Self::token_hash(token);
+        // failures of the cache are ignored, as the endpoint can still be called
+        if let Ok(Some(entry)) = req_ctx.runtime.cache.get(&key).await {
+            let entry: CachedToken = serde_json::from_value(entry.into_json()?)?;
+            if entry.token_hash == token_hash {
+                return Ok(Some(entry.claims));
+            }
+        }
+
+        let response = req_ctx.runtime.http.execute(self.request(token)?).await?;
+        if !response.status.is_success() {
+            anyhow::bail!("Introspection endpoint responded with {}", response.status);
+        }
+        let response: IntrospectionResponse = serde_json::from_slice(&response.body)?;
+
+        if !response.active {
+            return Ok(None);
+        }
+
+        let claims = response.claims;
+        let max_age = self.options.max_age.as_millis() as u64;
+        let ttl = match claims.get("exp").and_then(serde_json::Value::as_u64) {
+            Some(exp) => max_age.min((exp * 1000).saturating_sub(now())),
+            None => max_age,
+        };
+
+        if let Some(ttl) = NonZeroU64::new(ttl) {
+            let entry = CachedToken { token_hash, claims: claims.clone() };
+            let value = Value::from_json(serde_json::to_value(entry)?)?;
+            if let Err(err) = req_ctx.runtime.cache.set(key, value, ttl).await {
+                tracing::warn!("Failed to cache the introspected token: {}", err);
+            }
+        }
+
+        Ok(Some(claims))
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis() as u64)
+}
+
+#[async_trait::async_trait]
+impl Verify for IntrospectionVerifier {
+    async fn verify(&self, req_ctx: &RequestContext) -> Verification {
+        let token = req_ctx
+            .allowed_headers
+            .typed_try_get::<Authorization<Bearer>>();
+        let Ok(token) = token else {
+            return Verification::fail(Error::Invalid);
+        };
+        let Some(token) = token else {
+            return Verification::fail(Error::Missing);
+        };
+
+        match self.introspect(req_ctx, token.token()).await {
+            Ok(Some(claims)) => Verification::succeed_with(claims),
+            Ok(None) => Verification::fail(Error::Invalid),
+            Err(err) => Verification::fail(Error::Parse(err.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use hyper::body::Bytes;
+    use serde_json::json;
+
+    use super::*;
+    use crate::core::auth::jwt::jwt_verify::tests::create_jwt_auth_request;
+    use crate::core::http::Response;
+    use crate::core::{BodyStream, HttpIO};
+
+    #[derive(Default)]
+    struct IntrospectionHttp {
+        hits: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl HttpIO for IntrospectionHttp {
+        async fn execute(&self, request: reqwest::Request) -> anyhow::Result<Response<Bytes>> {
+            self.hits.fetch_add(1, Ordering::SeqCst);
+
+            let body = request.body().and_then(|body| body.as_bytes()).unwrap();
+            if body.starts_with(b"token=error") {
+                let status = hyper::StatusCode::INTERNAL_SERVER_ERROR;
+                return Ok(Response { status, ..Default::default() });
+            }
+            let body = if body.starts_with(b"token=active") {
+                json!({"active": true, "sub": "user", "scope": "read"})
+            } else {
+                json!({"active": false})
+            };
+
+            let body = Bytes::from(serde_json::to_vec(&body)?);
+            Ok(Response { body, ..Default::default() })
+        }
+
+        async fn execute_stream(
+            &self,
+            _: reqwest::Request,
+        ) -> anyhow::Result<Response<BodyStream>> {
+            unimplemented!()
+        }
+    }
+
+    fn verifier() -> IntrospectionVerifier {
+        IntrospectionVerifier::new(blueprint::Introspection {
+            url: "http://localhost/introspect".to_string(),
+            client_id: Some("client".to_string()),
+            client_secret: Some("secret".to_string()),
+            max_age: Duration::from_secs(60),
+        })
+    }
+
+    fn request(token: &str, http: Arc<IntrospectionHttp>) -> RequestContext {
+        let mut req_ctx = create_jwt_auth_request(token);
+        req_ctx.runtime.http = http;
+        req_ctx
+    }
+
+    #[tokio::test]
+    async fn test_active_token() {
+        let http = Arc::new(IntrospectionHttp::default());
+        let req_ctx = request("active", http.clone());
+        let verifier = verifier();
+
+        for _ in 0..2 {
+            let verification = verifier.verify(&req_ctx).await;
+            let claims = json!({"sub": "user", "scope": "read"});
+            let expected = Verification::succeed_with(serde_json::from_value(claims).unwrap());
+            assert_eq!(verification, expected);
+        }
+
+        // the second verification is read from the cache
+        assert_eq!(http.hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_inactive_token() {
+        let http = Arc::new(IntrospectionHttp::default());
+        let verification = verifier().verify(&request("revoked", http)).await;
+
+        assert_eq!(verification, Verification::fail(Error::Invalid));
+    }
+
+    #[tokio::test]
+    async fn test_endpoint_error() {
+        let http = Arc::new(IntrospectionHttp::default());
+        let verification = verifier().verify(&request("error", http)).await;
+
+        assert!(matches!(verification, Verification::Fail(Error::Parse(_))));
+    }
+
+    #[tokio::test]
+    async fn test_cached_token_mismatch() {
+        let http = Arc::new(IntrospectionHttp::default());
+        let req_ctx = request("revoked", http.clone());
+        let verifier = verifier();
+
+        // an entry of another token stored under the same key
+        let entry = CachedToken {
+            token_hash: IntrospectionVerifier::token_hash("active"),
+            claims: serde_json::from_value(json!({"sub": "user"})).unwrap(),
+        };
+        let value = Value::from_json(serde_json::to_value(entry).unwrap()).unwrap();
+        let ttl = NonZeroU64::new(60_000).unwrap();
+        req_ctx
+            .runtime
+            .cache
+            .set(verifier.cache_key("revoked"), value, ttl)
+            .await
+            .unwrap();
+
+        let verification = verifier.verify(&req_ctx).await;
+
+        assert_eq!(verification, Verification::fail(Error::Invalid));
+        assert_eq!(http.hits.load(Ordering::SeqCst), 1);
+    }
+}
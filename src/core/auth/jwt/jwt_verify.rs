@@ -1,10 +1,11 @@
 use headers::authorization::Bearer;
 use headers::{Authorization, HeaderMapExt};
+use jsonwebtoken::decode_header;
 use serde::Deserialize;
 
 use super::jwks::Jwks;
 use crate::core::auth::error::Error;
-use crate::core::auth::verification::Verification;
+use crate::core::auth::verification::{Claims, Verification};
 use crate::core::auth::verify::Verify;
 use crate::core::blueprint;
 use crate::core::http::RequestContext;
@@ -20,6 +21,23 @@ pub enum OneOrMany<T> {
 pub struct JwtClaim {
     pub aud: Option<OneOrMany<String>>,
     pub iss: Option<String>,
+    #[serde(flatten)]
+    pub other: Claims,
+}
+
+impl From<JwtClaim> for Claims {
+    fn from(claim: JwtClaim) -> Self {
+        let JwtClaim { aud, iss, mut other } = claim;
+        if let Some(iss) = iss {
+            other.insert("iss".to_string(), iss.into());
+        }
+        match aud {
+            Some(OneOrMany::One(aud)) => other.insert("aud".to_string(), aud.into()),
+            Some(OneOrMany::Vec(aud)) => other.insert("aud".to_string(), aud.into()),
+            None => None,
+        };
+        other
+    }
 }
 
 pub struct JwtVerifier {
@@ -49,17 +67,30 @@ impl JwtVerifier {
     async fn validate_token(&self, token: &str) -> Verification {
         Verification::from_result(
             self.decoder.decode(token),
-            |claims| self.validate_claims(&claims),
+            |claims| self.validate_claims(claims),
             |err| Verification::fail(Error::Parse(err.to_string())),
         )
     }
 
-    fn validate_claims(&self, claims: &JwtClaim) -> Verification {
-        if !validate_iss(&self.options, claims) || !validate_aud(&self.options, claims) {
+    /// Checks if the token of the request is signed by a key that isn't in the
+    /// key set, e.g. after the issuer has rotated its keys.
+    pub fn has_unknown_key(&self, request: &RequestContext) -> bool {
+        let Ok(Some(token)) = self.resolve_token(request) else {
+            return false;
+        };
+
+        decode_header(&token)
+            .ok()
+            .and_then(|header| header.kid)
+            .is_some_and(|kid| self.decoder.set.find(&kid).is_none())
+    }
+
+    fn validate_claims(&self, claims: JwtClaim) -> Verification {
+        if !validate_iss(&self.options, &claims) || !validate_aud(&self.options, &claims) {
             return Verification::fail(Error::Invalid);
         }
 
-        Verification::succeed()
+        Verification::succeed_with(claims.into())
     }
 }
 
@@ -163,6 +194,17 @@ pub mod tests {
         }
     }
 
+    /// Claims of [JWT_VALID_TOKEN_WITH_KID].
+    pub fn jwt_claims() -> Claims {
+        serde_json::from_value(serde_json::json!({
+            "exp": 2019056441.0,
+            "iss": "me",
+            "sub": "you",
+            "aud": ["them"]
+        }))
+        .unwrap()
+    }
+
     pub fn create_jwt_auth_request(token: &str) -> RequestContext {
         let mut req_context = RequestContext::default();
 
@@ -182,7 +224,7 @@ pub mod tests {
             .verify(&create_jwt_auth_request(JWT_VALID_TOKEN_WITH_KID))
             .await;
 
-        assert_eq!(valid, Verification::succeed());
+        assert_eq!(valid, Verification::succeed_with(jwt_claims()));
 
         let jwt_options = blueprint::Jwt {
             issuer: Some("me".to_owned()),
@@ -194,7 +236,7 @@ pub mod tests {
             .verify(&create_jwt_auth_request(JWT_VALID_TOKEN_WITH_KID))
             .await;
 
-        assert_eq!(valid, Verification::succeed());
+        assert_eq!(valid, Verification::succeed_with(jwt_claims()));
 
         let jwt_options = blueprint::Jwt {
             issuer: Some("another".to_owned()),
@@ -218,7 +260,7 @@ pub mod tests {
             .verify(&create_jwt_auth_request(JWT_VALID_TOKEN_WITH_KID))
             .await;
 
-        assert_eq!(valid, Verification::succeed());
+        assert_eq!(valid, Verification::succeed_with(jwt_claims()));
 
         let jwt_options = blueprint::Jwt {
             audiences: HashSet::from_iter(["them".to_string()]),
@@ -230,7 +272,7 @@ pub mod tests {
             .verify(&create_jwt_auth_request(JWT_VALID_TOKEN_WITH_KID))
             .await;
 
-        assert_eq!(valid, Verification::succeed());
+        assert_eq!(valid, Verification::succeed_with(jwt_claims()));
 
         let jwt_options = blueprint::Jwt {
             audiences: HashSet::from_iter(["anothem".to_string()]),
@@ -1,2 +1,3 @@
 pub mod jwks;
 pub mod jwt_verify;
+pub mod oidc;
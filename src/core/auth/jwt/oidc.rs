@@ -0,0 +1,244 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use jsonwebtoken::jwk::JwkSet;
+
+use super::jwt_verify::JwtVerifier;
+use crate::core::auth::verification::Verification;
+use crate::core::auth::verify::Verify;
+use crate::core::blueprint;
+use crate::core::http::RequestContext;
+use crate::core::HttpIO;
+
+/// Minimum interval between two refreshes caused by tokens signed by unknown
+/// keys, so that such tokens can't flood the provider with requests.
+const MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis() as u64)
+}
+
+/// Verifies the tokens issued by an OpenID Connect provider. The keys of the
+/// provider are fetched again from its `jwks_uri` in the background once they
+/// are older than the refresh interval, while the requests keep using the
+/// current ones. A token signed by an unknown key waits for the keys to be
+/// fetched again, since it can't be verified otherwise.
+pub struct OidcVerifier {
+    keys: Arc<Keys>,
+}
+
+/// The keys of the provider, shared with the task that refreshes them.
+struct Keys {
+    options: blueprint::Oidc,
+    verifier: RwLock<Arc<JwtVerifier>>,
+    fetched_at: AtomicU64,
+    refreshing: AtomicBool,
+}
+
+impl OidcVerifier {
+    pub fn new(options: blueprint::Oidc) -> Self {
+        let keys = Keys {
+            verifier: RwLock::new(Arc::new(JwtVerifier::new(options.jwt.clone()))),
+            options,
+            fetched_at: AtomicU64::new(now()),
+            refreshing: AtomicBool::new(false),
+        };
+        Self { keys: Arc::new(keys) }
+    }
+
+    /// Refreshes the keys without waiting for them to be fetched.
+    fn spawn_refresh(&self, req_ctx: &RequestContext) {
+        if self.keys.refreshing.load(Ordering::SeqCst) {
+            return;
+        }
+        let keys = self.keys.clone();
+        let http = req_ctx.runtime.http.clone();
+        let task = async move { keys.refresh(http.as_ref()).await };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        tokio::spawn(task);
+        #[cfg(target_arch = "wasm32")]
+        async_std::task::spawn_local(task);
+    }
+}
+
+impl Keys {
+    fn verifier(&self) -> Arc<JwtVerifier> {
+        self.verifier.read().unwrap().clone()
+    }
+
+    async fn fetch(&self, http: &dyn HttpIO) -> anyhow::Result<JwkSet> {
+        let url = self.options.jwks_uri.parse()?;
+        let request = reqwest::Request::new(reqwest::Method::GET, url);
+        let response = http.execute(request).await?;
+
+        Ok(serde_json::from_slice(&response.body)?)
+    }
+
+    /// Fetches the keys, unless they are already being fetched.
+    async fn refresh(&self, http: &dyn HttpIO) {
+        if self.refreshing.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        // the request can be cancelled while fetching, in which case the next
+        // one has to be able to refresh the keys
+        let _guard = RefreshGuard(&self.refreshing);
+
+        match self.fetch(http).await {
+            Ok(jwks) => {
+                let options = blueprint::Jwt { jwks, ..self.options.jwt.clone() };
+                *self.verifier.write().unwrap() = Arc::new(JwtVerifier::new(options));
+            }
+            Err(err) => {
+                let jwks_uri = &self.options.jwks_uri;
+                tracing::warn!("Failed to fetch the keys from {}: {}", jwks_uri, err)
+            }
+        }
+
+        // failures are not retried before the next interval either
+        self.fetched_at.store(now(), Ordering::SeqCst);
+    }
+}
+
+/// Releases the refresh of the keys when it completes or is cancelled.
+struct RefreshGuard<'a>(&'a AtomicBool);
+
+impl Drop for RefreshGuard<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+}
+
+#[async_trait::async_trait]
+impl Verify for OidcVerifier {
+    async fn verify(&self, req_ctx: &RequestContext) -> Verification {
+        let keys = &self.keys;
+        let fetched_at = keys.fetched_at.load(Ordering::SeqCst);
+        let elapsed = Duration::from_millis(now().saturating_sub(fetched_at));
+
+        if elapsed >= MIN_REFRESH_INTERVAL && keys.verifier().has_unknown_key(req_ctx) {
+            keys.refresh(req_ctx.runtime.http.as_ref()).await;
+        } else if elapsed >= keys.options.refresh_interval {
+            self.spawn_refresh(req_ctx);
+        }
+
+        keys.verifier().verify(req_ctx).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use hyper::body::Bytes;
+
+    use super::*;
+    use crate::core::auth::error::Error;
+    use crate::core::auth::jwt::jwt_verify::tests::{
+        create_jwt_auth_request, jwt_claims, JWK_SET, JWT_VALID_TOKEN_WITH_KID,
+    };
+    use crate::core::http::Response;
+    use crate::core::BodyStream;
+
+    struct JwksHttp;
+
+    struct PendingHttp;
+
+    #[async_trait::async_trait]
+    impl HttpIO for PendingHttp {
+        async fn execute(&self, _: reqwest::Request) -> anyhow::Result<Response<Bytes>> {
+            std::future::pending().await
+        }
+
+        async fn execute_stream(
+            &self,
+            _: reqwest::Request,
+        ) -> anyhow::Result<Response<BodyStream>> {
+            unimplemented!()
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl HttpIO for JwksHttp {
+        async fn execute(&self, _: reqwest::Request) -> anyhow::Result<Response<Bytes>> {
+            let body = serde_json::to_vec(&*JWK_SET)?;
+            Ok(Response { body: Bytes::from(body), ..Default::default() })
+        }
+
+        async fn execute_stream(
+            &self,
+            _: reqwest::Request,
+        ) -> anyhow::Result<Response<BodyStream>> {
+            unimplemented!()
+        }
+    }
+
+    fn oidc(refresh_interval: Duration) -> blueprint::Oidc {
+        blueprint::Oidc {
+            jwt: blueprint::Jwt {
+                issuer: Some("me".to_string()),
+                jwks: JwkSet { keys: vec![] },
+                ..blueprint::Jwt::test_value()
+            },
+            jwks_uri: "http://localhost/jwks.json".to_string(),
+            refresh_interval,
+        }
+    }
+
+    fn request() -> RequestContext {
+        let mut req_ctx = create_jwt_auth_request(JWT_VALID_TOKEN_WITH_KID);
+        req_ctx.runtime.http = Arc::new(JwksHttp);
+        req_ctx
+    }
+
+    #[tokio::test]
+    async fn test_refresh_after_interval() {
+        let verifier = OidcVerifier::new(oidc(Duration::ZERO));
+
+        // the keys are fetched in the background
+        let verification = verifier.verify(&request()).await;
+        assert!(matches!(verification, Verification::Fail(Error::Parse(_))));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let verification = verifier.verify(&request()).await;
+        assert_eq!(verification, Verification::succeed_with(jwt_claims()));
+    }
+
+    #[tokio::test]
+    async fn test_no_refresh_before_interval() {
+        let verifier = OidcVerifier::new(oidc(Duration::from_secs(3600)));
+        let verification = verifier.verify(&request()).await;
+
+        assert!(matches!(verification, Verification::Fail(Error::Parse(_))));
+    }
+
+    #[tokio::test]
+    async fn test_refresh_on_unknown_key() {
+        let verifier = OidcVerifier::new(oidc(Duration::from_secs(3600)));
+        let fetched_at = now() - 2 * MIN_REFRESH_INTERVAL.as_millis() as u64;
+        verifier.keys.fetched_at.store(fetched_at, Ordering::SeqCst);
+
+        let verification = verifier.verify(&request()).await;
+
+        assert_eq!(verification, Verification::succeed_with(jwt_claims()));
+    }
+
+    #[tokio::test]
+    async fn test_refresh_cancelled() {
+        let verifier = OidcVerifier::new(oidc(Duration::ZERO));
+        let mut req_ctx = request();
+        req_ctx.runtime.http = Arc::new(PendingHttp);
+
+        let refresh = verifier.keys.refresh(req_ctx.runtime.http.as_ref());
+        let result = tokio::time::timeout(Duration::from_millis(10), refresh).await;
+        assert!(result.is_err());
+        assert!(!verifier.keys.refreshing.load(Ordering::SeqCst));
+
+        // the keys are fetched by the next request
+        let verification = verifier.verify(&request()).await;
+        assert_eq!(verification, Verification::succeed_with(jwt_claims()));
+    }
+}
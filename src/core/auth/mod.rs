@@ -1,6 +1,10 @@
+pub mod api_key;
+pub mod authorization;
 pub mod basic;
+pub mod client_cert;
 pub mod context;
 pub mod error;
+pub mod introspection;
 pub mod jwt;
-mod verification;
+pub mod verification;
 pub mod verify;
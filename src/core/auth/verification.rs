@@ -1,12 +1,20 @@
 use std::cmp::max;
+use std::sync::Arc;
 
+use serde_json::Value;
+
+use super::authorization::authorize;
 use super::error::Error;
+use crate::core::blueprint::Protected;
+
+/// Claims of the verified client e.g. the claims of a JWT token.
+pub type Claims = serde_json::Map<String, serde_json::Value>;
 
 ///
 /// Represents the result of the auth verification process.
 #[derive(Clone, PartialEq, Debug)]
 pub enum Verification {
-    Succeed,
+    Succeed(Arc<Claims>),
     Fail(Error),
 }
 
@@ -16,29 +24,49 @@ impl Verification {
     }
 
     pub fn succeed() -> Self {
-        Verification::Succeed
+        Verification::Succeed(Default::default())
+    }
+
+    pub fn succeed_with(claims: Claims) -> Self {
+        Verification::Succeed(Arc::new(claims))
     }
 
-    pub fn fold(self, on_success: Self, on_error: impl Fn(Error) -> Self) -> Self {
+    /// Returns the claims of the client if the verification succeeded.
+    pub fn claims(&self) -> Option<&Claims> {
         match self {
-            Verification::Succeed => on_success,
-            Verification::Fail(err) => on_error(err),
+            Verification::Succeed(claims) => Some(claims),
+            Verification::Fail(_) => None,
         }
     }
 
     pub fn or(&self, other: Self) -> Self {
-        match self {
-            Verification::Succeed => Verification::Succeed,
-            Verification::Fail(this) => other.fold(Verification::succeed(), |that| {
+        match (self, other) {
+            (Verification::Succeed(_), _) => self.clone(),
+            (Verification::Fail(this), Verification::Fail(that)) => {
                 Verification::Fail(max(this.clone(), that))
-            }),
+            }
+            (Verification::Fail(_), other) => other,
         }
     }
 
+    /// Succeeds when both verifications succeed, with the claims of both. The
+    /// values of a claim set by both are merged in a list, so that the roles
+    /// granted by one provider are kept along with the ones of the other.
     pub fn and(self, other: Self) -> Self {
-        match self {
-            Verification::Succeed => other,
-            Verification::Fail(_) => self,
+        match (self, other) {
+            (Verification::Succeed(this), Verification::Succeed(that)) => {
+                let mut claims = this.as_ref().clone();
+                for (name, value) in that.as_ref() {
+                    let merged = match claims.get(name) {
+                        Some(current) => merge_claim(name, current.clone(), value.clone()),
+                        None => value.clone(),
+                    };
+                    claims.insert(name.clone(), merged);
+                }
+                Verification::succeed_with(claims)
+            }
+            (Verification::Succeed(_), other) => other,
+            (this, _) => this,
         }
     }
 
@@ -55,8 +83,90 @@ impl Verification {
 
     pub fn to_result(&self) -> Result<(), Error> {
         match self {
-            Verification::Succeed => Ok(()),
+            Verification::Succeed(_) => Ok(()),
+            Verification::Fail(err) => Err(err.clone()),
+        }
+    }
+
+    /// Checks that the verified client is allowed to access the protected
+    /// field.
+    pub fn authorize(&self, protected: &Protected) -> Result<(), Error> {
+        match self {
+            Verification::Succeed(claims) => authorize(claims, protected),
             Verification::Fail(err) => Err(err.clone()),
         }
     }
 }
+
+/// Merges the values of a claim in a list without duplicates. The scopes are
+/// split, since OAuth 2.0 sends them in a string of space separated values.
+fn merge_claim(name: &str, this: Value, that: Value) -> Value {
+    if this == that {
+        return this;
+    }
+
+    let values = |value: Value| match value {
+        Value::Array(values) => values,
+        Value::String(value) if name == "scope" || name == "scp" => {
+            value.split_whitespace().map(Value::from).collect()
+        }
+        value => vec![value],
+    };
+
+    let mut merged = values(this);
+    for value in values(that) {
+        if !merged.contains(&value) {
+            merged.push(value);
+        }
+    }
+    Value::Array(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn verification(claims: Value) -> Verification {
+        match claims {
+            Value::Object(claims) => Verification::succeed_with(claims),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_and_merges_claims() {
+        let basic = verification(json!({"sub": "alice", "roles": ["admin"]}));
+        let jwt = verification(json!({
+            "sub": "alice",
+            "roles": ["viewer", "admin"],
+            "scope": "read write",
+            "iss": "https://issuer"
+        }));
+        let api_key = verification(json!({"scope": "write delete"}));
+
+        let merged = basic.and(jwt).and(api_key);
+        assert_eq!(
+            Value::Object(merged.claims().unwrap().clone()),
+            json!({
+                "sub": "alice",
+                "roles": ["admin", "viewer"],
+                "scope": ["read", "write", "delete"],
+                "iss": "https://issuer"
+            })
+        );
+    }
+
+    #[test]
+    fn test_and_authorizes_roles_of_both() {
+        let basic = verification(json!({"roles": ["admin"]}));
+        let jwt = verification(json!({"roles": ["viewer"]}));
+        let merged = basic.and(jwt);
+
+        for role in ["admin", "viewer"] {
+            let protected = Protected { roles: [role.to_string()].into(), ..Default::default() };
+            assert_eq!(merged.authorize(&protected), Ok(()));
+        }
+    }
+}
@@ -1,7 +1,11 @@
 use futures_util::join;
 
+use super::api_key::ApiKeyVerifier;
 use super::basic::BasicVerifier;
+use super::client_cert::ClientCertVerifier;
+use super::introspection::IntrospectionVerifier;
 use super::jwt::jwt_verify::JwtVerifier;
+use super::jwt::oidc::OidcVerifier;
 use super::verification::Verification;
 use crate::core::blueprint;
 use crate::core::http::RequestContext;
@@ -14,6 +18,10 @@ pub(crate) trait Verify {
 pub enum Verifier {
     Basic(BasicVerifier),
     Jwt(JwtVerifier),
+    Oidc(OidcVerifier),
+    Introspection(IntrospectionVerifier),
+    ApiKeys(ApiKeyVerifier),
+    ClientCert(ClientCertVerifier),
 }
 
 pub enum AuthVerifier {
@@ -27,6 +35,14 @@ impl From<blueprint::Provider> for Verifier {
         match provider {
             blueprint::Provider::Basic(options) => Verifier::Basic(BasicVerifier::new(options)),
             blueprint::Provider::Jwt(options) => Verifier::Jwt(JwtVerifier::new(options)),
+            blueprint::Provider::Oidc(options) => Verifier::Oidc(OidcVerifier::new(options)),
+            blueprint::Provider::Introspection(options) => {
+                Verifier::Introspection(IntrospectionVerifier::new(options))
+            }
+            blueprint::Provider::ApiKeys(options) => {
+                Verifier::ApiKeys(ApiKeyVerifier::new(options))
+            }
+            blueprint::Provider::ClientCert => Verifier::ClientCert(ClientCertVerifier),
         }
     }
 }
@@ -51,6 +67,10 @@ impl Verify for Verifier {
         match self {
             Verifier::Basic(basic) => basic.verify(req_ctx).await,
             Verifier::Jwt(jwt) => jwt.verify(req_ctx).await,
+            Verifier::Oidc(oidc) => oidc.verify(req_ctx).await,
+            Verifier::Introspection(introspection) => introspection.verify(req_ctx).await,
+            Verifier::ApiKeys(api_keys) => api_keys.verify(req_ctx).await,
+            Verifier::ClientCert(client_cert) => client_cert.verify(req_ctx).await,
         }
     }
 }
@@ -65,7 +85,14 @@ impl Verify for AuthVerifier {
                 a.and(b)
             }
             AuthVerifier::Or(left, right) => {
-                left.verify(req_ctx).await.or(right.verify(req_ctx).await)
+                let left = left.verify(req_ctx).await;
+                // the right verifier is skipped as it might be costly to call, e.g.
+                // an introspection endpoint
+                if let Verification::Succeed(_) = left {
+                    return left;
+                }
+
+                left.or(right.verify(req_ctx).await)
             }
         }
     }
@@ -74,10 +101,10 @@ impl Verify for AuthVerifier {
 #[cfg(test)]
 mod tests {
     use super::AuthVerifier;
-    use crate::core::auth::basic::tests::create_basic_auth_request;
+    use crate::core::auth::basic::tests::{basic_claims, create_basic_auth_request};
     use crate::core::auth::error::Error;
     use crate::core::auth::jwt::jwt_verify::tests::{
-        create_jwt_auth_request, JWT_VALID_TOKEN_WITH_KID,
+        create_jwt_auth_request, jwt_claims, JWT_VALID_TOKEN_WITH_KID,
     };
     use crate::core::auth::verification::Verification;
     use crate::core::auth::verify::Verify;
@@ -95,7 +122,8 @@ mod tests {
     async fn verify_correct_password() {
         let verifier = setup_basic_verifier();
         let req_ctx = create_basic_auth_request("testuser1", "password123");
-        verify_and_assert(&verifier, &req_ctx, Verification::succeed()).await;
+        let expected = Verification::succeed_with(basic_claims("testuser1"));
+        verify_and_assert(&verifier, &req_ctx, expected).await;
     }
 
     #[tokio::test]
@@ -109,7 +137,8 @@ mod tests {
     async fn verify_and_correct_password() {
         let verifier = setup_and_verifier();
         let req_ctx = create_basic_auth_request("testuser1", "password123");
-        verify_and_assert(&verifier, &req_ctx, Verification::succeed()).await;
+        let expected = Verification::succeed_with(basic_claims("testuser1"));
+        verify_and_assert(&verifier, &req_ctx, expected).await;
     }
 
     #[tokio::test]
@@ -123,14 +152,16 @@ mod tests {
     async fn verify_any_correct_password() {
         let verifier = setup_or_verifier();
         let req_ctx = create_basic_auth_request("testuser1", "password123");
-        verify_and_assert(&verifier, &req_ctx, Verification::succeed()).await;
+        let expected = Verification::succeed_with(basic_claims("testuser1"));
+        verify_and_assert(&verifier, &req_ctx, expected).await;
     }
 
     #[tokio::test]
     async fn verify_any_jwt_valid_token() {
         let verifier = setup_or_verifier();
         let req_ctx = create_jwt_auth_request(JWT_VALID_TOKEN_WITH_KID);
-        verify_and_assert(&verifier, &req_ctx, Verification::succeed()).await;
+        let expected = Verification::succeed_with(jwt_claims());
+        verify_and_assert(&verifier, &req_ctx, expected).await;
     }
 
     // Helper Functions
@@ -1,14 +1,19 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::fmt::Debug;
+use std::time::Duration;
 
 use jsonwebtoken::jwk::JwkSet;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
 
-use crate::core::config::ConfigModule;
-use crate::core::valid::Valid;
+use crate::core::config::{self, ConfigModule, Content};
+use crate::core::valid::{Valid, Validator};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Basic {
     pub htpasswd: String,
+    /// Groups of every user, which are used as the roles of the user.
+    pub groups: BTreeMap<String, BTreeSet<String>>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -19,10 +24,82 @@ pub struct Jwt {
     pub jwks: JwkSet,
 }
 
+/// An OpenID Connect provider, whose keys are refreshed from its `jwks_uri`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Oidc {
+    pub jwt: Jwt,
+    pub jwks_uri: String,
+    pub refresh_interval: Duration,
+}
+
+/// An OAuth 2.0 token introspection endpoint, as described by RFC 7662, that
+/// verifies opaque tokens.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Introspection {
+    pub url: String,
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+    pub max_age: Duration,
+}
+
+/// Static API keys that are read from a header or from a query parameter of
+/// the request.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ApiKeys {
+    /// Claims of the client that owns every key.
+    pub keys: Vec<(String, serde_json::Map<String, serde_json::Value>)>,
+    pub header: Option<String>,
+    pub query: Option<String>,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Provider {
     Basic(Basic),
     Jwt(Jwt),
+    Oidc(Oidc),
+    Introspection(Introspection),
+    ApiKeys(ApiKeys),
+    /// The certificates of the clients that are verified by the TLS handshake.
+    ClientCert,
+}
+
+/// The header that carries the API key when the link doesn't specify where
+/// to read it from.
+const DEFAULT_API_KEY_HEADER: &str = "X-API-Key";
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct OidcOptions {
+    #[serde(default)]
+    audiences: HashSet<String>,
+    /// Interval in milliseconds after which the keys are fetched again.
+    refresh_interval: Option<u64>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct IntrospectionOptions {
+    client_id: Option<String>,
+    client_secret: Option<String>,
+    /// Duration in milliseconds for which the active tokens are cached.
+    max_age: Option<u64>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct ApiKeysOptions {
+    header: Option<String>,
+    query: Option<String>,
+}
+
+fn to_options<A: Default + DeserializeOwned>(meta: &Option<serde_json::Value>) -> Valid<A, String> {
+    match meta {
+        Some(meta) => match serde_json::from_value(meta.clone()) {
+            Ok(options) => Valid::succeed(options),
+            Err(err) => Valid::fail(err.to_string()).trace("meta"),
+        },
+        None => Valid::succeed(A::default()),
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -34,9 +111,11 @@ pub enum Auth {
 
 impl Auth {
     pub fn make(config_module: &ConfigModule) -> Valid<Option<Auth>, String> {
+        let groups = to_groups(&config_module.extensions().htgroup);
         let htpasswd = config_module.extensions().htpasswd.iter().map(|htpasswd| {
             Auth::Provider(Provider::Basic(Basic {
                 htpasswd: htpasswd.content.clone(),
+                groups: groups.clone(),
             }))
         });
 
@@ -50,9 +129,71 @@ impl Auth {
             }))
         });
 
-        let auth = htpasswd.chain(jwks).reduce(|left, right| left.or(right));
+        let oidc = Valid::from_iter(config_module.extensions().oidc.iter(), |oidc| {
+            to_options::<OidcOptions>(&oidc.meta).map(|options| {
+                Auth::Provider(Provider::Oidc(Oidc {
+                    jwt: Jwt {
+                        issuer: Some(oidc.issuer.clone()),
+                        audiences: options.audiences,
+                        optional_kid: true,
+                        jwks: oidc.jwks.clone(),
+                    },
+                    jwks_uri: oidc.jwks_uri.clone(),
+                    refresh_interval: Duration::from_millis(
+                        options.refresh_interval.unwrap_or(3600000),
+                    ),
+                }))
+            })
+        });
+
+        let introspection =
+            Valid::from_iter(config_module.extensions().introspection.iter(), |link| {
+                to_options::<IntrospectionOptions>(&link.meta).map(|options| {
+                    Auth::Provider(Provider::Introspection(Introspection {
+                        url: link.src.clone(),
+                        client_id: options.client_id,
+                        client_secret: options.client_secret,
+                        max_age: Duration::from_millis(options.max_age.unwrap_or(60000)),
+                    }))
+                })
+            });
+
+        let api_keys = Valid::from_iter(config_module.extensions().api_keys.iter(), |api_keys| {
+            to_options::<ApiKeysOptions>(&api_keys.meta).map(|options| {
+                let header = match (options.header, &options.query) {
+                    (None, None) => Some(DEFAULT_API_KEY_HEADER.to_string()),
+                    (header, _) => header,
+                };
+
+                Auth::Provider(Provider::ApiKeys(ApiKeys {
+                    keys: api_keys
+                        .keys
+                        .iter()
+                        .map(|api_key| (api_key.key.clone(), api_key.metadata.clone()))
+                        .collect(),
+                    header,
+                    query: options.query,
+                }))
+            })
+        });
 
-        Valid::succeed(auth)
+        let client_cert = config_module
+            .server
+            .client_cert
+            .as_ref()
+            .map(|_| Auth::Provider(Provider::ClientCert));
+
+        oidc.fuse(introspection)
+            .fuse(api_keys)
+            .map(|(oidc, introspection, api_keys)| {
+                htpasswd
+                    .chain(jwks)
+                    .chain(api_keys)
+                    .chain(client_cert)
+                    .chain(oidc)
+                    .chain(introspection)
+                    .reduce(|left, right| left.or(right))
+            })
     }
 
     pub fn and(self, other: Self) -> Self {
@@ -64,16 +205,59 @@ impl Auth {
     }
 }
 
+/// Reads the groups of every user from group files with lines such as
+/// `admin: user1 user2`.
+fn to_groups(htgroup: &[Content<String>]) -> BTreeMap<String, BTreeSet<String>> {
+    let mut groups = BTreeMap::<String, BTreeSet<String>>::new();
+    let lines = htgroup.iter().flat_map(|htgroup| htgroup.content.lines());
+
+    for (group, users) in lines.filter_map(|line| line.split_once(':')) {
+        for user in users.split_whitespace() {
+            groups
+                .entry(user.to_string())
+                .or_default()
+                .insert(group.trim().to_string());
+        }
+    }
+
+    groups
+}
+
+/// The roles, scopes and claims that a client needs to access a protected
+/// field. A client without any requirement only needs to be authenticated.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Protected {
+    pub roles: BTreeSet<String>,
+    pub scopes: BTreeSet<String>,
+    pub claims: BTreeMap<String, serde_json::Value>,
+}
+
+impl Protected {
+    pub fn has_requirements(&self) -> bool {
+        !self.roles.is_empty() || !self.scopes.is_empty() || !self.claims.is_empty()
+    }
+}
+
+impl From<&config::Protected> for Protected {
+    fn from(protected: &config::Protected) -> Self {
+        Self {
+            roles: protected.roles.iter().cloned().collect(),
+            scopes: protected.scopes.iter().cloned().collect(),
+            claims: protected.claims.clone(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{Auth, Basic, Jwt, Provider};
 
     fn test_basic_provider_1() -> Provider {
-        Provider::Basic(Basic { htpasswd: "1".into() })
+        Provider::Basic(Basic { htpasswd: "1".into(), groups: Default::default() })
     }
 
     fn test_basic_provider_2() -> Provider {
-        Provider::Basic(Basic { htpasswd: "2".into() })
+        Provider::Basic(Basic { htpasswd: "2".into(), groups: Default::default() })
     }
 
     fn test_jwt_provider() -> Provider {
@@ -1,4 +1,4 @@
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fmt::Formatter;
 use std::sync::Arc;
 
@@ -9,8 +9,9 @@ use derive_setters::Setters;
 use serde_json::Value;
 
 use super::telemetry::Telemetry;
-use super::{GlobalTimeout, Index};
+use super::{GlobalTimeout, Index, QueryLimiter};
 use crate::core::blueprint::{Server, Upstream};
+use crate::core::config;
 use crate::core::ir::model::IR;
 use crate::core::scalar;
 use crate::core::schema_extension::SchemaExtension;
@@ -26,6 +27,9 @@ pub struct Blueprint {
     pub server: Server,
     pub upstream: Upstream,
     pub telemetry: Telemetry,
+    /// Fields of the Query type that resolve the Apollo Federation entities
+    /// keyed by the entity type name.
+    pub entities: BTreeMap<String, String>,
 }
 
 #[derive(Clone)]
@@ -81,6 +85,14 @@ impl Type {
     pub fn is_list(&self) -> bool {
         matches!(self, Type::ListType { .. })
     }
+
+    /// returns the nullable version of the type
+    pub fn into_nullable(self) -> Self {
+        match self {
+            Type::NamedType { name, .. } => Type::NamedType { name, non_null: false },
+            Type::ListType { of_type, .. } => Type::ListType { of_type, non_null: false },
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -119,6 +131,7 @@ pub struct ObjectTypeDefinition {
     pub fields: Vec<FieldDefinition>,
     pub description: Option<String>,
     pub implements: BTreeSet<String>,
+    pub directives: Vec<Directive>,
 }
 
 #[derive(Clone, Debug)]
@@ -147,6 +160,7 @@ pub struct EnumValueDefinition {
 pub struct SchemaDefinition {
     pub query: String,
     pub mutation: Option<String>,
+    pub subscription: Option<String>,
     pub directives: Vec<Directive>,
 }
 
@@ -167,6 +181,7 @@ pub struct FieldDefinition {
     pub directives: Vec<Directive>,
     pub description: Option<String>,
     pub default_value: Option<serde_json::Value>,
+    pub cost: Option<config::Cost>,
 }
 
 impl FieldDefinition {
@@ -228,6 +243,10 @@ impl Blueprint {
         self.schema.mutation.clone()
     }
 
+    pub fn subscription(&self) -> Option<String> {
+        self.schema.subscription.clone()
+    }
+
     fn drop_resolvers(mut self) -> Self {
         for def in self.definitions.iter_mut() {
             if let Definition::Object(def) = def {
@@ -279,6 +298,13 @@ impl Blueprint {
             schema = schema.disable_introspection();
         }
 
+        if !server.query_limits.is_empty() {
+            schema = schema.extension(QueryLimiter::new(
+                server.query_limits.clone(),
+                blueprint.index(),
+            ));
+        }
+
         for extension in schema_modifiers.extensions.iter().cloned() {
             schema = schema.extension(extension);
         }
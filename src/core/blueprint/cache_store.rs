@@ -0,0 +1,148 @@
+use std::num::NonZeroUsize;
+
+use url::Url;
+
+use crate::core::config;
+use crate::core::valid::{Valid, ValidationError, Validator};
+
+const DEFAULT_CAPACITY: usize = 100000;
+const DEFAULT_POOL_SIZE: usize = 8;
+const DEFAULT_PREFIX: &str = "tailcall:";
+const DEFAULT_KV_BINDING: &str = "TMP_KV";
+
+/// Store of the results of the `@cache` fields. Each runtime falls back to
+/// the memory when it doesn't support the store.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CacheStore {
+    InMemory {
+        capacity: NonZeroUsize,
+    },
+    Redis {
+        url: Url,
+        prefix: String,
+        pool_size: NonZeroUsize,
+    },
+    CloudflareKv {
+        binding: String,
+        prefix: String,
+    },
+}
+
+impl Default for CacheStore {
+    fn default() -> Self {
+        CacheStore::InMemory { capacity: NonZeroUsize::new(DEFAULT_CAPACITY).unwrap() }
+    }
+}
+
+impl CacheStore {
+    pub fn make(store: &config::CacheStore) -> Valid<CacheStore, String> {
+        match store {
+            config::CacheStore::InMemory(in_memory) => {
+                to_non_zero(in_memory.capacity.unwrap_or(DEFAULT_CAPACITY))
+                    .trace("capacity")
+                    .map(|capacity| CacheStore::InMemory { capacity })
+                    .trace("inMemory")
+            }
+            config::CacheStore::Redis(redis) => to_redis_url(&redis.url)
+                .trace("url")
+                .fuse(to_non_zero(redis.pool_size.unwrap_or(DEFAULT_POOL_SIZE)).trace("poolSize"))
+                .map(|(url, pool_size)| CacheStore::Redis {
+                    url,
+                    prefix: redis.prefix.clone().unwrap_or(DEFAULT_PREFIX.to_string()),
+                    pool_size,
+                })
+                .trace("redis"),
+            config::CacheStore::CloudflareKv(kv) => Valid::succeed(CacheStore::CloudflareKv {
+                binding: kv.binding.clone().unwrap_or(DEFAULT_KV_BINDING.to_string()),
+                prefix: kv.prefix.clone().unwrap_or(DEFAULT_PREFIX.to_string()),
+            }),
+        }
+    }
+}
+
+fn to_non_zero(value: usize) -> Valid<NonZeroUsize, String> {
+    Valid::from_option(
+        NonZeroUsize::new(value),
+        "must be greater than 0".to_string(),
+    )
+}
+
+fn to_redis_url(url: &str) -> Valid<Url, String> {
+    Valid::from(Url::parse(url).map_err(|err| ValidationError::new(err.to_string()))).and_then(
+        |url| match url.scheme() {
+            "redis" | "rediss" if url.host_str().is_some() => Valid::succeed(url),
+            "redis" | "rediss" => Valid::fail("The URL has no host".to_string()),
+            scheme => Valid::fail(format!(
+                "Unsupported scheme {}, expected redis or rediss",
+                scheme
+            )),
+        },
+    )
+}
+
+/// Compiles the store of `@server`, which defaults to the memory.
+pub fn to_cache_store(config_server: &config::Server) -> Valid<CacheStore, String> {
+    config_server.cache.as_ref().map_or_else(
+        || Valid::succeed(CacheStore::default()),
+        |store| CacheStore::make(store).trace("cache"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_store(store: config::CacheStore) -> Result<CacheStore, ValidationError<String>> {
+        let server = config::Server { cache: Some(store), ..Default::default() };
+        to_cache_store(&server).to_result()
+    }
+
+    #[test]
+    fn test_redis_store() {
+        let store = config::CacheStore::Redis(config::RedisStore {
+            url: "redis://:secret@localhost:6380/2".to_string(),
+            ..Default::default()
+        });
+
+        let CacheStore::Redis { url, prefix, pool_size } = to_store(store).unwrap() else {
+            panic!("expected a redis store")
+        };
+        assert_eq!(url.port(), Some(6380));
+        assert_eq!(prefix, "tailcall:");
+        assert_eq!(pool_size.get(), 8);
+    }
+
+    #[test]
+    fn test_redis_tls_store() {
+        let store = config::CacheStore::Redis(config::RedisStore {
+            url: "rediss://localhost:6380".to_string(),
+            ..Default::default()
+        });
+
+        let CacheStore::Redis { url, .. } = to_store(store).unwrap() else {
+            panic!("expected a redis store")
+        };
+        assert_eq!(url.scheme(), "rediss");
+    }
+
+    #[test]
+    fn test_invalid_redis_url() {
+        let store = config::CacheStore::Redis(config::RedisStore {
+            url: "http://localhost:6379".to_string(),
+            ..Default::default()
+        });
+
+        assert!(to_store(store).is_err());
+    }
+
+    #[test]
+    fn test_default_store() {
+        let actual = to_cache_store(&config::Server::default())
+            .to_result()
+            .unwrap();
+        assert_eq!(actual, CacheStore::default());
+
+        let store = config::CacheStore::InMemory(config::InMemoryStore { capacity: Some(0) });
+        assert!(to_store(store).is_err());
+    }
+}
@@ -8,7 +8,7 @@ use crate::core::blueprint::Type::ListType;
 use crate::core::blueprint::*;
 use crate::core::config::{Config, Enum, Field, GraphQLOperationType, Protected, Union};
 use crate::core::directive::DirectiveCodec;
-use crate::core::ir::model::{Cache, IR};
+use crate::core::ir::model::{Cache, Stale, IR};
 use crate::core::try_fold::TryFold;
 use crate::core::valid::{Valid, Validator};
 use crate::core::{config, scalar};
@@ -255,14 +255,24 @@ fn to_object_type_definition(
     type_of: &config::Type,
     config_module: &ConfigModule,
 ) -> Valid<Definition, String> {
-    to_fields(name, type_of, config_module).map(|fields| {
-        Definition::Object(ObjectTypeDefinition {
-            name: name.to_string(),
-            description: type_of.doc.clone(),
-            fields,
-            implements: type_of.implements.clone(),
+    to_fields(name, type_of, config_module)
+        .fuse(Valid::from_iter(
+            type_of
+                .key
+                .iter()
+                .map(|key| key.to_directive())
+                .chain(type_of.shareable.iter().map(|s| s.to_directive())),
+            to_directive,
+        ))
+        .map(|(fields, directives)| {
+            Definition::Object(ObjectTypeDefinition {
+                name: name.to_string(),
+                description: type_of.doc.clone(),
+                fields,
+                implements: type_of.implements.clone(),
+                directives,
+            })
         })
-    })
 }
 
 fn update_args<'a>(
@@ -279,14 +289,25 @@ fn update_args<'a>(
                     default_value: arg.default_value.clone(),
                 })
             })
-            .map(|args| FieldDefinition {
+            .fuse(Valid::from_iter(
+                field
+                    .external
+                    .iter()
+                    .map(|d| d.to_directive())
+                    .chain(field.requires.iter().map(|d| d.to_directive()))
+                    .chain(field.provides.iter().map(|d| d.to_directive()))
+                    .chain(field.shareable.iter().map(|d| d.to_directive())),
+                to_directive,
+            ))
+            .map(|(args, directives)| FieldDefinition {
                 name: name.to_string(),
                 description: field.doc.clone(),
                 args,
                 of_type: to_type(*field, None),
-                directives: Vec::new(),
+                directives,
                 resolver: None,
                 default_value: field.default_value.clone(),
+                cost: field.cost.clone(),
             })
         },
     )
@@ -352,12 +373,20 @@ pub fn update_cache_resolvers<'a>(
 ) -> TryFold<'a, (&'a ConfigModule, &'a Field, &'a config::Type, &'a str), FieldDefinition, String>
 {
     TryFold::<(&ConfigModule, &Field, &config::Type, &str), FieldDefinition, String>::new(
-        move |(_config, field, typ, _name), mut b_field| {
-            if let Some(config::Cache { max_age }) = field.cache.as_ref().or(typ.cache.as_ref()) {
-                b_field.map_expr(|expression| Cache::wrap(*max_age, expression))
-            }
-
-            Valid::succeed(b_field)
+        move |(_config, field, typ, _name), mut b_field| match field
+            .cache
+            .as_ref()
+            .or(typ.cache.as_ref())
+        {
+            Some(cache) => to_tags(&cache.tags).map(|tags| {
+                let stale = Stale {
+                    while_revalidate: cache.stale_while_revalidate,
+                    if_error: cache.stale_if_error,
+                };
+                b_field.map_expr(|expression| Cache::wrap(cache.max_age, stale, tags, expression));
+                b_field
+            }),
+            None => Valid::succeed(b_field),
         },
     )
 }
@@ -383,6 +412,13 @@ fn to_fields(
         .eq(&Some(object_name))
     {
         GraphQLOperationType::Mutation
+    } else if config_module
+        .schema
+        .subscription
+        .as_deref()
+        .eq(&Some(object_name))
+    {
+        GraphQLOperationType::Subscription
     } else {
         GraphQLOperationType::Query
     };
@@ -511,11 +547,16 @@ pub fn to_field_definition(
         .and(update_grpc(operation_type).trace(config::Grpc::trace_name().as_str()))
         .and(update_const_field().trace(config::Expr::trace_name().as_str()))
         .and(update_js_field().trace(config::JS::trace_name().as_str()))
-        .and(update_graphql(operation_type).trace(config::GraphQL::trace_name().as_str()))
+        .and(
+            update_graphql(operation_type, object_name)
+                .trace(config::GraphQL::trace_name().as_str()),
+        )
         .and(update_modify().trace(config::Modify::trace_name().as_str()))
         .and(update_call(operation_type, object_name).trace(config::Call::trace_name().as_str()))
         .and(fix_dangling_resolvers())
-        .and(update_cache_resolvers())
+        .and(update_cache_resolvers().trace(config::Cache::trace_name().as_str()))
+        .and(update_invalidate(operation_type).trace(config::Invalidate::trace_name().as_str()))
+        .and(update_rate_limit(object_name).trace(config::RateLimit::trace_name().as_str()))
         .and(update_protected(object_name).trace(Protected::trace_name().as_str()))
         .and(update_enum_alias())
         .and(update_union_resolver())
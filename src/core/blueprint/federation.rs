@@ -0,0 +1,90 @@
+use std::collections::BTreeMap;
+
+use crate::core::blueprint::{Blueprint, Definition, FieldDefinition, TryFoldConfig};
+use crate::core::config::{self, ConfigModule};
+use crate::core::directive::DirectiveCodec;
+use crate::core::valid::{Valid, Validator};
+
+/// Finds the resolvers of the Apollo Federation entities i.e. the types marked
+/// with `@key`. An entity is resolved with the field of the Query type that
+/// returns a single instance of the entity and accepts the key fields as its
+/// arguments, so that requests for entities are batched by the same data loader
+/// as the field itself.
+pub fn to_entities<'a>() -> TryFoldConfig<'a, Blueprint> {
+    TryFoldConfig::<Blueprint>::new(|config_module, blueprint| {
+        let query = blueprint.query();
+        let query_fields = blueprint
+            .definitions
+            .iter()
+            .find_map(|def| match def {
+                Definition::Object(def) if def.name == query => Some(def.fields.as_slice()),
+                _ => None,
+            })
+            .unwrap_or_default();
+
+        Valid::from_iter(config_module.types.iter(), |(name, type_of)| {
+            let Some(key) = type_of.key.as_ref() else {
+                return Valid::succeed(None);
+            };
+
+            to_entity_field(config_module, name, key, query_fields)
+                .map(|field| Some((name.clone(), field)))
+                .trace(config::Key::trace_name().as_str())
+                .trace(name)
+        })
+        .map(|entities| {
+            let entities: BTreeMap<String, String> = entities.into_iter().flatten().collect();
+            blueprint.entities(entities)
+        })
+    })
+}
+
+fn to_entity_field(
+    config_module: &ConfigModule,
+    name: &str,
+    key: &config::Key,
+    query_fields: &[FieldDefinition],
+) -> Valid<String, String> {
+    let key_fields = key.field_names();
+
+    if key_fields.is_empty() {
+        return Valid::fail("Key fields can not be empty".to_string());
+    }
+
+    Valid::from_iter(key_fields.iter(), |key_field| {
+        if key_field.contains(['{', '}']) {
+            Valid::fail(format!("Nested key field {key_field} is not supported"))
+        } else if config_module
+            .find_type(name)
+            .map_or(true, |type_of| !type_of.fields.contains_key(*key_field))
+        {
+            Valid::fail(format!("Key field {key_field} is not defined on {name}"))
+        } else {
+            Valid::succeed(())
+        }
+    })
+    .and_then(|_| {
+        query_fields
+            .iter()
+            .find(|field| {
+                field.resolver.is_some()
+                    && field.of_type.name() == name
+                    && !field.of_type.is_list()
+                    && key_fields
+                        .iter()
+                        .all(|key_field| field.args.iter().any(|arg| arg.name == *key_field))
+                    && field.args.iter().all(|arg| {
+                        arg.of_type.is_nullable()
+                            || arg.default_value.is_some()
+                            || key_fields.contains(&arg.name.as_str())
+                    })
+            })
+            .map(|field| Valid::succeed(field.name.clone()))
+            .unwrap_or_else(|| {
+                Valid::fail(format!(
+                    "No field on the Query type returns {name} with the key fields {} as arguments",
+                    key_fields.join(", ")
+                ))
+            })
+    })
+}
@@ -44,6 +44,7 @@ pub fn config_blueprint<'a>() -> TryFold<'a, ConfigModule, Blueprint, String> {
     server
         .and(schema)
         .and(definitions)
+        .and(to_entities())
         .and(upstream)
         .and(links)
         .and(opentelemetry)
@@ -42,6 +42,17 @@ impl Index {
             .and_then(|(_, fields_map)| fields_map.get(field_name))
     }
 
+    /// Checks if the values of the type match the type condition of a
+    /// fragment, that is either the type itself or one of its interfaces.
+    pub fn is_type_of(&self, type_name: &str, type_condition: &str) -> bool {
+        type_name == type_condition
+            || matches!(
+                self.map.get(type_name),
+                Some((Definition::Object(object_def), _))
+                    if object_def.implements.contains(type_condition)
+            )
+    }
+
     pub fn get_query(&self) -> &String {
         &self.schema.query
     }
@@ -49,6 +60,10 @@ impl Index {
     pub fn get_mutation(&self) -> Option<&str> {
         self.schema.mutation.as_deref()
     }
+
+    pub fn get_subscription(&self) -> Option<&str> {
+        self.schema.subscription.as_deref()
+    }
 }
 
 impl From<&Blueprint> for Index {
@@ -1,17 +1,23 @@
 use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 use anyhow::{bail, Result};
-use async_graphql::dynamic::{self, FieldFuture, FieldValue, SchemaBuilder};
+use async_graphql::dynamic::{
+    self, FieldFuture, FieldValue, SchemaBuilder, SubscriptionFieldFuture,
+};
 use async_graphql::ErrorExtensions;
 use async_graphql_value::ConstValue;
-use futures_util::TryFutureExt;
+use futures_util::future::join_all;
+use futures_util::{stream, StreamExt, TryFutureExt};
 use strum::IntoEnumIterator;
 use tracing::Instrument;
 
-use crate::core::blueprint::{Blueprint, Definition, Type};
+use crate::core::auth::error::Error::Forbidden;
+use crate::core::blueprint::{Blueprint, Definition, Directive, ObjectTypeDefinition, Type};
 use crate::core::http::RequestContext;
-use crate::core::ir::{EvalContext, ResolverContext, TypeName};
+use crate::core::ir::model::IR;
+use crate::core::ir::{self, EvalContext, ResolverContext, TypeName};
 use crate::core::scalar;
 
 fn to_type_ref(type_of: &Type) -> dynamic::TypeRef {
@@ -82,12 +88,139 @@ fn to_field_value<'a>(
     })
 }
 
+fn federation_fields(directive: &Directive) -> Option<&str> {
+    directive
+        .arguments
+        .get("fields")
+        .and_then(|fields| fields.as_str())
+}
+
+/// Applies the Apollo Federation directives to the object.
+fn to_federated_object(mut object: dynamic::Object, directives: &[Directive]) -> dynamic::Object {
+    for directive in directives {
+        object = match (directive.name.as_str(), federation_fields(directive)) {
+            ("key", Some(fields)) => object.key(fields),
+            ("shareable", _) => object.shareable(),
+            _ => object,
+        };
+    }
+    object
+}
+
+/// Applies the Apollo Federation directives to the field.
+fn to_federated_field(mut field: dynamic::Field, directives: &[Directive]) -> dynamic::Field {
+    for directive in directives {
+        field = match (directive.name.as_str(), federation_fields(directive)) {
+            ("external", _) => field.external(),
+            ("requires", Some(fields)) => field.requires(fields),
+            ("provides", Some(fields)) => field.provides(fields),
+            ("shareable", _) => field.shareable(),
+            _ => field,
+        };
+    }
+    field
+}
+
+/// Resolves a single entity representation sent by the federation router. The
+/// representation is passed as the arguments of the entity resolver and is
+/// merged into the resolved value so that the external fields are available to
+/// the fields that require them.
+async fn to_entity<'a>(
+    entities: &BTreeMap<String, IR>,
+    ctx: &EvalContext<'a, ResolverContext<'a>>,
+    representation: ConstValue,
+) -> async_graphql::Result<FieldValue<'static>> {
+    let ConstValue::Object(fields) = &representation else {
+        return Err("Entity representation must be an object".into());
+    };
+    let Some(ConstValue::String(type_name)) = fields.get("__typename") else {
+        return Err("Entity representation must contain __typename".into());
+    };
+    let Some(expr) = entities.get(type_name) else {
+        return Err(format!("{type_name} is not an entity").into());
+    };
+
+    let ctx = &mut ctx.with_args(representation.clone());
+    let value = expr.eval(ctx).await.map_err(|err| err.extend())?;
+
+    Ok(match value {
+        ConstValue::Object(mut value) => {
+            for (name, field) in fields {
+                value.entry(name.clone()).or_insert_with(|| field.clone());
+            }
+            FieldValue::from(ConstValue::Object(value)).with_type(type_name.clone())
+        }
+        _ => FieldValue::NULL,
+    })
+}
+
+fn to_subscription(def: &ObjectTypeDefinition) -> dynamic::Type {
+    let mut subscription = dynamic::Subscription::new(def.name.clone());
+    for field in def.fields.iter() {
+        let field = field.clone();
+        let type_ref = to_type_ref(&field.of_type);
+        let field_name = &field.name.clone();
+
+        let mut dyn_schema_field = dynamic::SubscriptionField::new(
+            field_name,
+            type_ref.clone(),
+            move |ctx| {
+                let req_ctx = ctx.ctx.data::<Arc<RequestContext>>().unwrap();
+                let expr = field.resolver.clone();
+                let span = tracing::info_span!(
+                    "subscription_resolver",
+                    otel.name = ctx.path_node.map(|p| p.to_string()).unwrap_or(field.name.clone()), graphql.returnType = %type_ref
+                );
+
+                SubscriptionFieldFuture::new(
+                    async move {
+                        let Some(expr) = expr else {
+                            return Ok(stream::empty().boxed());
+                        };
+
+                        let ctx: ResolverContext = ctx.into();
+                        let ctx = &mut EvalContext::new(req_ctx, &ctx);
+                        let stream = expr.eval_stream(ctx).await.map_err(|err| err.extend())?;
+
+                        Ok(stream
+                            .map(|value| match value {
+                                Ok(ConstValue::Null) => Ok(FieldValue::NULL),
+                                Ok(value) => Ok(FieldValue::from(value)),
+                                Err(err) => {
+                                    tracing::error!(?err);
+                                    Err(err.extend())
+                                }
+                            })
+                            .boxed())
+                    }
+                    .instrument(span),
+                )
+            },
+        );
+        if let Some(description) = &field.description {
+            dyn_schema_field = dyn_schema_field.description(description);
+        }
+        for arg in field.args.iter() {
+            dyn_schema_field = dyn_schema_field.argument(set_default_value(
+                dynamic::InputValue::new(arg.name.clone(), to_type_ref(&arg.of_type)),
+                arg.default_value.clone(),
+            ));
+        }
+        subscription = subscription.field(dyn_schema_field);
+    }
+    if let Some(description) = &def.description {
+        subscription = subscription.description(description);
+    }
+
+    dynamic::Type::Subscription(subscription)
+}
+
 fn to_type(def: &Definition) -> dynamic::Type {
     match def {
         Definition::Object(def) => {
             let mut object = dynamic::Object::new(def.name.clone());
-            for field in def.fields.iter() {
-                let field = field.clone();
+            for def_field in def.fields.iter() {
+                let field = def_field.clone();
                 let type_ref = to_type_ref(&field.of_type);
                 let field_name = &field.name.clone();
 
@@ -122,11 +255,23 @@ fn to_type(def: &Definition) -> dynamic::Type {
                                 let expr = expr.to_owned();
                                 FieldFuture::new(
                                     async move {
+                                        let graphql_ctx = ctx.ctx;
                                         let ctx: ResolverContext = ctx.into();
                                         let ctx = &mut EvalContext::new(req_ctx, &ctx);
 
-                                        let value =
-                                            expr.eval(ctx).await.map_err(|err| err.extend())?;
+                                        let value = match expr.eval(ctx).await {
+                                            Ok(value) => value,
+                                            // fields that the client is not allowed to access
+                                            // resolve to null instead of failing the request
+                                            Err(err @ ir::Error::AuthError(Forbidden)) => {
+                                                let pos = graphql_ctx.item.pos;
+                                                let err = err.extend().into_server_error(pos);
+                                                let err = graphql_ctx.set_error_path(err);
+                                                graphql_ctx.add_error(err);
+                                                ConstValue::Null
+                                            }
+                                            Err(err) => return Err(err.extend()),
+                                        };
 
                                         if let ConstValue::Null = value {
                                             Ok(FieldValue::NONE)
@@ -155,6 +300,7 @@ fn to_type(def: &Definition) -> dynamic::Type {
                         arg.default_value.clone(),
                     ));
                 }
+                dyn_schema_field = to_federated_field(dyn_schema_field, &def_field.directives);
                 object = object.field(dyn_schema_field);
             }
             if let Some(description) = &def.description {
@@ -163,6 +309,7 @@ fn to_type(def: &Definition) -> dynamic::Type {
             for interface in def.implements.iter() {
                 object = object.implement(interface.clone());
             }
+            object = to_federated_object(object, &def.directives);
 
             dynamic::Type::Object(object)
         }
@@ -227,7 +374,9 @@ impl From<&Blueprint> for SchemaBuilder {
     fn from(blueprint: &Blueprint) -> Self {
         let query = blueprint.query();
         let mutation = blueprint.mutation();
-        let mut schema = dynamic::Schema::build(query.as_str(), mutation.as_deref(), None);
+        let subscription = blueprint.subscription();
+        let mut schema =
+            dynamic::Schema::build(query.as_str(), mutation.as_deref(), subscription.as_deref());
 
         for scalar in scalar::Scalar::iter() {
             let k = scalar.name();
@@ -237,7 +386,62 @@ impl From<&Blueprint> for SchemaBuilder {
         }
 
         for def in blueprint.definitions.iter() {
-            schema = schema.register(to_type(def));
+            match def {
+                Definition::Object(def) if subscription.as_ref() == Some(&def.name) => {
+                    schema = schema.register(to_subscription(def));
+                }
+                def => schema = schema.register(to_type(def)),
+            }
+        }
+
+        if !blueprint.entities.is_empty() {
+            let query_fields = blueprint
+                .definitions
+                .iter()
+                .find_map(|def| match def {
+                    Definition::Object(def) if def.name == query => Some(def.fields.as_slice()),
+                    _ => None,
+                })
+                .unwrap_or_default();
+            let entities: BTreeMap<String, IR> = blueprint
+                .entities
+                .iter()
+                .filter_map(|(type_name, field_name)| {
+                    let field = query_fields
+                        .iter()
+                        .find(|field| &field.name == field_name)?;
+                    Some((type_name.clone(), field.resolver.clone()?))
+                })
+                .collect();
+            let entities = Arc::new(entities);
+            schema = schema.enable_federation().entity_resolver(move |ctx| {
+                let entities = entities.clone();
+                FieldFuture::new(async move {
+                    let req_ctx = ctx.ctx.data::<Arc<RequestContext>>().unwrap();
+                    let representations = ctx
+                        .args
+                        .try_get("representations")?
+                        .list()?
+                        .as_values_slice()
+                        .to_vec();
+
+                    let ctx: ResolverContext = ctx.into();
+                    let ctx = EvalContext::new(req_ctx, &ctx);
+
+                    // Entities are resolved concurrently so that the requests are batched by
+                    // the data loaders of the resolvers.
+                    let entities = join_all(
+                        representations
+                            .into_iter()
+                            .map(|representation| to_entity(&entities, &ctx, representation)),
+                    )
+                    .await
+                    .into_iter()
+                    .collect::<async_graphql::Result<Vec<_>>>()?;
+
+                    Ok(Some(FieldValue::list(entities)))
+                })
+            });
         }
 
         schema
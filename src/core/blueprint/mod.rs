@@ -1,17 +1,26 @@
 mod auth;
 mod blueprint;
+mod cache_store;
 mod compress;
 mod cors;
 mod definitions;
 mod dynamic_value;
+mod federation;
 mod from_config;
 mod index;
 mod into_schema;
 mod links;
 mod mustache;
+mod oauth2;
 mod operators;
+mod persisted_queries;
+mod query_limits;
+mod rate_limit;
+mod response_cache;
+mod retry;
 mod schema;
 mod server;
+mod signing;
 pub mod telemetry;
 mod timeout;
 mod union_resolver;
@@ -19,15 +28,24 @@ mod upstream;
 
 pub use auth::*;
 pub use blueprint::*;
+pub use cache_store::*;
 pub use cors::*;
 pub use definitions::*;
 pub use dynamic_value::*;
+pub use federation::*;
 pub use from_config::*;
 pub use index::*;
 pub use links::*;
+pub use oauth2::*;
 pub use operators::*;
+pub use persisted_queries::*;
+pub use query_limits::*;
+pub use rate_limit::*;
+pub use response_cache::*;
+pub use retry::*;
 pub use schema::*;
 pub use server::*;
+pub use signing::*;
 pub use timeout::GlobalTimeout;
 pub use upstream::*;
 
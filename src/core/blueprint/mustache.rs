@@ -92,6 +92,12 @@ impl<'a> MustachePartsValidator<'a> {
                 // "headers" and "env" refers to values known at runtime, which
                 // we can't validate here
             }
+            "auth" => {
+                // the claims are known only once the client is verified
+                if tail != "claims" {
+                    return Valid::fail(format!("unknown auth value '{tail}'"));
+                }
+            }
             _ => {
                 return Valid::fail(format!("unknown template directive '{head}'"));
             }
@@ -218,6 +224,7 @@ mod test {
             directives: vec![],
             description: None,
             default_value: None,
+            cost: None,
         };
 
         (config, fld)
@@ -0,0 +1,88 @@
+use std::time::Duration;
+
+use url::Url;
+
+use crate::core::config;
+use crate::core::valid::{Valid, Validator};
+
+/// Credentials of the OAuth 2.0 client-credentials flow of an upstream.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OAuth2 {
+    pub token_url: Url,
+    pub client_id: String,
+    pub client_secret: String,
+    pub scopes: Vec<String>,
+    pub audience: Option<String>,
+    pub refresh_before: Duration,
+}
+
+impl OAuth2 {
+    pub fn make(oauth2: &config::OAuth2) -> Valid<OAuth2, String> {
+        let token_url = match Url::parse(&oauth2.token_url) {
+            Ok(token_url) => Valid::succeed(token_url),
+            Err(err) => Valid::fail(err.to_string()).trace("tokenUrl"),
+        };
+
+        token_url.map(|token_url| OAuth2 {
+            token_url,
+            client_id: oauth2.client_id.clone(),
+            client_secret: oauth2.client_secret.clone(),
+            scopes: oauth2.scopes.clone(),
+            audience: oauth2.audience.clone(),
+            refresh_before: Duration::from_millis(oauth2.refresh_before.unwrap_or(30000)),
+        })
+    }
+}
+
+/// Compiles the OAuth 2.0 credentials of an operator, which default to the ones
+/// of the upstream.
+pub fn to_oauth2(
+    upstream: &config::Upstream,
+    oauth2: Option<&config::OAuth2>,
+) -> Valid<Option<OAuth2>, String> {
+    oauth2.or(upstream.oauth2.as_ref()).map_or_else(
+        || Valid::succeed(None),
+        |oauth2| OAuth2::make(oauth2).map(Some).trace("oauth2"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_oauth2_override() {
+        let upstream = config::Upstream {
+            oauth2: Some(config::OAuth2 {
+                token_url: "http://auth.example/token".to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let oauth2 = config::OAuth2 {
+            token_url: "http://other.example/token".to_string(),
+            refresh_before: Some(1000),
+            ..Default::default()
+        };
+
+        let actual = to_oauth2(&upstream, None).to_result().unwrap().unwrap();
+        assert_eq!(actual.token_url.as_str(), "http://auth.example/token");
+        assert_eq!(actual.refresh_before, Duration::from_secs(30));
+
+        let actual = to_oauth2(&upstream, Some(&oauth2))
+            .to_result()
+            .unwrap()
+            .unwrap();
+        assert_eq!(actual.token_url.as_str(), "http://other.example/token");
+        assert_eq!(actual.refresh_before, Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_oauth2_invalid_url() {
+        let oauth2 = config::OAuth2 { token_url: "token".to_string(), ..Default::default() };
+
+        assert!(to_oauth2(&Default::default(), Some(&oauth2))
+            .to_result()
+            .is_err());
+    }
+}
@@ -1,41 +1,66 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 
-use crate::core::blueprint::FieldDefinition;
-use crate::core::config::{Config, ConfigModule, Field, GraphQL, GraphQLOperationType, Type};
-use crate::core::graphql::RequestTemplate;
+use crate::core::blueprint::{to_retry, FieldDefinition, Retry};
+use crate::core::config::{Config, ConfigModule, Field, GraphQL, GraphQLOperationType, Key, Type};
+use crate::core::graphql::{Entity, RequestTemplate};
 use crate::core::helpers;
 use crate::core::ir::model::{IO, IR};
 use crate::core::ir::RelatedFields;
+use crate::core::mustache::Mustache;
 use crate::core::try_fold::TryFold;
 use crate::core::valid::{Valid, ValidationError, Validator};
 
+/// Checks if the field is an entity field resolved by the same upstream, in
+/// which case it can be requested along with its parent.
+fn is_upstream_entity_field(config: &Config, field: &Field, base_url: &str) -> bool {
+    field.graphql.as_ref().is_some_and(|graphql| {
+        graphql.entity.is_some()
+            && graphql
+                .base_url
+                .as_ref()
+                .or(config.upstream.base_url.as_ref())
+                .is_some_and(|url| url == base_url)
+    })
+}
+
 fn create_related_fields(
     config: &Config,
     type_name: &str,
+    base_url: &str,
     visited: &mut HashSet<String>,
 ) -> RelatedFields {
     let mut map = HashMap::new();
+    let mut required = BTreeSet::new();
     if visited.contains(type_name) {
-        return RelatedFields(map);
+        return RelatedFields { fields: map, required };
     }
     visited.insert(type_name.to_string());
 
     if let Some(type_) = config.find_type(type_name) {
         for (name, field) in &type_.fields {
-            if !field.has_resolver() {
+            if !field.has_resolver() || is_upstream_entity_field(config, field, base_url) {
                 map.insert(
                     name.clone(),
-                    create_related_fields(config, &field.type_of, visited),
+                    create_related_fields(config, &field.type_of, base_url, visited),
                 );
             }
+
+            // key fields are needed to request the entity fields from other subgraphs
+            if let Some(key) = field
+                .graphql
+                .as_ref()
+                .and_then(|graphql| graphql.entity.as_ref())
+            {
+                required.extend(key.field_names().into_iter().map(String::from));
+            }
         }
     } else if let Some(union_) = config.find_union(type_name) {
         for type_name in &union_.types {
-            map.extend(create_related_fields(config, type_name, visited).0);
+            map.extend(create_related_fields(config, type_name, base_url, visited).fields);
         }
     };
 
-    RelatedFields(map)
+    RelatedFields { fields: map, required }
 }
 
 pub fn compile_graphql(
@@ -61,28 +86,70 @@ pub fn compile_graphql(
                 &graphql.name,
                 args,
                 headers,
-                create_related_fields(config, type_name, &mut HashSet::new()),
+                create_related_fields(config, type_name, base_url, &mut HashSet::new()),
             )
             .map_err(|e| ValidationError::new(e.to_string())),
         )
     })
-    .map(|req_template| {
+    .zip(to_retry(&config.upstream, graphql.retry.as_ref()))
+    .map(|(req_template, retry)| {
+        // the queries are sent with POST requests but don't have side effects
+        let retry = match operation_type {
+            GraphQLOperationType::Query => retry.map(Retry::idempotent),
+            _ => retry,
+        };
         let field_name = graphql.name.clone();
         let batch = graphql.batch;
-        IR::IO(IO::GraphQL { req_template, field_name, batch, dl_id: None })
+        IR::IO(IO::GraphQL { req_template, field_name, batch, dl_id: None, retry })
     })
 }
 
+/// Creates the representation of the entity from the values of its key fields
+/// in the parent value.
+fn compile_entity(object_name: &str, key: &Key) -> Valid<Entity, String> {
+    let names = key.field_names();
+
+    if names.is_empty() {
+        return Valid::fail("Key fields can not be empty".to_string());
+    }
+
+    let fields = names
+        .iter()
+        .map(|name| format!("{name}: {{{{.value.{name}}}}}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    Valid::from(
+        Mustache::parse(&format!(r#"{{__typename: "{object_name}", {fields} }}"#))
+            .map_err(|e| ValidationError::new(e.to_string())),
+    )
+    .map(|representation| Entity { type_name: object_name.to_string(), representation })
+}
+
 pub fn update_graphql<'a>(
     operation_type: &'a GraphQLOperationType,
+    object_name: &'a str,
 ) -> TryFold<'a, (&'a ConfigModule, &'a Field, &'a Type, &'a str), FieldDefinition, String> {
     TryFold::<(&ConfigModule, &Field, &Type, &'a str), FieldDefinition, String>::new(
-        |(config, field, type_of, _), b_field| {
+        move |(config, field, type_of, _), b_field| {
             let Some(graphql) = &field.graphql else {
                 return Valid::succeed(b_field);
             };
 
+            let entity = match &graphql.entity {
+                Some(key) => compile_entity(object_name, key).map(Some),
+                None => Valid::succeed(None),
+            };
+
             compile_graphql(config, operation_type, &field.type_of, graphql)
+                .zip(entity)
+                .map(|(resolver, entity)| match resolver {
+                    IR::IO(IO::GraphQL { req_template, field_name, batch, dl_id, retry }) => {
+                        let req_template = req_template.entity(entity);
+                        IR::IO(IO::GraphQL { req_template, field_name, batch, dl_id, retry })
+                    }
+                    resolver => resolver,
+                })
                 .map(|resolver| b_field.resolver(Some(resolver)))
                 .and_then(|b_field| b_field.validate_field(type_of, config).map_to(b_field))
         },
@@ -3,7 +3,7 @@ use std::fmt::Display;
 use prost_reflect::prost_types::FileDescriptorSet;
 use prost_reflect::FieldDescriptor;
 
-use crate::core::blueprint::{FieldDefinition, TypeLike};
+use crate::core::blueprint::{to_retry, FieldDefinition, Retry, TypeLike};
 use crate::core::config::group_by::GroupBy;
 use crate::core::config::{Config, ConfigModule, Field, GraphQLOperationType, Grpc};
 use crate::core::grpc::protobuf::{ProtobufOperation, ProtobufSet};
@@ -156,6 +156,24 @@ impl TryFrom<&str> for GrpcMethod {
     }
 }
 
+/// Server-streaming methods produce multiple responses, so they can only
+/// resolve subscriptions, while client-streaming methods aren't supported.
+fn validate_streaming(
+    operation: &ProtobufOperation,
+    operation_type: &GraphQLOperationType,
+    method: &str,
+) -> Valid<(), String> {
+    if operation.is_client_streaming() {
+        return Valid::fail(format!("Client streaming method {method} is not supported"));
+    }
+    if operation.is_server_streaming() && operation_type != &GraphQLOperationType::Subscription {
+        return Valid::fail(format!(
+            "Server streaming method {method} can only be used on subscription fields"
+        ));
+    }
+    Valid::succeed(())
+}
+
 pub fn compile_grpc(inputs: CompileGrpc) -> Valid<IR, String> {
     let config_module = inputs.config_module;
     let operation_type = inputs.operation_type;
@@ -177,6 +195,10 @@ pub fn compile_grpc(inputs: CompileGrpc) -> Valid<IR, String> {
                 .fuse(helpers::body::to_body(grpc.body.as_ref()))
                 .into()
         })
+        .and_then(|(operation, url, headers, body)| {
+            validate_streaming(&operation, operation_type, &grpc.method)
+                .map(|_| (operation, url, headers, body))
+        })
         .and_then(|(operation, url, headers, body)| {
             let validation = if validate_with_schema {
                 let field_schema = json_schema_from_field(config_module, field);
@@ -190,7 +212,11 @@ pub fn compile_grpc(inputs: CompileGrpc) -> Valid<IR, String> {
             };
             validation.map(|_| (url, headers, operation, body))
         })
-        .map(|(url, headers, operation, body)| {
+        .zip(to_retry(&config_module.upstream, grpc.retry.as_ref()))
+        .map(|((url, headers, operation, body), retry)| {
+            // the unary calls are sent with POST requests, and the streams are never
+            // retried
+            let retry = retry.map(Retry::idempotent);
             let req_template = RequestTemplate {
                 url,
                 headers,
@@ -203,9 +229,10 @@ pub fn compile_grpc(inputs: CompileGrpc) -> Valid<IR, String> {
                     req_template,
                     group_by: Some(GroupBy::new(grpc.batch_key.clone(), None)),
                     dl_id: None,
+                    retry,
                 })
             } else {
-                IR::IO(IO::Grpc { req_template, group_by: None, dl_id: None })
+                IR::IO(IO::Grpc { req_template, group_by: None, dl_id: None, retry })
             }
         })
 }
@@ -53,7 +53,27 @@ pub fn compile_http(
             .map_err(|e| ValidationError::new(e.to_string()))
             .into()
         })
-        .map(|req_template| {
+        .fuse(to_retry(&config_module.upstream, http.retry.as_ref()))
+        .fuse(to_oauth2(&config_module.upstream, http.oauth2.as_ref()))
+        .fuse(to_signing(&config_module.upstream, http.signing.as_ref()))
+        .and_then(|(req_template, retry, oauth2, signing)| {
+            Valid::<(), String>::fail(
+                "Signing is not supported for batched requests".to_string(),
+            )
+            .trace("signing")
+            .when(|| signing.is_some() && !http.batch_key.is_empty())
+            .and(
+                Valid::<(), String>::fail(
+                    "awsSigV4 can't be combined with oauth2, as both set the Authorization header"
+                        .to_string(),
+                )
+                .trace("signing")
+                .when(|| matches!(signing, Some(Signing::AwsSigV4(_))) && oauth2.is_some()),
+            )
+            .map(|_| (req_template.signing(signing), retry, oauth2))
+        })
+        .map(|(req_template, retry, oauth2)| {
+            let oauth2 = oauth2.map(Box::new);
             // marge http and upstream on_request
             let http_filter = http
                 .on_request
@@ -75,9 +95,18 @@ pub fn compile_http(
                     group_by: Some(GroupBy::new(http.batch_key.clone(), key)),
                     dl_id: None,
                     http_filter,
+                    retry,
+                    oauth2,
                 })
             } else {
-                IR::IO(IO::Http { req_template, group_by: None, dl_id: None, http_filter })
+                IR::IO(IO::Http {
+                    req_template,
+                    group_by: None,
+                    dl_id: None,
+                    http_filter,
+                    retry,
+                    oauth2,
+                })
             }
         })
 }
@@ -0,0 +1,41 @@
+use crate::core::blueprint::FieldDefinition;
+use crate::core::config::{self, ConfigModule, Field, GraphQLOperationType};
+use crate::core::ir::model::{self, IR};
+use crate::core::mustache::Mustache;
+use crate::core::try_fold::TryFold;
+use crate::core::valid::{Valid, ValidationError, Validator};
+
+/// Parses the templates of the tags of `@cache` and `@invalidate`.
+pub fn to_tags(tags: &[String]) -> Valid<Vec<Mustache>, String> {
+    Valid::from_iter(tags.iter(), |tag| {
+        Valid::from(Mustache::parse(tag).map_err(|err| ValidationError::new(err.to_string())))
+            .trace(tag)
+    })
+    .trace("tags")
+}
+
+pub fn update_invalidate<'a>(
+    operation_type: &'a GraphQLOperationType,
+) -> TryFold<'a, (&'a ConfigModule, &'a Field, &'a config::Type, &'a str), FieldDefinition, String>
+{
+    TryFold::<(&ConfigModule, &Field, &config::Type, &'a str), FieldDefinition, String>::new(
+        move |(_, field, _, _), mut b_field| match &field.invalidate {
+            Some(_) if operation_type != &GraphQLOperationType::Mutation => Valid::fail(
+                "@invalidate can only be used on the fields of the mutation type".to_string(),
+            ),
+            Some(invalidate) => to_tags(&invalidate.tags).map(|tags| {
+                let expr = b_field
+                    .resolver
+                    .take()
+                    .unwrap_or(IR::ContextPath(vec![b_field.name.clone()]));
+
+                b_field.resolver = Some(IR::Invalidate(model::Invalidate {
+                    tags,
+                    expr: Box::new(expr),
+                }));
+                b_field
+            }),
+            None => Valid::succeed(b_field),
+        },
+    )
+}
@@ -4,9 +4,11 @@ mod expr;
 mod graphql;
 mod grpc;
 mod http;
+mod invalidate;
 mod js;
 mod modify;
 mod protected;
+mod rate_limit;
 
 pub use call::*;
 pub use enum_alias::*;
@@ -14,6 +16,8 @@ pub use expr::*;
 pub use graphql::*;
 pub use grpc::*;
 pub use http::*;
+pub use invalidate::*;
 pub use js::*;
 pub use modify::*;
 pub use protected::*;
+pub use rate_limit::*;
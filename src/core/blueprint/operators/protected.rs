@@ -1,4 +1,4 @@
-use crate::core::blueprint::FieldDefinition;
+use crate::core::blueprint::{FieldDefinition, Protected};
 use crate::core::config::{self, ConfigModule, Field};
 use crate::core::ir::model::IR;
 use crate::core::try_fold::TryFold;
@@ -10,28 +10,47 @@ pub fn update_protected<'a>(
 {
     TryFold::<(&ConfigModule, &Field, &config::Type, &'a str), FieldDefinition, String>::new(
         |(config, field, type_, _), mut b_field| {
-            if field.protected.is_some() // check the field itself has marked as protected
-                || type_.protected.is_some() // check the type that contains current field
-                || config // check that output type of the field is protected
+            let mut protections = [
+                // check the field itself has marked as protected
+                field.protected.as_ref(),
+                // check the type that contains current field
+                type_.protected.as_ref(),
+                // check that output type of the field is protected
+                config
                     .find_type(&field.type_of)
-                    .and_then(|type_| type_.protected.as_ref())
-                    .is_some()
-            {
+                    .and_then(|type_| type_.protected.as_ref()),
+            ]
+            .into_iter()
+            .flatten()
+            .map(Protected::from)
+            .collect::<Vec<_>>();
+
+            if !protections.is_empty() {
                 if config.input_types().contains(type_name) {
                     return Valid::fail("Input types can not be protected".to_owned());
                 }
 
-                if !config.extensions().has_auth() {
+                if !config.extensions().has_auth() && config.server.client_cert.is_none() {
                     return Valid::fail(
                         "@protected operator is used but there is no @link definitions for auth providers".to_owned(),
                     );
                 }
 
-                b_field.resolver = Some(IR::Protect(Box::new(
-                    b_field
-                        .resolver
-                        .unwrap_or(IR::ContextPath(vec![b_field.name.clone()])),
-                )));
+                protections.dedup();
+
+                // fields that the client is not allowed to access resolve to null
+                // instead of nulling their parent
+                if protections.iter().any(Protected::has_requirements) {
+                    b_field.of_type = b_field.of_type.into_nullable();
+                }
+
+                let resolver = b_field
+                    .resolver
+                    .unwrap_or(IR::ContextPath(vec![b_field.name.clone()]));
+                b_field.resolver =
+                    Some(protections.into_iter().fold(resolver, |expr, protected| {
+                        IR::Protect(protected, Box::new(expr))
+                    }));
             }
 
             Valid::succeed(b_field)
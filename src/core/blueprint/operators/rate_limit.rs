@@ -0,0 +1,29 @@
+use crate::core::blueprint::{FieldDefinition, RateLimit};
+use crate::core::config::{self, ConfigModule, Field};
+use crate::core::ir::model::{self, IR};
+use crate::core::try_fold::TryFold;
+use crate::core::valid::{Valid, Validator};
+
+pub fn update_rate_limit<'a>(
+    type_name: &'a str,
+) -> TryFold<'a, (&'a ConfigModule, &'a Field, &'a config::Type, &'a str), FieldDefinition, String>
+{
+    TryFold::<(&ConfigModule, &Field, &config::Type, &'a str), FieldDefinition, String>::new(
+        move |(_, field, _, name), mut b_field| match &field.rate_limit {
+            Some(rate_limit) => RateLimit::make(rate_limit).map(|limit| {
+                let expr = b_field
+                    .resolver
+                    .take()
+                    .unwrap_or(IR::ContextPath(vec![b_field.name.clone()]));
+
+                b_field.resolver = Some(IR::RateLimit(model::RateLimit {
+                    name: format!("{}.{}", type_name, name),
+                    limit,
+                    expr: Box::new(expr),
+                }));
+                b_field
+            }),
+            None => Valid::succeed(b_field),
+        },
+    )
+}
@@ -0,0 +1,344 @@
+use std::collections::{HashMap, HashSet};
+use std::num::NonZeroU64;
+
+use async_graphql::parser::parse_query;
+use async_graphql::parser::types::{ExecutableDocument, Selection, SelectionSet};
+use async_graphql::{Name, Pos};
+use sha2::{Digest, Sha256};
+
+use crate::core::config::ConfigModule;
+use crate::core::valid::{Valid, Validator};
+
+/// Default time in milliseconds for which the queries registered by the
+/// clients are kept in the cache.
+const DEFAULT_TTL: u64 = 86_400_000;
+
+#[derive(Clone, Debug)]
+pub struct PersistedQueries {
+    pub ttl: NonZeroU64,
+    pub safelist: Option<Safelist>,
+}
+
+/// Operations linked with `@link(type: Operation)`. Every operation of a
+/// document is registered along with the fragments it uses, as well as the
+/// whole document. An entry is identified by the SHA-256 hash of its content,
+/// and a query is part of the safelist when it only differs from an entry by
+/// its formatting.
+#[derive(Clone, Debug, Default)]
+pub struct Safelist {
+    queries: HashMap<String, String>,
+    normalized: HashSet<String>,
+}
+
+impl Safelist {
+    pub fn new<'a>(documents: impl IntoIterator<Item = &'a String>) -> Self {
+        let mut safelist = Safelist::default();
+        for document in documents {
+            safelist.insert(document.clone());
+            // the documents are validated with the config
+            if let Ok(parsed) = parse_query(document) {
+                for operation in operations(document, &parsed) {
+                    safelist.insert(operation);
+                }
+            }
+        }
+        safelist
+    }
+
+    fn insert(&mut self, document: String) {
+        self.normalized.insert(normalize(&document));
+        self.queries.insert(sha256(&document), document);
+    }
+
+    /// Returns the document with the given SHA-256 hash.
+    pub fn get(&self, hash: &str) -> Option<&String> {
+        self.queries.get(hash)
+    }
+
+    pub fn contains(&self, query: &str) -> bool {
+        self.normalized.contains(&normalize(query))
+    }
+}
+
+/// Splits a document into one document per operation, made of the operation
+/// followed by the fragments it uses, in the order of the original document.
+fn operations(document: &str, parsed: &ExecutableDocument) -> Vec<String> {
+    let mut definitions = parsed
+        .operations
+        .iter()
+        .map(|(_, operation)| operation.pos)
+        .chain(parsed.fragments.values().map(|fragment| fragment.pos))
+        .map(|pos| offset(document, pos))
+        .collect::<Vec<_>>();
+    definitions.sort();
+
+    // the text of a definition goes up to the closing brace of its selection
+    // set, anything between it and the next definition is ignored
+    let text = |pos: Pos| {
+        let start = offset(document, pos);
+        let end = definitions
+            .iter()
+            .find(|offset| **offset > start)
+            .copied()
+            .unwrap_or(document.len());
+        let text = &document[start..end];
+        text.rfind('}').map_or(text, |index| &text[..=index])
+    };
+
+    parsed
+        .operations
+        .iter()
+        .map(|(_, operation)| {
+            let mut fragments = Vec::new();
+            spreads(&operation.node.selection_set.node, parsed, &mut fragments);
+
+            let mut positions = fragments
+                .into_iter()
+                .filter_map(|name| parsed.fragments.get(name))
+                .map(|fragment| fragment.pos)
+                .collect::<Vec<_>>();
+            positions.sort_by_key(|pos| offset(document, *pos));
+            positions.insert(0, operation.pos);
+
+            positions
+                .into_iter()
+                .map(text)
+                .collect::<Vec<_>>()
+                .join("\n\n")
+        })
+        .collect()
+}
+
+/// Collects the names of the fragments used by the selection set, including
+/// the ones used by other fragments.
+fn spreads<'a>(
+    selection_set: &'a SelectionSet,
+    document: &'a ExecutableDocument,
+    fragments: &mut Vec<&'a Name>,
+) {
+    for selection in &selection_set.items {
+        match &selection.node {
+            Selection::Field(field) => spreads(&field.node.selection_set.node, document, fragments),
+            Selection::InlineFragment(fragment) => {
+                spreads(&fragment.node.selection_set.node, document, fragments)
+            }
+            Selection::FragmentSpread(spread) => {
+                let name = &spread.node.fragment_name.node;
+                if fragments.contains(&name) {
+                    continue;
+                }
+                fragments.push(name);
+                if let Some(fragment) = document.fragments.get(name) {
+                    spreads(&fragment.node.selection_set.node, document, fragments);
+                }
+            }
+        }
+    }
+}
+
+/// Converts the position of the parser, whose line and column start at 1, to
+/// an offset in the document.
+fn offset(document: &str, pos: Pos) -> usize {
+    let line = document
+        .split_inclusive('\n')
+        .take(pos.line.saturating_sub(1))
+        .map(str::len)
+        .sum::<usize>();
+    document[line..]
+        .char_indices()
+        .nth(pos.column.saturating_sub(1))
+        .map_or(document.len(), |(index, _)| line + index)
+}
+
+impl PersistedQueries {
+    pub fn make(config_module: &ConfigModule) -> Valid<Option<PersistedQueries>, String> {
+        let Some(persisted_queries) = config_module.server.persisted_queries.as_ref() else {
+            return Valid::succeed(None);
+        };
+
+        let operations = &config_module.extensions().operations;
+        let safelist = if persisted_queries.safelist.unwrap_or(false) {
+            if operations.is_empty() {
+                return Valid::fail(
+                    "The safelist requires the operations to be linked with @link(type: Operation)"
+                        .to_string(),
+                )
+                .trace("safelist")
+                .trace("persistedQueries");
+            }
+            for operation in operations {
+                if let Err(err) = parse_query(operation) {
+                    return Valid::fail(format!("Invalid operation in the safelist: {}", err))
+                        .trace("safelist")
+                        .trace("persistedQueries");
+                }
+            }
+            Some(Safelist::new(operations))
+        } else {
+            None
+        };
+
+        Valid::from_option(
+            NonZeroU64::new(persisted_queries.ttl.unwrap_or(DEFAULT_TTL)),
+            "ttl must be greater than 0".to_string(),
+        )
+        .trace("ttl")
+        .trace("persistedQueries")
+        .map(|ttl| Some(PersistedQueries { ttl, safelist }))
+    }
+}
+
+/// Hex encoded SHA-256 hash of the query, as used by the Automatic Persisted
+/// Queries protocol.
+pub fn sha256(query: &str) -> String {
+    hex::encode(Sha256::digest(query.as_bytes()))
+}
+
+/// Removes the insignificant characters of a GraphQL document i.e. comments,
+/// commas and the whitespaces that don't separate two names or values.
+pub fn normalize(query: &str) -> String {
+    let mut normalized = String::with_capacity(query.len());
+    let mut chars = query.chars();
+    let mut separated = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                if separated && needs_separator(&normalized) {
+                    normalized.push(' ');
+                }
+                separated = false;
+                normalized.push(c);
+                while let Some(c) = chars.next() {
+                    normalized.push(c);
+                    match c {
+                        '\\' => normalized.extend(chars.next()),
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '#' => {
+                separated = true;
+                for c in chars.by_ref() {
+                    if c == '\n' || c == '\r' {
+                        break;
+                    }
+                }
+            }
+            c if c.is_whitespace() || c == ',' || c == '\u{feff}' => separated = true,
+            c => {
+                if separated && needs_separator(&normalized) && !is_punctuator(c) {
+                    normalized.push(' ');
+                }
+                separated = false;
+                normalized.push(c);
+            }
+        }
+    }
+
+    normalized
+}
+
+fn is_punctuator(c: char) -> bool {
+    matches!(
+        c,
+        '!' | '$' | '&' | '(' | ')' | ':' | '=' | '@' | '[' | ']' | '{' | '|' | '}'
+    )
+}
+
+fn needs_separator(normalized: &str) -> bool {
+    normalized
+        .chars()
+        .last()
+        .map_or(false, |c| !is_punctuator(c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sha256() {
+        assert_eq!(
+            sha256("{__typename}"),
+            "ecf4edb46db40b5132295c0291d62fb65d6759a9eedfa4d5d612dd5ec54a6b38"
+        );
+    }
+
+    #[test]
+    fn test_normalize() {
+        let query = r#"
+            # Fetches a user
+            query user($id: Int!) {
+              user(id: $id, name: "A  B") {
+                id,
+                name
+              }
+            }
+        "#;
+
+        assert_eq!(
+            normalize(query),
+            r#"query user($id:Int!){user(id:$id name:"A  B"){id name}}"#
+        );
+    }
+
+    #[test]
+    fn test_safelist() {
+        let document = "query { user { id name } }".to_string();
+        let safelist = Safelist::new([&document]);
+
+        assert!(safelist.contains("query {\n  user {\n    id\n    name\n  }\n}"));
+        assert!(!safelist.contains("query { user { id } }"));
+        assert_eq!(safelist.get(&sha256(&document)), Some(&document));
+    }
+
+    #[test]
+    fn test_safelist_operations() {
+        let document = r#"
+            query users {
+              users {
+                ...UserFields
+              }
+            }
+
+            # Fetches a post
+            query post($id: Int!) {
+              post(id: $id) {
+                title
+                user {
+                  ... on User {
+                    ...UserName
+                  }
+                }
+              }
+            }
+
+            fragment UserFields on User {
+              id
+              ...UserName
+            }
+
+            fragment UserName on User {
+              name
+            }
+        "#
+        .to_string();
+        let safelist = Safelist::new([&document]);
+
+        let users = "query users {\n  users {\n    ...UserFields\n  }\n}\n\nfragment UserFields on User {\n  id\n  ...UserName\n}\n\nfragment UserName on User {\n  name\n}";
+        let post = "query post($id: Int!) { post(id: $id) { title user { ... on User { ...UserName } } } } fragment UserName on User { name }";
+        assert!(safelist.contains(users));
+        assert!(safelist.contains(post));
+        assert!(!safelist.contains("query users { users { ...UserFields } }"));
+        assert!(!safelist.contains("query post($id: Int!) { post(id: $id) { title } }"));
+        assert!(safelist.contains(&document));
+
+        let operations = operations(&document, &parse_query(&document).unwrap());
+        assert_eq!(operations.len(), 2);
+        for operation in operations {
+            assert_eq!(safelist.get(&sha256(&operation)), Some(&operation));
+        }
+    }
+}
@@ -0,0 +1,364 @@
+use std::sync::Arc;
+
+use async_graphql::extensions::{Extension, ExtensionContext, ExtensionFactory, NextParseQuery};
+use async_graphql::parser::types::{
+    ExecutableDocument, Field, OperationDefinition, OperationType, Selection, SelectionSet,
+};
+use async_graphql::{ServerError, ServerResult, Variables};
+use async_graphql_value::{ConstValue, Value};
+use thiserror::Error;
+
+use crate::core::blueprint::{FieldDefinition, Index, QueryField};
+use crate::core::config;
+
+/// Limits of the operations that protect the server against abusive queries.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct QueryLimits {
+    pub max_depth: Option<usize>,
+    pub max_aliases: Option<usize>,
+    pub max_root_fields: Option<usize>,
+    pub max_cost: Option<usize>,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum LimitError {
+    #[error("Query is nested too deep: depth of {depth} exceeds the limit of {max}")]
+    Depth { depth: usize, max: usize },
+    #[error("Query has too many aliases: {aliases} aliases exceed the limit of {max}")]
+    Aliases { aliases: usize, max: usize },
+    #[error("Query selects too many root fields: {fields} fields exceed the limit of {max}")]
+    RootFields { fields: usize, max: usize },
+    #[error("Query is too expensive: cost of {cost} exceeds the limit of {max}")]
+    Cost { cost: usize, max: usize },
+}
+
+impl QueryLimits {
+    pub fn new(server: &config::Server) -> Self {
+        Self {
+            max_depth: server.max_depth,
+            max_aliases: server.max_aliases,
+            max_root_fields: server.max_root_fields,
+            max_cost: server.max_cost,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Checks every operation of the document against the limits.
+    pub fn check_document<'a>(
+        &self,
+        index: &Index,
+        document: &ExecutableDocument,
+        variables: impl Fn(&str) -> Option<&'a ConstValue> + Copy,
+    ) -> Result<(), LimitError> {
+        document
+            .operations
+            .iter()
+            .try_for_each(|(_, operation)| self.check(index, document, &operation.node, variables))
+    }
+
+    /// Checks the operation against the limits. Fragments are expanded, and
+    /// the introspection fields aren't counted.
+    pub fn check<'a>(
+        &self,
+        index: &Index,
+        document: &ExecutableDocument,
+        operation: &OperationDefinition,
+        variables: impl Fn(&str) -> Option<&'a ConstValue>,
+    ) -> Result<(), LimitError> {
+        if self.is_empty() {
+            return Ok(());
+        }
+
+        let root = match operation.ty {
+            OperationType::Query => Some(index.get_query().as_str()),
+            OperationType::Mutation => index.get_mutation(),
+            OperationType::Subscription => index.get_subscription(),
+        };
+        let Some(root) = root else {
+            return Ok(());
+        };
+
+        let mut walker = Walker {
+            index,
+            document,
+            variables,
+            fragments: Vec::new(),
+            depth: 0,
+            aliases: 0,
+            root_fields: 0,
+        };
+        let cost = walker.walk(&operation.selection_set.node, root, 1);
+
+        check_limit(walker.depth, self.max_depth, |depth, max| {
+            LimitError::Depth { depth, max }
+        })?;
+        check_limit(walker.aliases, self.max_aliases, |aliases, max| {
+            LimitError::Aliases { aliases, max }
+        })?;
+        check_limit(walker.root_fields, self.max_root_fields, |fields, max| {
+            LimitError::RootFields { fields, max }
+        })?;
+        check_limit(cost, self.max_cost, |cost, max| LimitError::Cost {
+            cost,
+            max,
+        })
+    }
+}
+
+fn check_limit(
+    value: usize,
+    max: Option<usize>,
+    error: impl FnOnce(usize, usize) -> LimitError,
+) -> Result<(), LimitError> {
+    match max {
+        Some(max) if value > max => Err(error(value, max)),
+        _ => Ok(()),
+    }
+}
+
+struct Walker<'a, F> {
+    index: &'a Index,
+    document: &'a ExecutableDocument,
+    variables: F,
+    fragments: Vec<&'a str>,
+    depth: usize,
+    aliases: usize,
+    root_fields: usize,
+}
+
+impl<'a, 'b, F: Fn(&str) -> Option<&'b ConstValue>> Walker<'a, F> {
+    /// Measures the selection set of the type at the given depth, and returns
+    /// its cost.
+    fn walk(&mut self, selection_set: &'a SelectionSet, type_name: &'a str, depth: usize) -> usize {
+        let mut cost = 0usize;
+
+        for selection in &selection_set.items {
+            let selection_cost = match &selection.node {
+                Selection::Field(field) => self.walk_field(&field.node, type_name, depth),
+                Selection::FragmentSpread(spread) => {
+                    let name = spread.node.fragment_name.node.as_str();
+                    let Some(fragment) = self.document.fragments.get(name) else {
+                        continue;
+                    };
+                    if self.fragments.contains(&name) {
+                        continue;
+                    }
+
+                    self.fragments.push(name);
+                    let type_name = fragment.node.type_condition.node.on.node.as_str();
+                    let cost = self.walk(&fragment.node.selection_set.node, type_name, depth);
+                    self.fragments.pop();
+                    cost
+                }
+                Selection::InlineFragment(fragment) => {
+                    let type_name = fragment
+                        .node
+                        .type_condition
+                        .as_ref()
+                        .map_or(type_name, |condition| condition.node.on.node.as_str());
+                    self.walk(&fragment.node.selection_set.node, type_name, depth)
+                }
+            };
+            cost = cost.saturating_add(selection_cost);
+        }
+
+        cost
+    }
+
+    fn walk_field(&mut self, field: &'a Field, type_name: &'a str, depth: usize) -> usize {
+        if field.alias.is_some() {
+            self.aliases += 1;
+        }
+        if field.name.node.starts_with("__") {
+            return 0;
+        }
+        if depth == 1 {
+            self.root_fields += 1;
+        }
+        self.depth = self.depth.max(depth);
+
+        let Some(QueryField::Field((definition, _))) =
+            self.index.get_field(type_name, field.name.node.as_str())
+        else {
+            return 0;
+        };
+
+        let nested = self.walk(
+            &field.selection_set.node,
+            definition.of_type.name(),
+            depth + 1,
+        );
+        let (weight, multiplier) = match &definition.cost {
+            Some(cost) => (
+                cost.weight.unwrap_or(1),
+                cost.multipliers.iter().fold(1usize, |multiplier, arg| {
+                    multiplier.saturating_mul(self.argument(field, definition, arg))
+                }),
+            ),
+            None => (1, 1),
+        };
+
+        weight.saturating_add(multiplier.saturating_mul(nested))
+    }
+
+    /// Returns the value of an integer argument of the field, or its default
+    /// value when it isn't set.
+    fn argument(&self, field: &Field, definition: &FieldDefinition, name: &str) -> usize {
+        let value = match field.get_argument(name).map(|value| &value.node) {
+            Some(Value::Number(number)) => number.as_u64(),
+            Some(Value::Variable(variable)) => match (self.variables)(variable.as_str()) {
+                Some(ConstValue::Number(number)) => number.as_u64(),
+                _ => None,
+            },
+            _ => definition
+                .args
+                .iter()
+                .find(|arg| arg.name == name)
+                .and_then(|arg| arg.default_value.as_ref())
+                .and_then(|value| value.as_u64()),
+        };
+
+        value.map_or(1, |value| value as usize)
+    }
+}
+
+/// Rejects the queries that exceed the limits before they are validated.
+#[derive(Clone)]
+pub struct QueryLimiter {
+    limits: Arc<QueryLimits>,
+    index: Arc<Index>,
+}
+
+impl QueryLimiter {
+    pub fn new(limits: QueryLimits, index: Index) -> Self {
+        Self { limits: Arc::new(limits), index: Arc::new(index) }
+    }
+}
+
+impl ExtensionFactory for QueryLimiter {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(self.clone())
+    }
+}
+
+#[async_trait::async_trait]
+impl Extension for QueryLimiter {
+    async fn parse_query(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        query: &str,
+        variables: &Variables,
+        next: NextParseQuery<'_>,
+    ) -> ServerResult<ExecutableDocument> {
+        let document = next.run(ctx, query, variables).await?;
+        self.limits
+            .check_document(&self.index, &document, |name| variables.get(name))
+            .map_err(|err| ServerError::new(err.to_string(), None))?;
+        Ok(document)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_graphql::parser::parse_query;
+
+    use super::*;
+    use crate::core::blueprint::Blueprint;
+    use crate::core::config::{Config, ConfigModule};
+    use crate::core::valid::Validator;
+
+    const CONFIG: &str = r#"
+        schema @server(port: 8000) {
+          query: Query
+        }
+
+        type Query {
+          users(first: Int = 10): [User]
+            @cost(weight: 2, multipliers: ["first"])
+            @http(baseURL: "http://localhost", path: "/users")
+        }
+
+        type User {
+          id: Int
+          name: String
+          friends(first: Int): [User]
+            @cost(multipliers: ["first"])
+            @http(baseURL: "http://localhost", path: "/friends")
+        }
+    "#;
+
+    fn index() -> Index {
+        let config = Config::from_sdl(CONFIG).to_result().unwrap();
+        Blueprint::try_from(&ConfigModule::from(config))
+            .unwrap()
+            .index()
+    }
+
+    fn check(limits: QueryLimits, query: &str) -> Result<(), LimitError> {
+        let variables = Variables::from_json(serde_json::json!({ "first": 3 }));
+        let document = parse_query(query).unwrap();
+        limits.check_document(&index(), &document, |name| variables.get(name))
+    }
+
+    #[test]
+    fn test_depth() {
+        let limits = QueryLimits { max_depth: Some(2), ..Default::default() };
+
+        assert_eq!(check(limits.clone(), "{ users { id } }"), Ok(()));
+        assert_eq!(
+            check(limits, "{ users { friends { id } } }"),
+            Err(LimitError::Depth { depth: 3, max: 2 })
+        );
+    }
+
+    #[test]
+    fn test_aliases_and_root_fields() {
+        let query = "{ a: users { id } b: users { id } ...F } fragment F on Query { users { id } }";
+
+        assert_eq!(
+            check(
+                QueryLimits { max_aliases: Some(1), ..Default::default() },
+                query
+            ),
+            Err(LimitError::Aliases { aliases: 2, max: 1 })
+        );
+        assert_eq!(
+            check(
+                QueryLimits { max_root_fields: Some(2), ..Default::default() },
+                query
+            ),
+            Err(LimitError::RootFields { fields: 3, max: 2 })
+        );
+    }
+
+    #[test]
+    fn test_cost() {
+        let limits = QueryLimits { max_cost: Some(100), ..Default::default() };
+
+        // 2 + 10 * (1 + 1) = 22
+        assert_eq!(
+            check(limits.clone(), "{ users { id name __typename } }"),
+            Ok(())
+        );
+        // 2 + 10 * (1 + (1 + 3 * (1 + 1))) = 82
+        let query = "query($first: Int) { users { id friends(first: $first) { id name } } }";
+        assert_eq!(check(limits.clone(), query), Ok(()));
+        // 2 + 10 * (1 + (1 + 5 * (1 + (1 + 2 * 1)))) = 222
+        let query = "{ users { id friends(first: 5) { id friends(first: 2) { id } } } }";
+        assert_eq!(
+            check(limits, query),
+            Err(LimitError::Cost { cost: 222, max: 100 })
+        );
+    }
+
+    #[test]
+    fn test_recursive_fragment() {
+        let limits = QueryLimits { max_depth: Some(10), ..Default::default() };
+        let query = "{ users { ...F } } fragment F on User { friends { ...F } }";
+
+        assert_eq!(check(limits, query), Ok(()));
+    }
+}
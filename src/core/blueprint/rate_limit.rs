@@ -0,0 +1,113 @@
+use std::num::NonZeroU64;
+use std::time::Duration;
+
+use hyper::header::HeaderName;
+
+use crate::core::config;
+use crate::core::valid::{Valid, ValidationError, Validator};
+
+/// Number of requests allowed over a duration, enforced with a token bucket
+/// that holds up to `requests` tokens and is refilled over the `duration`.
+#[derive(Clone, Debug, PartialEq, Eq, schemars::JsonSchema)]
+pub struct Rate {
+    pub requests: NonZeroU64,
+    pub duration: Duration,
+}
+
+/// Identifies the client whose requests are limited. The IP address of the
+/// client is used when the header or the claim is missing. The clients whose
+/// IP address is unknown to the runtime, like behind a load balancer on AWS
+/// Lambda, share a single bucket.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RateLimitKey {
+    Ip,
+    Header(HeaderName),
+    Claim(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RateLimit {
+    pub rate: Rate,
+    pub key: RateLimitKey,
+}
+
+impl Rate {
+    pub fn make(requests: u64, duration: u64) -> Valid<Rate, String> {
+        Valid::from_option(
+            NonZeroU64::new(requests),
+            "requests must be greater than 0".to_string(),
+        )
+        .trace("requests")
+        .fuse(
+            Valid::from_option(
+                NonZeroU64::new(duration),
+                "duration must be greater than 0".to_string(),
+            )
+            .trace("duration"),
+        )
+        .map(|(requests, duration)| Rate {
+            requests,
+            duration: Duration::from_millis(duration.get()),
+        })
+    }
+}
+
+impl RateLimit {
+    pub fn make(rate_limit: &config::RateLimit) -> Valid<RateLimit, String> {
+        let key = match (&rate_limit.header, &rate_limit.claim) {
+            (Some(_), Some(_)) => {
+                Valid::fail("Only one of header and claim can identify the client".to_string())
+            }
+            (Some(header), None) => Valid::from(
+                HeaderName::try_from(header.as_str())
+                    .map(RateLimitKey::Header)
+                    .map_err(|err| ValidationError::new(err.to_string())),
+            )
+            .trace("header"),
+            (None, Some(claim)) => Valid::succeed(RateLimitKey::Claim(claim.clone())),
+            (None, None) => Valid::succeed(RateLimitKey::Ip),
+        };
+
+        Rate::make(rate_limit.requests, rate_limit.duration)
+            .fuse(key)
+            .map(|(rate, key)| RateLimit { rate, key })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limit() {
+        let config = config::RateLimit {
+            requests: 10,
+            duration: 60000,
+            header: Some("X-Api-Key".to_string()),
+            claim: None,
+        };
+
+        let rate_limit = RateLimit::make(&config).to_result().unwrap();
+
+        assert_eq!(rate_limit.rate.requests.get(), 10);
+        assert_eq!(rate_limit.rate.duration, Duration::from_secs(60));
+        assert_eq!(
+            rate_limit.key,
+            RateLimitKey::Header(HeaderName::from_static("x-api-key"))
+        );
+    }
+
+    #[test]
+    fn test_rate_limit_invalid() {
+        let config = config::RateLimit {
+            requests: 0,
+            duration: 1000,
+            header: Some("x-api-key".to_string()),
+            claim: Some("sub".to_string()),
+        };
+
+        let errors = RateLimit::make(&config).to_result().unwrap_err();
+
+        assert_eq!(errors.as_vec().len(), 2);
+    }
+}
@@ -0,0 +1,50 @@
+use std::num::NonZeroU64;
+
+use hyper::header::HeaderName;
+
+use crate::core::config;
+use crate::core::valid::{Valid, ValidationError, Validator};
+
+/// Cache of the whole responses of the queries, that are identified by their
+/// normalized document, their variables and the listed headers and claims of
+/// the client.
+#[derive(Clone, Debug)]
+pub struct ResponseCache {
+    pub max_age: NonZeroU64,
+    pub headers: Vec<HeaderName>,
+    pub claims: Vec<String>,
+}
+
+impl ResponseCache {
+    pub fn make(config_server: &config::Server) -> Valid<Option<ResponseCache>, String> {
+        let Some(response_cache) = config_server.response_cache.as_ref() else {
+            return Valid::succeed(None);
+        };
+
+        let max_age = Valid::from_option(
+            NonZeroU64::new(response_cache.max_age),
+            "maxAge must be greater than 0".to_string(),
+        )
+        .trace("maxAge");
+        let headers = Valid::from_iter(response_cache.headers.iter(), |name| {
+            Valid::from(
+                HeaderName::from_bytes(name.as_bytes())
+                    .map_err(|err| ValidationError::new(format!("{}: {}", name, err))),
+            )
+        })
+        .trace("headers");
+
+        max_age
+            .fuse(headers)
+            .map(|(max_age, headers)| {
+                Some(ResponseCache {
+                    max_age,
+                    headers,
+                    claims: response_cache.claims.iter().cloned().collect(),
+                })
+            })
+            .trace("responseCache")
+            .trace("@server")
+            .trace("schema")
+    }
+}
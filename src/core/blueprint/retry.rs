@@ -0,0 +1,162 @@
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+use crate::core::config;
+use crate::core::http::Method;
+use crate::core::valid::{Valid, Validator};
+
+/// Policy to send the failed requests to an upstream again.
+#[derive(Clone, Debug, PartialEq, Eq, schemars::JsonSchema)]
+pub struct Retry {
+    pub max_attempts: usize,
+    pub backoff: Duration,
+    pub max_backoff: Duration,
+    pub status_codes: BTreeSet<u16>,
+    pub methods: Vec<Method>,
+    /// The calls are idempotent, so they're retried whatever their method.
+    pub idempotent: bool,
+}
+
+/// Fails the requests to a host right away after `failure_threshold`
+/// consecutive failures, until `reset_timeout` has passed.
+#[derive(Clone, Debug, PartialEq, Eq, schemars::JsonSchema)]
+pub struct CircuitBreaker {
+    pub failure_threshold: usize,
+    pub reset_timeout: Duration,
+}
+
+impl Retry {
+    pub fn make(retry: &config::Retry) -> Valid<Retry, String> {
+        let max_attempts = retry.max_attempts.unwrap_or(3);
+        let backoff = Duration::from_millis(retry.backoff.unwrap_or(100));
+        let max_backoff = Duration::from_millis(retry.max_backoff.unwrap_or(5000));
+
+        Valid::<(), String>::fail("maxAttempts must be greater than 0".to_string())
+            .trace("maxAttempts")
+            .when(|| max_attempts == 0)
+            .and(
+                Valid::<(), String>::fail("maxBackoff must not be less than backoff".to_string())
+                    .trace("maxBackoff")
+                    .when(|| max_backoff < backoff),
+            )
+            .map(|_| Retry {
+                max_attempts,
+                backoff,
+                max_backoff,
+                status_codes: retry
+                    .status_codes
+                    .clone()
+                    .map_or_else(|| BTreeSet::from([429, 502, 503, 504]), BTreeSet::from_iter),
+                methods: retry.methods.clone().unwrap_or_else(|| {
+                    vec![
+                        Method::GET,
+                        Method::HEAD,
+                        Method::OPTIONS,
+                        Method::PUT,
+                        Method::DELETE,
+                    ]
+                }),
+                idempotent: false,
+            })
+    }
+
+    /// Retries the calls whatever their method, for the protocols that send
+    /// idempotent calls with POST requests.
+    pub fn idempotent(self) -> Self {
+        Self { idempotent: true, ..self }
+    }
+
+    /// Checks if the requests with the given method can be retried.
+    pub fn allows(&self, method: &hyper::Method) -> bool {
+        self.idempotent
+            || self
+                .methods
+                .iter()
+                .any(|allowed| allowed.clone().to_hyper() == *method)
+    }
+
+    /// Delay before the given retry, starting at 1. The delay doubles with
+    /// every retry up to the `max_backoff`, and a random jitter of up to half
+    /// of it is subtracted so that the clients don't retry all at once.
+    pub fn delay(&self, retry: u32) -> Duration {
+        let delay = self
+            .backoff
+            .saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+            .min(self.max_backoff);
+
+        delay.mul_f64(1.0 - rand::random::<f64>() / 2.0)
+    }
+}
+
+/// Compiles the retry policy of an operator, which defaults to the one of the
+/// upstream.
+pub fn to_retry(
+    upstream: &config::Upstream,
+    retry: Option<&config::Retry>,
+) -> Valid<Option<Retry>, String> {
+    retry.or(upstream.retry.as_ref()).map_or_else(
+        || Valid::succeed(None),
+        |retry| Retry::make(retry).map(Some).trace("retry"),
+    )
+}
+
+impl CircuitBreaker {
+    pub fn make(circuit_breaker: &config::CircuitBreaker) -> Valid<CircuitBreaker, String> {
+        let failure_threshold = circuit_breaker.failure_threshold.unwrap_or(5);
+        let reset_timeout = Duration::from_millis(circuit_breaker.reset_timeout.unwrap_or(30000));
+
+        Valid::<(), String>::fail("failureThreshold must be greater than 0".to_string())
+            .trace("failureThreshold")
+            .when(|| failure_threshold == 0)
+            .map(|_| CircuitBreaker { failure_threshold, reset_timeout })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_defaults() {
+        let retry = Retry::make(&config::Retry::default()).to_result().unwrap();
+
+        assert_eq!(retry.max_attempts, 3);
+        assert!(retry.allows(&hyper::Method::GET));
+        assert!(!retry.allows(&hyper::Method::POST));
+        assert!(retry.status_codes.contains(&503));
+
+        let retry = retry.idempotent();
+        assert!(retry.allows(&hyper::Method::POST));
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        let config = config::Retry {
+            backoff: Some(100),
+            max_backoff: Some(300),
+            ..Default::default()
+        };
+        let retry = Retry::make(&config).to_result().unwrap();
+
+        let delay = retry.delay(1);
+        assert!(delay >= Duration::from_millis(50) && delay <= Duration::from_millis(100));
+        let delay = retry.delay(2);
+        assert!(delay >= Duration::from_millis(100) && delay <= Duration::from_millis(200));
+        let delay = retry.delay(10);
+        assert!(delay >= Duration::from_millis(150) && delay <= Duration::from_millis(300));
+    }
+
+    #[test]
+    fn test_retry_invalid() {
+        let config = config::Retry {
+            max_attempts: Some(0),
+            backoff: Some(1000),
+            max_backoff: Some(10),
+            ..Default::default()
+        };
+
+        let errors = Retry::make(&config).to_result().unwrap_err();
+
+        assert_eq!(errors.as_vec().len(), 2);
+    }
+}
@@ -9,9 +9,12 @@ use hyper::header::{HeaderName, HeaderValue};
 use hyper::HeaderMap;
 use rustls_pki_types::{CertificateDer, PrivateKeyDer};
 
-use super::Auth;
+use super::{
+    to_cache_store, Auth, CacheStore, PersistedQueries, QueryLimits, RateLimit, ResponseCache,
+};
 use crate::core::blueprint::Cors;
 use crate::core::config::{self, ConfigModule, HttpVersion};
+use crate::core::mustache::Mustache;
 use crate::core::valid::{Valid, ValidationError, Validator};
 
 #[derive(Clone, Debug, Setters)]
@@ -38,6 +41,12 @@ pub struct Server {
     pub experimental_headers: HashSet<HeaderName>,
     pub auth: Option<Auth>,
     pub dedupe: bool,
+    pub persisted_queries: Option<PersistedQueries>,
+    pub response_cache: Option<ResponseCache>,
+    pub query_limits: QueryLimits,
+    pub rate_limit: Option<RateLimit>,
+    pub cache: CacheStore,
+    pub admin: Option<Admin>,
 }
 
 /// Mimic of mini_v8::Script that's wasm compatible
@@ -53,9 +62,25 @@ pub enum Http {
     HTTP2 {
         cert: Vec<CertificateDer<'static>>,
         key: Arc<PrivateKeyDer<'static>>,
+        client_cert: Option<ClientCert>,
     },
 }
 
+/// The administration endpoints, that are served under the path to the
+/// clients sending the token.
+#[derive(Clone, Debug)]
+pub struct Admin {
+    pub path: String,
+    pub token: Mustache,
+}
+
+/// The CA certificates that verify the certificates of the clients.
+#[derive(Clone, Debug)]
+pub struct ClientCert {
+    pub ca: Vec<CertificateDer<'static>>,
+    pub required: bool,
+}
+
 impl Default for Server {
     fn default() -> Self {
         // NOTE: Using unwrap because try_from default will never fail
@@ -108,9 +133,18 @@ impl TryFrom<crate::core::config::ConfigModule> for Server {
 
                 let key: Arc<PrivateKeyDer<'_>> = Arc::new(key_file);
 
-                Valid::succeed(Http::HTTP2 { cert, key })
+                to_client_cert(&config_module).map(|client_cert| Http::HTTP2 {
+                    cert,
+                    key,
+                    client_cert,
+                })
             }
-            _ => Valid::succeed(Http::HTTP1),
+            _ => Valid::<(), String>::fail("clientCert requires the HTTP2 version".to_string())
+                .trace("clientCert")
+                .trace("@server")
+                .trace("schema")
+                .when(|| config_server.client_cert.is_some())
+                .map_to(Http::HTTP1),
         };
 
         validate_hostname((config_server).get_hostname().to_lowercase())
@@ -129,8 +163,30 @@ impl TryFrom<crate::core::config::ConfigModule> for Server {
                     .and_then(|headers| headers.get_cors()),
             ))
             .fuse(Auth::make(&config_module))
+            .fuse(PersistedQueries::make(&config_module))
+            .fuse(ResponseCache::make(&config_server))
+            .fuse(to_rate_limit(&config_server))
+            .fuse(
+                to_cache_store(&config_server)
+                    .trace("@server")
+                    .trace("schema"),
+            )
+            .fuse(to_admin(&config_server))
             .map(
-                |(hostname, http, response_headers, script, experimental_headers, cors, auth)| {
+                |(
+                    hostname,
+                    http,
+                    response_headers,
+                    script,
+                    experimental_headers,
+                    cors,
+                    auth,
+                    persisted_queries,
+                    response_cache,
+                    rate_limit,
+                    cache,
+                    admin,
+                )| {
                     Server {
                         enable_jit: (config_server).enable_jit(),
                         enable_apollo_tracing: (config_server).enable_apollo_tracing(),
@@ -154,6 +210,12 @@ impl TryFrom<crate::core::config::ConfigModule> for Server {
                         cors,
                         auth,
                         dedupe: config_server.get_dedupe(),
+                        persisted_queries,
+                        response_cache,
+                        query_limits: QueryLimits::new(&config_server),
+                        rate_limit,
+                        cache,
+                        admin,
                     }
                 },
             )
@@ -161,6 +223,67 @@ impl TryFrom<crate::core::config::ConfigModule> for Server {
     }
 }
 
+fn to_rate_limit(config_server: &config::Server) -> Valid<Option<RateLimit>, String> {
+    config_server.rate_limit.as_ref().map_or_else(
+        || Valid::succeed(None),
+        |rate_limit| RateLimit::make(rate_limit).map(Some).trace("rateLimit"),
+    )
+}
+
+fn to_admin(config_server: &config::Server) -> Valid<Option<Admin>, String> {
+    let Some(admin) = &config_server.admin else {
+        return Valid::succeed(None);
+    };
+
+    let path = admin.path.as_deref().unwrap_or("/admin");
+    let path = match path.trim_end_matches('/') {
+        trimmed if path.starts_with('/') && !trimmed.is_empty() => {
+            Valid::succeed(trimmed.to_string())
+        }
+        _ => Valid::fail(format!("{} must start with / and not be the root", path)),
+    };
+    let token = Valid::from(
+        Mustache::parse(&admin.token).map_err(|err| ValidationError::new(err.to_string())),
+    );
+
+    path.trace("path")
+        .fuse(token.trace("token"))
+        .map(|(path, token)| Some(Admin { path, token }))
+        .trace("admin")
+        .trace("@server")
+        .trace("schema")
+}
+
+fn to_client_cert(config_module: &ConfigModule) -> Valid<Option<ClientCert>, String> {
+    let Some(client_cert) = &config_module.server.client_cert else {
+        return Valid::succeed(None);
+    };
+
+    let ca = config_module
+        .extensions()
+        .tls_certs
+        .iter()
+        .find(|cert| cert.id.as_ref() == Some(&client_cert.ca));
+
+    match ca {
+        Some(ca) => Valid::from(
+            rustls_pemfile::certs(&mut ca.content.as_bytes())
+                .map_err(|e| ValidationError::new(e.to_string())),
+        ),
+        None => Valid::fail(format!("No Cert link with the id {}", client_cert.ca)),
+    }
+    .map(|ca| {
+        Some(ClientCert {
+            ca: ca.into_iter().map(CertificateDer::from).collect(),
+            required: client_cert.required.unwrap_or(true),
+        })
+    })
+    .trace("ca")
+    .trace("clientCert")
+    .trace("@server")
+    .trace("schema")
+}
+
 fn to_script(config_module: &crate::core::config::ConfigModule) -> Valid<Option<Script>, String> {
     config_module.extensions().script.as_ref().map_or_else(
         || Valid::succeed(None),
@@ -0,0 +1,151 @@
+use reqwest::header::HeaderName;
+
+use crate::core::config::{self, HmacAlgorithm};
+use crate::core::mustache::Mustache;
+use crate::core::valid::{Valid, ValidationError, Validator};
+
+const DEFAULT_SIGNATURE_HEADER: &str = "X-Signature";
+
+/// Signature of the requests to an upstream. The credentials are templates
+/// that are rendered with the context of each request.
+#[derive(Clone, Debug)]
+pub enum Signing {
+    AwsSigV4(AwsSigV4),
+    Hmac(Hmac),
+}
+
+#[derive(Clone, Debug)]
+pub struct AwsSigV4 {
+    pub region: String,
+    pub service: String,
+    pub access_key_id: Mustache,
+    pub secret_access_key: Mustache,
+    pub session_token: Option<Mustache>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Hmac {
+    pub secret: Mustache,
+    pub algorithm: HmacAlgorithm,
+    pub header: HeaderName,
+    pub signed_headers: Vec<HeaderName>,
+    pub timestamp_header: Option<HeaderName>,
+}
+
+impl Signing {
+    pub fn make(signing: &config::Signing) -> Valid<Signing, String> {
+        match signing {
+            config::Signing::AwsSigV4(sigv4) => to_mustache(&sigv4.access_key_id)
+                .trace("accessKeyId")
+                .zip(to_mustache(&sigv4.secret_access_key).trace("secretAccessKey"))
+                .zip(
+                    Valid::from_iter(sigv4.session_token.iter(), |token| to_mustache(token))
+                        .trace("sessionToken"),
+                )
+                .map(|((access_key_id, secret_access_key), session_token)| {
+                    Signing::AwsSigV4(AwsSigV4 {
+                        region: sigv4.region.clone(),
+                        service: sigv4.service.clone(),
+                        access_key_id,
+                        secret_access_key,
+                        session_token: session_token.into_iter().next(),
+                    })
+                })
+                .trace("awsSigV4"),
+            config::Signing::Hmac(hmac) => to_mustache(&hmac.secret)
+                .trace("secret")
+                .zip(
+                    to_header_name(hmac.header.as_deref().unwrap_or(DEFAULT_SIGNATURE_HEADER))
+                        .trace("header"),
+                )
+                .zip(
+                    Valid::from_iter(hmac.signed_headers.iter(), |name| to_header_name(name))
+                        .trace("signedHeaders"),
+                )
+                .zip(
+                    Valid::from_iter(hmac.timestamp_header.iter(), |name| to_header_name(name))
+                        .trace("timestampHeader"),
+                )
+                .map(|(((secret, header), signed_headers), timestamp_header)| {
+                    Signing::Hmac(Hmac {
+                        secret,
+                        algorithm: hmac.algorithm.clone().unwrap_or_default(),
+                        header,
+                        signed_headers,
+                        timestamp_header: timestamp_header.into_iter().next(),
+                    })
+                })
+                .trace("hmac"),
+        }
+    }
+}
+
+fn to_mustache(value: &str) -> Valid<Mustache, String> {
+    Valid::from(Mustache::parse(value).map_err(|err| ValidationError::new(err.to_string())))
+}
+
+fn to_header_name(name: &str) -> Valid<HeaderName, String> {
+    Valid::from(
+        HeaderName::from_bytes(name.as_bytes())
+            .map_err(|err| ValidationError::new(format!("{}: {}", name, err))),
+    )
+}
+
+/// Compiles the signing of an operator, which defaults to the one of the
+/// upstream.
+pub fn to_signing(
+    upstream: &config::Upstream,
+    signing: Option<&config::Signing>,
+) -> Valid<Option<Signing>, String> {
+    signing.or(upstream.signing.as_ref()).map_or_else(
+        || Valid::succeed(None),
+        |signing| Signing::make(signing).map(Some).trace("signing"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signing_override() {
+        let upstream = config::Upstream {
+            signing: Some(config::Signing::Hmac(config::Hmac {
+                secret: "{{.env.SECRET}}".to_string(),
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        let signing = config::Signing::AwsSigV4(config::AwsSigV4 {
+            region: "us-east-1".to_string(),
+            service: "execute-api".to_string(),
+            ..Default::default()
+        });
+
+        let actual = to_signing(&upstream, None).to_result().unwrap().unwrap();
+        let Signing::Hmac(hmac) = actual else {
+            panic!("expected an HMAC signing")
+        };
+        assert_eq!(hmac.header.as_str(), "x-signature");
+        assert_eq!(hmac.algorithm, HmacAlgorithm::Sha256);
+        assert!(!hmac.secret.is_const());
+
+        let actual = to_signing(&upstream, Some(&signing))
+            .to_result()
+            .unwrap()
+            .unwrap();
+        assert!(matches!(actual, Signing::AwsSigV4(sigv4) if sigv4.region == "us-east-1"));
+    }
+
+    #[test]
+    fn test_signing_invalid_header() {
+        let signing = config::Signing::Hmac(config::Hmac {
+            signed_headers: vec!["x date".to_string()],
+            ..Default::default()
+        });
+
+        assert!(to_signing(&Default::default(), Some(&signing))
+            .to_result()
+            .is_err());
+    }
+}
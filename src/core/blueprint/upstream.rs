@@ -1,15 +1,84 @@
 use std::collections::BTreeSet;
 
+use anyhow::bail;
 use derive_setters::Setters;
+use rustls_pemfile::Item;
+use rustls_pki_types::{CertificateDer, PrivateKeyDer};
 
-use crate::core::config::{self, Batch, ConfigModule};
+use super::{CircuitBreaker, Rate, Retry};
+use crate::core::config::{self, Batch, ConfigModule, Content};
 use crate::core::valid::{Valid, ValidationError, Validator};
 
+const DEFAULT_DISK_CACHE_PATH: &str = ".tailcall/http-cache";
+const DEFAULT_DISK_CACHE_MAX_SIZE: u64 = 100 * 1024 * 1024;
+
 #[derive(PartialEq, Eq, Clone, Debug, schemars::JsonSchema)]
 pub struct Proxy {
     pub url: String,
 }
 
+/// Certificates of the TLS connections to the upstreams whose URL starts with
+/// `base_url`.
+#[derive(PartialEq, Eq, Clone, Debug, schemars::JsonSchema)]
+pub struct UpstreamTls {
+    pub base_url: String,
+    /// Certificate chain and private key of the client, in PEM.
+    pub identity: Option<String>,
+    /// Trusted CA certificates, in PEM.
+    pub ca: Option<String>,
+}
+
+impl UpstreamTls {
+    /// Parses the certificate chain and the private key of the client.
+    pub fn parse_identity(
+        &self,
+    ) -> anyhow::Result<Option<(Vec<CertificateDer<'static>>, PrivateKeyDer<'static>)>> {
+        let Some(ref identity) = self.identity else {
+            return Ok(None);
+        };
+
+        let mut certificates = Vec::new();
+        let mut key = None;
+        for item in rustls_pemfile::read_all(&mut identity.as_bytes())? {
+            match item {
+                Item::X509Certificate(cert) => certificates.push(CertificateDer::from(cert)),
+                Item::RSAKey(der) => key = key.or(Some(PrivateKeyDer::Pkcs1(der.into()))),
+                Item::ECKey(der) => key = key.or(Some(PrivateKeyDer::Sec1(der.into()))),
+                Item::PKCS8Key(der) => key = key.or(Some(PrivateKeyDer::Pkcs8(der.into()))),
+                _ => {}
+            }
+        }
+
+        match key {
+            Some(key) if !certificates.is_empty() => Ok(Some((certificates, key))),
+            Some(_) => bail!("No certificate found in the cert"),
+            None => bail!("No private key found in the key"),
+        }
+    }
+
+    /// Parses the trusted CA certificates.
+    pub fn parse_ca(&self) -> anyhow::Result<Vec<CertificateDer<'static>>> {
+        let Some(ref ca) = self.ca else {
+            return Ok(Vec::new());
+        };
+
+        let certificates = rustls_pemfile::certs(&mut ca.as_bytes())?;
+        if certificates.is_empty() {
+            bail!("No certificate found in the ca");
+        }
+        Ok(certificates.into_iter().map(CertificateDer::from).collect())
+    }
+}
+
+/// Storage of the responses cached by the HTTP caching of the upstreams.
+#[derive(PartialEq, Eq, Clone, Debug, schemars::JsonSchema)]
+pub enum HttpCache {
+    /// Keeps up to `capacity` responses in memory.
+    InMemory { capacity: u64 },
+    /// Keeps up to `max_size` bytes of responses in the directory at `path`.
+    Disk { path: String, max_size: u64 },
+}
+
 #[derive(PartialEq, Eq, Clone, Debug, Setters, schemars::JsonSchema)]
 pub struct Upstream {
     pub pool_idle_timeout: u64,
@@ -24,10 +93,14 @@ pub struct Upstream {
     pub user_agent: String,
     pub allowed_headers: BTreeSet<String>,
     pub base_url: Option<String>,
-    pub http_cache: u64,
+    pub http_cache: Option<HttpCache>,
     pub batch: Option<Batch>,
     pub http2_only: bool,
     pub on_request: Option<String>,
+    pub rate_limit: Option<Rate>,
+    pub retry: Option<Retry>,
+    pub circuit_breaker: Option<CircuitBreaker>,
+    pub tls: Vec<UpstreamTls>,
 }
 
 impl Upstream {
@@ -62,31 +135,64 @@ impl TryFrom<&ConfigModule> for Upstream {
             allowed_headers.insert(hyper::header::AUTHORIZATION.to_string());
         }
 
-        get_batch(&config_upstream)
+        check_oauth2(config_module)
+            .and(get_batch(&config_upstream))
             .fuse(get_base_url(&config_upstream))
             .fuse(get_proxy(&config_upstream))
-            .map(|(batch, base_url, proxy)| Upstream {
-                pool_idle_timeout: (config_upstream).get_pool_idle_timeout(),
-                pool_max_idle_per_host: (config_upstream).get_pool_max_idle_per_host(),
-                keep_alive_interval: (config_upstream).get_keep_alive_interval(),
-                keep_alive_timeout: (config_upstream).get_keep_alive_timeout(),
-                keep_alive_while_idle: (config_upstream).get_keep_alive_while_idle(),
-                proxy,
-                connect_timeout: (config_upstream).get_connect_timeout(),
-                timeout: (config_upstream).get_timeout(),
-                tcp_keep_alive: (config_upstream).get_tcp_keep_alive(),
-                user_agent: (config_upstream).get_user_agent(),
-                allowed_headers,
-                base_url,
-                http_cache: (config_upstream).get_http_cache_size(),
-                batch,
-                http2_only: (config_upstream).get_http_2_only(),
-                on_request: (config_upstream).get_on_request(),
-            })
+            .fuse(get_rate_limit(&config_upstream))
+            .fuse(get_retry(&config_upstream))
+            .fuse(get_circuit_breaker(&config_upstream))
+            .fuse(get_tls(config_module))
+            .fuse(get_http_cache(&config_upstream))
+            .map(
+                |(batch, base_url, proxy, rate_limit, retry, circuit_breaker, tls, http_cache)| {
+                    Upstream {
+                        pool_idle_timeout: (config_upstream).get_pool_idle_timeout(),
+                        pool_max_idle_per_host: (config_upstream).get_pool_max_idle_per_host(),
+                        keep_alive_interval: (config_upstream).get_keep_alive_interval(),
+                        keep_alive_timeout: (config_upstream).get_keep_alive_timeout(),
+                        keep_alive_while_idle: (config_upstream).get_keep_alive_while_idle(),
+                        proxy,
+                        connect_timeout: (config_upstream).get_connect_timeout(),
+                        timeout: (config_upstream).get_timeout(),
+                        tcp_keep_alive: (config_upstream).get_tcp_keep_alive(),
+                        user_agent: (config_upstream).get_user_agent(),
+                        allowed_headers,
+                        base_url,
+                        http_cache,
+                        batch,
+                        http2_only: (config_upstream).get_http_2_only(),
+                        on_request: (config_upstream).get_on_request(),
+                        rate_limit,
+                        retry,
+                        circuit_breaker,
+                        tls,
+                    }
+                },
+            )
             .to_result()
     }
 }
 
+/// Fails when the upstream has an oauth2 that would be silently ignored by the
+/// resolvers other than `@http`.
+fn check_oauth2(config_module: &ConfigModule) -> Valid<(), String> {
+    let has_other_resolvers = || {
+        config_module
+            .types
+            .values()
+            .flat_map(|type_of| type_of.fields.values())
+            .any(|field| field.graphql.is_some() || field.grpc.is_some())
+    };
+
+    Valid::<(), String>::fail(
+        "oauth2 is only applied to @http, and can't be set on the upstream of @graphql or @grpc fields"
+            .to_string(),
+    )
+    .trace("oauth2")
+    .when(|| config_module.upstream.oauth2.is_some() && has_other_resolvers())
+}
+
 fn get_batch(upstream: &config::Upstream) -> Valid<Option<Batch>, String> {
     upstream.batch.as_ref().map_or_else(
         || Valid::succeed(None),
@@ -109,6 +215,59 @@ fn get_base_url(upstream: &config::Upstream) -> Valid<Option<String>, String> {
     }
 }
 
+fn get_rate_limit(upstream: &config::Upstream) -> Valid<Option<Rate>, String> {
+    upstream.rate_limit.as_ref().map_or_else(
+        || Valid::succeed(None),
+        |rate_limit| {
+            Rate::make(rate_limit.requests, rate_limit.duration)
+                .map(Some)
+                .trace("rateLimit")
+        },
+    )
+}
+
+fn get_retry(upstream: &config::Upstream) -> Valid<Option<Retry>, String> {
+    upstream.retry.as_ref().map_or_else(
+        || Valid::succeed(None),
+        |retry| Retry::make(retry).map(Some).trace("retry"),
+    )
+}
+
+fn get_circuit_breaker(upstream: &config::Upstream) -> Valid<Option<CircuitBreaker>, String> {
+    upstream.circuit_breaker.as_ref().map_or_else(
+        || Valid::succeed(None),
+        |circuit_breaker| {
+            CircuitBreaker::make(circuit_breaker)
+                .map(Some)
+                .trace("circuitBreaker")
+        },
+    )
+}
+
+fn get_http_cache(upstream: &config::Upstream) -> Valid<Option<HttpCache>, String> {
+    match upstream.http_cache {
+        None | Some(config::HttpCache::InMemory(0)) => Valid::succeed(None),
+        Some(config::HttpCache::InMemory(capacity)) => {
+            Valid::succeed(Some(HttpCache::InMemory { capacity }))
+        }
+        Some(config::HttpCache::Disk { ref disk }) => {
+            let max_size = disk.max_size.unwrap_or(DEFAULT_DISK_CACHE_MAX_SIZE);
+            if max_size == 0 {
+                return Valid::fail("maxSize must be greater than 0".to_string())
+                    .trace("maxSize")
+                    .trace("disk")
+                    .trace("httpCache");
+            }
+
+            let path = disk
+                .path
+                .clone()
+                .unwrap_or(DEFAULT_DISK_CACHE_PATH.to_string());
+            Valid::succeed(Some(HttpCache::Disk { path, max_size }))
+        }
+    }
+}
+
 fn get_proxy(upstream: &config::Upstream) -> Valid<Option<Proxy>, String> {
     if let Some(ref proxy) = upstream.proxy {
         Valid::succeed(Some(Proxy { url: proxy.url.clone() }))
@@ -116,3 +275,182 @@ fn get_proxy(upstream: &config::Upstream) -> Valid<Option<Proxy>, String> {
         Valid::succeed(None)
     }
 }
+
+fn get_tls(config_module: &ConfigModule) -> Valid<Vec<UpstreamTls>, String> {
+    let extensions = config_module.extensions();
+    let find = |links: &[Content<String>], id: &Option<String>, kind: &str| match id {
+        Some(id) => Valid::from_option(
+            links
+                .iter()
+                .find(|link| link.id.as_ref() == Some(id))
+                .map(|link| Some(link.content.clone())),
+            format!("No {} link with the id {}", kind, id),
+        ),
+        None => Valid::succeed(None),
+    };
+
+    Valid::from_iter(config_module.upstream.tls.iter(), |tls| {
+        Valid::from(
+            reqwest::Url::parse(&tls.base_url).map_err(|e| ValidationError::new(e.to_string())),
+        )
+        .trace("baseURL")
+        .and(find(&extensions.tls_certs, &tls.cert, "Cert").trace("cert"))
+        .fuse(find(&extensions.tls_keys, &tls.key, "Key").trace("key"))
+        .fuse(find(&extensions.tls_certs, &tls.ca, "Cert").trace("ca"))
+        .and_then(|(cert, key, ca)| {
+            let identity = match (cert, key) {
+                (Some(cert), Some(key)) => Valid::succeed(Some(format!("{}\n{}", cert, key))),
+                (None, None) => Valid::succeed(None),
+                _ => Valid::fail("cert and key must be set together".to_string()),
+            };
+
+            identity.map(|identity| UpstreamTls { base_url: tls.base_url.clone(), identity, ca })
+        })
+        .and_then(|tls| {
+            // the certificates are parsed once to be sure that the clients can be
+            // built with them
+            Valid::from(
+                tls.parse_identity()
+                    .map_err(|e| ValidationError::new(e.to_string())),
+            )
+            .trace("cert")
+            .and(
+                Valid::from(
+                    tls.parse_ca()
+                        .map_err(|e| ValidationError::new(e.to_string())),
+                )
+                .trace("ca"),
+            )
+            .map_to(tls)
+        })
+    })
+    .trace("tls")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::{Config, DiskHttpCache, Extensions, UpstreamTls as ConfigTls};
+
+    const CERT: &str = include_str!("../../../tests/server/config/client.crt");
+    const KEY: &str = include_str!("../../../tests/server/config/client.key");
+    const CA: &str = include_str!("../../../tests/server/config/client-ca.crt");
+
+    fn config_module(tls: ConfigTls) -> ConfigModule {
+        let mut config = Config::default();
+        config.upstream.tls = vec![tls];
+        let extensions = Extensions {
+            tls_certs: vec![
+                Content { id: Some("client".to_string()), content: CERT.to_string() },
+                Content { id: Some("ca".to_string()), content: CA.to_string() },
+                Content { id: Some("invalid".to_string()), content: "cert".to_string() },
+            ],
+            tls_keys: vec![
+                Content { id: Some("client".to_string()), content: KEY.to_string() },
+                Content { id: Some("invalid".to_string()), content: "key".to_string() },
+            ],
+            ..Default::default()
+        };
+
+        ConfigModule::from(config).set_extensions(extensions)
+    }
+
+    #[test]
+    fn test_tls() {
+        let config_module = config_module(ConfigTls {
+            base_url: "https://payments.example".to_string(),
+            cert: Some("client".to_string()),
+            key: Some("client".to_string()),
+            ca: Some("ca".to_string()),
+        });
+
+        let actual = get_tls(&config_module).to_result().unwrap();
+        let expected = vec![UpstreamTls {
+            base_url: "https://payments.example".to_string(),
+            identity: Some(format!("{}\n{}", CERT, KEY)),
+            ca: Some(CA.to_string()),
+        }];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_tls_invalid_certificates() {
+        let invalid_identity = config_module(ConfigTls {
+            base_url: "https://payments.example".to_string(),
+            cert: Some("invalid".to_string()),
+            key: Some("invalid".to_string()),
+            ..Default::default()
+        });
+        assert!(get_tls(&invalid_identity).to_result().is_err());
+
+        // the key isn't a private key in PEM
+        let missing_key = config_module(ConfigTls {
+            base_url: "https://payments.example".to_string(),
+            cert: Some("client".to_string()),
+            key: Some("invalid".to_string()),
+            ..Default::default()
+        });
+        assert!(get_tls(&missing_key).to_result().is_err());
+
+        let invalid_ca = config_module(ConfigTls {
+            base_url: "https://payments.example".to_string(),
+            ca: Some("invalid".to_string()),
+            ..Default::default()
+        });
+        assert!(get_tls(&invalid_ca).to_result().is_err());
+    }
+
+    #[test]
+    fn test_tls_invalid_links() {
+        // the key of the certificate is missing
+        let missing_key = config_module(ConfigTls {
+            base_url: "https://payments.example".to_string(),
+            cert: Some("client".to_string()),
+            ..Default::default()
+        });
+        assert!(get_tls(&missing_key).to_result().is_err());
+
+        let unknown_ca = config_module(ConfigTls {
+            base_url: "https://payments.example".to_string(),
+            ca: Some("other".to_string()),
+            ..Default::default()
+        });
+        assert!(get_tls(&unknown_ca).to_result().is_err());
+    }
+
+    #[test]
+    fn test_http_cache() {
+        let mut upstream = config::Upstream::default();
+        assert_eq!(get_http_cache(&upstream).to_result().unwrap(), None);
+
+        upstream.http_cache = Some(config::HttpCache::InMemory(0));
+        assert_eq!(get_http_cache(&upstream).to_result().unwrap(), None);
+
+        upstream.http_cache = Some(config::HttpCache::InMemory(42));
+        let actual = get_http_cache(&upstream).to_result().unwrap();
+        assert_eq!(actual, Some(HttpCache::InMemory { capacity: 42 }));
+
+        upstream.http_cache = Some(config::HttpCache::Disk { disk: DiskHttpCache::default() });
+        let actual = get_http_cache(&upstream).to_result().unwrap();
+        let expected = HttpCache::Disk {
+            path: DEFAULT_DISK_CACHE_PATH.to_string(),
+            max_size: DEFAULT_DISK_CACHE_MAX_SIZE,
+        };
+        assert_eq!(actual, Some(expected));
+
+        let disk = DiskHttpCache { max_size: Some(0), ..Default::default() };
+        upstream.http_cache = Some(config::HttpCache::Disk { disk });
+        assert!(get_http_cache(&upstream).to_result().is_err());
+    }
+
+    #[test]
+    fn test_http_cache_config() {
+        let actual: config::HttpCache = serde_json::from_str("42").unwrap();
+        assert_eq!(actual, config::HttpCache::InMemory(42));
+
+        let actual: config::HttpCache =
+            serde_json::from_str(r#"{"disk": {"path": "cache", "maxSize": 1024}}"#).unwrap();
+        let disk = DiskHttpCache { path: Some("cache".to_string()), max_size: Some(1024) };
+        assert_eq!(actual, config::HttpCache::Disk { disk });
+    }
+}
@@ -1,8 +1,9 @@
+use std::collections::HashMap;
 use std::hash::Hash;
 use std::num::NonZeroU64;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, RwLock};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use ttl_cache::TtlCache;
 
@@ -10,11 +11,12 @@ use super::error::Result;
 
 pub struct InMemoryCache<K: Hash + Eq, V> {
     data: Arc<RwLock<TtlCache<K, V>>>,
+    /// The keys associated with each tag, along with their expiry.
+    tags: Arc<RwLock<HashMap<String, HashMap<K, Instant>>>>,
     hits: AtomicUsize,
     miss: AtomicUsize,
 }
 
-// TODO: take this from the user instead of hardcoding it
 const CACHE_CAPACITY: usize = 100000;
 
 impl<K: Hash + Eq, V: Clone> Default for InMemoryCache<K, V> {
@@ -25,8 +27,13 @@ impl<K: Hash + Eq, V: Clone> Default for InMemoryCache<K, V> {
 
 impl<K: Hash + Eq, V: Clone> InMemoryCache<K, V> {
     pub fn new() -> Self {
+        Self::with_capacity(CACHE_CAPACITY)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
         InMemoryCache {
-            data: Arc::new(RwLock::new(TtlCache::new(CACHE_CAPACITY))),
+            data: Arc::new(RwLock::new(TtlCache::new(capacity))),
+            tags: Arc::new(RwLock::new(HashMap::new())),
             hits: AtomicUsize::new(0),
             miss: AtomicUsize::new(0),
         }
@@ -34,7 +41,7 @@ impl<K: Hash + Eq, V: Clone> InMemoryCache<K, V> {
 }
 
 #[async_trait::async_trait]
-impl<K: Hash + Eq + Send + Sync, V: Clone + Send + Sync> crate::core::Cache
+impl<K: Hash + Eq + Clone + Send + Sync, V: Clone + Send + Sync> crate::core::Cache
     for InMemoryCache<K, V>
 {
     type Key = K;
@@ -56,6 +63,49 @@ impl<K: Hash + Eq + Send + Sync, V: Clone + Send + Sync> crate::core::Cache
         Ok(val)
     }
 
+    async fn tag<'a>(&'a self, key: &'a K, tags: &'a [String], ttl: NonZeroU64) -> Result<()> {
+        let now = Instant::now();
+        let expiry = now + Duration::from_millis(ttl.get());
+        let mut index = self.tags.write().unwrap();
+
+        for tag in tags {
+            let keys = index.entry(tag.clone()).or_default();
+            keys.retain(|_, expiry| *expiry > now);
+            keys.insert(key.clone(), expiry);
+        }
+
+        // the tags that are never purged are dropped once their keys expire
+        let capacity = self.data.read().unwrap().capacity();
+        if index.len() > capacity {
+            index.retain(|_, keys| {
+                keys.retain(|_, expiry| *expiry > now);
+                !keys.is_empty()
+            });
+        }
+
+        Ok(())
+    }
+
+    async fn delete<'a>(&'a self, key: &'a K) -> Result<bool> {
+        Ok(self.data.write().unwrap().remove(key).is_some())
+    }
+
+    async fn purge<'a>(&'a self, tags: &'a [String]) -> Result<usize> {
+        let keys = {
+            let mut index = self.tags.write().unwrap();
+            tags.iter()
+                .filter_map(|tag| index.remove(tag))
+                .flat_map(|keys| keys.into_keys())
+                .collect::<Vec<_>>()
+        };
+
+        let mut data = self.data.write().unwrap();
+        Ok(keys
+            .iter()
+            .filter(|key| data.remove(*key).is_some())
+            .count())
+    }
+
     fn hit_rate(&self) -> Option<f64> {
         let cache = self.data.read().unwrap();
         let hits = self.hits.load(Ordering::Relaxed);
@@ -92,4 +142,28 @@ mod tests {
         tokio::time::sleep(Duration::from_millis(ttl.get())).await;
         assert_eq!(cache.get(&10).await.ok(), Some(None));
     }
+
+    #[tokio::test]
+    async fn test_purge_tags() {
+        let cache: crate::core::cache::InMemoryCache<u64, String> =
+            crate::core::cache::InMemoryCache::default();
+        let ttl = NonZeroU64::new(10000).unwrap();
+        let user = vec!["user:1".to_string()];
+        let posts = vec!["user:1".to_string(), "posts".to_string()];
+
+        cache.set(1, "user".into(), ttl).await.unwrap();
+        cache.tag(&1, &user, ttl).await.unwrap();
+        cache.set(2, "posts".into(), ttl).await.unwrap();
+        cache.tag(&2, &posts, ttl).await.unwrap();
+        cache.set(3, "comments".into(), ttl).await.unwrap();
+
+        assert_eq!(cache.purge(&["posts".to_string()]).await.unwrap(), 1);
+        assert_eq!(cache.get(&2).await.unwrap(), None);
+        assert_eq!(cache.purge(&user).await.unwrap(), 1);
+        assert_eq!(cache.get(&1).await.unwrap(), None);
+        assert_eq!(cache.get(&3).await.unwrap(), Some("comments".into()));
+
+        assert!(cache.delete(&3).await.unwrap());
+        assert!(!cache.delete(&3).await.unwrap());
+    }
 }
@@ -0,0 +1,60 @@
+use async_graphql_value::ConstValue;
+
+use super::error::{Error, Result};
+
+/// Encodes a value in CBOR, which is more compact and faster to decode than
+/// JSON, and keeps the binary values, for the stores outside of the memory.
+pub fn encode(value: &ConstValue) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    ciborium::into_writer(value, &mut bytes).map_err(|err| Error::Codec(err.to_string()))?;
+    Ok(bytes)
+}
+
+pub fn decode(bytes: &[u8]) -> Result<ConstValue> {
+    ciborium::from_reader(bytes).map_err(|err| Error::Codec(err.to_string()))
+}
+
+/// Key of an entry in the stores that are shared by the replicas.
+pub fn key(prefix: &str, key: u64) -> String {
+    format!("{}{:016x}", prefix, key)
+}
+
+#[cfg(test)]
+mod tests {
+    use async_graphql_value::Name;
+    use hyper::body::Bytes;
+    use indexmap::IndexMap;
+
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let value = ConstValue::Object(IndexMap::from([
+            (Name::new("id"), ConstValue::from(1)),
+            (Name::new("score"), ConstValue::from(-0.5)),
+            (Name::new("name"), ConstValue::from("Leanne Graham")),
+            (
+                Name::new("avatar"),
+                ConstValue::Binary(Bytes::from_static(b"\x89PNG")),
+            ),
+            (
+                Name::new("tags"),
+                ConstValue::List(vec![ConstValue::Boolean(true), ConstValue::Null]),
+            ),
+        ]));
+
+        let bytes = encode(&value).unwrap();
+        assert!(bytes.len() < value.to_string().len());
+        assert_eq!(decode(&bytes).unwrap(), value);
+    }
+
+    #[test]
+    fn test_invalid_bytes() {
+        assert!(decode(&[0xff, 0x00]).is_err());
+    }
+
+    #[test]
+    fn test_key() {
+        assert_eq!(key("tailcall:", 255), "tailcall:00000000000000ff");
+    }
+}
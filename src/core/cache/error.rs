@@ -11,6 +11,10 @@ pub enum Error {
     #[debug(fmt = "Kv Error: {}", _0)]
     #[from(ignore)]
     Kv(String),
+
+    #[debug(fmt = "Codec Error: {}", _0)]
+    #[from(ignore)]
+    Codec(String),
 }
 
 impl From<serde_json::Error> for Error {
@@ -26,6 +30,7 @@ impl Display for Error {
         match self {
             Error::SerdeJson(error) => write!(f, "Serde Json Error: {}", error),
             Error::Kv(error) => write!(f, "Kv Error: {}", error),
+            Error::Codec(error) => write!(f, "Codec Error: {}", error),
         }
     }
 }
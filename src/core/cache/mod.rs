@@ -1,4 +1,5 @@
 pub mod cache;
+pub mod codec;
 pub mod error;
 pub use cache::*;
 pub use error::Error;
@@ -13,7 +13,10 @@ use tailcall_typedefs_common::input_definition::InputDefinition;
 use tailcall_typedefs_common::ServiceDocumentBuilder;
 
 use super::telemetry::Telemetry;
-use super::{KeyValue, Link, Server, Upstream};
+use super::{
+    External, Key, KeyValue, Link, OAuth2, Provides, Requires, Retry, Server, Shareable, Signing,
+    Upstream,
+};
 use crate::core::config::from_document::from_document;
 use crate::core::config::npo::QueryPath;
 use crate::core::config::source::Source;
@@ -117,6 +120,14 @@ pub struct Type {
     ///
     /// Contains source information for the type.
     pub tag: Option<Tag>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    ///
+    /// Marks the type as a federation entity identified by the key fields.
+    pub key: Option<Key>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    ///
+    /// Marks the type as resolvable by multiple subgraphs.
+    pub shareable: Option<Shareable>,
 }
 
 impl Type {
@@ -155,6 +166,74 @@ pub struct Tag {
     pub id: String,
 }
 
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Deserialize,
+    Serialize,
+    Eq,
+    schemars::JsonSchema,
+    MergeRight,
+    DirectiveDefinition,
+    InputDefinition,
+)]
+#[directive_definition(locations = "FieldDefinition")]
+/// The @rateLimit directive limits the number of times each client can
+/// request the field, with a token bucket that holds up to `requests` tokens
+/// and is refilled over `duration`. Clients are identified by their IP address,
+/// unless a header or a claim of their JWT token is given. The clients whose IP
+/// address is unknown to the runtime share a single bucket.
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+pub struct RateLimit {
+    /// Number of requests that a client can make in the duration.
+    pub requests: u64,
+
+    /// Duration in milliseconds over which the requests are allowed.
+    pub duration: u64,
+
+    /// Name of the header that identifies the client.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub header: Option<String>,
+
+    /// Name of the claim of the JWT token that identifies the client.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub claim: Option<String>,
+}
+
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Deserialize,
+    Serialize,
+    Eq,
+    schemars::JsonSchema,
+    MergeRight,
+    DirectiveDefinition,
+    InputDefinition,
+)]
+#[directive_definition(locations = "FieldDefinition")]
+/// The @cost directive sets the weight of the field in the cost of a query,
+/// which is limited by the `maxCost` setting of the server. The cost of the
+/// fields nested in the field is multiplied by the value of the given
+/// arguments, such as the number of items requested from a list.
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+pub struct Cost {
+    /// The cost of requesting the field. @default `1`.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub weight: Option<usize>,
+
+    /// Names of the integer arguments of the field that multiply the cost of
+    /// its nested fields.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub multipliers: Vec<String>,
+}
+
 #[derive(
     Clone,
     Debug,
@@ -176,6 +255,48 @@ pub struct Cache {
     /// Specifies the duration, in milliseconds, of how long the value has to be
     /// stored in the cache.
     pub max_age: NonZeroU64,
+
+    /// Specifies the duration, in milliseconds, after the `maxAge` during which
+    /// the expired value is served right away, while it's refreshed in the
+    /// background.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub stale_while_revalidate: Option<NonZeroU64>,
+
+    /// Specifies the duration, in milliseconds, after the `maxAge` during which
+    /// the expired value is served when the upstream fails.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub stale_if_error: Option<NonZeroU64>,
+
+    /// Tags of the cached values, that can be templates e.g.
+    /// `user:{{.args.id}}`. The values are removed before their `maxAge` when
+    /// one of their tags is purged by `@invalidate` or the admin endpoint.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub tags: Vec<String>,
+}
+
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Deserialize,
+    Serialize,
+    Eq,
+    schemars::JsonSchema,
+    MergeRight,
+    DirectiveDefinition,
+    InputDefinition,
+)]
+#[directive_definition(locations = "FieldDefinition")]
+/// The @invalidate directive removes the values cached with any of the given
+/// tags once the mutation it is applied to succeeds, so that the following
+/// queries read what the mutation wrote.
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+pub struct Invalidate {
+    /// Tags of the cached values to remove, that can be templates e.g.
+    /// `user:{{.args.id}}`.
+    pub tags: Vec<String>,
 }
 
 #[derive(
@@ -191,7 +312,35 @@ pub struct Cache {
     DirectiveDefinition,
 )]
 #[directive_definition(locations = "Object,FieldDefinition")]
-pub struct Protected {}
+/// The @protected directive restricts the access to a field or a type to the
+/// authenticated clients. The access can be further restricted to the clients
+/// with specific roles, scopes or claims.
+#[serde(rename_all = "camelCase")]
+pub struct Protected {
+    /// The client must have at least one of these roles, read from the `roles`
+    /// claim, or from the groups of the user with basic auth.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub roles: Vec<String>,
+
+    /// The client must have all of these scopes, read from the `scope` or the
+    /// `scp` claim.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub scopes: Vec<String>,
+
+    /// The claims of the client must match these values. A claim that is a
+    /// list matches when it contains the value.
+    #[serde(default, skip_serializing_if = "is_default")]
+    #[merge_right(merge_right_fn = "merge_right_claims")]
+    pub claims: BTreeMap<String, Value>,
+}
+
+fn merge_right_claims(
+    mut left: BTreeMap<String, Value>,
+    right: BTreeMap<String, Value>,
+) -> BTreeMap<String, Value> {
+    left.extend(right);
+    left
+}
 
 #[derive(
     Serialize,
@@ -304,15 +453,50 @@ pub struct Field {
     /// Sets the cache configuration for a field
     pub cache: Option<Cache>,
 
+    ///
+    /// Removes cached values when the mutation succeeds
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub invalidate: Option<Invalidate>,
+
     ///
     /// Marks field as protected by auth provider
     #[serde(default)]
     pub protected: Option<Protected>,
 
+    ///
+    /// Sets the weight of the field in the cost of a query
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub cost: Option<Cost>,
+
+    ///
+    /// Limits the rate of the requests of each client to the field
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub rate_limit: Option<RateLimit>,
+
     ///
     /// Stores the default value for the field
     #[serde(default, skip_serializing_if = "is_default")]
     pub default_value: Option<Value>,
+
+    ///
+    /// Marks the field as resolved by another subgraph
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub external: Option<External>,
+
+    ///
+    /// External fields of the entity required to resolve the field
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub requires: Option<Requires>,
+
+    ///
+    /// External fields of the returned entity resolved by the field
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub provides: Option<Provides>,
+
+    ///
+    /// Marks the field as resolvable by multiple subgraphs
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub shareable: Option<Shareable>,
 }
 
 // It's a terminal implementation of MergeRight
@@ -594,6 +778,19 @@ pub struct Http {
     /// first parameter referencing a field in the current value using mustache
     /// syntax is automatically selected as the batching parameter.
     pub query: Vec<KeyValue>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// The retry policy of the API call, overriding the one of `@upstream`.
+    pub retry: Option<Retry>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// The OAuth 2.0 client credentials of the API call, overriding the ones
+    /// of `@upstream`.
+    pub oauth2: Option<OAuth2>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// The signing of the API call, overriding the one of `@upstream`.
+    pub signing: Option<Signing>,
 }
 
 ///
@@ -682,6 +879,9 @@ pub struct Grpc {
     /// This refers to the gRPC method you're going to call. For instance
     /// `GetAllNews`.
     pub method: String,
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// The retry policy of the gRPC call, overriding the one of `@upstream`.
+    pub retry: Option<Retry>,
 }
 
 #[derive(
@@ -719,6 +919,13 @@ pub struct GraphQL {
     /// to the `@graphQL` operator.
     pub batch: bool,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Requests the field through the `_entities` field of an Apollo Federation
+    /// subgraph, as a field of the entity type it is defined on. The entity is
+    /// identified by the values of the given key fields in the parent value and
+    /// the request is skipped when the parent value already contains the field.
+    pub entity: Option<Key>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// The headers parameter allows you to customize the headers of the GraphQL
     /// request made by the `@graphQL` operator. It is used by specifying a
@@ -730,14 +937,20 @@ pub struct GraphQL {
     /// is received for this field, Tailcall requests data from the
     /// corresponding upstream field.
     pub name: String,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// The retry policy of the GraphQL request, overriding the one of
+    /// `@upstream`.
+    pub retry: Option<Retry>,
 }
 
-#[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "lowercase")]
 pub enum GraphQLOperationType {
     #[default]
     Query,
     Mutation,
+    Subscription,
 }
 
 impl Display for GraphQLOperationType {
@@ -745,6 +958,7 @@ impl Display for GraphQLOperationType {
         f.write_str(match self {
             Self::Query => "query",
             Self::Mutation => "mutation",
+            Self::Subscription => "subscription",
         })
     }
 }
@@ -816,6 +1030,15 @@ impl Config {
         Ok(serde_yaml::to_string(self)?)
     }
 
+    /// Renders the config in TOML, which has no null. The null values of the
+    /// objects are left out, as they read the same as the missing ones, while
+    /// the config fails to render with a null in a list.
+    pub fn to_toml(&self) -> Result<String> {
+        let mut value = serde_json::to_value(self)?;
+        strip_nulls(&mut value, "")?;
+        Ok(toml::to_string_pretty(&value)?)
+    }
+
     pub fn to_json(&self, pretty: bool) -> Result<String> {
         if pretty {
             Ok(serde_json::to_string_pretty(self)?)
@@ -853,10 +1076,18 @@ impl Config {
         Ok(serde_json::from_str(json)?)
     }
 
+    pub fn from_json5(json5: &str) -> Result<Self> {
+        Ok(json5::from_str(json5)?)
+    }
+
     pub fn from_yaml(yaml: &str) -> Result<Self> {
         Ok(serde_yaml::from_str(yaml)?)
     }
 
+    pub fn from_toml(toml: &str) -> Result<Self> {
+        Ok(toml::from_str(toml)?)
+    }
+
     pub fn from_sdl(sdl: &str) -> Valid<Self, String> {
         let doc = async_graphql::parser::parse_schema(sdl);
         match doc {
@@ -869,7 +1100,9 @@ impl Config {
         match source {
             Source::GraphQL => Ok(Config::from_sdl(schema).to_result()?),
             Source::Json => Ok(Config::from_json(schema)?),
+            Source::Json5 => Ok(Config::from_json5(schema)?),
             Source::Yml => Ok(Config::from_yaml(schema)?),
+            Source::Toml => Ok(Config::from_toml(schema)?),
         }
     }
 
@@ -939,6 +1172,10 @@ impl Config {
             types = self.find_connections(mutation, types);
         }
 
+        if let Some(ref subscription) = &self.schema.subscription {
+            types = self.find_connections(subscription, types);
+        }
+
         types
     }
 
@@ -994,6 +1231,9 @@ impl Config {
         if let Some(mutation) = &self.schema.mutation {
             stack.push(mutation.clone());
         }
+        if let Some(subscription) = &self.schema.subscription {
+            stack.push(subscription.clone());
+        }
         while let Some(type_name) = stack.pop() {
             if set.contains(&type_name) {
                 continue;
@@ -1030,15 +1270,21 @@ impl Config {
             .add_directive(Cache::directive_definition(generated_types))
             .add_directive(Call::directive_definition(generated_types))
             .add_directive(Expr::directive_definition(generated_types))
+            .add_directive(External::directive_definition(generated_types))
             .add_directive(GraphQL::directive_definition(generated_types))
             .add_directive(Grpc::directive_definition(generated_types))
             .add_directive(Http::directive_definition(generated_types))
+            .add_directive(Invalidate::directive_definition(generated_types))
             .add_directive(JS::directive_definition(generated_types))
+            .add_directive(Key::directive_definition(generated_types))
             .add_directive(Link::directive_definition(generated_types))
             .add_directive(Modify::directive_definition(generated_types))
             .add_directive(Omit::directive_definition(generated_types))
             .add_directive(Protected::directive_definition(generated_types))
+            .add_directive(Provides::directive_definition(generated_types))
+            .add_directive(Requires::directive_definition(generated_types))
             .add_directive(Server::directive_definition(generated_types))
+            .add_directive(Shareable::directive_definition(generated_types))
             .add_directive(Tag::directive_definition(generated_types))
             .add_directive(Telemetry::directive_definition(generated_types))
             .add_directive(Upstream::directive_definition(generated_types))
@@ -1080,6 +1326,36 @@ pub enum Encoding {
     ApplicationXWwwFormUrlencoded,
 }
 
+/// Removes the null values of the objects, and fails on the ones of the lists
+/// that can't be left out without moving the other values.
+fn strip_nulls(value: &mut Value, path: &str) -> Result<()> {
+    match value {
+        Value::Object(fields) => {
+            fields.retain(|_, value| !value.is_null());
+            for (name, value) in fields.iter_mut() {
+                let path = if path.is_empty() {
+                    name.to_string()
+                } else {
+                    format!("{}.{}", path, name)
+                };
+                strip_nulls(value, &path)?;
+            }
+        }
+        Value::Array(values) => {
+            for (index, value) in values.iter_mut().enumerate() {
+                let path = format!("{}[{}]", path, index);
+                if value.is_null() {
+                    anyhow::bail!("TOML has no null, found one in the list at {}", path);
+                }
+                strip_nulls(value, &path)?;
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
@@ -1198,4 +1474,62 @@ mod tests {
             .collect();
         assert_eq!(union_types, expected_union_types);
     }
+
+    #[test]
+    fn test_toml_round_trip() {
+        let sdl = std::fs::read_to_string(tailcall_fixtures::configs::UNION_CONFIG).unwrap();
+        let config = Config::from_sdl(&sdl).to_result().unwrap();
+
+        let actual = Config::from_toml(&config.to_toml().unwrap()).unwrap();
+        assert_eq!(actual.to_sdl(), config.to_sdl());
+    }
+
+    #[test]
+    fn test_toml_round_trip_json_values() {
+        let sdl = |address: &str| {
+            format!(
+                r#"
+                schema {{ query: Query }}
+                type Query {{
+                  user: JSON @expr(body: {{name: "Leanne", tags: ["a", 1, true], address: {}}})
+                }}
+                "#,
+                address
+            )
+        };
+        let config = Config::from_sdl(&sdl(r#"{city: "Gwenborough", zipcode: null}"#))
+            .to_result()
+            .unwrap();
+
+        // the null values of the objects are left out
+        let actual = Config::from_toml(&config.to_toml().unwrap()).unwrap();
+        let expected = Config::from_sdl(&sdl(r#"{city: "Gwenborough"}"#))
+            .to_result()
+            .unwrap();
+        assert_eq!(actual.to_sdl(), expected.to_sdl());
+
+        let config = Config::from_sdl(&sdl("[1, null]")).to_result().unwrap();
+        let error = config.to_toml().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "TOML has no null, found one in the list at types.Query.fields.user.expr.body.address[1]"
+        );
+    }
+
+    #[test]
+    fn test_from_json5() {
+        let json5 = r#"{
+            // comments and trailing commas are allowed
+            schema: { query: 'Query' },
+            types: {
+                Query: { fields: { foo: { type: 'String' } } },
+            },
+        }"#;
+        let actual = Config::from_json5(json5).unwrap();
+        let expected = Config::default().query("Query").types(vec![(
+            "Query",
+            Type::default().fields(vec![("foo", Field::string())]),
+        )]);
+        assert_eq!(actual, expected);
+    }
 }
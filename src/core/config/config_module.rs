@@ -6,7 +6,7 @@ use jsonwebtoken::jwk::JwkSet;
 use prost_reflect::prost_types::{FileDescriptorProto, FileDescriptorSet};
 use rustls_pki_types::{CertificateDer, PrivateKeyDer};
 
-use crate::core::config::Config;
+use crate::core::config::{Config, Link};
 use crate::core::macros::MergeRight;
 use crate::core::merge_right::MergeRight;
 use crate::core::proto_reader::ProtoMetadata;
@@ -125,12 +125,63 @@ pub struct Extensions {
     /// Contains the key used on HTTP2 with TLS
     pub keys: Arc<Vec<PrivateKeyDer<'static>>>,
 
+    /// Contains the PEM certificates of the `Cert` links with an id
+    pub tls_certs: Vec<Content<String>>,
+
+    /// Contains the PEM private keys of the `Key` links with an id
+    pub tls_keys: Vec<Content<String>>,
+
     /// Contains the endpoints
     pub endpoint_set: EndpointSet<Unchecked>,
 
+    /// Contains the documents of the operations, used as the safelist of the
+    /// persisted queries
+    pub operations: Vec<String>,
+
     pub htpasswd: Vec<Content<String>>,
 
+    /// Contains the group files of the users of the htpasswd files, in the
+    /// format of the Apache `AuthGroupFile`
+    pub htgroup: Vec<Content<String>>,
+
     pub jwks: Vec<Content<JwkSet>>,
+
+    /// Contains the OpenID Connect providers resolved from their discovery
+    /// documents
+    pub oidc: Vec<Content<OidcProvider>>,
+
+    /// Contains the links to the OAuth 2.0 token introspection endpoints
+    pub introspection: Vec<Link>,
+
+    /// Contains the static API keys of the clients
+    pub api_keys: Vec<Content<ApiKeys>>,
+}
+
+/// An OpenID Connect provider, with the keys that were published at its
+/// `jwks_uri` when the config was read.
+#[derive(Clone, Debug)]
+pub struct OidcProvider {
+    pub issuer: String,
+    pub jwks_uri: String,
+    pub jwks: JwkSet,
+    pub meta: Option<serde_json::Value>,
+}
+
+/// The API keys read from an `ApiKeys` link, along with the options of the
+/// link.
+#[derive(Clone, Debug)]
+pub struct ApiKeys {
+    pub keys: Vec<ApiKey>,
+    pub meta: Option<serde_json::Value>,
+}
+
+/// An API key and the metadata of its owner e.g. `owner` or `roles`, that are
+/// used as the claims of the client.
+#[derive(Clone, Debug, serde::Deserialize)]
+pub struct ApiKey {
+    pub key: String,
+    #[serde(flatten)]
+    pub metadata: serde_json::Map<String, serde_json::Value>,
 }
 
 impl Extensions {
@@ -146,7 +197,11 @@ impl Extensions {
     }
 
     pub fn has_auth(&self) -> bool {
-        !self.htpasswd.is_empty() || !self.jwks.is_empty()
+        !self.htpasswd.is_empty()
+            || !self.jwks.is_empty()
+            || !self.oidc.is_empty()
+            || !self.introspection.is_empty()
+            || !self.api_keys.is_empty()
     }
 }
 
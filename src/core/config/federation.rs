@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+use tailcall_macros::DirectiveDefinition;
+
+use crate::core::macros::MergeRight;
+use crate::core::merge_right::MergeRight;
+
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Deserialize,
+    Serialize,
+    Eq,
+    schemars::JsonSchema,
+    MergeRight,
+    DirectiveDefinition,
+)]
+#[directive_definition(locations = "Object")]
+#[serde(deny_unknown_fields)]
+/// The @key directive marks the type as an Apollo Federation entity that can be
+/// resolved by other subgraphs using the given key fields. The entity is
+/// resolved by the field of the Query type that returns the entity and accepts
+/// the key fields as its arguments.
+pub struct Key {
+    /// Space separated list of the fields that uniquely identify the entity.
+    pub fields: String,
+}
+
+impl Key {
+    /// Returns the names of the key fields.
+    pub fn field_names(&self) -> Vec<&str> {
+        self.fields.split_whitespace().collect()
+    }
+}
+
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Deserialize,
+    Serialize,
+    Eq,
+    schemars::JsonSchema,
+    MergeRight,
+    DirectiveDefinition,
+)]
+#[directive_definition(locations = "Object,FieldDefinition")]
+#[serde(deny_unknown_fields)]
+/// The @shareable directive indicates that the type or field can be resolved by
+/// multiple subgraphs.
+pub struct Shareable {}
+
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Deserialize,
+    Serialize,
+    Eq,
+    schemars::JsonSchema,
+    MergeRight,
+    DirectiveDefinition,
+)]
+#[directive_definition(locations = "FieldDefinition")]
+#[serde(deny_unknown_fields)]
+/// The @external directive indicates that the field is usually resolved by
+/// another subgraph.
+pub struct External {}
+
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Deserialize,
+    Serialize,
+    Eq,
+    schemars::JsonSchema,
+    MergeRight,
+    DirectiveDefinition,
+)]
+#[directive_definition(locations = "FieldDefinition")]
+#[serde(deny_unknown_fields)]
+/// The @requires directive indicates that resolving the field depends on the
+/// values of external fields of the entity. The values are sent by the router
+/// along with the entity representation and are available in `{{.value}}`.
+pub struct Requires {
+    /// Space separated list of the external fields required by the field.
+    pub fields: String,
+}
+
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Deserialize,
+    Serialize,
+    Eq,
+    schemars::JsonSchema,
+    MergeRight,
+    DirectiveDefinition,
+)]
+#[directive_definition(locations = "FieldDefinition")]
+#[serde(deny_unknown_fields)]
+/// The @provides directive indicates the external fields of the returned
+/// entity that are resolved by the field.
+pub struct Provides {
+    /// Space separated list of the fields provided by the field.
+    pub fields: String,
+}
@@ -12,8 +12,9 @@ use async_graphql_value::ConstValue;
 use super::telemetry::Telemetry;
 use super::{Alias, Tag, JS};
 use crate::core::config::{
-    self, Cache, Call, Config, Enum, GraphQL, Grpc, Link, Modify, Omit, Protected, RootSchema,
-    Server, Union, Upstream, Variant,
+    self, Cache, Call, Config, Cost, Enum, External, GraphQL, Grpc, Invalidate, Key, Link, Modify,
+    Omit, Protected, Provides, RateLimit, Requires, RootSchema, Server, Shareable, Union, Upstream,
+    Variant,
 };
 use crate::core::directive::DirectiveCodec;
 use crate::core::valid::{Valid, ValidationError, Validator};
@@ -243,11 +244,23 @@ where
         .fuse(to_fields(fields))
         .fuse(Protected::from_directives(directives.iter()))
         .fuse(Tag::from_directives(directives.iter()))
-        .map(|(cache, fields, protected, tag)| {
+        .fuse(Key::from_directives(directives.iter()))
+        .fuse(Shareable::from_directives(directives.iter()))
+        .map(|(cache, fields, protected, tag, key, shareable)| {
             let doc = description.to_owned().map(|pos| pos.node);
             let implements = implements.iter().map(|pos| pos.node.to_string()).collect();
             let added_fields = to_add_fields_from_directives(directives);
-            config::Type { fields, added_fields, doc, implements, cache, protected, tag }
+            config::Type {
+                fields,
+                added_fields,
+                doc,
+                implements,
+                cache,
+                protected,
+                tag,
+                key,
+                shareable,
+            }
         })
 }
 fn to_input_object(
@@ -325,16 +338,37 @@ where
     let doc = description.to_owned().map(|pos| pos.node);
     config::Http::from_directives(directives.iter())
         .fuse(GraphQL::from_directives(directives.iter()))
-        .fuse(Cache::from_directives(directives.iter()))
+        .fuse(Valid::from(
+            Cache::from_directives(directives.iter())
+                .fuse(Invalidate::from_directives(directives.iter())),
+        ))
         .fuse(Grpc::from_directives(directives.iter()))
         .fuse(Omit::from_directives(directives.iter()))
         .fuse(Modify::from_directives(directives.iter()))
         .fuse(JS::from_directives(directives.iter()))
         .fuse(Call::from_directives(directives.iter()))
         .fuse(Protected::from_directives(directives.iter()))
+        .fuse(Valid::from(
+            Cost::from_directives(directives.iter())
+                .fuse(RateLimit::from_directives(directives.iter())),
+        ))
         .fuse(default_value)
+        .fuse(to_federation_field(directives))
         .map(
-            |(http, graphql, cache, grpc, omit, modify, script, call, protected, default_value)| {
+            |(
+                http,
+                graphql,
+                (cache, invalidate),
+                grpc,
+                omit,
+                modify,
+                script,
+                call,
+                protected,
+                (cost, rate_limit),
+                default_value,
+                FederationField { external, requires, provides, shareable },
+            )| {
                 let const_field = to_const_field(directives);
                 config::Field {
                     type_of,
@@ -351,14 +385,46 @@ where
                     const_field,
                     graphql,
                     cache,
+                    invalidate,
                     call,
                     protected,
+                    cost,
+                    rate_limit,
                     default_value,
+                    external,
+                    requires,
+                    provides,
+                    shareable,
                 }
             },
         )
 }
 
+/// The Apollo Federation directives of a field.
+struct FederationField {
+    external: Option<External>,
+    requires: Option<Requires>,
+    provides: Option<Provides>,
+    shareable: Option<Shareable>,
+}
+
+fn to_federation_field(
+    directives: &[Positioned<ConstDirective>],
+) -> Valid<FederationField, String> {
+    External::from_directives(directives.iter())
+        .fuse(Requires::from_directives(directives.iter()))
+        .fuse(Provides::from_directives(directives.iter()))
+        .fuse(Shareable::from_directives(directives.iter()))
+        .map(
+            |(external, requires, provides, shareable)| FederationField {
+                external,
+                requires,
+                provides,
+                shareable,
+            },
+        )
+}
+
 fn to_type_of(type_: &Type) -> String {
     match &type_.base {
         BaseType::Named(name) => name.to_string(),
@@ -216,6 +216,13 @@ fn config_document(config: &Config) -> ServiceDocument {
                         .map(|protected| pos(protected.to_directive())),
                 )
                 .chain(type_def.tag.as_ref().map(|tag| pos(tag.to_directive())))
+                .chain(type_def.key.as_ref().map(|key| pos(key.to_directive())))
+                .chain(
+                    type_def
+                        .shareable
+                        .as_ref()
+                        .map(|shareable| pos(shareable.to_directive())),
+                )
                 .collect::<Vec<_>>(),
             kind,
         })));
@@ -274,8 +281,15 @@ fn get_directives(field: &crate::core::config::Field) -> Vec<Positioned<ConstDir
         field.graphql.as_ref().map(|d| pos(d.to_directive())),
         field.grpc.as_ref().map(|d| pos(d.to_directive())),
         field.cache.as_ref().map(|d| pos(d.to_directive())),
+        field.invalidate.as_ref().map(|d| pos(d.to_directive())),
         field.call.as_ref().map(|d| pos(d.to_directive())),
         field.protected.as_ref().map(|d| pos(d.to_directive())),
+        field.cost.as_ref().map(|d| pos(d.to_directive())),
+        field.rate_limit.as_ref().map(|d| pos(d.to_directive())),
+        field.external.as_ref().map(|d| pos(d.to_directive())),
+        field.requires.as_ref().map(|d| pos(d.to_directive())),
+        field.provides.as_ref().map(|d| pos(d.to_directive())),
+        field.shareable.as_ref().map(|d| pos(d.to_directive())),
     ];
 
     directives.into_iter().flatten().collect()
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use tailcall_macros::DirectiveDefinition;
 
 use super::super::is_default;
@@ -23,8 +24,13 @@ pub enum LinkType {
     Key,
     Operation,
     Htpasswd,
+    Htgroup,
     Jwks,
+    Oidc,
+    Introspection,
+    ApiKeys,
     Grpc,
+    Subgraph,
 }
 
 /// The @link directive allows you to import external resources, such as
@@ -46,6 +52,8 @@ pub enum LinkType {
 pub struct Link {
     ///
     /// The id of the link. It is used to reference the link in the schema.
+    /// `Cert` and `Key` links with an id are used by `@upstream(tls)` and
+    /// `@server(clientCert)`, instead of being the certificate of the server.
     #[serde(default, skip_serializing_if = "is_default")]
     pub id: Option<String>,
     ///
@@ -54,7 +62,15 @@ pub struct Link {
     #[serde(default, skip_serializing_if = "is_default")]
     pub src: String,
     ///
-    /// The type of the link. It can be `Config`, or `Protobuf`.
+    /// The type of the link. It can be `Config`, `Protobuf` or `Subgraph` among
+    /// others. A `Subgraph` link points to the endpoint of an upstream GraphQL
+    /// service whose schema is introspected and composed into the config.
     #[serde(default, skip_serializing_if = "is_default", rename = "type")]
     pub type_of: LinkType,
+    ///
+    /// Additional options of the link, such as the `audiences` of an `Oidc`
+    /// link, the `clientId` and `clientSecret` of an `Introspection` link, or
+    /// the `header` and `query` that carry the key of an `ApiKeys` link.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub meta: Option<Value>,
 }
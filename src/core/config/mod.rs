@@ -1,6 +1,7 @@
 pub use apollo::*;
 pub use config::*;
 pub use config_module::*;
+pub use federation::*;
 pub use key_values::*;
 pub use link::*;
 pub use npo::QueryPath;
@@ -13,6 +14,7 @@ mod apollo;
 mod config;
 mod config_module;
 pub mod cors;
+mod federation;
 mod from_document;
 pub mod group_by;
 mod headers;
@@ -7,13 +7,22 @@ use rustls_pki_types::{
 };
 use url::Url;
 
-use super::{ConfigModule, Content, Link, LinkType};
+use super::{ApiKeys, ConfigModule, Content, Link, LinkType, OidcProvider};
 use crate::core::config::{Config, ConfigReaderContext, Source};
 use crate::core::merge_right::MergeRight;
 use crate::core::proto_reader::ProtoReader;
 use crate::core::resource_reader::{Cached, Resource, ResourceReader};
 use crate::core::rest::EndpointSet;
 use crate::core::runtime::TargetRuntime;
+use crate::core::subgraph::{compose, SubgraphReader};
+
+/// The fields of the OpenID Connect discovery document that are used to
+/// verify the tokens.
+#[derive(serde::Deserialize)]
+struct OidcDiscovery {
+    issuer: String,
+    jwks_uri: String,
+}
 
 /// Reads the configuration from a file or from an HTTP URL and resolves all
 /// linked extensions to create a ConfigModule.
@@ -21,6 +30,7 @@ pub struct ConfigReader {
     runtime: TargetRuntime,
     resource_reader: ResourceReader<Cached>,
     proto_reader: ProtoReader,
+    subgraph_reader: SubgraphReader,
 }
 
 impl ConfigReader {
@@ -29,7 +39,8 @@ impl ConfigReader {
         Self {
             runtime: runtime.clone(),
             resource_reader: resource_reader.clone(),
-            proto_reader: ProtoReader::init(resource_reader, runtime),
+            proto_reader: ProtoReader::init(resource_reader, runtime.clone()),
+            subgraph_reader: SubgraphReader::init(runtime),
         }
     }
 
@@ -58,6 +69,7 @@ impl ConfigReader {
         }
 
         let mut extensions = config_module.extensions().clone();
+        let mut subgraphs = vec![];
         // let mut base_config = config_module.config().clone();
 
         for link in links.iter() {
@@ -90,18 +102,39 @@ impl ConfigReader {
                 LinkType::Cert => {
                     let source = self.resource_reader.read_file(path).await?;
                     let content = source.content;
-                    extensions.cert.extend(self.load_cert(content).await?);
+                    let cert = self.load_cert(content.clone()).await?;
+
+                    match &link.id {
+                        Some(id) if cert.is_empty() => {
+                            anyhow::bail!("No certificate in the Cert link {}", id)
+                        }
+                        Some(id) => extensions
+                            .tls_certs
+                            .push(Content { id: Some(id.clone()), content }),
+                        None => extensions.cert.extend(cert),
+                    }
                 }
                 LinkType::Key => {
                     let source = self.resource_reader.read_file(path).await?;
                     let content = source.content;
-                    extensions.keys = Arc::new(self.load_private_key(content).await?)
+                    let keys = self.load_private_key(content.clone()).await?;
+
+                    match &link.id {
+                        Some(id) if keys.is_empty() => {
+                            anyhow::bail!("No private key in the Key link {}", id)
+                        }
+                        Some(id) => extensions
+                            .tls_keys
+                            .push(Content { id: Some(id.clone()), content }),
+                        None => extensions.keys = Arc::new(keys),
+                    }
                 }
                 LinkType::Operation => {
                     let source = self.resource_reader.read_file(path).await?;
                     let content = source.content;
 
                     extensions.endpoint_set = EndpointSet::try_new(&content)?;
+                    extensions.operations.push(content);
                 }
                 LinkType::Htpasswd => {
                     let source = self.resource_reader.read_file(path).await?;
@@ -111,6 +144,14 @@ impl ConfigReader {
                         .htpasswd
                         .push(Content { id: link.id.clone(), content });
                 }
+                LinkType::Htgroup => {
+                    let source = self.resource_reader.read_file(path).await?;
+                    let content = source.content;
+
+                    extensions
+                        .htgroup
+                        .push(Content { id: link.id.clone(), content });
+                }
                 LinkType::Jwks => {
                     let source = self.resource_reader.read_file(path).await?;
                     let content = source.content;
@@ -122,6 +163,57 @@ impl ConfigReader {
                         content: serde_path_to_error::deserialize(de)?,
                     })
                 }
+                LinkType::Oidc => {
+                    let issuer = link.src.trim_end_matches('/');
+                    let discovery_url = format!("{}/.well-known/openid-configuration", issuer);
+                    let source = self.resource_reader.read_file(discovery_url).await?;
+
+                    let de = &mut serde_json::Deserializer::from_str(&source.content);
+                    let discovery: OidcDiscovery = serde_path_to_error::deserialize(de)?;
+
+                    // the tokens are verified against the issuer of the discovery, that has to
+                    // be the provider of the link, as required by OpenID Connect Discovery
+                    if discovery.issuer.trim_end_matches('/') != issuer {
+                        anyhow::bail!(
+                            "The issuer {} of the discovery doesn't match the Oidc link {}",
+                            discovery.issuer,
+                            link.src
+                        )
+                    }
+
+                    let source = self
+                        .resource_reader
+                        .read_file(discovery.jwks_uri.clone())
+                        .await?;
+                    let de = &mut serde_json::Deserializer::from_str(&source.content);
+
+                    extensions.oidc.push(Content {
+                        id: link.id.clone(),
+                        content: OidcProvider {
+                            issuer: discovery.issuer,
+                            jwks_uri: discovery.jwks_uri,
+                            jwks: serde_path_to_error::deserialize(de)?,
+                            meta: link.meta.clone(),
+                        },
+                    })
+                }
+                LinkType::Introspection => {
+                    extensions.introspection.push(link.clone());
+                }
+                LinkType::ApiKeys => {
+                    let source = self.resource_reader.read_file(path).await?;
+                    let content = source.content;
+
+                    let de = &mut serde_json::Deserializer::from_str(&content);
+
+                    extensions.api_keys.push(Content {
+                        id: link.id.clone(),
+                        content: ApiKeys {
+                            keys: serde_path_to_error::deserialize(de)?,
+                            meta: link.meta.clone(),
+                        },
+                    })
+                }
                 LinkType::Grpc => {
                     let meta = self.proto_reader.fetch(link.src.as_str()).await?;
 
@@ -129,9 +221,16 @@ impl ConfigReader {
                         extensions.add_proto(m);
                     }
                 }
+                LinkType::Subgraph => {
+                    subgraphs.push(self.subgraph_reader.read(&path).await?);
+                }
             }
         }
 
+        if !subgraphs.is_empty() {
+            config_module = config_module.merge_right(ConfigModule::from(compose(subgraphs)));
+        }
+
         // Recreating the ConfigModule in order to recompute the values of
         // `input_types`, `output_types` and `interface_types`
         Ok(config_module.set_extensions(extensions))
@@ -203,6 +302,11 @@ impl ConfigReader {
         Ok(config_module)
     }
 
+    /// Paths of the local files that were read, including the linked ones.
+    pub fn local_files(&self) -> Vec<String> {
+        self.resource_reader.local_paths()
+    }
+
     /// Resolves all the links in a Config to create a ConfigModule
     pub async fn resolve(
         &self,
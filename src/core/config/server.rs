@@ -5,7 +5,7 @@ use tailcall_macros::DirectiveDefinition;
 
 use super::merge_key_value_vecs;
 use crate::core::config::headers::Headers;
-use crate::core::config::KeyValue;
+use crate::core::config::{KeyValue, RateLimit};
 use crate::core::is_default;
 use crate::core::macros::MergeRight;
 use crate::core::merge_right::MergeRight;
@@ -35,6 +35,12 @@ pub struct Server {
     #[serde(default, skip_serializing_if = "is_default", rename = "enableJIT")]
     pub enable_jit: Option<bool>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `admin` enables the administration endpoints of the server, such as
+    /// `POST /admin/cache/purge` that removes the cached values by tag or by
+    /// key.
+    pub admin: Option<Admin>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// `apolloTracing` exposes GraphQL query performance data, including
     /// execution time of queries and individual resolvers.
@@ -46,6 +52,18 @@ pub struct Server {
     /// debugging. Use judiciously. @default `false`.
     pub batch_requests: Option<bool>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `cache` selects the store of the results of the `@cache` fields, that
+    /// can be shared by the replicas of the server with `redis` or
+    /// `cloudflareKv`. @default `{inMemory: {}}`.
+    pub cache: Option<CacheStore>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `clientCert` verifies the certificates of the clients of a `HTTP2`
+    /// server, that are issued by the CA certificates of a `Cert` link. The
+    /// subject of a verified certificate is exposed to `@protected`.
+    pub client_cert: Option<ClientCert>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// Enables deduplication of IO operations to enhance performance.
     ///
@@ -76,6 +94,32 @@ pub struct Server {
     /// and operations. @default `true`.
     pub introspection: Option<bool>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `maxAliases` sets the maximum number of aliases in an operation.
+    pub max_aliases: Option<usize>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `maxCost` sets the maximum cost of an operation. Every field costs the
+    /// weight set with `@cost`, one by default, and the cost of the fields
+    /// nested in a list is multiplied by the arguments of `@cost`.
+    pub max_cost: Option<usize>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `maxDepth` sets the maximum depth of the fields of an operation, where
+    /// the fields of the root type have a depth of one.
+    pub max_depth: Option<usize>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `maxRootFields` sets the maximum number of fields of the root type
+    /// that an operation can select.
+    pub max_root_fields: Option<usize>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `persistedQueries` enables the Automatic Persisted Queries protocol of
+    /// Apollo, that allows the clients to send the SHA-256 hash of a query
+    /// instead of its text.
+    pub persisted_queries: Option<PersistedQueries>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// `pipelineFlush` allows to control flushing behavior of the server
     /// pipeline.
@@ -91,6 +135,18 @@ pub struct Server {
     /// @default `false`.
     pub query_validation: Option<bool>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `rateLimit` limits the rate of the requests of each client to the
+    /// server. The clients exceeding the limit get a `429 Too Many Requests`
+    /// response.
+    pub rate_limit: Option<RateLimit>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `responseCache` caches the whole responses of the queries, by their
+    /// normalized document, their variables and the headers and claims of the
+    /// client that are listed.
+    pub response_cache: Option<ResponseCache>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// `responseValidation` Tailcall automatically validates responses from
     /// upstream services using inferred schema. @default `false`.
@@ -133,6 +189,135 @@ pub struct ScriptOptions {
     pub timeout: Option<u64>,
 }
 
+#[derive(
+    Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, schemars::JsonSchema, MergeRight,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct PersistedQueries {
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `safelist` rejects the operations that aren't defined in the documents
+    /// linked with `@link(type: Operation)`, and the clients can't register
+    /// new queries. @default `false`.
+    pub safelist: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `ttl` sets the time in milliseconds for which the queries registered by
+    /// the clients are kept in the cache. @default `86400000` i.e. one day.
+    pub ttl: Option<u64>,
+}
+
+#[derive(
+    Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, schemars::JsonSchema, MergeRight,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseCache {
+    /// `maxAge` sets the time in milliseconds for which a response is cached.
+    /// With `cacheControl` headers, a shorter `max-age` of the upstreams takes
+    /// precedence and the `private` responses are only cached by the `claims`
+    /// of the verified client.
+    pub max_age: u64,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `headers` lists the headers of the request that the responses vary
+    /// by.
+    pub headers: BTreeSet<String>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `claims` lists the claims of the verified client that the responses
+    /// vary by. The responses of the clients that send credentials, or of the
+    /// servers with `@link` to an auth provider, are only cached when it's
+    /// set, once the client is verified.
+    pub claims: BTreeSet<String>,
+}
+
+#[derive(
+    Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, schemars::JsonSchema, MergeRight,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientCert {
+    /// Id of the `Cert` link with the CA certificates that issue the
+    /// certificates of the clients.
+    pub ca: String,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `required` rejects the connections of the clients without a
+    /// certificate. Otherwise, only the `@protected` fields need one.
+    /// @default `true`.
+    pub required: Option<bool>,
+}
+
+#[derive(
+    Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, schemars::JsonSchema, MergeRight,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct Admin {
+    /// Token that the requests to the administration endpoints send in the
+    /// `Authorization: Bearer` header, that can refer to the environment e.g.
+    /// `{{.env.ADMIN_TOKEN}}`.
+    pub token: String,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Path under which the administration endpoints are served. @default
+    /// `/admin`.
+    pub path: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, schemars::JsonSchema, MergeRight)]
+#[serde(rename_all = "camelCase")]
+/// Store of the results of the `@cache` fields.
+pub enum CacheStore {
+    InMemory(InMemoryStore),
+    Redis(RedisStore),
+    CloudflareKv(CloudflareKvStore),
+}
+
+#[derive(
+    Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, schemars::JsonSchema, MergeRight,
+)]
+#[serde(rename_all = "camelCase")]
+/// Cache in the memory of each replica, that evicts the oldest entries once
+/// it is full.
+pub struct InMemoryStore {
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Maximum number of entries. @default `100000`.
+    pub capacity: Option<usize>,
+}
+
+#[derive(
+    Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, schemars::JsonSchema, MergeRight,
+)]
+#[serde(rename_all = "camelCase")]
+/// Cache in a server speaking the Redis protocol, such as Redis, Valkey or
+/// KeyDB. Only available in the CLI.
+pub struct RedisStore {
+    /// URL of the server e.g. `redis://:password@localhost:6379/0`, or `rediss://` for TLS.
+    pub url: String,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Prefix of the keys. @default `tailcall:`.
+    pub prefix: Option<String>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Number of connections opened to the server. @default `8`.
+    pub pool_size: Option<usize>,
+}
+
+#[derive(
+    Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, schemars::JsonSchema, MergeRight,
+)]
+#[serde(rename_all = "camelCase")]
+/// Cache in a Workers KV namespace. Only available on Cloudflare, where the
+/// entries live for at least 60 seconds.
+pub struct CloudflareKvStore {
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Name of the binding of the namespace. @default `TMP_KV`.
+    pub binding: Option<String>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Prefix of the keys. @default `tailcall:`.
+    pub prefix: Option<String>,
+}
+
 #[derive(
     Deserialize, Serialize, Debug, PartialEq, Eq, Clone, Default, schemars::JsonSchema, MergeRight,
 )]
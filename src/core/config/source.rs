@@ -9,7 +9,9 @@ use crate::core::valid::{ValidationError, Validator};
 #[serde(rename_all = "camelCase")]
 pub enum Source {
     Json,
+    Json5,
     Yml,
+    Toml,
     #[default]
     GraphQL,
 }
@@ -18,16 +20,26 @@ impl std::fmt::Display for Source {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Source::Json => write!(f, "JSON"),
+            Source::Json5 => write!(f, "JSON5"),
             Source::Yml => write!(f, "YML"),
+            Source::Toml => write!(f, "TOML"),
             Source::GraphQL => write!(f, "GraphQL"),
         }
     }
 }
 
 const JSON_EXT: &str = "json";
+const JSON5_EXT: &str = "json5";
 const YML_EXT: &str = "yml";
+const TOML_EXT: &str = "toml";
 const GRAPHQL_EXT: &str = "graphql";
-const ALL: [Source; 3] = [Source::Json, Source::Yml, Source::GraphQL];
+const ALL: [Source; 5] = [
+    Source::Json,
+    Source::Json5,
+    Source::Yml,
+    Source::Toml,
+    Source::GraphQL,
+];
 
 #[derive(Debug, Error, PartialEq)]
 #[error("Unsupported config extension: {0}")]
@@ -39,7 +51,9 @@ impl std::str::FromStr for Source {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
             "json" => Ok(Source::Json),
+            "json5" => Ok(Source::Json5),
             "yml" | "yaml" => Ok(Source::Yml),
+            "toml" => Ok(Source::Toml),
             "graphql" | "gql" => Ok(Source::GraphQL),
             _ => Err(UnsupportedConfigFormat(s.to_string())),
         }
@@ -51,7 +65,9 @@ impl Source {
     pub fn ext(&self) -> &'static str {
         match self {
             Source::Json => JSON_EXT,
+            Source::Json5 => JSON5_EXT,
             Source::Yml => YML_EXT,
+            Source::Toml => TOML_EXT,
             Source::GraphQL => GRAPHQL_EXT,
         }
     }
@@ -71,8 +87,10 @@ impl Source {
     pub fn encode(&self, config: &Config) -> Result<String, anyhow::Error> {
         match self {
             Source::Yml => Ok(config.to_yaml()?),
+            Source::Toml => Ok(config.to_toml()?),
             Source::GraphQL => Ok(config.to_sdl()),
-            Source::Json => Ok(config.to_json(true)?),
+            // JSON is valid JSON5, that only adds syntax for the humans writing it
+            Source::Json | Source::Json5 => Ok(config.to_json(true)?),
         }
     }
 
@@ -81,9 +99,15 @@ impl Source {
         match self {
             Source::Yml => Config::from_yaml(data).map_err(|e| ValidationError::new(e.to_string())),
             Source::GraphQL => Config::from_sdl(data).to_result(),
+            Source::Toml => {
+                Config::from_toml(data).map_err(|e| ValidationError::new(e.to_string()))
+            }
             Source::Json => {
                 Config::from_json(data).map_err(|e| ValidationError::new(e.to_string()))
             }
+            Source::Json5 => {
+                Config::from_json5(data).map_err(|e| ValidationError::new(e.to_string()))
+            }
         }
     }
 }
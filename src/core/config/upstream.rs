@@ -4,6 +4,7 @@ use derive_setters::Setters;
 use serde::{Deserialize, Serialize};
 use tailcall_macros::{DirectiveDefinition, InputDefinition};
 
+use crate::core::http::Method;
 use crate::core::is_default;
 use crate::core::macros::MergeRight;
 use crate::core::merge_right::MergeRight;
@@ -35,6 +36,207 @@ pub struct Proxy {
     pub url: String,
 }
 
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug, schemars::JsonSchema, MergeRight)]
+#[serde(rename_all = "camelCase")]
+pub struct UpstreamRateLimit {
+    /// Number of requests that can be sent to an upstream host in the
+    /// duration.
+    pub requests: u64,
+    /// Duration in milliseconds over which the requests are allowed.
+    pub duration: u64,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug, schemars::JsonSchema, MergeRight)]
+#[serde(untagged)]
+/// Storage of the responses cached by `httpCache`. A number keeps up to that
+/// many responses in memory, while `{disk: {}}` keeps them across restarts.
+pub enum HttpCache {
+    InMemory(u64),
+    Disk { disk: DiskHttpCache },
+}
+
+#[derive(
+    Serialize, Deserialize, PartialEq, Eq, Clone, Debug, Default, schemars::JsonSchema, MergeRight,
+)]
+#[serde(rename_all = "camelCase")]
+/// Cache of the responses in a directory, that evicts the least recently used
+/// ones once they take more than `maxSize`. Only available in the CLI.
+pub struct DiskHttpCache {
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Directory of the cache, relative to the working directory of the
+    /// server. @default `.tailcall/http-cache`.
+    pub path: Option<String>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Maximum size in bytes of the cached responses. @default `104857600`.
+    pub max_size: Option<u64>,
+}
+
+#[derive(
+    Serialize, Deserialize, PartialEq, Eq, Clone, Debug, Default, schemars::JsonSchema, MergeRight,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct Retry {
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Maximum number of attempts, including the first one. @default `3`.
+    pub max_attempts: Option<usize>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Delay in milliseconds before the first retry. The delay doubles with
+    /// every retry and a random jitter of up to half of it is subtracted.
+    /// @default `100`.
+    pub backoff: Option<u64>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Maximum delay in milliseconds between two attempts. @default `5000`.
+    pub max_backoff: Option<u64>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Status codes of the responses that are retried. Requests that fail
+    /// without a response, like on a timeout, are always retried. @default
+    /// `[429, 502, 503, 504]`.
+    pub status_codes: Option<Vec<u16>>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Methods of the requests that are retried. The gRPC calls and the
+    /// GraphQL queries are idempotent and always retried. @default `[GET,
+    /// HEAD, OPTIONS, PUT, DELETE]`.
+    pub methods: Option<Vec<Method>>,
+}
+
+#[derive(
+    Serialize, Deserialize, PartialEq, Eq, Clone, Debug, Default, schemars::JsonSchema, MergeRight,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct CircuitBreaker {
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Number of consecutive failures of a host after which the circuit opens.
+    /// @default `5`.
+    pub failure_threshold: Option<usize>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Time in milliseconds the circuit stays open before a request is let
+    /// through to probe the host. @default `30000`.
+    pub reset_timeout: Option<u64>,
+}
+
+#[derive(
+    Serialize, Deserialize, PartialEq, Eq, Clone, Debug, Default, schemars::JsonSchema, MergeRight,
+)]
+#[serde(rename_all = "camelCase")]
+/// Credentials of the OAuth 2.0 client-credentials flow, used to obtain the
+/// bearer token that is sent to the upstream.
+pub struct OAuth2 {
+    /// URL of the token endpoint of the authorization server.
+    pub token_url: String,
+
+    /// Identifier of the client, sent with the `clientSecret` using basic
+    /// authentication.
+    pub client_id: String,
+
+    pub client_secret: String,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Scopes requested for the token.
+    pub scopes: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Audience of the token, for the authorization servers that require it.
+    pub audience: Option<String>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Time in milliseconds before the expiry of the token at which a new one
+    /// is fetched. @default `30000`.
+    pub refresh_before: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug, schemars::JsonSchema, MergeRight)]
+#[serde(rename_all = "camelCase")]
+/// Signature of the requests to an upstream, computed over their method,
+/// path, headers and body once the templates are rendered. The credentials
+/// can refer to the environment e.g. `{{.env.AWS_SECRET_ACCESS_KEY}}`.
+pub enum Signing {
+    AwsSigV4(AwsSigV4),
+    Hmac(Hmac),
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug, Default, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+/// AWS Signature Version 4, as required by API Gateway and S3-compatible
+/// stores.
+pub struct AwsSigV4 {
+    /// Region of the service e.g. `us-east-1`.
+    pub region: String,
+
+    /// Name of the service e.g. `execute-api` or `s3`.
+    pub service: String,
+
+    pub access_key_id: String,
+
+    pub secret_access_key: String,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Token of temporary security credentials.
+    pub session_token: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug, Default, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+/// HMAC of the method, path and query, timestamp, signed headers as
+/// `name:value` and body of the request, separated by new lines and sent in
+/// hexadecimal in a header.
+pub struct Hmac {
+    pub secret: String,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Hash function of the HMAC. @default `Sha256`.
+    pub algorithm: Option<HmacAlgorithm>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Header of the signature. @default `X-Signature`.
+    pub header: Option<String>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Headers of the request that are signed, in the given order.
+    pub signed_headers: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Header in which the UNIX time in seconds of the request is sent. The
+    /// time is signed after the path, so that the signature can't be
+    /// replayed later.
+    pub timestamp_header: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug, Default, schemars::JsonSchema)]
+pub enum HmacAlgorithm {
+    #[default]
+    Sha256,
+    Sha512,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug, Default, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+/// Certificates of the TLS connections to the upstreams whose URL starts with
+/// `baseURL`. The certificates are the ids of `Cert` and `Key` links.
+pub struct UpstreamTls {
+    #[serde(rename = "baseURL")]
+    pub base_url: String,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Id of the `Cert` link with the certificate chain of the client, that is
+    /// presented to upstreams requiring mutual TLS.
+    pub cert: Option<String>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Id of the `Key` link with the private key of the client certificate.
+    pub key: Option<String>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Id of the `Cert` link with the CA certificates that are trusted along
+    /// with the default ones, e.g. for upstreams with private certificates.
+    pub ca: Option<String>,
+}
+
 #[derive(
     Serialize,
     Deserialize,
@@ -82,14 +284,19 @@ pub struct Upstream {
     /// the batch).
     pub batch: Option<Batch>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `circuitBreaker` stops sending requests to a host that keeps failing,
+    /// and fails them right away until the host is probed again.
+    pub circuit_breaker: Option<CircuitBreaker>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// The time in seconds that the connection will wait for a response before
     /// timing out.
     pub connect_timeout: Option<u64>,
 
     #[serde(default, skip_serializing_if = "is_default")]
-    /// Providing httpCache size enables Tailcall's HTTP caching, adhering to the [HTTP Caching RFC](https://tools.ietf.org/html/rfc7234), to enhance performance by minimizing redundant data fetches. Defaults to `0` if unspecified.
-    pub http_cache: Option<u64>,
+    /// Providing httpCache size enables Tailcall's HTTP caching, adhering to the [HTTP Caching RFC](https://tools.ietf.org/html/rfc7234), to enhance performance by minimizing redundant data fetches. Defaults to `0` if unspecified. Use `{disk: {path, maxSize}}` instead of a size to keep the responses on the disk across restarts.
+    pub http_cache: Option<HttpCache>,
 
     #[setters(strip_option)]
     #[serde(rename = "http2Only", default, skip_serializing_if = "is_default")]
@@ -114,6 +321,12 @@ pub struct Upstream {
     /// sent while the connection is idle.
     pub keep_alive_while_idle: Option<bool>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `oauth2` obtains a bearer token with the OAuth 2.0 client-credentials
+    /// flow, and sends it in the `Authorization` header of the requests of
+    /// `@http`. It can be overridden on `@http`.
+    pub oauth2: Option<OAuth2>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// The maximum number of idle connections that will be maintained per host.
     pub pool_max_idle_per_host: Option<usize>,
@@ -130,6 +343,22 @@ pub struct Upstream {
     /// enabling custom routing and security policies.
     pub proxy: Option<Proxy>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `rateLimit` caps the number of requests sent to each upstream host, and
+    /// fails the requests over the cap instead of sending them.
+    pub rate_limit: Option<UpstreamRateLimit>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `retry` sends the failed requests to the upstream again, with an
+    /// exponential backoff. It can be overridden on `@http`, `@grpc` and
+    /// `@graphQL`.
+    pub retry: Option<Retry>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `signing` signs the requests of `@http` with AWS SigV4 or an HMAC. It
+    /// can be overridden on `@http`.
+    pub signing: Option<Signing>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// The time in seconds between each TCP keep-alive message sent to maintain
     /// the connection.
@@ -140,6 +369,11 @@ pub struct Upstream {
     /// response.
     pub timeout: Option<u64>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `tls` sets the client certificates and the trusted CA certificates of
+    /// the connections to the upstreams, by their base URL.
+    pub tls: Vec<UpstreamTls>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// The User-Agent header value to be used in HTTP requests. @default
     /// `Tailcall/1.0`
@@ -176,9 +410,6 @@ impl Upstream {
             .clone()
             .unwrap_or("Tailcall/1.0".to_string())
     }
-    pub fn get_http_cache_size(&self) -> u64 {
-        self.http_cache.unwrap_or(0)
-    }
     pub fn get_allowed_headers(&self) -> BTreeSet<String> {
         self.allowed_headers.clone().unwrap_or_default()
     }
@@ -17,6 +17,9 @@ use crate::core::config::{Arg, Config, Enum, Field, Grpc, Tag, Type, Union, Vari
 use crate::core::transform::{Transform, TransformerOps};
 use crate::core::valid::Validator;
 
+/// Root GraphQL type of the server streaming methods.
+const SUBSCRIPTION: &str = "Subscription";
+
 /// Assists in the mapping and retrieval of proto type names to custom formatted
 /// strings based on the descriptor type.
 #[derive(Setters)]
@@ -334,6 +337,11 @@ impl Context {
             let path = parent_path.extend(PathField::Service, index as i32);
 
             for (method_index, method) in service.method.iter().enumerate() {
+                // client streaming isn't supported by the @grpc resolver
+                if method.client_streaming() {
+                    continue;
+                }
+
                 let field_name = GraphQLType::new(method.name())
                     .extend(self.namespace.as_slice())
                     .push(service_name)
@@ -372,20 +380,32 @@ impl Context {
                     batch_key: vec![],
                     headers: vec![],
                     method: field_name.id(),
+                    retry: None,
                 });
 
                 let method_path =
                     PathBuilder::new(&path).extend(PathField::Method, method_index as i32);
                 cfg_field.doc = self.comments_builder.get_comments(&method_path);
 
-                let ty = self
-                    .config
-                    .types
-                    .entry(self.query.clone())
-                    .or_insert_with(|| {
-                        self.config.schema.query = Some(self.query.clone());
-                        Type::default()
-                    });
+                // server streaming methods deliver multiple messages, so they
+                // are exposed as subscriptions
+                let ty = if method.server_streaming() {
+                    self.config
+                        .types
+                        .entry(SUBSCRIPTION.to_string())
+                        .or_insert_with(|| {
+                            self.config.schema.subscription = Some(SUBSCRIPTION.to_string());
+                            Type::default()
+                        })
+                } else {
+                    self.config
+                        .types
+                        .entry(self.query.clone())
+                        .or_insert_with(|| {
+                            self.config.schema.query = Some(self.query.clone());
+                            Type::default()
+                        })
+                };
 
                 ty.fields.insert(field_name.to_string(), cfg_field);
             }
@@ -587,4 +607,10 @@ mod test {
     fn test_oneof_types() {
         assert_gen!(protobuf::ONEOF);
     }
+
+    #[test]
+    fn test_streaming_methods() {
+        // server streaming methods are exposed as subscriptions
+        assert_gen!(protobuf::STREAM);
+    }
 }
@@ -79,6 +79,7 @@ impl Generator {
             id: None,
             src: metadata.path.to_owned(),
             type_of: LinkType::Protobuf,
+            meta: None,
         });
         Ok(config)
     }
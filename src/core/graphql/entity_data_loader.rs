@@ -0,0 +1,311 @@
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_graphql::async_trait;
+use async_graphql::futures_util::future::join_all;
+use async_graphql_value::{ConstValue, Name};
+use indexmap::IndexMap;
+use tailcall_hasher::TailcallHasher;
+
+use super::entities_query;
+use crate::core::config::{Batch, GraphQLOperationType};
+use crate::core::data_loader::{DataLoader, Loader};
+use crate::core::http::Response;
+use crate::core::runtime::TargetRuntime;
+
+/// Request of the fields of a single entity. The requests of the entities of
+/// the same type that select the same fields are sent to the subgraph as a
+/// single `_entities` request.
+#[derive(Debug)]
+pub struct EntityRequest {
+    /// Request to the subgraph without its body.
+    pub request: reqwest::Request,
+    pub operation_type: GraphQLOperationType,
+    pub type_name: String,
+    pub selection: String,
+    /// Representation of the entity, escaped to be embedded in the query.
+    pub representation: String,
+}
+
+impl EntityRequest {
+    /// Identifies the requests that can be batched together. All the headers
+    /// are part of it, so that the entities requested on behalf of different
+    /// clients are never mixed.
+    fn batch_id(&self) -> u64 {
+        let mut hasher = TailcallHasher::default();
+        self.request.url().hash(&mut hasher);
+        for (name, value) in self.request.headers() {
+            name.hash(&mut hasher);
+            value.hash(&mut hasher);
+        }
+        self.operation_type.hash(&mut hasher);
+        self.type_name.hash(&mut hasher);
+        self.selection.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn request_without_body(&self) -> reqwest::Request {
+        let mut request =
+            reqwest::Request::new(self.request.method().clone(), self.request.url().clone());
+        request.headers_mut().extend(self.request.headers().clone());
+        request
+    }
+
+    fn to_request(&self, representations: &[String]) -> reqwest::Request {
+        let mut request = self.request_without_body();
+        request.body_mut().replace(
+            entities_query(
+                &self.operation_type,
+                &self.type_name,
+                representations,
+                &self.selection,
+            )
+            .into(),
+        );
+        request
+    }
+
+    /// Creates the request of this entity only.
+    pub fn to_single_request(&self) -> reqwest::Request {
+        self.to_request(std::slice::from_ref(&self.representation))
+    }
+}
+
+impl Hash for EntityRequest {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.batch_id().hash(state);
+        self.representation.hash(state);
+    }
+}
+
+impl PartialEq for EntityRequest {
+    fn eq(&self, other: &Self) -> bool {
+        self.batch_id() == other.batch_id() && self.representation == other.representation
+    }
+}
+
+impl Eq for EntityRequest {}
+
+impl Clone for EntityRequest {
+    fn clone(&self) -> Self {
+        Self {
+            request: self.request_without_body(),
+            operation_type: self.operation_type.clone(),
+            type_name: self.type_name.clone(),
+            selection: self.selection.clone(),
+            representation: self.representation.clone(),
+        }
+    }
+}
+
+/// Loads the fields of the entities of a subgraph, the entities requested
+/// during the same tick share a single `_entities` request.
+pub struct EntityDataLoader {
+    pub runtime: TargetRuntime,
+}
+
+impl EntityDataLoader {
+    pub fn new(runtime: TargetRuntime) -> Self {
+        EntityDataLoader { runtime }
+    }
+
+    pub fn into_data_loader(self, batch: Batch) -> DataLoader<EntityRequest, EntityDataLoader> {
+        // the entities are batched even if the batching of the upstream is
+        // disabled, so there's always a delay to collect them
+        DataLoader::new(self)
+            .delay(Duration::from_millis(batch.delay.max(1) as u64))
+            .max_batch_size(batch.max_size.unwrap_or_default())
+    }
+
+    async fn load_batch(
+        &self,
+        mut keys: Vec<&EntityRequest>,
+    ) -> anyhow::Result<Vec<(EntityRequest, Response<ConstValue>)>> {
+        // the representations are sorted to render the same query for the same
+        // set of entities
+        keys.sort_by(|a, b| a.representation.cmp(&b.representation));
+        let representations = keys
+            .iter()
+            .map(|key| key.representation.clone())
+            .collect::<Vec<_>>();
+
+        let response = self
+            .runtime
+            .http
+            .execute(keys[0].to_request(&representations))
+            .await?
+            .to_json()?;
+
+        Ok(keys
+            .into_iter()
+            .enumerate()
+            .map(|(index, key)| {
+                let body = entity_response(&response.body, index);
+                let response = Response {
+                    status: response.status,
+                    headers: response.headers.clone(),
+                    body,
+                };
+                (key.clone(), response)
+            })
+            .collect())
+    }
+}
+
+#[async_trait::async_trait]
+impl Loader<EntityRequest> for EntityDataLoader {
+    type Value = Response<ConstValue>;
+    type Error = Arc<anyhow::Error>;
+
+    #[allow(clippy::mutable_key_type)]
+    async fn load(
+        &self,
+        keys: &[EntityRequest],
+    ) -> async_graphql::Result<HashMap<EntityRequest, Self::Value>, Self::Error> {
+        let mut batches: BTreeMap<u64, Vec<&EntityRequest>> = BTreeMap::new();
+        for key in keys {
+            batches.entry(key.batch_id()).or_default().push(key);
+        }
+
+        let results = join_all(batches.into_values().map(|keys| self.load_batch(keys))).await;
+
+        #[allow(clippy::mutable_key_type)]
+        let mut hashmap = HashMap::new();
+        for result in results {
+            hashmap.extend(result?);
+        }
+
+        Ok(hashmap)
+    }
+}
+
+/// Extracts the response of the entity at the given index of a batched
+/// `_entities` response, as if the entity had been requested alone. The errors
+/// that don't belong to any entity are kept in the response of the first one.
+fn entity_response(body: &ConstValue, index: usize) -> ConstValue {
+    let entities = Name::new("_entities");
+    let data = match body {
+        ConstValue::Object(body) => body.get("data"),
+        _ => None,
+    };
+    let entity = match data {
+        Some(ConstValue::Object(data)) => match data.get(&entities) {
+            Some(ConstValue::List(values)) => values.get(index).cloned(),
+            _ => None,
+        },
+        _ => None,
+    };
+
+    let errors = match body {
+        ConstValue::Object(body) => match body.get("errors") {
+            Some(ConstValue::List(errors)) => errors
+                .iter()
+                .filter_map(|error| entity_error(error, index))
+                .collect(),
+            _ => Vec::new(),
+        },
+        _ => Vec::new(),
+    };
+
+    let mut response = IndexMap::new();
+    if let Some(entity) = entity {
+        let data = IndexMap::from([(entities, ConstValue::List(vec![entity]))]);
+        response.insert(Name::new("data"), ConstValue::Object(data));
+    }
+    if !errors.is_empty() {
+        response.insert(Name::new("errors"), ConstValue::List(errors));
+    }
+    ConstValue::Object(response)
+}
+
+/// Returns the error if it belongs to the entity at the given index, with its
+/// path pointing to the entity as if it had been requested alone.
+fn entity_error(error: &ConstValue, index: usize) -> Option<ConstValue> {
+    let ConstValue::Object(fields) = error else {
+        return (index == 0).then(|| error.clone());
+    };
+
+    let path = match fields.get("path") {
+        Some(ConstValue::List(path)) => path,
+        _ => return (index == 0).then(|| error.clone()),
+    };
+
+    match (path.first(), path.get(1)) {
+        (Some(ConstValue::String(field)), Some(ConstValue::Number(position)))
+            if field == "_entities" =>
+        {
+            if position.as_u64() != Some(index as u64) {
+                return None;
+            }
+
+            let mut path = path.clone();
+            path[1] = ConstValue::from(0);
+            let mut fields = fields.clone();
+            fields.insert(Name::new("path"), ConstValue::List(path));
+            Some(ConstValue::Object(fields))
+        }
+        _ => (index == 0).then(|| error.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_entity_response() {
+        let body = ConstValue::from_json(json!({
+            "data": {"_entities": [{"name": "Leanne"}, {"name": "Ervin"}]},
+            "errors": [
+                {"message": "second", "path": ["_entities", 1, "name"]},
+                {"message": "global"}
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(
+            entity_response(&body, 0).into_json().unwrap(),
+            json!({
+                "data": {"_entities": [{"name": "Leanne"}]},
+                "errors": [{"message": "global"}]
+            })
+        );
+        assert_eq!(
+            entity_response(&body, 1).into_json().unwrap(),
+            json!({
+                "data": {"_entities": [{"name": "Ervin"}]},
+                "errors": [{"message": "second", "path": ["_entities", 0, "name"]}]
+            })
+        );
+    }
+
+    #[test]
+    fn test_batch_id() {
+        let request = |url: &str, selection: &str, representation: &str| EntityRequest {
+            request: reqwest::Request::new(reqwest::Method::POST, url.parse().unwrap()),
+            operation_type: GraphQLOperationType::Query,
+            type_name: "User".to_string(),
+            selection: selection.to_string(),
+            representation: representation.to_string(),
+        };
+
+        let first = request("http://users/graphql", "name", "{id: 1}");
+        let second = request("http://users/graphql", "name", "{id: 2}");
+        assert_eq!(first.batch_id(), second.batch_id());
+        assert_ne!(first, second);
+
+        let other_selection = request("http://users/graphql", "name email", "{id: 1}");
+        assert_ne!(first.batch_id(), other_selection.batch_id());
+
+        let mut authorized = first.clone();
+        authorized
+            .request
+            .headers_mut()
+            .insert("authorization", "Bearer token".parse().unwrap());
+        assert_ne!(first.batch_id(), authorized.batch_id());
+    }
+}
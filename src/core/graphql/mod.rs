@@ -1,5 +1,7 @@
 mod data_loader;
+mod entity_data_loader;
 mod request_template;
 
 pub use data_loader::*;
+pub use entity_data_loader::*;
 pub use request_template::*;
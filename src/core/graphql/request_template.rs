@@ -8,6 +8,7 @@ use hyper::HeaderMap;
 use reqwest::header::HeaderValue;
 use tailcall_hasher::TailcallHasher;
 
+use super::EntityRequest;
 use crate::core::config::{GraphQLOperationType, KeyValue};
 use crate::core::has_headers::HasHeaders;
 use crate::core::helpers::headers::MustacheHeaders;
@@ -17,6 +18,29 @@ use crate::core::ir::{GraphQLOperationContext, RelatedFields};
 use crate::core::mustache::Mustache;
 use crate::core::path::PathGraphql;
 
+/// Entity of an Apollo Federation subgraph that the requested field is
+/// resolved on. The field is requested through the `_entities` field of the
+/// subgraph with the representation rendered from the parent value.
+#[derive(Debug, Clone)]
+pub struct Entity {
+    pub type_name: String,
+    pub representation: Mustache,
+}
+
+/// Renders the body of the request of the fields of the entities with the
+/// given representations, that are already escaped.
+pub fn entities_query(
+    operation_type: &GraphQLOperationType,
+    type_name: &str,
+    representations: &[String],
+    selection: &str,
+) -> String {
+    format!(
+        r#"{{ "query": "{operation_type} {{ _entities(representations: [{}]) {{ ... on {type_name} {{ {selection} }} }} }}" }}"#,
+        representations.join(", ")
+    )
+}
+
 /// RequestTemplate for GraphQL requests (See RequestTemplate documentation)
 #[derive(Setters, Debug, Clone)]
 pub struct RequestTemplate {
@@ -27,6 +51,7 @@ pub struct RequestTemplate {
     pub operation_arguments: Option<Vec<(String, Mustache)>>,
     pub headers: MustacheHeaders,
     pub related_fields: RelatedFields,
+    pub entity: Option<Entity>,
 }
 
 impl RequestTemplate {
@@ -81,11 +106,36 @@ impl RequestTemplate {
         req
     }
 
-    fn render_graphql_query<C: PathGraphql + HasHeaders + GraphQLOperationContext>(
+    /// Creates the request of the fields of the entity, that is batched with
+    /// the requests of the other entities of the same type.
+    pub fn to_entity_request<C: PathGraphql + HasHeaders + GraphQLOperationContext>(
+        &self,
+        ctx: &C,
+    ) -> anyhow::Result<EntityRequest> {
+        let Some(entity) = &self.entity else {
+            anyhow::bail!("{} is not an entity field", self.operation_name);
+        };
+
+        let request = reqwest::Request::new(POST.to_hyper(), url::Url::parse(self.url.as_str())?);
+        Ok(EntityRequest {
+            request: self.set_headers(request, ctx),
+            operation_type: self.operation_type.clone(),
+            type_name: entity.type_name.clone(),
+            selection: self.render_selection(ctx),
+            representation: entity
+                .representation
+                .render_graphql(ctx)
+                .escape_default()
+                .to_string(),
+        })
+    }
+
+    /// Renders the requested field along with its arguments, directives and
+    /// selection set.
+    fn render_selection<C: PathGraphql + HasHeaders + GraphQLOperationContext>(
         &self,
         ctx: &C,
     ) -> String {
-        let operation_type = &self.operation_type;
         let selection_set = ctx.selection_set(&self.related_fields).unwrap_or_default();
 
         let mut operation = Cow::Borrowed(&self.operation_name);
@@ -122,7 +172,27 @@ impl RequestTemplate {
             }
         }
 
-        format!(r#"{{ "query": "{operation_type} {{ {operation} {selection_set} }}" }}"#)
+        format!("{operation} {selection_set}")
+    }
+
+    fn render_graphql_query<C: PathGraphql + HasHeaders + GraphQLOperationContext>(
+        &self,
+        ctx: &C,
+    ) -> String {
+        if let Some(entity) = &self.entity {
+            let representation = entity.representation.render_graphql(ctx);
+
+            return entities_query(
+                &self.operation_type,
+                &entity.type_name,
+                &[representation.escape_default().to_string()],
+                &self.render_selection(ctx),
+            );
+        }
+
+        let operation_type = &self.operation_type;
+        let selection = self.render_selection(ctx);
+        format!(r#"{{ "query": "{operation_type} {{ {selection} }}" }}"#)
     }
 
     pub fn new(
@@ -150,6 +220,7 @@ impl RequestTemplate {
             operation_arguments,
             headers,
             related_fields,
+            entity: None,
         })
     }
 }
@@ -173,12 +244,13 @@ mod tests {
     use serde_json::json;
 
     use crate::core::config::GraphQLOperationType;
-    use crate::core::graphql::request_template::RelatedFields;
+    use crate::core::graphql::request_template::{Entity, RelatedFields};
     use crate::core::graphql::RequestTemplate;
     use crate::core::has_headers::HasHeaders;
     use crate::core::ir::model::CacheKey;
     use crate::core::ir::GraphQLOperationContext;
     use crate::core::json::JsonLike;
+    use crate::core::mustache::Mustache;
     use crate::core::path::PathGraphql;
 
     struct Context {
@@ -339,4 +411,38 @@ mod tests {
 
         assert_eq!(arr.len(), cache_key_set.len());
     }
+
+    #[test]
+    fn test_entity_query() {
+        let tmpl = RequestTemplate::new(
+            "http://localhost:3000".to_string(),
+            &GraphQLOperationType::Query,
+            "reviews",
+            None,
+            vec![],
+            RelatedFields::default(),
+        )
+        .unwrap()
+        .entity(Some(Entity {
+            type_name: "User".to_string(),
+            representation: Mustache::parse(r#"{__typename: "User", id: {{foo.bar}} }"#).unwrap(),
+        }));
+        let ctx = Context {
+            value: Value::from_json(json!({
+              "foo": {
+                "bar": "baz"
+              }
+            }))
+            .unwrap(),
+            headers: Default::default(),
+        };
+
+        let req = tmpl.to_request(&ctx).unwrap();
+        let body = req.body().unwrap().as_bytes().unwrap().to_owned();
+
+        assert_eq!(
+            std::str::from_utf8(&body).unwrap(),
+            r#"{ "query": "query { _entities(representations: [{__typename: \"User\", id: \"baz\" }]) { ... on User { reviews { a,b,c } } } }" }"#
+        );
+    }
 }
@@ -165,6 +165,15 @@ impl ProtobufOperation {
         self.method.parent_service().name()
     }
 
+    /// Checks if the upstream responds with a stream of messages.
+    pub fn is_server_streaming(&self) -> bool {
+        self.method.is_server_streaming()
+    }
+
+    pub fn is_client_streaming(&self) -> bool {
+        self.method.is_client_streaming()
+    }
+
     pub fn convert_input(&self, input: &str) -> Result<Vec<u8>> {
         let message = to_message(&self.input_type, input)?;
 
@@ -266,6 +275,7 @@ pub mod tests {
             id: Some(id.clone()),
             src: path.to_string(),
             type_of: LinkType::Protobuf,
+            meta: None,
         }]);
 
         let method = GrpcMethod { package: id, service: "a".to_owned(), name: "b".to_owned() };
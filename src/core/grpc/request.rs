@@ -1,11 +1,17 @@
+use std::future::ready;
+
 use anyhow::{bail, Result};
-use hyper::{HeaderMap, Method};
+use futures_util::stream::{self, BoxStream};
+use futures_util::{StreamExt, TryStreamExt};
+use hyper::body::Bytes;
+use hyper::{HeaderMap, Method, StatusCode};
 use reqwest::Request;
 use url::Url;
 
 use super::protobuf::ProtobufOperation;
 use crate::core::http::Response;
 use crate::core::runtime::TargetRuntime;
+use crate::core::{BodyStream, Frame};
 
 pub static GRPC_STATUS: &str = "grpc-status";
 
@@ -23,7 +29,14 @@ pub async fn execute_grpc_request(
     request: Request,
 ) -> Result<Response<async_graphql::Value>> {
     let response = runtime.http2_only.execute(request).await?;
+    to_grpc_response(operation, response)
+}
 
+/// Decodes the response of a unary call, or the error of its gRPC status.
+pub fn to_grpc_response(
+    operation: &ProtobufOperation,
+    response: Response<Bytes>,
+) -> Result<Response<async_graphql::Value>> {
     let grpc_status = response
         .headers
         .get(GRPC_STATUS)
@@ -39,12 +52,100 @@ pub async fn execute_grpc_request(
     bail!("Failed to execute request");
 }
 
+/// Executes the request of a server-streaming method and decodes every
+/// message as soon as it's received from the upstream.
+pub async fn execute_grpc_stream(
+    runtime: &TargetRuntime,
+    operation: &ProtobufOperation,
+    request: Request,
+) -> Result<BoxStream<'static, Result<async_graphql::Value>>> {
+    let response = runtime.http2_only.execute_stream(request).await?;
+
+    if !response.status.is_success() {
+        bail!("Failed to execute request");
+    }
+
+    // errors that happen before any message is sent are reported through the
+    // headers of a trailers-only response
+    let status = response.status;
+    check_grpc_status(status, &response.headers, operation)?;
+
+    let operation = operation.clone();
+    Ok(parts(response.body)
+        .try_filter_map(move |part| {
+            ready(match part {
+                Part::Message(message) => operation.convert_output(&message).map(Some),
+                // errors that happen once the stream has started are reported
+                // through the trailers
+                Part::Trailers(trailers) => {
+                    check_grpc_status(status, &trailers, &operation).map(|_| None)
+                }
+            })
+        })
+        .boxed())
+}
+
+fn check_grpc_status(
+    status: StatusCode,
+    headers: &HeaderMap,
+    operation: &ProtobufOperation,
+) -> Result<()> {
+    let grpc_status = headers
+        .get(GRPC_STATUS)
+        .and_then(|header_value| header_value.to_str().ok());
+
+    if grpc_status.is_some_and(|grpc_status| grpc_status != "0") {
+        let response = Response { status, headers: headers.clone(), body: Bytes::new() };
+        return Err(response.to_grpc_error(operation));
+    }
+    Ok(())
+}
+
+/// A part of the body of a server-streaming method.
+#[derive(Debug, PartialEq)]
+enum Part {
+    /// A Length-Prefixed Message of the gRPC protocol, that keeps its 5 bytes
+    /// prefix.
+    Message(Vec<u8>),
+    Trailers(HeaderMap),
+}
+
+/// Splits the body into the messages of the stream followed by its trailers,
+/// if the upstream sent them.
+fn parts(body: BodyStream) -> BoxStream<'static, Result<Part>> {
+    stream::try_unfold((body, Vec::new()), |(mut body, mut buffer)| async move {
+        loop {
+            if buffer.len() >= 5 {
+                let len = u32::from_be_bytes([buffer[1], buffer[2], buffer[3], buffer[4]]) as usize;
+                if buffer.len() >= 5 + len {
+                    let message = buffer.drain(..5 + len).collect::<Vec<u8>>();
+                    return Ok(Some((Part::Message(message), (body, buffer))));
+                }
+            }
+
+            match body.try_next().await? {
+                Some(Frame::Data(chunk)) => buffer.extend_from_slice(&chunk),
+                Some(Frame::Trailers(_)) if !buffer.is_empty() => {
+                    bail!("Incomplete message received from the upstream")
+                }
+                Some(Frame::Trailers(trailers)) => {
+                    return Ok(Some((Part::Trailers(trailers), (body, buffer))))
+                }
+                None if buffer.is_empty() => return Ok(None),
+                None => bail!("Incomplete message received from the upstream"),
+            }
+        }
+    })
+    .boxed()
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
 
     use anyhow::Result;
     use async_trait::async_trait;
+    use futures_util::{stream, StreamExt, TryStreamExt};
     use hyper::body::Bytes;
     use reqwest::header::HeaderMap;
     use reqwest::{Method, Request, StatusCode};
@@ -54,11 +155,11 @@ mod tests {
 
     use crate::core::blueprint::GrpcMethod;
     use crate::core::grpc::protobuf::{ProtobufOperation, ProtobufSet};
-    use crate::core::grpc::request::execute_grpc_request;
+    use crate::core::grpc::request::{execute_grpc_request, execute_grpc_stream, parts, Part};
     use crate::core::http::Response;
     use crate::core::ir::Error;
     use crate::core::runtime::TargetRuntime;
-    use crate::core::HttpIO;
+    use crate::core::{BodyStream, Frame, HttpIO};
 
     enum TestScenario {
         SuccessWithoutGrpcStatus,
@@ -102,7 +203,7 @@ mod tests {
         }
     }
     async fn prepare_args(
-        test_http: TestHttp,
+        test_http: impl HttpIO,
     ) -> Result<(TargetRuntime, ProtobufOperation, Request)> {
         let mut runtime = crate::core::runtime::test::init(None);
         runtime.http2_only = Arc::new(test_http);
@@ -187,6 +288,83 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_parts() -> Result<()> {
+        let chunks = [&b"\0\0\0\0\x02ab\0\0"[..], &b"\0\0\x01c\0\0\0\0\0"[..]];
+        let mut trailers = HeaderMap::new();
+        trailers.insert("grpc-status", "0".parse()?);
+        let body = stream::iter(chunks.map(|chunk| Ok(Frame::Data(Bytes::from_static(chunk)))))
+            .chain(stream::once(async { Ok(Frame::Trailers(trailers)) }))
+            .boxed();
+
+        let decoded = parts(body).try_collect::<Vec<_>>().await?;
+
+        let mut trailers = HeaderMap::new();
+        trailers.insert("grpc-status", "0".parse()?);
+        assert_eq!(
+            decoded,
+            vec![
+                Part::Message(b"\0\0\0\0\x02ab".to_vec()),
+                Part::Message(b"\0\0\0\0\x01c".to_vec()),
+                Part::Message(b"\0\0\0\0\0".to_vec()),
+                Part::Trailers(trailers),
+            ]
+        );
+
+        let incomplete =
+            stream::once(async { Ok(Frame::Data(Bytes::from_static(b"\0\0\0\0\x02a"))) }).boxed();
+        assert!(parts(incomplete).try_collect::<Vec<_>>().await.is_err());
+
+        Ok(())
+    }
+
+    struct TestStreamHttp;
+
+    #[async_trait]
+    impl HttpIO for TestStreamHttp {
+        async fn execute(&self, _request: Request) -> Result<Response<Bytes>> {
+            unimplemented!("Not needed for this test")
+        }
+
+        async fn execute_stream(&self, _request: Request) -> Result<Response<BodyStream>> {
+            let message = Bytes::from_static(b"\0\0\0\0\x0e\n\x0ctest message");
+            let mut trailers = HeaderMap::new();
+            Status::new(Code::Unavailable, "stream interrupted").add_header(&mut trailers)?;
+            let body = stream::iter([Ok(Frame::Data(message)), Ok(Frame::Trailers(trailers))]);
+
+            Ok(Response {
+                status: StatusCode::OK,
+                headers: HeaderMap::new(),
+                body: body.boxed(),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_grpc_stream_error_in_trailers() -> Result<()> {
+        let (runtime, operation, request) = prepare_args(TestStreamHttp).await?;
+
+        let values = execute_grpc_stream(&runtime, &operation, request)
+            .await?
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(values.len(), 2);
+        assert_eq!(
+            values[0].as_ref().unwrap(),
+            &async_graphql::Value::from_json(json!({"message": "test message"}))?
+        );
+        match values[1].as_ref().unwrap_err().downcast_ref::<Error>() {
+            Some(Error::GRPCError { grpc_code, grpc_status_message, .. }) => {
+                assert_eq!(*grpc_code, Code::Unavailable as i32);
+                assert_eq!(*grpc_status_message, "stream interrupted");
+            }
+            _ => panic!("Expected GRPCError"),
+        }
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_grpc_request_error() -> Result<()> {
         let test_http = TestHttp { scenario: TestScenario::Error };
@@ -158,6 +158,7 @@ mod tests {
             id: Some(id.clone()),
             src: test_file.to_string(),
             type_of: LinkType::Protobuf,
+            meta: None,
         }]);
         let method = GrpcMethod {
             package: id.to_string(),
@@ -0,0 +1,147 @@
+use anyhow::{anyhow, Result};
+use hyper::header::{AUTHORIZATION, CONTENT_TYPE};
+use hyper::{Body, Method, Request, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
+
+use super::request_handler::create_request_context;
+use crate::core::app_context::AppContext;
+use crate::core::blueprint::Admin;
+use crate::core::ir::model::IoId;
+use crate::core::ir::{EmptyResolverContext, EvalContext};
+use crate::core::Cache;
+
+const PURGE_PATH: &str = "/cache/purge";
+
+/// Body of `POST {path}/cache/purge`. The keys are the hexadecimal ids that
+/// the cached values are stored under.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PurgeRequest {
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    keys: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct PurgeResponse {
+    purged: usize,
+}
+
+pub fn is_admin_request(req: &Request<Body>, admin: &Admin) -> bool {
+    req.uri()
+        .path()
+        .strip_prefix(admin.path.as_str())
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// Serves the administration endpoints to the requests that send the token of
+/// the admin as a bearer token.
+pub async fn handle(
+    req: Request<Body>,
+    app_ctx: &AppContext,
+    admin: &Admin,
+) -> Result<Response<Body>> {
+    let req_ctx = create_request_context(&req, app_ctx);
+    let token = admin
+        .token
+        .render(&EvalContext::new(&req_ctx, &EmptyResolverContext {}));
+    let authorized = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|bearer| {
+            !token.is_empty() && bool::from(bearer.as_bytes().ct_eq(token.as_bytes()))
+        });
+
+    if !authorized {
+        return respond(StatusCode::UNAUTHORIZED, Body::empty());
+    }
+
+    let path = &req.uri().path()[admin.path.len()..];
+    match (req.method(), path) {
+        (&Method::POST, PURGE_PATH) => purge(req, app_ctx).await,
+        _ => respond(StatusCode::NOT_FOUND, Body::empty()),
+    }
+}
+
+async fn purge(req: Request<Body>, app_ctx: &AppContext) -> Result<Response<Body>> {
+    let bytes = hyper::body::to_bytes(req.into_body()).await?;
+    let Some((tags, keys)) = parse_purge_request(&bytes) else {
+        return respond(StatusCode::BAD_REQUEST, Body::empty());
+    };
+
+    let cache = &app_ctx.runtime.cache;
+    let mut purged = cache.purge(&tags).await.map_err(|err| anyhow!("{}", err))?;
+    // the cached responses are purged with the values, but aren't counted
+    app_ctx
+        .response_cache
+        .purge(&tags)
+        .await
+        .map_err(|err| anyhow!("{}", err))?;
+    for key in keys {
+        if cache.delete(&key).await.map_err(|err| anyhow!("{}", err))? {
+            purged += 1;
+        }
+    }
+
+    tracing::info!("Purged {} cached values", purged);
+    let body = serde_json::to_vec(&PurgeResponse { purged })?;
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(body))?)
+}
+
+fn parse_purge_request(bytes: &[u8]) -> Option<(Vec<String>, Vec<IoId>)> {
+    let request: PurgeRequest = if bytes.is_empty() {
+        PurgeRequest::default()
+    } else {
+        serde_json::from_slice(bytes).ok()?
+    };
+    let keys = request
+        .keys
+        .iter()
+        .map(|key| u64::from_str_radix(key, 16).ok().map(IoId::new))
+        .collect::<Option<Vec<_>>>()?;
+
+    Some((request.tags, keys))
+}
+
+fn respond(status: StatusCode, body: Body) -> Result<Response<Body>> {
+    Ok(Response::builder().status(status).body(body)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::mustache::Mustache;
+
+    #[test]
+    fn test_is_admin_request() {
+        let admin = Admin {
+            path: "/admin".to_string(),
+            token: Mustache::parse("secret").unwrap(),
+        };
+        let request = |path: &str| Request::post(path).body(Body::empty()).unwrap();
+
+        assert!(is_admin_request(&request("/admin/cache/purge"), &admin));
+        assert!(is_admin_request(&request("/admin"), &admin));
+        assert!(!is_admin_request(&request("/administrator"), &admin));
+        assert!(!is_admin_request(&request("/graphql"), &admin));
+    }
+
+    #[test]
+    fn test_parse_purge_request() {
+        let (tags, keys) =
+            parse_purge_request(br#"{"tags": ["user:1"], "keys": ["00000000000000ff"]}"#).unwrap();
+        assert_eq!(tags, vec!["user:1".to_string()]);
+        assert_eq!(keys, vec![IoId::new(255)]);
+
+        assert!(parse_purge_request(b"").is_some());
+        assert!(parse_purge_request(br#"{"keys": ["not-hex"]}"#).is_none());
+        assert!(parse_purge_request(br#"{"tag": "user:1"}"#).is_none());
+    }
+}
@@ -3,22 +3,31 @@ pub use data_loader::*;
 pub use data_loader_request::*;
 use headers::HeaderValue;
 pub use method::Method;
+pub use persisted_query::SafelistExecutor;
 pub use query_encoder::QueryEncoder;
 pub use request_context::RequestContext;
-pub use request_handler::{handle_request, API_URL_PREFIX};
+pub use request_handler::{create_request_context, handle_request, API_URL_PREFIX};
 pub use request_template::RequestTemplate;
 pub use response::*;
+pub use retry::RetryHttp;
 
+mod admin;
 mod cache;
 mod data_loader;
 mod data_loader_request;
 mod method;
+pub mod oauth2;
+mod persisted_query;
 mod query_encoder;
 mod request_context;
 mod request_handler;
 mod request_template;
 mod response;
+mod response_cache;
+pub mod retry;
 pub mod showcase;
+pub mod signing;
+pub mod sse;
 mod telemetry;
 
 pub static TAILCALL_HTTPS_ORIGIN: HeaderValue = HeaderValue::from_static("https://tailcall.run");
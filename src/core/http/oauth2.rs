@@ -0,0 +1,289 @@
+use std::hash::{Hash, Hasher};
+use std::num::NonZeroU64;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use async_graphql::Value;
+use headers::{Authorization, HeaderMapExt};
+use reqwest::header::{HeaderValue, ACCEPT, CONTENT_TYPE};
+use serde::{Deserialize, Serialize};
+use tailcall_hasher::TailcallHasher;
+
+use super::RequestContext;
+use crate::core::blueprint::OAuth2;
+use crate::core::ir::model::IoId;
+use crate::core::ir::{Error, Revalidation};
+
+/// Lifetime in seconds of the tokens whose response has no `expires_in`.
+const DEFAULT_EXPIRES_IN: u64 = 3600;
+
+/// Response of the token endpoint, as described by RFC 6749.
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: Option<u64>,
+}
+
+/// Token that is kept in the cache of the runtime until it expires.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Token {
+    access_token: String,
+    /// Time of the expiry in milliseconds since the UNIX epoch.
+    expires_at: u64,
+}
+
+/// Returns the `Authorization` header with the bearer token of the client.
+pub async fn authorization(
+    req_ctx: &RequestContext,
+    oauth2: &OAuth2,
+) -> Result<HeaderValue, Error> {
+    let token = token(req_ctx, oauth2).await?;
+    HeaderValue::from_str(&format!("Bearer {}", token.access_token))
+        .map_err(|err| Error::IOException(format!("Invalid OAuth2 token: {}", err)))
+}
+
+/// Returns the cached token of the client, or fetches a new one when the
+/// cached token is missing or expired. A token that is about to expire is
+/// still served, while a new one is fetched in the background. Concurrent
+/// requests share the same fetch of the token.
+async fn token(req_ctx: &RequestContext, oauth2: &OAuth2) -> Result<Token, Error> {
+    let key = cache_key(oauth2);
+    // failures of the cache are ignored, as a new token can still be fetched
+    let cached = match req_ctx.runtime.cache.get(&key).await {
+        Ok(Some(value)) => from_value(value).ok(),
+        _ => None,
+    };
+
+    let now = now();
+    let refresh_before = oauth2.refresh_before.as_millis() as u64;
+    match cached {
+        Some(token) if now + refresh_before < token.expires_at => Ok(token),
+        Some(token) if now < token.expires_at => {
+            refresh(req_ctx, oauth2, key);
+            Ok(token)
+        }
+        _ => fetch_cached(req_ctx, oauth2, key).await,
+    }
+}
+
+/// Fetches a new token in the background. The requests that happen while the
+/// token is fetched don't start another fetch.
+fn refresh(req_ctx: &RequestContext, oauth2: &OAuth2, key: IoId) {
+    let Some(revalidation) = Revalidation::start(&req_ctx.revalidating, &key) else {
+        return;
+    };
+    let req_ctx = req_ctx.detach();
+    let oauth2 = oauth2.clone();
+    let task = async move {
+        let _revalidation = revalidation;
+        if let Err(err) = fetch_cached(&req_ctx, &oauth2, key).await {
+            // the cached token is still served until it expires
+            tracing::warn!("Failed to refresh the OAuth2 token: {}", err);
+        }
+    };
+
+    #[cfg(not(target_arch = "wasm32"))]
+    tokio::spawn(task);
+    #[cfg(target_arch = "wasm32")]
+    async_std::task::spawn_local(task);
+}
+
+/// Fetches a new token and caches it until it expires.
+async fn fetch_cached(
+    req_ctx: &RequestContext,
+    oauth2: &OAuth2,
+    key: IoId,
+) -> Result<Token, Error> {
+    req_ctx
+        .dedupe_handler
+        .dedupe(&key, || async {
+            let token = fetch(req_ctx, oauth2).await?;
+            let value = to_value(&token)?;
+
+            if let Some(ttl) = NonZeroU64::new(token.expires_at.saturating_sub(now())) {
+                if let Err(err) = req_ctx
+                    .runtime
+                    .cache
+                    .set(key.clone(), value.clone(), ttl)
+                    .await
+                {
+                    tracing::warn!("Failed to cache the OAuth2 token: {}", err);
+                }
+            }
+
+            Ok(value)
+        })
+        .await
+        .and_then(|value| Ok(from_value(value)?))
+}
+
+/// Fetches a new token from the token endpoint with the client-credentials
+/// grant.
+async fn fetch(req_ctx: &RequestContext, oauth2: &OAuth2) -> Result<Token, Error> {
+    let response = req_ctx.runtime.http.execute(request(oauth2)).await?;
+    let response: TokenResponse = serde_json::from_slice(&response.body)
+        .map_err(|err| Error::IOException(format!("Invalid OAuth2 token response: {}", err)))?;
+
+    let expires_in = response.expires_in.unwrap_or(DEFAULT_EXPIRES_IN);
+
+    Ok(Token {
+        access_token: response.access_token,
+        expires_at: now() + expires_in * 1000,
+    })
+}
+
+fn request(oauth2: &OAuth2) -> reqwest::Request {
+    let mut request = reqwest::Request::new(reqwest::Method::POST, oauth2.token_url.clone());
+    let headers = request.headers_mut();
+    headers.insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static("application/x-www-form-urlencoded"),
+    );
+    headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
+    headers.typed_insert(Authorization::basic(
+        &oauth2.client_id,
+        &oauth2.client_secret,
+    ));
+
+    let mut body = url::form_urlencoded::Serializer::new(String::new());
+    body.append_pair("grant_type", "client_credentials");
+    if !oauth2.scopes.is_empty() {
+        body.append_pair("scope", &oauth2.scopes.join(" "));
+    }
+    if let Some(audience) = &oauth2.audience {
+        body.append_pair("audience", audience);
+    }
+    *request.body_mut() = Some(body.finish().into());
+
+    request
+}
+
+fn to_value(token: &Token) -> anyhow::Result<Value> {
+    Ok(Value::from_json(serde_json::to_value(token)?)?)
+}
+
+fn from_value(value: Value) -> anyhow::Result<Token> {
+    Ok(serde_json::from_value(value.into_json()?)?)
+}
+
+fn cache_key(oauth2: &OAuth2) -> IoId {
+    let mut hasher = TailcallHasher::default();
+    oauth2.token_url.as_str().hash(&mut hasher);
+    oauth2.client_id.hash(&mut hasher);
+    oauth2.scopes.hash(&mut hasher);
+    oauth2.audience.hash(&mut hasher);
+    IoId::new(hasher.finish())
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use hyper::body::Bytes;
+    use serde_json::json;
+
+    use super::*;
+    use crate::core::http::Response;
+    use crate::core::{BodyStream, HttpIO};
+
+    #[derive(Default)]
+    struct TokenHttp {
+        hits: AtomicUsize,
+        fail: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl HttpIO for TokenHttp {
+        async fn execute(&self, request: reqwest::Request) -> anyhow::Result<Response<Bytes>> {
+            let hits = self.hits.fetch_add(1, Ordering::SeqCst) + 1;
+            if self.fail && hits > 1 {
+                anyhow::bail!("token endpoint is down");
+            }
+
+            let body = request.body().and_then(|body| body.as_bytes()).unwrap();
+            assert_eq!(body, b"grant_type=client_credentials&scope=read+write");
+
+            let body = json!({"access_token": format!("token-{}", hits), "expires_in": 60});
+            let body = Bytes::from(serde_json::to_vec(&body)?);
+            Ok(Response { body, ..Default::default() })
+        }
+
+        async fn execute_stream(
+            &self,
+            _: reqwest::Request,
+        ) -> anyhow::Result<Response<BodyStream>> {
+            unimplemented!()
+        }
+    }
+
+    fn oauth2(refresh_before: Duration) -> OAuth2 {
+        OAuth2 {
+            token_url: "http://localhost/token".parse().unwrap(),
+            client_id: "client".to_string(),
+            client_secret: "secret".to_string(),
+            scopes: vec!["read".to_string(), "write".to_string()],
+            audience: None,
+            refresh_before,
+        }
+    }
+
+    fn request_context(http: Arc<TokenHttp>) -> RequestContext {
+        let mut req_ctx = RequestContext::default();
+        req_ctx.runtime.http = http;
+        req_ctx
+    }
+
+    async fn bearer(req_ctx: &RequestContext, oauth2: &OAuth2) -> String {
+        let header = authorization(req_ctx, oauth2).await.unwrap();
+        header.to_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_cached_token() {
+        let http = Arc::new(TokenHttp::default());
+        let req_ctx = request_context(http.clone());
+        let oauth2 = oauth2(Duration::from_secs(30));
+
+        assert_eq!(bearer(&req_ctx, &oauth2).await, "Bearer token-1");
+        assert_eq!(bearer(&req_ctx, &oauth2).await, "Bearer token-1");
+        assert_eq!(http.hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_ahead_of_expiry() {
+        let http = Arc::new(TokenHttp::default());
+        let req_ctx = request_context(http.clone());
+        // the tokens are refreshed as soon as they are fetched
+        let oauth2 = oauth2(Duration::from_secs(60));
+
+        assert_eq!(bearer(&req_ctx, &oauth2).await, "Bearer token-1");
+        // the token is still served while the new one is fetched
+        assert_eq!(bearer(&req_ctx, &oauth2).await, "Bearer token-1");
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        assert_eq!(bearer(&req_ctx, &oauth2).await, "Bearer token-2");
+    }
+
+    #[tokio::test]
+    async fn test_failed_refresh() {
+        let http = Arc::new(TokenHttp { fail: true, ..Default::default() });
+        let req_ctx = request_context(http.clone());
+        let oauth2 = oauth2(Duration::from_secs(60));
+
+        assert_eq!(bearer(&req_ctx, &oauth2).await, "Bearer token-1");
+        assert_eq!(bearer(&req_ctx, &oauth2).await, "Bearer token-1");
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(http.hits.load(Ordering::SeqCst), 2);
+
+        // the token is still valid when it can't be refreshed
+        assert_eq!(bearer(&req_ctx, &oauth2).await, "Bearer token-1");
+    }
+}
@@ -0,0 +1,160 @@
+use std::future::{ready, Future};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use async_graphql::{Data, ErrorExtensionValues, Executor, Response, ServerError};
+use async_graphql_value::ConstValue;
+use futures_util::stream::{self, BoxStream};
+use futures_util::StreamExt;
+use serde::Deserialize;
+use tailcall_hasher::TailcallHasher;
+
+use crate::core::app_context::AppContext;
+use crate::core::blueprint::{sha256, PersistedQueries, Safelist};
+use crate::core::ir::model::IoId;
+
+/// The `persistedQuery` extension of a request sent with the Automatic
+/// Persisted Queries protocol.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PersistedQuery {
+    sha256_hash: String,
+}
+
+fn error(message: &str, code: &str) -> ServerError {
+    let mut extensions = ErrorExtensionValues::default();
+    extensions.set("code", code);
+
+    let mut error = ServerError::new(message, None);
+    error.extensions = Some(extensions);
+    error
+}
+
+fn cache_key(hash: &str) -> IoId {
+    let mut hasher = TailcallHasher::default();
+    "persistedQuery".hash(&mut hasher);
+    hash.hash(&mut hasher);
+    IoId::new(hasher.finish())
+}
+
+fn persisted_query_hash(request: &async_graphql::Request) -> Option<String> {
+    request
+        .extensions
+        .get("persistedQuery")
+        .and_then(|value| async_graphql::from_value::<PersistedQuery>(value.clone()).ok())
+        .map(|persisted_query| persisted_query.sha256_hash)
+}
+
+/// Replaces the hash of a persisted document with the document of the
+/// safelist, and rejects the queries that aren't part of the safelist.
+fn resolve_safelisted(
+    safelist: &Safelist,
+    request: &mut async_graphql::Request,
+) -> Result<(), ServerError> {
+    match persisted_query_hash(request) {
+        Some(hash) if request.query.is_empty() => {
+            let query = safelist
+                .get(&hash)
+                .ok_or_else(|| error("PersistedQueryNotFound", "PERSISTED_QUERY_NOT_FOUND"))?;
+            request.query = query.clone();
+            Ok(())
+        }
+        _ if safelist.contains(&request.query) => Ok(()),
+        _ => Err(error(
+            "Operation is not in the safelist",
+            "OPERATION_NOT_IN_SAFELIST",
+        )),
+    }
+}
+
+/// Replaces the hash of a persisted query with the query it was registered
+/// with, and registers the queries sent along with their hash. With a safelist,
+/// only the documents of the safelist can be executed.
+pub async fn resolve(
+    app_ctx: &AppContext,
+    persisted_queries: &PersistedQueries,
+    request: &mut async_graphql::Request,
+) -> Result<(), ServerError> {
+    if let Some(safelist) = &persisted_queries.safelist {
+        return resolve_safelisted(safelist, request);
+    }
+
+    match persisted_query_hash(request) {
+        Some(hash) if request.query.is_empty() => {
+            let query = app_ctx
+                .runtime
+                .cache
+                .get(&cache_key(&hash))
+                .await
+                .ok()
+                .flatten();
+            match query {
+                Some(ConstValue::String(query)) => request.query = query,
+                _ => return Err(error("PersistedQueryNotFound", "PERSISTED_QUERY_NOT_FOUND")),
+            }
+        }
+        Some(hash) => {
+            if sha256(&request.query) != hash {
+                return Err(error(
+                    "provided sha does not match query",
+                    "PERSISTED_QUERY_HASH_MISMATCH",
+                ));
+            }
+
+            let query = ConstValue::String(request.query.clone());
+            if let Err(err) = app_ctx
+                .runtime
+                .cache
+                .set(cache_key(&hash), query, persisted_queries.ttl)
+                .await
+            {
+                tracing::warn!("Failed to persist the query {}: {}", hash, err);
+            }
+        }
+        None => {}
+    }
+
+    Ok(())
+}
+
+/// Executes only the operations of the safelist, for the requests that don't
+/// go through the request handler e.g. the subscriptions over WebSocket.
+#[derive(Clone)]
+pub struct SafelistExecutor<E> {
+    executor: E,
+    safelist: Safelist,
+}
+
+impl<E> SafelistExecutor<E> {
+    pub fn new(executor: E, safelist: Safelist) -> Self {
+        Self { executor, safelist }
+    }
+}
+
+impl<E: Executor> Executor for SafelistExecutor<E> {
+    fn execute(
+        &self,
+        mut request: async_graphql::Request,
+    ) -> impl Future<Output = Response> + Send {
+        let resolved = resolve_safelisted(&self.safelist, &mut request);
+        let executor = self.executor.clone();
+
+        async move {
+            match resolved {
+                Ok(()) => executor.execute(request).await,
+                Err(err) => Response::from_errors(vec![err]),
+            }
+        }
+    }
+
+    fn execute_stream(
+        &self,
+        mut request: async_graphql::Request,
+        session_data: Option<Arc<Data>>,
+    ) -> BoxStream<'static, Response> {
+        match resolve_safelisted(&self.safelist, &mut request) {
+            Ok(()) => self.executor.execute_stream(request, session_data),
+            Err(err) => stream::once(ready(Response::from_errors(vec![err]))).boxed(),
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+use std::net::IpAddr;
 use std::num::NonZeroU64;
 use std::str::FromStr;
 use std::sync::{Arc, Mutex};
@@ -5,15 +7,18 @@ use std::sync::{Arc, Mutex};
 use async_graphql_value::ConstValue;
 use cache_control::{Cachability, CacheControl};
 use derive_setters::Setters;
+use hyper::body::Bytes;
 use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use rustls_pki_types::CertificateDer;
 
 use crate::core::app_context::AppContext;
+use crate::core::async_graphql_hyper::OperationId;
 use crate::core::auth::context::AuthContext;
 use crate::core::blueprint::{Server, Upstream};
 use crate::core::data_loader::{DataLoader, DedupeResult};
-use crate::core::graphql::GraphqlDataLoader;
+use crate::core::graphql::{EntityDataLoader, EntityRequest, GraphqlDataLoader};
 use crate::core::grpc::data_loader::GrpcDataLoader;
-use crate::core::http::{DataLoaderRequest, HttpDataLoader};
+use crate::core::http::{DataLoaderRequest, HttpDataLoader, Response};
 use crate::core::ir::model::IoId;
 use crate::core::ir::Error;
 use crate::core::runtime::TargetRuntime;
@@ -28,15 +33,32 @@ pub struct RequestContext {
     // A subset of all the headers received in the GraphQL Request that will be sent to the
     // upstream.
     pub allowed_headers: HeaderMap,
+    // All the headers received in the request, used to identify the client.
+    pub request_headers: HeaderMap,
+    // The URI of the request, used to identify the client e.g. by an API key in its query.
+    #[setters(strip_option)]
+    pub request_uri: Option<hyper::Uri>,
+    #[setters(strip_option)]
+    pub client_ip: Option<IpAddr>,
+    // The certificate of the client, that was verified by the TLS handshake.
+    #[setters(strip_option)]
+    pub client_cert: Option<CertificateDer<'static>>,
     pub auth_ctx: AuthContext,
     pub http_data_loaders: Arc<Vec<DataLoader<DataLoaderRequest, HttpDataLoader>>>,
     pub gql_data_loaders: Arc<Vec<DataLoader<DataLoaderRequest, GraphqlDataLoader>>>,
+    pub entity_data_loaders: Arc<Vec<DataLoader<EntityRequest, EntityDataLoader>>>,
     pub grpc_data_loaders: Arc<Vec<DataLoader<grpc::DataLoaderRequest, GrpcDataLoader>>>,
     pub min_max_age: Arc<Mutex<Option<i32>>>,
     pub cache_public: Arc<Mutex<Option<bool>>>,
     pub runtime: TargetRuntime,
     pub cache: DedupeResult<IoId, ConstValue, Error>,
     pub dedupe_handler: Arc<DedupeResult<IoId, ConstValue, Error>>,
+    // The keys of the stale values that are being refreshed in the background.
+    pub revalidating: Arc<Mutex<HashSet<IoId>>>,
+    pub response_cache: Arc<cache::InMemoryCache<OperationId, Response<Bytes>>>,
+    // The tags of the `@cache` fields resolved by the request, that its cached response is
+    // tagged with.
+    pub cache_tags: Arc<Mutex<HashSet<String>>>,
 }
 
 impl RequestContext {
@@ -48,16 +70,55 @@ impl RequestContext {
             cookie_headers: None,
             http_data_loaders: Arc::new(vec![]),
             gql_data_loaders: Arc::new(vec![]),
+            entity_data_loaders: Arc::new(vec![]),
             grpc_data_loaders: Arc::new(vec![]),
             min_max_age: Arc::new(Mutex::new(None)),
             cache_public: Arc::new(Mutex::new(None)),
             runtime: target_runtime,
             cache: DedupeResult::new(true),
             dedupe_handler: Arc::new(DedupeResult::new(false)),
+            revalidating: Arc::new(Mutex::new(HashSet::new())),
+            response_cache: Default::default(),
+            cache_tags: Default::default(),
             allowed_headers: HeaderMap::new(),
+            request_headers: HeaderMap::new(),
+            request_uri: None,
+            client_ip: None,
+            client_cert: None,
             auth_ctx: AuthContext::default(),
         }
     }
+
+    /// Copies the context for a task that outlives the request. The headers
+    /// and the cache control of the responses of the task aren't sent to the
+    /// client.
+    pub fn detach(&self) -> RequestContext {
+        RequestContext {
+            server: self.server.clone(),
+            upstream: self.upstream.clone(),
+            x_response_headers: Arc::new(Mutex::new(HeaderMap::new())),
+            cookie_headers: None,
+            allowed_headers: self.allowed_headers.clone(),
+            request_headers: self.request_headers.clone(),
+            request_uri: self.request_uri.clone(),
+            client_ip: self.client_ip,
+            client_cert: self.client_cert.clone(),
+            auth_ctx: self.auth_ctx.clone(),
+            http_data_loaders: self.http_data_loaders.clone(),
+            gql_data_loaders: self.gql_data_loaders.clone(),
+            entity_data_loaders: self.entity_data_loaders.clone(),
+            grpc_data_loaders: self.grpc_data_loaders.clone(),
+            min_max_age: Arc::new(Mutex::new(None)),
+            cache_public: Arc::new(Mutex::new(None)),
+            runtime: self.runtime.clone(),
+            cache: DedupeResult::new(true),
+            dedupe_handler: self.dedupe_handler.clone(),
+            revalidating: self.revalidating.clone(),
+            response_cache: self.response_cache.clone(),
+            cache_tags: Default::default(),
+        }
+    }
+
     fn set_min_max_age_conc(&self, min_max_age: i32) {
         *self.min_max_age.lock().unwrap() = Some(min_max_age);
     }
@@ -196,15 +257,23 @@ impl From<&AppContext> for RequestContext {
             x_response_headers: Arc::new(Mutex::new(HeaderMap::new())),
             cookie_headers,
             allowed_headers: HeaderMap::new(),
+            request_headers: HeaderMap::new(),
+            request_uri: None,
+            client_ip: None,
+            client_cert: None,
             auth_ctx: (&app_ctx.auth_ctx).into(),
             http_data_loaders: app_ctx.http_data_loaders.clone(),
             gql_data_loaders: app_ctx.gql_data_loaders.clone(),
+            entity_data_loaders: app_ctx.entity_data_loaders.clone(),
             grpc_data_loaders: app_ctx.grpc_data_loaders.clone(),
             min_max_age: Arc::new(Mutex::new(None)),
             cache_public: Arc::new(Mutex::new(None)),
             runtime: app_ctx.runtime.clone(),
             cache: DedupeResult::new(true),
             dedupe_handler: app_ctx.dedupe_handler.clone(),
+            revalidating: app_ctx.revalidating.clone(),
+            response_cache: app_ctx.response_cache.clone(),
+            cache_tags: Default::default(),
         }
     }
 }
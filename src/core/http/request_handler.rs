@@ -1,27 +1,35 @@
 use std::collections::BTreeSet;
+use std::net::SocketAddr;
 use std::ops::Deref;
 use std::sync::Arc;
 
 use anyhow::Result;
-use async_graphql::ServerError;
+use async_graphql::{ErrorExtensions, ServerError};
+use hyper::body::Bytes;
 use hyper::header::{self, HeaderValue, CONTENT_TYPE};
 use hyper::http::Method;
 use hyper::{Body, HeaderMap, Request, Response, StatusCode};
 use opentelemetry::trace::SpanKind;
 use opentelemetry_semantic_conventions::trace::{HTTP_REQUEST_METHOD, HTTP_ROUTE};
 use prometheus::{Encoder, ProtobufEncoder, TextEncoder, TEXT_FORMAT};
+use rustls_pki_types::CertificateDer;
 use serde::de::DeserializeOwned;
 use tracing::Instrument;
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 
 use super::request_context::RequestContext;
+use super::sse::TEXT_EVENT_STREAM;
 use super::telemetry::{get_response_status_code, RequestCounter};
-use super::{showcase, telemetry, TAILCALL_HTTPS_ORIGIN, TAILCALL_HTTP_ORIGIN};
+use super::{
+    admin, persisted_query, response_cache, showcase, telemetry, TAILCALL_HTTPS_ORIGIN,
+    TAILCALL_HTTP_ORIGIN,
+};
 use crate::core::app_context::AppContext;
-use crate::core::async_graphql_hyper::{GraphQLRequestLike, GraphQLResponse};
+use crate::core::async_graphql_hyper::{GraphQLRequestLike, GraphQLResponse, OperationId};
 use crate::core::blueprint::telemetry::TelemetryExporter;
 use crate::core::config::{PrometheusExporter, PrometheusFormat};
 use crate::core::jit::JITExecutor;
+use crate::core::{ir, rate_limit};
 
 pub const API_URL_PREFIX: &str = "/api";
 
@@ -53,13 +61,26 @@ fn not_found() -> Result<Response<Body>> {
         .body(Body::empty())?)
 }
 
-fn create_request_context(req: &Request<Body>, app_ctx: &AppContext) -> RequestContext {
+pub fn create_request_context(req: &Request<Body>, app_ctx: &AppContext) -> RequestContext {
     let upstream = app_ctx.blueprint.upstream.clone();
     let allowed = upstream.allowed_headers;
     let allowed_headers = create_allowed_headers(req.headers(), &allowed);
 
     let _allowed = app_ctx.blueprint.server.get_experimental_headers();
-    RequestContext::from(app_ctx).allowed_headers(allowed_headers)
+    let req_ctx = RequestContext::from(app_ctx)
+        .allowed_headers(allowed_headers)
+        .request_headers(req.headers().clone())
+        .request_uri(req.uri().clone());
+
+    let req_ctx = match req.extensions().get::<SocketAddr>() {
+        Some(addr) => req_ctx.client_ip(addr.ip()),
+        None => req_ctx,
+    };
+
+    match req.extensions().get::<CertificateDer<'static>>() {
+        Some(cert) => req_ctx.client_cert(cert.clone()),
+        None => req_ctx,
+    }
 }
 
 fn update_cache_control_header(
@@ -104,28 +125,59 @@ pub async fn graphql_request<T: DeserializeOwned + GraphQLRequestLike>(
 ) -> Result<Response<Body>> {
     req_counter.set_http_route("/graphql");
     let req_ctx = Arc::new(create_request_context(&req, app_ctx));
+    if let Some(response) = check_rate_limit(app_ctx, &req_ctx).await? {
+        return Ok(response);
+    }
     let (req, body) = req.into_parts();
     let bytes = hyper::body::to_bytes(body).await?;
-    let graphql_request = serde_json::from_slice::<T>(&bytes);
-    match graphql_request {
-        Ok(mut request) => {
-            if !(app_ctx.blueprint.server.dedupe && request.is_query()) {
-                Ok(execute_query(app_ctx, &req_ctx, request).await?)
-            } else {
+    let mut graphql_request = serde_json::from_slice::<T>(&bytes);
+    if let (Ok(request), Some(persisted_queries)) = (
+        graphql_request.as_mut(),
+        &app_ctx.blueprint.server.persisted_queries,
+    ) {
+        for request in request.requests_mut() {
+            if let Err(err) = persisted_query::resolve(app_ctx, persisted_queries, request).await {
+                let response = async_graphql::Response::from_errors(vec![err]);
+                return GraphQLResponse::from(response).into_response();
+            }
+        }
+    }
+    match (graphql_request, stream_format(&req.headers)) {
+        (Ok(request), Some(format)) => Ok(execute_stream(app_ctx, &req_ctx, request, format)?),
+        (Ok(mut request), None) => {
+            let server = &app_ctx.blueprint.server;
+            let is_query = (server.dedupe || server.response_cache.is_some()) && request.is_query();
+            let cache_key = match &server.response_cache {
+                Some(response_cache) if is_query => {
+                    response_cache::key(response_cache, &mut request, &req_ctx).await
+                }
+                _ => None,
+            };
+            if let Some(cache_key) = &cache_key {
+                if let Some(out) = response_cache::get(app_ctx, cache_key).await {
+                    return Ok(hyper::Response::from(out));
+                }
+            }
+
+            if server.dedupe && is_query {
                 let operation_id = request.operation_id(&req.headers);
                 let out = app_ctx
                     .dedupe_operation_handler
                     .dedupe(&operation_id, || {
                         Box::pin(async move {
-                            let resp = execute_query(app_ctx, &req_ctx, request).await?;
-                            Ok(crate::core::http::Response::from_hyper(resp).await?)
+                            execute_cached_query(app_ctx, &req_ctx, request, cache_key).await
                         })
                     })
                     .await?;
                 Ok(hyper::Response::from(out))
+            } else if cache_key.is_some() {
+                let out = execute_cached_query(app_ctx, &req_ctx, request, cache_key).await?;
+                Ok(hyper::Response::from(out))
+            } else {
+                Ok(execute_query(app_ctx, &req_ctx, request).await?)
             }
         }
-        Err(err) => {
+        (Err(err), _) => {
             tracing::error!(
                 "Failed to parse request: {}",
                 String::from_utf8(bytes.to_vec()).unwrap()
@@ -141,25 +193,131 @@ pub async fn graphql_request<T: DeserializeOwned + GraphQLRequestLike>(
     }
 }
 
-async fn execute_query<T: DeserializeOwned + GraphQLRequestLike>(
+/// Takes a token from the bucket of the client when the server is rate limited,
+/// and returns the response to send when the client is over the limit.
+async fn check_rate_limit(
+    app_ctx: &AppContext,
+    req_ctx: &RequestContext,
+) -> Result<Option<Response<Body>>> {
+    let Some(limit) = &app_ctx.blueprint.server.rate_limit else {
+        return Ok(None);
+    };
+    if rate_limit::acquire(req_ctx, "server", limit).await {
+        return Ok(None);
+    }
+
+    let error = ir::Error::RateLimitError("too many requests".to_string()).extend();
+    let mut server_error = ServerError::new(error.message, None);
+    server_error.extensions = error.extensions;
+
+    let response = async_graphql::Response::from_errors(vec![server_error]);
+    let mut response = GraphQLResponse::from(response).into_response()?;
+    *response.status_mut() = StatusCode::TOO_MANY_REQUESTS;
+    Ok(Some(response))
+}
+
+async fn execute<T: DeserializeOwned + GraphQLRequestLike>(
     app_ctx: &Arc<AppContext>,
     req_ctx: &Arc<RequestContext>,
     request: T,
-) -> anyhow::Result<Response<Body>> {
-    let mut response = if app_ctx.blueprint.server.enable_jit {
+) -> GraphQLResponse {
+    let response = if app_ctx.blueprint.server.enable_jit {
         request
             .execute(&JITExecutor::new(app_ctx.clone(), req_ctx.clone()))
             .await
     } else {
         request.data(req_ctx.clone()).execute(&app_ctx.schema).await
     };
-    response = update_cache_control_header(response, app_ctx, req_ctx.clone());
+    update_cache_control_header(response, app_ctx, req_ctx.clone())
+}
+
+async fn execute_query<T: DeserializeOwned + GraphQLRequestLike>(
+    app_ctx: &Arc<AppContext>,
+    req_ctx: &Arc<RequestContext>,
+    request: T,
+) -> anyhow::Result<Response<Body>> {
+    let response = execute(app_ctx, req_ctx, request).await;
 
     let mut resp = response.into_response()?;
     update_response_headers(&mut resp, req_ctx, app_ctx);
     Ok(resp)
 }
 
+/// Executes a query and caches its response when it has no errors, so that
+/// the identical queries are answered without being executed.
+async fn execute_cached_query<T: DeserializeOwned + GraphQLRequestLike>(
+    app_ctx: &Arc<AppContext>,
+    req_ctx: &Arc<RequestContext>,
+    request: T,
+    cache_key: Option<OperationId>,
+) -> Result<crate::core::http::Response<Bytes>, ir::Error> {
+    let response = execute(app_ctx, req_ctx, request).await;
+    let is_ok = response.0.is_ok();
+
+    let mut resp = response.into_response()?;
+    update_response_headers(&mut resp, req_ctx, app_ctx);
+    let resp = crate::core::http::Response::from_hyper(resp).await?;
+
+    if let (Some(cache_key), Some(response_cache), true) =
+        (cache_key, &app_ctx.blueprint.server.response_cache, is_ok)
+    {
+        response_cache::set(app_ctx, response_cache, req_ctx, cache_key, &resp).await;
+    }
+    Ok(resp)
+}
+
+/// Formats in which the response can be streamed to the client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StreamFormat {
+    /// Server-Sent Events as described by the "GraphQL over SSE" protocol.
+    EventStream,
+    /// `multipart/mixed` response used for incremental delivery.
+    Multipart,
+}
+
+/// Finds the streaming format the client asked for with the `Accept` header,
+/// preferring `multipart/mixed` when both are accepted.
+fn stream_format(headers: &HeaderMap) -> Option<StreamFormat> {
+    let accepts = |expected: &str| {
+        headers
+            .get_all(header::ACCEPT)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .any(|mime| mime.trim().starts_with(expected))
+    };
+
+    if accepts("multipart/mixed") {
+        Some(StreamFormat::Multipart)
+    } else if accepts(TEXT_EVENT_STREAM) {
+        Some(StreamFormat::EventStream)
+    } else {
+        None
+    }
+}
+
+fn execute_stream<T: DeserializeOwned + GraphQLRequestLike>(
+    app_ctx: &Arc<AppContext>,
+    req_ctx: &Arc<RequestContext>,
+    request: T,
+    format: StreamFormat,
+) -> anyhow::Result<Response<Body>> {
+    let response = if app_ctx.blueprint.server.enable_jit {
+        request.execute_incremental(&JITExecutor::new(app_ctx.clone(), req_ctx.clone()))
+    } else {
+        request
+            .data(req_ctx.clone())
+            .execute_stream(&app_ctx.schema)
+    };
+
+    let mut resp = match format {
+        StreamFormat::EventStream => response.into_sse_response()?,
+        StreamFormat::Multipart => response.into_multipart_response()?,
+    };
+    update_response_headers(&mut resp, req_ctx, app_ctx);
+    Ok(resp)
+}
+
 fn create_allowed_headers(headers: &HeaderMap, allowed: &BTreeSet<String>) -> HeaderMap {
     let mut new_headers = HeaderMap::new();
     for (k, v) in headers.iter() {
@@ -265,6 +423,9 @@ async fn handle_rest_apis(
 ) -> Result<Response<Body>> {
     *request.uri_mut() = request.uri().path().replace(API_URL_PREFIX, "").parse()?;
     let req_ctx = Arc::new(create_request_context(&request, app_ctx.as_ref()));
+    if let Some(response) = check_rate_limit(&app_ctx, &req_ctx).await? {
+        return Ok(response);
+    }
     if let Some(p_request) = app_ctx.endpoints.matches(&request) {
         let http_route = format!("{API_URL_PREFIX}{}", p_request.path.as_str());
         req_counter.set_http_route(&http_route);
@@ -298,6 +459,12 @@ async fn handle_request_inner<T: DeserializeOwned + GraphQLRequestLike>(
     app_ctx: Arc<AppContext>,
     req_counter: &mut RequestCounter,
 ) -> Result<Response<Body>> {
+    if let Some(admin) = &app_ctx.blueprint.server.admin {
+        if admin::is_admin_request(&req, admin) {
+            return admin::handle(req, &app_ctx, admin).await;
+        }
+    }
+
     if req.uri().path().starts_with(API_URL_PREFIX) {
         return handle_rest_apis(req, app_ctx, req_counter).await;
     }
@@ -397,4 +564,29 @@ mod test {
         assert_eq!(new_headers.get("x-foo").unwrap(), "bar");
         assert_eq!(new_headers.get("x-bar").unwrap(), "foo");
     }
+
+    #[test]
+    fn test_stream_format() {
+        use hyper::header::{HeaderMap, HeaderValue, ACCEPT};
+
+        use super::{stream_format, StreamFormat};
+
+        let mut headers = HeaderMap::new();
+        assert_eq!(stream_format(&headers), None);
+
+        headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
+        assert_eq!(stream_format(&headers), None);
+
+        headers.insert(
+            ACCEPT,
+            HeaderValue::from_static("application/json, text/event-stream; charset=utf-8"),
+        );
+        assert_eq!(stream_format(&headers), Some(StreamFormat::EventStream));
+
+        headers.insert(
+            ACCEPT,
+            HeaderValue::from_static("multipart/mixed; deferSpec=20220824, application/json"),
+        );
+        assert_eq!(stream_format(&headers), Some(StreamFormat::Multipart));
+    }
 }
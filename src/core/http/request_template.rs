@@ -3,11 +3,13 @@ use std::hash::{Hash, Hasher};
 
 use derive_setters::Setters;
 use hyper::HeaderMap;
-use reqwest::header::HeaderValue;
+use reqwest::header::{HeaderValue, AUTHORIZATION};
 use tailcall_hasher::TailcallHasher;
 use url::Url;
 
 use super::query_encoder::QueryEncoder;
+use super::signing;
+use crate::core::blueprint::Signing;
 use crate::core::config::Encoding;
 use crate::core::endpoint::Endpoint;
 use crate::core::has_headers::HasHeaders;
@@ -30,6 +32,7 @@ pub struct RequestTemplate {
     pub endpoint: Endpoint,
     pub encoding: Encoding,
     pub query_encoder: QueryEncoder,
+    pub signing: Option<Signing>,
 }
 
 impl RequestTemplate {
@@ -100,6 +103,17 @@ impl RequestTemplate {
     pub fn to_request<C: PathString + HasHeaders + PathValue>(
         &self,
         ctx: &C,
+    ) -> anyhow::Result<reqwest::Request> {
+        self.to_authorized_request(ctx, None)
+    }
+
+    /// Creates a Request for the given context with the `Authorization`
+    /// header, that is set before the request is signed so that it can be
+    /// part of the signed headers.
+    pub fn to_authorized_request<C: PathString + HasHeaders + PathValue>(
+        &self,
+        ctx: &C,
+        authorization: Option<HeaderValue>,
     ) -> anyhow::Result<reqwest::Request> {
         // Create url
         let url = self.create_url(ctx)?;
@@ -107,6 +121,12 @@ impl RequestTemplate {
         let mut req = reqwest::Request::new(method, url);
         req = self.set_headers(req, ctx);
         req = self.set_body(req, ctx)?;
+        if let Some(authorization) = authorization {
+            req.headers_mut().insert(AUTHORIZATION, authorization);
+        }
+        if let Some(signing) = &self.signing {
+            signing::sign(signing, &mut req, ctx)?;
+        }
 
         Ok(req)
     }
@@ -179,6 +199,7 @@ impl RequestTemplate {
             endpoint: Endpoint::new(root_url.to_string()),
             encoding: Default::default(),
             query_encoder: Default::default(),
+            signing: Default::default(),
         })
     }
 
@@ -225,6 +246,7 @@ impl TryFrom<Endpoint> for RequestTemplate {
             endpoint,
             encoding,
             query_encoder: Default::default(),
+            signing: Default::default(),
         })
     }
 }
@@ -293,9 +315,12 @@ mod tests {
     use hyper::header::HeaderName;
     use hyper::HeaderMap;
     use pretty_assertions::assert_eq;
+    use reqwest::header::{HeaderValue, AUTHORIZATION};
     use serde_json::json;
 
     use super::RequestTemplate;
+    use crate::core::blueprint::{Hmac, Signing};
+    use crate::core::config::HmacAlgorithm;
     use crate::core::has_headers::HasHeaders;
     use crate::core::json::JsonLike;
     use crate::core::mustache::Mustache;
@@ -608,6 +633,30 @@ mod tests {
         assert_eq!(body, "baz");
     }
 
+    #[test]
+    fn test_authorization_signed() {
+        let hmac = Hmac {
+            secret: Mustache::parse("secret").unwrap(),
+            algorithm: HmacAlgorithm::Sha256,
+            header: "x-signature".parse().unwrap(),
+            signed_headers: vec![AUTHORIZATION],
+            timestamp_header: None,
+        };
+        let tmpl = RequestTemplate::new("http://localhost:3000")
+            .unwrap()
+            .signing(Some(Signing::Hmac(hmac)));
+        let ctx = Context::default();
+        let signature = |token: &'static str| {
+            let authorization = Some(HeaderValue::from_static(token));
+            let req = tmpl.to_authorized_request(&ctx, authorization).unwrap();
+            assert_eq!(req.headers()[AUTHORIZATION], token);
+            req.headers()["x-signature"].clone()
+        };
+
+        assert_eq!(signature("Bearer a"), signature("Bearer a"));
+        assert_ne!(signature("Bearer a"), signature("Bearer b"));
+    }
+
     mod endpoint {
         use hyper::HeaderMap;
         use serde_json::json;
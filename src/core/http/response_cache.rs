@@ -0,0 +1,230 @@
+use std::hash::{Hash, Hasher};
+use std::num::NonZeroU64;
+
+use hyper::body::Bytes;
+use hyper::header::{HeaderName, AUTHORIZATION, COOKIE, PROXY_AUTHORIZATION, SET_COOKIE};
+use tailcall_hasher::TailcallHasher;
+
+use super::{RequestContext, Response};
+use crate::core::app_context::AppContext;
+use crate::core::async_graphql_hyper::{GraphQLRequestLike, OperationId};
+use crate::core::auth::verification::Verification;
+use crate::core::blueprint::{normalize, ResponseCache};
+use crate::core::Cache;
+
+/// Headers with which the clients send their credentials.
+const CREDENTIALS: [HeaderName; 3] = [AUTHORIZATION, PROXY_AUTHORIZATION, COOKIE];
+
+/// Identifies the response of a request by the normalized documents and the
+/// variables of its operations, along with the headers and the claims of the
+/// client that the responses vary by. The responses of the clients that send
+/// credentials, or that the server verifies, are only identified once the
+/// client is verified and by its claims, so that they're never served to
+/// another client.
+pub async fn key<T: GraphQLRequestLike>(
+    response_cache: &ResponseCache,
+    request: &mut T,
+    req_ctx: &RequestContext,
+) -> Option<OperationId> {
+    if is_authenticated(req_ctx) {
+        if response_cache.claims.is_empty() || req_ctx.server.auth.is_none() {
+            return None;
+        }
+        let Verification::Succeed(_) = req_ctx.auth_ctx.validate(req_ctx).await else {
+            return None;
+        };
+    }
+
+    let mut hasher = TailcallHasher::default();
+    for request in request.requests_mut() {
+        normalize(&request.query).hash(&mut hasher);
+        request.operation_name.hash(&mut hasher);
+
+        let mut variables = request.variables.iter().collect::<Vec<_>>();
+        variables.sort_by(|(a, _), (b, _)| a.cmp(b));
+        for (name, value) in variables {
+            name.hash(&mut hasher);
+            value.to_string().hash(&mut hasher);
+        }
+    }
+
+    for name in &response_cache.headers {
+        name.hash(&mut hasher);
+        for value in req_ctx.request_headers.get_all(name) {
+            value.hash(&mut hasher);
+        }
+    }
+
+    if !response_cache.claims.is_empty() {
+        req_ctx.auth_ctx.validate(req_ctx).await;
+    }
+    let claims = req_ctx.auth_ctx.claims();
+    for name in &response_cache.claims {
+        name.hash(&mut hasher);
+        let claim = claims.as_ref().and_then(|claims| claims.get(name));
+        claim.map(|claim| claim.to_string()).hash(&mut hasher);
+    }
+
+    Some(OperationId::new(hasher.finish()))
+}
+
+/// Whether the response can depend on the identity of the client.
+fn is_authenticated(req_ctx: &RequestContext) -> bool {
+    req_ctx.server.auth.is_some()
+        || req_ctx.client_cert.is_some()
+        || CREDENTIALS
+            .iter()
+            .any(|name| req_ctx.request_headers.contains_key(name))
+}
+
+pub async fn get(app_ctx: &AppContext, key: &OperationId) -> Option<Response<Bytes>> {
+    match app_ctx.response_cache.get(key).await {
+        Ok(response) => response,
+        Err(err) => {
+            tracing::warn!("Failed to read the response cache: {}", err);
+            None
+        }
+    }
+}
+
+/// Caches the response of a query, unless it sets cookies or the upstreams
+/// don't allow it to be cached.
+pub async fn set(
+    app_ctx: &AppContext,
+    response_cache: &ResponseCache,
+    req_ctx: &RequestContext,
+    key: OperationId,
+    response: &Response<Bytes>,
+) {
+    if response.headers.contains_key(SET_COOKIE) {
+        return;
+    }
+    let Some(ttl) = ttl(response_cache, req_ctx) else {
+        return;
+    };
+
+    let response_cache = &app_ctx.response_cache;
+    if let Err(err) = response_cache.set(key.clone(), response.clone(), ttl).await {
+        tracing::warn!("Failed to write the response cache: {}", err);
+        return;
+    }
+
+    // the response is purged along with the values of its `@cache` fields
+    let tags = req_ctx
+        .cache_tags
+        .lock()
+        .unwrap()
+        .iter()
+        .cloned()
+        .collect::<Vec<_>>();
+    if tags.is_empty() {
+        return;
+    }
+    if let Err(err) = response_cache.tag(&key, &tags, ttl).await {
+        tracing::warn!("Failed to tag the response cache: {}", err);
+    }
+}
+
+/// Returns the time in milliseconds for which the response is cached, that is
+/// capped by the least `max-age` of the upstreams. The `private` responses are
+/// only cached when the key holds the claims of the verified client.
+fn ttl(response_cache: &ResponseCache, req_ctx: &RequestContext) -> Option<NonZeroU64> {
+    let is_verified = !response_cache.claims.is_empty()
+        && req_ctx.server.auth.is_some()
+        && req_ctx.auth_ctx.claims().is_some();
+    if req_ctx.is_cache_public() == Some(false) && !is_verified {
+        return None;
+    }
+
+    match req_ctx.get_min_max_age() {
+        Some(max_age) => NonZeroU64::new((max_age.max(0) as u64).saturating_mul(1000))
+            .map(|max_age| max_age.min(response_cache.max_age)),
+        None => Some(response_cache.max_age),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::header::HeaderName;
+
+    use super::*;
+    use crate::core::async_graphql_hyper::GraphQLRequest;
+    use crate::core::runtime::test::init;
+
+    fn response_cache(headers: &[&'static str]) -> ResponseCache {
+        ResponseCache {
+            max_age: NonZeroU64::new(60_000).unwrap(),
+            headers: headers
+                .iter()
+                .map(|name| HeaderName::from_static(name))
+                .collect(),
+            claims: vec![],
+        }
+    }
+
+    fn request(query: &str) -> GraphQLRequest {
+        GraphQLRequest(async_graphql::Request::new(query))
+    }
+
+    #[tokio::test]
+    async fn test_key_ignores_formatting() {
+        let response_cache = response_cache(&[]);
+        let req_ctx = RequestContext::new(init(None));
+
+        let actual = key(&response_cache, &mut request("{ user { id } }"), &req_ctx).await;
+        let expected = key(
+            &response_cache,
+            &mut request("{\n  user {\n    id\n  }\n}"),
+            &req_ctx,
+        )
+        .await;
+        assert_eq!(actual, expected);
+
+        let other = key(&response_cache, &mut request("{ user { name } }"), &req_ctx).await;
+        assert_ne!(actual, other);
+    }
+
+    #[tokio::test]
+    async fn test_key_without_credentials() {
+        let response_cache = response_cache(&[]);
+        let mut headers = hyper::HeaderMap::new();
+        headers.insert(AUTHORIZATION, "Bearer token".parse().unwrap());
+        let req_ctx = RequestContext::new(init(None)).request_headers(headers);
+
+        // the client can't be identified without the claims of the cache
+        let actual = key(&response_cache, &mut request("{ user { id } }"), &req_ctx).await;
+        assert_eq!(actual, None);
+    }
+
+    #[tokio::test]
+    async fn test_key_varies_by_headers() {
+        let response_cache = response_cache(&["accept-language"]);
+        let mut headers = hyper::HeaderMap::new();
+        headers.insert("accept-language", "en".parse().unwrap());
+        let english = RequestContext::new(init(None)).request_headers(headers.clone());
+        headers.insert("accept-language", "fr".parse().unwrap());
+        let french = RequestContext::new(init(None)).request_headers(headers);
+
+        assert_ne!(
+            key(&response_cache, &mut request("{ user { id } }"), &english).await,
+            key(&response_cache, &mut request("{ user { id } }"), &french).await
+        );
+    }
+
+    #[test]
+    fn test_ttl() {
+        let response_cache = response_cache(&[]);
+        let req_ctx = RequestContext::new(init(None));
+        assert_eq!(ttl(&response_cache, &req_ctx), NonZeroU64::new(60_000));
+
+        req_ctx.set_min_max_age(10);
+        assert_eq!(ttl(&response_cache, &req_ctx), NonZeroU64::new(10_000));
+
+        req_ctx.set_cache_public_false();
+        assert_eq!(ttl(&response_cache, &req_ctx), None);
+
+        let req_ctx = RequestContext::new(init(None));
+        req_ctx.set_min_max_age(-1);
+        assert_eq!(ttl(&response_cache, &req_ctx), None);
+    }
+}
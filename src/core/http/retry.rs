@@ -0,0 +1,187 @@
+use std::sync::Arc;
+
+use hyper::body::Bytes;
+
+use super::Response;
+use crate::core::blueprint::Retry;
+use crate::core::{BodyStream, HttpIO};
+
+/// Sends the failed requests again according to the retry policy. Streamed
+/// requests aren't retried, since a part of the response might have already
+/// been consumed.
+pub struct RetryHttp {
+    http: Arc<dyn HttpIO>,
+    retry: Retry,
+}
+
+impl RetryHttp {
+    pub fn new(http: Arc<dyn HttpIO>, retry: Retry) -> Self {
+        Self { http, retry }
+    }
+}
+
+#[async_trait::async_trait]
+impl HttpIO for RetryHttp {
+    async fn execute(&self, request: reqwest::Request) -> anyhow::Result<Response<Bytes>> {
+        execute(self.http.as_ref(), request, Some(&self.retry)).await
+    }
+
+    async fn execute_stream(
+        &self,
+        request: reqwest::Request,
+    ) -> anyhow::Result<Response<BodyStream>> {
+        self.http.execute_stream(request).await
+    }
+}
+
+/// Executes the request, and sends it again while it fails with a retryable
+/// error and attempts are left.
+pub async fn execute(
+    http: &dyn HttpIO,
+    request: reqwest::Request,
+    retry: Option<&Retry>,
+) -> anyhow::Result<Response<Bytes>> {
+    let Some(retry) = retry.filter(|retry| retry.allows(request.method())) else {
+        return http.execute(request).await;
+    };
+
+    let mut attempt = 1;
+    loop {
+        // requests with a streamed body can't be cloned and sent again
+        let next = match request.try_clone() {
+            Some(next) if attempt < retry.max_attempts => next,
+            _ => return http.execute(request).await,
+        };
+
+        let result = http.execute(next).await;
+        if !is_retryable(retry, &result) {
+            return result;
+        }
+
+        tracing::warn!(
+            "Retrying {} {} after attempt {} failed",
+            request.method(),
+            request.url(),
+            attempt
+        );
+        futures_timer::Delay::new(retry.delay(attempt as u32)).await;
+        attempt += 1;
+    }
+}
+
+fn is_retryable(retry: &Retry, result: &anyhow::Result<Response<Bytes>>) -> bool {
+    let error = match result {
+        Ok(response) => return retry.status_codes.contains(&response.status.as_u16()),
+        Err(error) => error,
+    };
+    let error = match error.downcast_ref::<reqwest_middleware::Error>() {
+        Some(reqwest_middleware::Error::Reqwest(error)) => Some(error),
+        Some(reqwest_middleware::Error::Middleware(_)) => None,
+        None => error.downcast_ref::<reqwest::Error>(),
+    };
+
+    match error.map(|error| (error.status(), error)) {
+        Some((Some(status), _)) => retry.status_codes.contains(&status.as_u16()),
+        // the request failed without a response
+        Some((None, error)) => error.is_timeout() || error.is_connect(),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use hyper::StatusCode;
+
+    use super::*;
+    use crate::core::config;
+    use crate::core::valid::Validator;
+
+    struct FailingHttp {
+        failures: usize,
+        attempts: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl HttpIO for FailingHttp {
+        async fn execute(&self, _: reqwest::Request) -> anyhow::Result<Response<Bytes>> {
+            let attempt = self.attempts.fetch_add(1, Ordering::SeqCst);
+            let status = if attempt < self.failures {
+                StatusCode::SERVICE_UNAVAILABLE
+            } else {
+                StatusCode::OK
+            };
+
+            Ok(Response { status, ..Default::default() })
+        }
+    }
+
+    fn retry(max_attempts: usize) -> Retry {
+        let config = config::Retry {
+            max_attempts: Some(max_attempts),
+            backoff: Some(1),
+            ..Default::default()
+        };
+
+        Retry::make(&config).to_result().unwrap()
+    }
+
+    fn request(method: reqwest::Method) -> reqwest::Request {
+        reqwest::Request::new(method, "http://localhost/users".parse().unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_retry_until_success() {
+        let http = FailingHttp { failures: 2, attempts: AtomicUsize::new(0) };
+
+        let response = execute(&http, request(reqwest::Method::GET), Some(&retry(3)))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status, StatusCode::OK);
+        assert_eq!(http.attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_attempts_exhausted() {
+        let http = FailingHttp { failures: 5, attempts: AtomicUsize::new(0) };
+
+        let response = tokio::time::timeout(
+            Duration::from_secs(5),
+            execute(&http, request(reqwest::Method::GET), Some(&retry(2))),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(response.status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(http.attempts.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_retry_method_not_allowed() {
+        let http = FailingHttp { failures: 1, attempts: AtomicUsize::new(0) };
+
+        let response = execute(&http, request(reqwest::Method::POST), Some(&retry(3)))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(http.attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_retry_idempotent() {
+        let http = FailingHttp { failures: 1, attempts: AtomicUsize::new(0) };
+
+        let retry = retry(3).idempotent();
+        let response = execute(&http, request(reqwest::Method::POST), Some(&retry))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status, StatusCode::OK);
+        assert_eq!(http.attempts.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,369 @@
+use std::fmt::Write;
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, HOST};
+use sha2::{Digest, Sha256, Sha512};
+
+use crate::core::blueprint::{self, Signing};
+use crate::core::config::HmacAlgorithm;
+use crate::core::path::PathString;
+
+/// Signs the request with the rendered credentials of the signing. It has to
+/// be the last change to the request, as any later change to its method, URL,
+/// signed headers or body invalidates the signature.
+pub fn sign<C: PathString>(
+    signing: &Signing,
+    request: &mut reqwest::Request,
+    ctx: &C,
+) -> anyhow::Result<()> {
+    match signing {
+        Signing::AwsSigV4(sigv4) => {
+            let credentials = Credentials {
+                access_key_id: sigv4.access_key_id.render(ctx),
+                secret_access_key: sigv4.secret_access_key.render(ctx),
+                session_token: sigv4.session_token.as_ref().map(|token| token.render(ctx)),
+            };
+            sign_sigv4(sigv4, &credentials, request, Utc::now())
+        }
+        Signing::Hmac(hmac) => sign_hmac(hmac, &hmac.secret.render(ctx), request, Utc::now()),
+    }
+}
+
+struct Credentials {
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+}
+
+/// Signs the request with AWS Signature Version 4, as described in
+/// https://docs.aws.amazon.com/IAM/latest/UserGuide/create-signed-request.html
+fn sign_sigv4(
+    sigv4: &blueprint::AwsSigV4,
+    credentials: &Credentials,
+    request: &mut reqwest::Request,
+    time: DateTime<Utc>,
+) -> anyhow::Result<()> {
+    let amz_date = time.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = time.format("%Y%m%d").to_string();
+    let payload_hash = hex::encode(Sha256::digest(body(request)));
+    // S3 requires the hash of the payload in a header, and doesn't normalize
+    // the path of the requests
+    let is_s3 = sigv4.service == "s3";
+
+    let host = match (request.url().host_str(), request.url().port()) {
+        (Some(host), Some(port)) => format!("{}:{}", host, port),
+        (Some(host), None) => host.to_string(),
+        (None, _) => anyhow::bail!("Missing host in the URL of the signed request"),
+    };
+    let headers = request.headers_mut();
+    headers.insert(HOST, HeaderValue::from_str(&host)?);
+    headers.insert("x-amz-date", HeaderValue::from_str(&amz_date)?);
+    if is_s3 {
+        headers.insert(
+            "x-amz-content-sha256",
+            HeaderValue::from_str(&payload_hash)?,
+        );
+    }
+    if let Some(session_token) = &credentials.session_token {
+        headers.insert(
+            "x-amz-security-token",
+            HeaderValue::from_str(session_token)?,
+        );
+    }
+
+    let (canonical_headers, signed_headers) = canonical_headers(request.headers());
+    let path = decode(request.url().path());
+    let canonical_path = if is_s3 {
+        encode(&path, false)
+    } else {
+        encode(&encode(&path, false), false)
+    };
+    let canonical_request = [
+        request.method().as_str(),
+        &canonical_path,
+        &canonical_query(request.url()),
+        &canonical_headers,
+        &signed_headers,
+        &payload_hash,
+    ]
+    .join("\n");
+
+    let scope = format!("{}/{}/{}/aws4_request", date, sigv4.region, sigv4.service);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let secret = format!("AWS4{}", credentials.secret_access_key);
+    let key = hmac_sha256(secret.as_bytes(), date.as_bytes())?;
+    let key = hmac_sha256(&key, sigv4.region.as_bytes())?;
+    let key = hmac_sha256(&key, sigv4.service.as_bytes())?;
+    let key = hmac_sha256(&key, b"aws4_request")?;
+    let signature = hex::encode(hmac_sha256(&key, string_to_sign.as_bytes())?);
+
+    let authorization = format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        credentials.access_key_id, scope, signed_headers, signature
+    );
+    request
+        .headers_mut()
+        .insert(AUTHORIZATION, HeaderValue::from_str(&authorization)?);
+
+    Ok(())
+}
+
+/// Returns the headers of the request sorted by their name, with the values
+/// of each header joined by commas, and the list of their names.
+fn canonical_headers(headers: &HeaderMap) -> (String, String) {
+    let mut names = headers.keys().map(|name| name.as_str()).collect::<Vec<_>>();
+    names.sort_unstable();
+
+    let mut canonical = String::new();
+    for name in &names {
+        let values = headers
+            .get_all(*name)
+            .iter()
+            .map(|value| {
+                let value = String::from_utf8_lossy(value.as_bytes());
+                value.split_whitespace().collect::<Vec<_>>().join(" ")
+            })
+            .collect::<Vec<_>>();
+        let _ = writeln!(canonical, "{}:{}", name, values.join(","));
+    }
+
+    (canonical, names.join(";"))
+}
+
+/// Returns the parameters of the query encoded and sorted by name and value.
+fn canonical_query(url: &url::Url) -> String {
+    let mut params = url
+        .query_pairs()
+        .map(|(name, value)| (encode(&name, true), encode(&value, true)))
+        .collect::<Vec<_>>();
+    params.sort();
+
+    params
+        .into_iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Percent-encodes every byte besides the unreserved characters of RFC 3986
+/// and, unless `encode_slash` is set, the slashes.
+fn encode(value: &str, encode_slash: bool) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            b'/' if !encode_slash => encoded.push('/'),
+            byte => {
+                let _ = write!(encoded, "%{:02X}", byte);
+            }
+        }
+    }
+
+    encoded
+}
+
+fn decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok());
+        match hex.map(|hex| u8::from_str_radix(hex, 16)) {
+            Some(Ok(byte)) if bytes[i] == b'%' => {
+                decoded.push(byte);
+                i += 3;
+            }
+            _ => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Signs the method, the path and query, the timestamp, the signed headers as
+/// `name:value` and the body of the request, separated by new lines, and sets
+/// the signature in hexadecimal in the header of the signing.
+fn sign_hmac(
+    hmac: &blueprint::Hmac,
+    secret: &str,
+    request: &mut reqwest::Request,
+    time: DateTime<Utc>,
+) -> anyhow::Result<()> {
+    if let Some(timestamp_header) = &hmac.timestamp_header {
+        let timestamp = HeaderValue::from(time.timestamp());
+        request.headers_mut().insert(timestamp_header, timestamp);
+    }
+
+    let url = request.url();
+    let mut message = format!("{}\n{}", request.method(), url.path());
+    if let Some(query) = url.query() {
+        message.push('?');
+        message.push_str(query);
+    }
+    message.push('\n');
+    if hmac.timestamp_header.is_some() {
+        let _ = writeln!(message, "{}", time.timestamp());
+    }
+    for name in &hmac.signed_headers {
+        let value = request
+            .headers()
+            .get(name)
+            .map(|value| String::from_utf8_lossy(value.as_bytes()))
+            .unwrap_or_default();
+        let _ = writeln!(message, "{}:{}", name, value.trim());
+    }
+
+    let mut message = message.into_bytes();
+    message.extend_from_slice(body(request));
+
+    let signature = match hmac.algorithm {
+        HmacAlgorithm::Sha256 => hex::encode(hmac_sha256(secret.as_bytes(), &message)?),
+        HmacAlgorithm::Sha512 => {
+            let mut mac = Hmac::<Sha512>::new_from_slice(secret.as_bytes())?;
+            mac.update(&message);
+            hex::encode(mac.finalize().into_bytes())
+        }
+    };
+    request
+        .headers_mut()
+        .insert(&hmac.header, HeaderValue::from_str(&signature)?);
+
+    Ok(())
+}
+
+fn hmac_sha256(key: &[u8], message: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key)?;
+    mac.update(message);
+    Ok(mac.finalize().into_bytes().to_vec())
+}
+
+fn body(request: &reqwest::Request) -> &[u8] {
+    request
+        .body()
+        .and_then(|body| body.as_bytes())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::core::mustache::Mustache;
+
+    fn sigv4(service: &str) -> blueprint::AwsSigV4 {
+        blueprint::AwsSigV4 {
+            region: "us-east-1".to_string(),
+            service: service.to_string(),
+            access_key_id: Mustache::parse("AKIDEXAMPLE").unwrap(),
+            secret_access_key: Mustache::parse("secret").unwrap(),
+            session_token: None,
+        }
+    }
+
+    fn credentials() -> Credentials {
+        Credentials {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+            session_token: None,
+        }
+    }
+
+    fn time() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2015, 8, 30, 12, 36, 0).unwrap()
+    }
+
+    fn authorization(method: reqwest::Method, url: &str) -> String {
+        let mut request = reqwest::Request::new(method, url.parse().unwrap());
+        sign_sigv4(&sigv4("service"), &credentials(), &mut request, time()).unwrap();
+
+        request.headers()[AUTHORIZATION]
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    // the requests of the test suite of AWS Signature Version 4
+    #[test]
+    fn test_sigv4_get_vanilla() {
+        let actual = authorization(reqwest::Method::GET, "https://example.amazonaws.com/");
+        let expected = "AWS4-HMAC-SHA256 \
+            Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+            SignedHeaders=host;x-amz-date, \
+            Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_sigv4_get_vanilla_query_order() {
+        let url = "https://example.amazonaws.com/?Param2=value2&Param1=value1";
+        let actual = authorization(reqwest::Method::GET, url);
+        let expected = "AWS4-HMAC-SHA256 \
+            Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+            SignedHeaders=host;x-amz-date, \
+            Signature=b97d918cfa904a5beff61c982a1b6f458b799221646efd99d3219ec94cdf2500";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_sigv4_post_vanilla() {
+        let actual = authorization(reqwest::Method::POST, "https://example.amazonaws.com/");
+        let expected = "AWS4-HMAC-SHA256 \
+            Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+            SignedHeaders=host;x-amz-date, \
+            Signature=5da7c1a2acd57cee7505fc6676e4e544621c30862966e37dddb68e92efbe5d6b";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_sigv4_s3_payload_hash() {
+        let url = "https://bucket.s3.amazonaws.com/my%20file.txt";
+        let mut request = reqwest::Request::new(reqwest::Method::GET, url.parse().unwrap());
+        sign_sigv4(&sigv4("s3"), &credentials(), &mut request, time()).unwrap();
+
+        let headers = request.headers();
+        assert_eq!(
+            headers["x-amz-content-sha256"],
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        let authorization = headers[AUTHORIZATION].to_str().unwrap();
+        assert!(authorization.contains("SignedHeaders=host;x-amz-content-sha256;x-amz-date,"));
+    }
+
+    #[test]
+    fn test_hmac() {
+        let hmac = blueprint::Hmac {
+            secret: Mustache::parse("secret").unwrap(),
+            algorithm: HmacAlgorithm::Sha256,
+            header: "x-signature".parse().unwrap(),
+            signed_headers: vec!["x-partner".parse().unwrap()],
+            timestamp_header: Some("x-timestamp".parse().unwrap()),
+        };
+        let url = "http://partner.example/hooks?id=1";
+        let mut request = reqwest::Request::new(reqwest::Method::POST, url.parse().unwrap());
+        request
+            .headers_mut()
+            .insert("x-partner", HeaderValue::from_static("acme"));
+        *request.body_mut() = Some(r#"{"id":1}"#.into());
+        sign_hmac(&hmac, "secret", &mut request, time()).unwrap();
+
+        let message = "POST\n/hooks?id=1\n1440938160\nx-partner:acme\n{\"id\":1}";
+        let expected = hex::encode(hmac_sha256(b"secret", message.as_bytes()).unwrap());
+        assert_eq!(request.headers()["x-timestamp"], "1440938160");
+        assert_eq!(request.headers()["x-signature"], expected.as_str());
+    }
+}
@@ -0,0 +1,242 @@
+use std::fmt::Display;
+
+use futures_util::stream::{self, BoxStream};
+use futures_util::StreamExt;
+use hyper::body::Bytes;
+
+/// The mime type used by upstreams that stream Server-Sent Events.
+pub const TEXT_EVENT_STREAM: &str = "text/event-stream";
+
+/// A single event received over a `text/event-stream` connection.
+/// More info [here](https://html.spec.whatwg.org/multipage/server-sent-events.html#event-stream-interpretation)
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Event {
+    /// Value of the `event` field, `None` means the default `message` event.
+    pub event: Option<String>,
+    /// Concatenated `data` fields of the event.
+    pub data: String,
+    /// Value of the `id` field.
+    pub id: Option<String>,
+}
+
+impl Event {
+    /// Creates a `next` event as used by the "GraphQL over SSE" protocol.
+    pub fn next(data: String) -> Self {
+        Self { event: Some("next".to_owned()), data, id: None }
+    }
+
+    /// Creates the `complete` event that signals the end of the stream.
+    pub fn complete() -> Self {
+        Self {
+            event: Some("complete".to_owned()),
+            data: String::new(),
+            id: None,
+        }
+    }
+}
+
+impl Display for Event {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(event) = &self.event {
+            writeln!(f, "event: {}", event)?;
+        }
+        if let Some(id) = &self.id {
+            writeln!(f, "id: {}", id)?;
+        }
+        for line in self.data.split('\n') {
+            writeln!(f, "data: {}", line)?;
+        }
+        writeln!(f)
+    }
+}
+
+/// Incrementally parses a `text/event-stream` body.
+/// Chunks can split events and lines at arbitrary positions so the decoder
+/// buffers the unfinished tail until the next chunk arrives.
+#[derive(Default)]
+pub struct Decoder {
+    buffer: Vec<u8>,
+    event: Option<String>,
+    data: Vec<String>,
+    id: Option<String>,
+}
+
+impl Decoder {
+    /// Feeds a chunk of the body into the decoder and returns all the events
+    /// that were completed by it.
+    pub fn decode(&mut self, chunk: &[u8]) -> Vec<Event> {
+        self.buffer.extend_from_slice(chunk);
+        let mut events = vec![];
+
+        while let Some(pos) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line = self.buffer.drain(..=pos).collect::<Vec<_>>();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\n', '\r']);
+
+            if let Some(event) = self.process_line(line) {
+                events.push(event);
+            }
+        }
+
+        events
+    }
+
+    /// Flushes the event that is pending when the body is finished without a
+    /// trailing blank line.
+    pub fn finish(&mut self) -> Option<Event> {
+        let rest = std::mem::take(&mut self.buffer);
+        let rest = String::from_utf8_lossy(&rest);
+        let rest = rest.trim_end_matches(['\n', '\r']);
+        if !rest.is_empty() {
+            self.process_line(rest);
+        }
+        self.dispatch()
+    }
+
+    fn process_line(&mut self, line: &str) -> Option<Event> {
+        if line.is_empty() {
+            return self.dispatch();
+        }
+
+        // lines starting with colon are comments and used as keep-alive
+        if line.starts_with(':') {
+            return None;
+        }
+
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+
+        match field {
+            "event" => self.event = Some(value.to_owned()),
+            "data" => self.data.push(value.to_owned()),
+            "id" => self.id = Some(value.to_owned()),
+            _ => {}
+        }
+
+        None
+    }
+
+    fn dispatch(&mut self) -> Option<Event> {
+        let event = self.event.take();
+        let id = self.id.take();
+        if self.data.is_empty() {
+            return None;
+        }
+        let data = std::mem::take(&mut self.data).join("\n");
+
+        Some(Event { event, data, id })
+    }
+}
+
+/// Converts a stream of body chunks into a stream of Server-Sent Events.
+pub fn decode(
+    body: BoxStream<'static, anyhow::Result<Bytes>>,
+) -> BoxStream<'static, anyhow::Result<Event>> {
+    stream::unfold(
+        (body, Decoder::default(), false),
+        |(mut body, mut decoder, done)| async move {
+            if done {
+                return None;
+            }
+
+            match body.next().await {
+                Some(Ok(chunk)) => {
+                    let events = decoder.decode(&chunk);
+                    Some((
+                        stream::iter(events.into_iter().map(Ok)).boxed(),
+                        (body, decoder, false),
+                    ))
+                }
+                Some(Err(err)) => Some((stream::iter([Err(err)]).boxed(), (body, decoder, true))),
+                None => {
+                    let events = decoder.finish().into_iter().map(Ok);
+                    Some((stream::iter(events).boxed(), (body, decoder, true)))
+                }
+            }
+        },
+    )
+    .flatten()
+    .boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::TryStreamExt;
+
+    use super::*;
+
+    fn event(event: Option<&str>, data: &str) -> Event {
+        Event {
+            event: event.map(|e| e.to_owned()),
+            data: data.to_owned(),
+            id: None,
+        }
+    }
+
+    #[test]
+    fn test_decode_single_event() {
+        let mut decoder = Decoder::default();
+        let events = decoder.decode(b"data: {\"a\": 1}\n\n");
+        assert_eq!(events, vec![event(None, "{\"a\": 1}")]);
+    }
+
+    #[test]
+    fn test_decode_split_chunks() {
+        let mut decoder = Decoder::default();
+        assert!(decoder.decode(b"event: next\nda").is_empty());
+        assert!(decoder.decode(b"ta: 1\r\n").is_empty());
+        let events = decoder.decode(b"\r\ndata: 2\n\n");
+        assert_eq!(events, vec![event(Some("next"), "1"), event(None, "2")]);
+    }
+
+    #[test]
+    fn test_decode_multiline_data_and_comments() {
+        let mut decoder = Decoder::default();
+        let events = decoder.decode(b": keep-alive\n\nid: 7\ndata: a\ndata: b\n\n");
+        assert_eq!(
+            events,
+            vec![Event {
+                event: None,
+                data: "a\nb".to_owned(),
+                id: Some("7".to_owned())
+            }]
+        );
+    }
+
+    #[test]
+    fn test_finish_flushes_pending_event() {
+        let mut decoder = Decoder::default();
+        assert!(decoder.decode(b"data: 1\n").is_empty());
+        assert_eq!(decoder.finish(), Some(event(None, "1")));
+    }
+
+    #[test]
+    fn test_encode_roundtrip() {
+        let events = [
+            Event::next("{\"data\":\n{}}".to_owned()),
+            Event { id: Some("1".to_owned()), ..event(None, "1") },
+            Event::complete(),
+        ];
+        let encoded = events.iter().map(|e| e.to_string()).collect::<String>();
+        assert_eq!(
+            encoded,
+            "event: next\ndata: {\"data\":\ndata: {}}\n\nid: 1\ndata: 1\n\nevent: complete\ndata: \n\n"
+        );
+
+        let mut decoder = Decoder::default();
+        assert_eq!(decoder.decode(encoded.as_bytes()), events);
+    }
+
+    #[tokio::test]
+    async fn test_decode_stream() {
+        let body = stream::iter([
+            Ok(Bytes::from_static(b"data: 1\n\nda")),
+            Ok(Bytes::from_static(b"ta: 2\n\n")),
+        ])
+        .boxed();
+        let events = decode(body).try_collect::<Vec<_>>().await.unwrap();
+        assert_eq!(events, vec![event(None, "1"), event(None, "2")]);
+    }
+}
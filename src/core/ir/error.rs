@@ -37,23 +37,29 @@ pub enum Error {
 
     #[error("Cache Error: {0}")]
     CacheError(cache::Error),
+
+    #[error("Rate Limit Exceeded: {0}")]
+    #[from(ignore)]
+    RateLimitError(String),
 }
 
 impl ErrorExtensions for Error {
     fn extend(&self) -> async_graphql::Error {
-        async_graphql::Error::new(format!("{}", self)).extend_with(|_err, e| {
-            if let Error::GRPCError {
+        async_graphql::Error::new(format!("{}", self)).extend_with(|_err, e| match self {
+            Error::GRPCError {
                 grpc_code,
                 grpc_description,
                 grpc_status_message,
                 grpc_status_details,
-            } = self
-            {
+            } => {
                 e.set("grpcCode", *grpc_code);
                 e.set("grpcDescription", grpc_description);
                 e.set("grpcStatusMessage", grpc_status_message);
                 e.set("grpcStatusDetails", grpc_status_details.clone());
             }
+            Error::RateLimitError(_) => e.set("code", "RATE_LIMITED"),
+            Error::AuthError(auth::error::Error::Forbidden) => e.set("code", "FORBIDDEN"),
+            _ => {}
         })
     }
 }
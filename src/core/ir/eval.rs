@@ -1,13 +1,15 @@
 use std::future::Future;
-use std::ops::Deref;
 
 use async_graphql_value::ConstValue;
 
+use super::eval_cache::eval_cache;
 use super::eval_io::eval_io;
-use super::model::{Cache, CacheKey, Map, IR};
+use super::model::{Invalidate, Map, RateLimit, IR};
 use super::{Error, EvalContext, ResolverContextLike};
 use crate::core::json::JsonLike;
+use crate::core::rate_limit;
 use crate::core::serde_value_ext::ValueExt;
+use crate::core::Cache;
 
 // Fake trait to capture proper lifetimes.
 // see discussion https://users.rust-lang.org/t/rpitit-allows-more-flexible-code-in-comparison-with-raw-rpit-in-inherit-impl/113417
@@ -25,6 +27,14 @@ impl IR {
         Ctx: ResolverContextLike + Sync,
     {
         Box::pin(async move {
+            // the client is verified only once a template can read its claims
+            if matches!(
+                self,
+                IR::Dynamic(_) | IR::IO(_) | IR::Cache(_) | IR::Invalidate(_)
+            ) {
+                ctx.authenticate().await;
+            }
+
             match self {
                 IR::ContextPath(path) => Ok(ctx
                     .path_value(path)
@@ -38,33 +48,36 @@ impl IR {
                         .clone())
                 }
                 IR::Dynamic(value) => Ok(value.render_value(ctx)),
-                IR::Protect(expr) => {
+                IR::Protect(protected, expr) => {
                     ctx.request_ctx
                         .auth_ctx
                         .validate(ctx.request_ctx)
                         .await
-                        .to_result()?;
+                        .authorize(protected)?;
+                    expr.eval(ctx).await
+                }
+                IR::RateLimit(RateLimit { name, limit, expr }) => {
+                    rate_limit::check_field(ctx.request_ctx, name, limit).await?;
                     expr.eval(ctx).await
                 }
                 IR::IO(io) => eval_io(io, ctx).await,
-                IR::Cache(Cache { max_age, io }) => {
-                    let io = io.deref();
-                    let key = io.cache_key(ctx);
-                    if let Some(key) = key {
-                        if let Some(val) = ctx.request_ctx.runtime.cache.get(&key).await? {
-                            Ok(val)
-                        } else {
-                            let val = eval_io(io, ctx).await?;
-                            ctx.request_ctx
-                                .runtime
-                                .cache
-                                .set(key, val.clone(), max_age.to_owned())
-                                .await?;
-                            Ok(val)
+                IR::Cache(cache) => eval_cache(cache, ctx).await,
+                IR::Invalidate(Invalidate { tags, expr }) => {
+                    let value = expr.eval(ctx).await?;
+                    let tags = tags.iter().map(|tag| tag.render(ctx)).collect::<Vec<_>>();
+                    // the mutation succeeded, so a failure of the cache isn't reported to the
+                    // client
+                    match ctx.request_ctx.runtime.cache.purge(&tags).await {
+                        Ok(purged) => tracing::debug!("Purged {} values tagged {:?}", purged, tags),
+                        Err(err) => tracing::warn!("Failed to purge the cache: {}", err),
+                    }
+                    match ctx.request_ctx.response_cache.purge(&tags).await {
+                        Ok(purged) => {
+                            tracing::debug!("Purged {} responses tagged {:?}", purged, tags)
                         }
-                    } else {
-                        eval_io(io, ctx).await
+                        Err(err) => tracing::warn!("Failed to purge the response cache: {}", err),
                     }
+                    Ok(value)
                 }
                 IR::Map(Map { input, map }) => {
                     let value = input.eval(ctx).await?;
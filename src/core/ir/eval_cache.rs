@@ -0,0 +1,400 @@
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+use std::num::NonZeroU64;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use async_graphql::{Name, Value};
+use async_graphql_value::ConstValue;
+use indexmap::IndexMap;
+use tailcall_hasher::TailcallHasher;
+
+use super::eval_io::eval_io;
+use super::model::{Cache, CacheKey, IoId};
+use super::{Error, EvalContext, ResolverContextLike};
+use crate::core::mustache::Mustache;
+
+const VALUE: &str = "value";
+const EXPIRES_AT: &str = "expiresAt";
+
+pub async fn eval_cache<Ctx>(
+    cache: &Cache,
+    ctx: &mut EvalContext<'_, Ctx>,
+) -> Result<ConstValue, Error>
+where
+    Ctx: ResolverContextLike + Sync,
+{
+    let req_ctx = ctx.request_ctx;
+    if req_ctx.server.response_cache.is_some() && !cache.tags.is_empty() {
+        // the cached response of the request is purged along with the value
+        let tags = cache.tags.iter().map(|tag| tag.render(ctx));
+        req_ctx.cache_tags.lock().unwrap().extend(tags);
+    }
+
+    let Some(key) = cache.io.cache_key(ctx) else {
+        return eval_io(&cache.io, ctx).await;
+    };
+    if cache.stale.is_empty() {
+        return eval_fresh(cache, key, ctx).await;
+    }
+
+    let key = stale_key(&key);
+    // failures of the cache are ignored, as the value can still be resolved
+    let entry = match ctx.request_ctx.runtime.cache.get(&key).await {
+        Ok(entry) => entry.and_then(StaleEntry::from_value),
+        Err(err) => {
+            tracing::warn!("Failed to read the cache: {}", err);
+            None
+        }
+    };
+    let Some(entry) = entry else {
+        let value = eval_io(&cache.io, ctx).await?;
+        store(cache, key, value.clone(), ctx).await;
+        return Ok(value);
+    };
+
+    let now = now_ms();
+    let within = |window: Option<NonZeroU64>| {
+        window.is_some_and(|window| now < entry.expires_at.saturating_add(window.get()))
+    };
+    if now < entry.expires_at {
+        Ok(entry.value)
+    } else if within(cache.stale.while_revalidate) {
+        revalidate(cache, key, ctx);
+        Ok(entry.value)
+    } else {
+        match eval_io(&cache.io, ctx).await {
+            Ok(value) => {
+                store(cache, key, value.clone(), ctx).await;
+                Ok(value)
+            }
+            Err(err) if within(cache.stale.if_error) => {
+                tracing::warn!("Serving a stale value, as the upstream failed: {}", err);
+                Ok(entry.value)
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// Caches the values of the fields without stale windows as they are, until
+/// their `max_age`.
+async fn eval_fresh<Ctx>(
+    cache: &Cache,
+    key: IoId,
+    ctx: &mut EvalContext<'_, Ctx>,
+) -> Result<ConstValue, Error>
+where
+    Ctx: ResolverContextLike + Sync,
+{
+    // failures of the cache are ignored, as the value can still be resolved
+    let cached = ctx.request_ctx.runtime.cache.get(&key).await;
+    if let Ok(Some(value)) = cached {
+        return Ok(value);
+    }
+    if let Err(err) = cached {
+        tracing::warn!("Failed to read the cache: {}", err);
+    }
+
+    let value = eval_io(&cache.io, ctx).await?;
+    let runtime_cache = &ctx.request_ctx.runtime.cache;
+    if let Err(err) = runtime_cache
+        .set(key.clone(), value.clone(), cache.max_age)
+        .await
+    {
+        tracing::warn!("Failed to write the cache: {}", err);
+    } else {
+        set_tags(&cache.tags, &key, cache.max_age, ctx).await;
+    }
+    Ok(value)
+}
+
+/// Refreshes the stale value in the background, through the same dedupe of
+/// the requests to the upstream as the other requests. The stale hits that
+/// happen while the value is refreshed don't start another refresh.
+fn revalidate<Ctx>(cache: &Cache, key: IoId, ctx: &EvalContext<'_, Ctx>)
+where
+    Ctx: ResolverContextLike + Sync,
+{
+    let Some(revalidation) = Revalidation::start(&ctx.request_ctx.revalidating, &key) else {
+        return;
+    };
+    let detached = ctx.detach();
+    let cache = cache.clone();
+    let task = async move {
+        let _revalidation = revalidation;
+        let mut ctx = detached.eval_ctx();
+        match eval_io(&cache.io, &mut ctx).await {
+            Ok(value) => store(&cache, key, value, &ctx).await,
+            Err(err) => tracing::warn!("Failed to refresh a stale value: {}", err),
+        }
+    };
+
+    #[cfg(not(target_arch = "wasm32"))]
+    tokio::spawn(task);
+    #[cfg(target_arch = "wasm32")]
+    async_std::task::spawn_local(task);
+}
+
+/// Marks a key as being refreshed, until it's dropped along with the task of
+/// the refresh.
+pub(crate) struct Revalidation {
+    revalidating: Arc<Mutex<HashSet<IoId>>>,
+    key: IoId,
+}
+
+impl Revalidation {
+    pub(crate) fn start(revalidating: &Arc<Mutex<HashSet<IoId>>>, key: &IoId) -> Option<Self> {
+        if !revalidating.lock().unwrap().insert(key.clone()) {
+            return None;
+        }
+
+        Some(Self { revalidating: revalidating.clone(), key: key.clone() })
+    }
+}
+
+impl Drop for Revalidation {
+    fn drop(&mut self) {
+        self.revalidating.lock().unwrap().remove(&self.key);
+    }
+}
+
+/// Stores the value along with its expiry, for as long as it can be served
+/// stale.
+async fn store<Ctx>(cache: &Cache, key: IoId, value: ConstValue, ctx: &EvalContext<'_, Ctx>)
+where
+    Ctx: ResolverContextLike + Sync,
+{
+    let entry = StaleEntry {
+        value,
+        expires_at: now_ms().saturating_add(cache.max_age.get()),
+    };
+    let ttl = cache.max_age.saturating_add(cache.stale.max_window());
+    let runtime_cache = &ctx.request_ctx.runtime.cache;
+    if let Err(err) = runtime_cache
+        .set(key.clone(), entry.into_value(), ttl)
+        .await
+    {
+        tracing::warn!("Failed to write the cache: {}", err);
+    } else {
+        set_tags(&cache.tags, &key, ttl, ctx).await;
+    }
+}
+
+async fn set_tags<Ctx>(tags: &[Mustache], key: &IoId, ttl: NonZeroU64, ctx: &EvalContext<'_, Ctx>)
+where
+    Ctx: ResolverContextLike + Sync,
+{
+    if tags.is_empty() {
+        return;
+    }
+
+    let tags = tags.iter().map(|tag| tag.render(ctx)).collect::<Vec<_>>();
+    if let Err(err) = ctx.request_ctx.runtime.cache.tag(key, &tags, ttl).await {
+        tracing::warn!("Failed to tag the cache: {}", err);
+    }
+}
+
+/// The values that can be served stale are stored under another key, so that
+/// they aren't mistaken for the values of the fields without stale windows.
+fn stale_key(key: &IoId) -> IoId {
+    let mut hasher = TailcallHasher::default();
+    key.hash(&mut hasher);
+    EXPIRES_AT.hash(&mut hasher);
+    IoId::new(hasher.finish())
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// A cached value, along with the time in milliseconds since the epoch after
+/// which it's stale.
+#[derive(Debug, PartialEq)]
+struct StaleEntry {
+    value: ConstValue,
+    expires_at: u64,
+}
+
+impl StaleEntry {
+    fn into_value(self) -> ConstValue {
+        let mut entry = IndexMap::new();
+        entry.insert(Name::new(VALUE), self.value);
+        entry.insert(Name::new(EXPIRES_AT), Value::from(self.expires_at));
+        ConstValue::Object(entry)
+    }
+
+    fn from_value(value: ConstValue) -> Option<Self> {
+        let ConstValue::Object(mut entry) = value else {
+            return None;
+        };
+        let expires_at = match entry.get(EXPIRES_AT)? {
+            ConstValue::Number(number) => number.as_u64()?,
+            _ => return None,
+        };
+        let value = entry.swap_remove(VALUE)?;
+
+        Some(Self { value, expires_at })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use hyper::body::Bytes;
+    use serde_json::json;
+    use tokio::sync::Semaphore;
+
+    use super::*;
+    use crate::core::http::{RequestContext, RequestTemplate, Response};
+    use crate::core::ir::model::{Stale, IO};
+    use crate::core::ir::EmptyResolverContext;
+    use crate::core::{BodyStream, HttpIO};
+
+    /// Upstream that answers once it's released, so that the stale hits
+    /// happen while the value is refreshed.
+    struct UpstreamHttp {
+        hits: AtomicUsize,
+        fail: AtomicBool,
+        release: Semaphore,
+    }
+
+    impl UpstreamHttp {
+        fn new(fail: bool) -> Self {
+            Self {
+                hits: AtomicUsize::new(0),
+                fail: AtomicBool::new(fail),
+                release: Semaphore::new(0),
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl HttpIO for UpstreamHttp {
+        async fn execute(&self, _: reqwest::Request) -> anyhow::Result<Response<Bytes>> {
+            self.hits.fetch_add(1, Ordering::SeqCst);
+            if self.fail.load(Ordering::SeqCst) {
+                return Err(anyhow::anyhow!("Upstream unavailable"));
+            }
+            self.release.acquire().await?.forget();
+
+            let body = Bytes::from(serde_json::to_vec(&json!({"name": "fresh"}))?);
+            Ok(Response { body, ..Default::default() })
+        }
+
+        async fn execute_stream(
+            &self,
+            _: reqwest::Request,
+        ) -> anyhow::Result<Response<BodyStream>> {
+            unimplemented!()
+        }
+    }
+
+    fn cache(stale: Stale) -> Cache {
+        Cache {
+            max_age: NonZeroU64::new(60_000).unwrap(),
+            stale,
+            tags: vec![],
+            io: Box::new(IO::Http {
+                req_template: RequestTemplate::new("http://upstream/users/1").unwrap(),
+                group_by: None,
+                dl_id: None,
+                http_filter: None,
+                retry: None,
+                oauth2: None,
+            }),
+        }
+    }
+
+    /// Creates a context with a value that expired at the given time ago.
+    async fn request_ctx(cache: &Cache, http: Arc<UpstreamHttp>, expired: u64) -> RequestContext {
+        let mut runtime = crate::core::runtime::test::init(None);
+        runtime.http = http;
+        let req_ctx = RequestContext::new(runtime);
+
+        let ctx = EvalContext::new(&req_ctx, &EmptyResolverContext {});
+        let key = stale_key(&cache.io.cache_key(&ctx).unwrap());
+        let entry = StaleEntry { value: json_value("stale"), expires_at: now_ms() - expired };
+        let ttl = NonZeroU64::new(600_000).unwrap();
+        req_ctx
+            .runtime
+            .cache
+            .set(key, entry.into_value(), ttl)
+            .await
+            .unwrap();
+
+        req_ctx
+    }
+
+    fn json_value(name: &str) -> ConstValue {
+        ConstValue::from_json(json!({"name": name})).unwrap()
+    }
+
+    async fn eval(cache: &Cache, req_ctx: &RequestContext) -> Result<ConstValue, Error> {
+        let mut ctx = EvalContext::new(req_ctx, &EmptyResolverContext {});
+        eval_cache(cache, &mut ctx).await
+    }
+
+    #[tokio::test]
+    async fn test_revalidate() {
+        let window = NonZeroU64::new(60_000);
+        let cache = cache(Stale { while_revalidate: window, if_error: None });
+        let http = Arc::new(UpstreamHttp::new(false));
+        let req_ctx = request_ctx(&cache, http.clone(), 1).await;
+
+        // the stale value is served while a single refresh is in flight
+        for _ in 0..3 {
+            assert_eq!(eval(&cache, &req_ctx).await.unwrap(), json_value("stale"));
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(req_ctx.revalidating.lock().unwrap().len(), 1);
+
+        http.release.add_permits(1);
+        for _ in 0..100 {
+            if req_ctx.revalidating.lock().unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        assert_eq!(eval(&cache, &req_ctx).await.unwrap(), json_value("fresh"));
+        assert_eq!(http.hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_stale_if_error() {
+        let window = NonZeroU64::new(60_000);
+        let cache = cache(Stale { while_revalidate: None, if_error: window });
+        let http = Arc::new(UpstreamHttp::new(true));
+
+        let req_ctx = request_ctx(&cache, http.clone(), 1).await;
+        assert_eq!(eval(&cache, &req_ctx).await.unwrap(), json_value("stale"));
+
+        // past the window, the failure of the upstream is returned
+        let req_ctx = request_ctx(&cache, http.clone(), 120_000).await;
+        assert!(eval(&cache, &req_ctx).await.is_err());
+        assert_eq!(http.hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_stale_entry() {
+        let entry = StaleEntry { value: ConstValue::from(42), expires_at: 1_700_000_000_000 };
+        let actual = StaleEntry::from_value(entry.into_value()).unwrap();
+        let expected = StaleEntry { value: ConstValue::from(42), expires_at: 1_700_000_000_000 };
+        assert_eq!(actual, expected);
+
+        assert_eq!(StaleEntry::from_value(ConstValue::from("value")), None);
+    }
+
+    #[test]
+    fn test_stale_key() {
+        let key = IoId::new(42);
+        assert_eq!(stale_key(&key), stale_key(&IoId::new(42)));
+        assert_ne!(stale_key(&key), key);
+    }
+}
@@ -1,14 +1,18 @@
 use std::borrow::Cow;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::Arc;
 
 use async_graphql::{ServerError, Value};
 use reqwest::header::HeaderMap;
 
 use super::discriminator::TypeName;
-use super::{GraphQLOperationContext, RelatedFields, ResolverContextLike, SelectionField};
+use super::{
+    DetachedResolverContext, GraphQLOperationContext, RelatedFields, ResolverContextLike,
+    SelectionField,
+};
 use crate::core::document::print_directives;
 use crate::core::http::RequestContext;
+use crate::core::json::JsonLike;
 
 // TODO: rename to ResolverContext
 #[derive(Clone)]
@@ -111,15 +115,69 @@ impl<'a, Ctx: ResolverContextLike> EvalContext<'a, Ctx> {
         &self.request_ctx.server.vars
     }
 
+    /// Verifies the client, unless it was already verified, so that its claims
+    /// can be read by the templates that are rendered next.
+    pub async fn authenticate(&self) {
+        self.request_ctx.auth_ctx.validate(self.request_ctx).await;
+    }
+
+    /// Returns the claims of the verified client at the given path e.g.
+    /// `claims.sub`. The client has to be authenticated first.
+    pub fn path_auth<T: AsRef<str>>(&self, path: &[T]) -> Option<Value> {
+        let (head, tail) = path.split_first()?;
+        if head.as_ref() != "claims" {
+            return None;
+        }
+
+        let claims = self.request_ctx.auth_ctx.claims()?;
+        let value = match tail.split_first() {
+            Some((name, path)) => claims.get(name.as_ref())?.get_path(path)?.clone(),
+            None => serde_json::Value::Object(claims.as_ref().clone()),
+        };
+
+        Value::from_json(value).ok()
+    }
+
     pub fn add_error(&self, error: ServerError) {
         self.graphql_ctx.add_error(error)
     }
 
+    /// Copies the context, so that it can be moved into a task that outlives
+    /// the request e.g. the refresh of a stale cached value.
+    pub fn detach(&self) -> DetachedContext {
+        DetachedContext {
+            request_ctx: self.request_ctx.detach(),
+            graphql_ctx: DetachedResolverContext::new(self.graphql_ctx),
+            graphql_ctx_value: self.graphql_ctx_value.clone(),
+            graphql_ctx_args: self.graphql_ctx_args.clone(),
+        }
+    }
+
     pub fn set_type_name(&mut self, type_name: TypeName) {
         self.type_name = Some(type_name);
     }
 }
 
+/// Owned copy of an [EvalContext], that is created by [EvalContext::detach].
+pub struct DetachedContext {
+    request_ctx: RequestContext,
+    graphql_ctx: DetachedResolverContext,
+    graphql_ctx_value: Option<Arc<Value>>,
+    graphql_ctx_args: Option<Arc<Value>>,
+}
+
+impl DetachedContext {
+    pub fn eval_ctx(&self) -> EvalContext<'_, DetachedResolverContext> {
+        EvalContext {
+            request_ctx: &self.request_ctx,
+            graphql_ctx: &self.graphql_ctx,
+            graphql_ctx_value: self.graphql_ctx_value.clone(),
+            graphql_ctx_args: self.graphql_ctx_args.clone(),
+            type_name: None,
+        }
+    }
+}
+
 impl<'a, Ctx: ResolverContextLike> GraphQLOperationContext for EvalContext<'a, Ctx> {
     fn directives(&self) -> Option<String> {
         let selection_field = self.graphql_ctx.field()?;
@@ -139,8 +197,10 @@ fn format_selection_set<'a>(
     selection_set: impl Iterator<Item = &'a SelectionField>,
     related_fields: &RelatedFields,
 ) -> Option<String> {
-    let set = selection_set
+    let mut selected = BTreeSet::new();
+    let mut set = selection_set
         .filter_map(|field| {
+            selected.insert(field.name());
             // add to set only related fields that should be resolved with current resolver
             related_fields
                 .get(field.name())
@@ -148,6 +208,15 @@ fn format_selection_set<'a>(
         })
         .collect::<Vec<_>>();
 
+    // add the fields that are required by the resolvers of the nested fields
+    set.extend(
+        related_fields
+            .required
+            .iter()
+            .filter(|name| !selected.contains(name.as_str()))
+            .cloned(),
+    );
+
     if set.is_empty() {
         return None;
     }
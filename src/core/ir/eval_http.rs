@@ -1,16 +1,18 @@
 use std::sync::Arc;
 
 use async_graphql::from_value;
+use reqwest::header::HeaderValue;
 use reqwest::Request;
 
 use super::model::DataLoaderId;
 use super::{EvalContext, ResolverContextLike};
+use crate::core::blueprint::Retry;
 use crate::core::data_loader::{DataLoader, Loader};
 use crate::core::grpc::protobuf::ProtobufOperation;
-use crate::core::grpc::request::execute_grpc_request;
+use crate::core::grpc::request::to_grpc_response;
 use crate::core::grpc::request_template::RenderedRequestTemplate;
 use crate::core::http::{
-    cache_policy, DataLoaderRequest, HttpDataLoader, HttpFilter, RequestTemplate, Response,
+    cache_policy, retry, DataLoaderRequest, HttpDataLoader, HttpFilter, RequestTemplate, Response,
 };
 use crate::core::ir::Error;
 use crate::core::json::JsonLike;
@@ -26,6 +28,7 @@ pub struct EvalHttp<'a, 'ctx, Context: ResolverContextLike + Sync> {
     evaluation_ctx: &'ctx EvalContext<'a, Context>,
     data_loader: Option<&'a DataLoader<DataLoaderRequest, HttpDataLoader>>,
     request_template: &'a http::RequestTemplate,
+    retry: Option<&'a Retry>,
 }
 
 impl<'a, 'ctx, Context: ResolverContextLike + Sync> EvalHttp<'a, 'ctx, Context> {
@@ -33,6 +36,7 @@ impl<'a, 'ctx, Context: ResolverContextLike + Sync> EvalHttp<'a, 'ctx, Context>
         evaluation_ctx: &'ctx EvalContext<'a, Context>,
         request_template: &'a RequestTemplate,
         id: &Option<DataLoaderId>,
+        retry: Option<&'a Retry>,
     ) -> Self {
         let data_loader = if evaluation_ctx.request_ctx.is_batching_enabled() {
             id.and_then(|id| {
@@ -45,11 +49,13 @@ impl<'a, 'ctx, Context: ResolverContextLike + Sync> EvalHttp<'a, 'ctx, Context>
             None
         };
 
-        Self { evaluation_ctx, data_loader, request_template }
+        Self { evaluation_ctx, data_loader, request_template, retry }
     }
 
-    pub fn init_request(&self) -> Result<Request, Error> {
-        Ok(self.request_template.to_request(self.evaluation_ctx)?)
+    pub fn init_request(&self, authorization: Option<HeaderValue>) -> Result<Request, Error> {
+        Ok(self
+            .request_template
+            .to_authorized_request(self.evaluation_ctx, authorization)?)
     }
 
     pub async fn execute(&self, req: Request) -> Result<Response<async_graphql::Value>, Error> {
@@ -59,7 +65,7 @@ impl<'a, 'ctx, Context: ResolverContextLike + Sync> EvalHttp<'a, 'ctx, Context>
         let response = if is_get && dl.is_some() {
             execute_request_with_dl(ctx, req, self.data_loader).await?
         } else {
-            execute_raw_request(ctx, req).await?
+            execute_raw_request(ctx, req, self.retry).await?
         };
 
         if ctx.request_ctx.server.get_enable_http_validation() {
@@ -178,12 +184,9 @@ fn set_cookie_headers<Ctx: ResolverContextLike>(
 pub async fn execute_raw_request<Ctx: ResolverContextLike>(
     ctx: &EvalContext<'_, Ctx>,
     req: Request,
+    retry: Option<&Retry>,
 ) -> Result<Response<async_graphql::Value>, Error> {
-    let response = ctx
-        .request_ctx
-        .runtime
-        .http
-        .execute(req)
+    let response = retry::execute(ctx.request_ctx.runtime.http.as_ref(), req, retry)
         .await
         .map_err(Error::from)?
         .to_json()?;
@@ -195,10 +198,13 @@ pub async fn execute_raw_grpc_request<Ctx: ResolverContextLike>(
     ctx: &EvalContext<'_, Ctx>,
     req: Request,
     operation: &ProtobufOperation,
+    retry: Option<&Retry>,
 ) -> Result<Response<async_graphql::Value>, Error> {
-    execute_grpc_request(&ctx.request_ctx.runtime, operation, req)
+    let response = retry::execute(ctx.request_ctx.runtime.http2_only.as_ref(), req, retry)
         .await
-        .map_err(Error::from)
+        .map_err(Error::from)?;
+
+    to_grpc_response(operation, response).map_err(Error::from)
 }
 
 pub async fn execute_grpc_request_with_dl<
@@ -1,5 +1,6 @@
 use async_graphql_value::ConstValue;
 
+use super::eval_context::get_path_value;
 use super::eval_http::{
     execute_grpc_request_with_dl, execute_raw_grpc_request, execute_raw_request,
     execute_request_with_dl, parse_graphql_response, set_headers, EvalHttp,
@@ -8,16 +9,29 @@ use super::model::{CacheKey, IO};
 use super::{EvalContext, ResolverContextLike};
 use crate::core::config::GraphQLOperationType;
 use crate::core::data_loader::DataLoader;
-use crate::core::graphql::GraphqlDataLoader;
+use crate::core::graphql::{GraphqlDataLoader, RequestTemplate};
 use crate::core::grpc;
 use crate::core::grpc::data_loader::GrpcDataLoader;
-use crate::core::http::DataLoaderRequest;
+use crate::core::http::{oauth2, DataLoaderRequest};
 use crate::core::ir::Error;
 
 pub async fn eval_io<Ctx>(io: &IO, ctx: &mut EvalContext<'_, Ctx>) -> Result<ConstValue, Error>
 where
     Ctx: ResolverContextLike + Sync,
 {
+    // Entity fields that were already requested along with the parent entity
+    // don't need another request to the subgraph.
+    if let IO::GraphQL {
+        req_template: RequestTemplate { entity: Some(_), .. },
+        field_name,
+        ..
+    } = io
+    {
+        if let Some(value) = ctx.path_value(&[field_name]) {
+            return Ok(value.into_owned());
+        }
+    }
+
     // Note: Handled the case separately for performance reasons. It avoids cache
     // key generation when it's not required
     if !ctx.request_ctx.server.dedupe || !ctx.is_query() {
@@ -43,10 +57,14 @@ where
     Ctx: ResolverContextLike + Sync,
 {
     match io {
-        IO::Http { req_template, dl_id, http_filter, .. } => {
+        IO::Http { req_template, dl_id, http_filter, retry, oauth2, .. } => {
             let worker = &ctx.request_ctx.runtime.cmd_worker;
-            let eval_http = EvalHttp::new(ctx, req_template, dl_id);
-            let request = eval_http.init_request()?;
+            let authorization = match oauth2 {
+                Some(oauth2) => Some(oauth2::authorization(ctx.request_ctx, oauth2).await?),
+                None => None,
+            };
+            let eval_http = EvalHttp::new(ctx, req_template, dl_id, retry.as_ref());
+            let request = eval_http.init_request(authorization)?;
             let response = match (&worker, http_filter) {
                 (Some(worker), Some(http_filter)) => {
                     eval_http
@@ -58,7 +76,32 @@ where
 
             Ok(response.body)
         }
-        IO::GraphQL { req_template, field_name, dl_id, .. } => {
+        IO::GraphQL { req_template, field_name, dl_id, retry, .. }
+            if req_template.entity.is_some() =>
+        {
+            let request = req_template.to_entity_request(ctx)?;
+            let data_loader =
+                dl_id.and_then(|dl| ctx.request_ctx.entity_data_loaders.get(dl.as_usize()));
+
+            let res = match data_loader {
+                Some(data_loader) => data_loader
+                    .load_one(request)
+                    .await
+                    .map_err(Error::from)?
+                    .unwrap_or_default(),
+                None => {
+                    execute_raw_request(ctx, request.to_single_request(), retry.as_ref()).await?
+                }
+            };
+
+            set_headers(ctx, &res);
+
+            let entities = parse_graphql_response(ctx, res, "_entities")?;
+            Ok(get_path_value(&entities, &["0", field_name])
+                .cloned()
+                .unwrap_or_default())
+        }
+        IO::GraphQL { req_template, field_name, dl_id, retry, .. } => {
             let req = req_template.to_request(ctx)?;
 
             let res = if ctx.request_ctx.upstream.batch.is_some()
@@ -68,13 +111,14 @@ where
                     dl_id.and_then(|dl| ctx.request_ctx.gql_data_loaders.get(dl.as_usize()));
                 execute_request_with_dl(ctx, req, data_loader).await?
             } else {
-                execute_raw_request(ctx, req).await?
+                execute_raw_request(ctx, req, retry.as_ref()).await?
             };
 
             set_headers(ctx, &res);
+
             parse_graphql_response(ctx, res, field_name)
         }
-        IO::Grpc { req_template, dl_id, .. } => {
+        IO::Grpc { req_template, dl_id, retry, .. } => {
             let rendered = req_template.render(ctx)?;
 
             let res = if ctx.request_ctx.upstream.batch.is_some() &&
@@ -86,7 +130,7 @@ where
                 execute_grpc_request_with_dl(ctx, rendered, data_loader).await?
             } else {
                 let req = rendered.to_request()?;
-                execute_raw_grpc_request(ctx, req, &req_template.operation).await?
+                execute_raw_grpc_request(ctx, req, &req_template.operation, retry.as_ref()).await?
             };
 
             set_headers(ctx, &res);
@@ -0,0 +1,193 @@
+use std::future::{ready, Future};
+
+use async_graphql_value::ConstValue;
+use futures_util::stream::{self, BoxStream};
+use futures_util::{StreamExt, TryStreamExt};
+use reqwest::header::{HeaderValue, ACCEPT, CONTENT_TYPE};
+
+use super::eval::Captures;
+use super::eval_io::eval_io;
+use super::model::{Cache, RateLimit, IO, IR};
+use super::{Error, EvalContext, ResolverContextLike};
+use crate::core::grpc::request::execute_grpc_stream;
+use crate::core::http::sse::{self, TEXT_EVENT_STREAM};
+use crate::core::http::{oauth2, Response};
+use crate::core::json::JsonLike;
+use crate::core::rate_limit;
+use crate::core::BodyStream;
+
+/// Stream of values produced by a subscription resolver.
+pub type ValueStream = BoxStream<'static, Result<ConstValue, Error>>;
+
+impl IR {
+    /// Evaluates the expression into a stream of values.
+    /// IO that is able to stream (Server-Sent Events over `@http`, upstream
+    /// GraphQL subscriptions and server-streaming gRPC methods) produces a
+    /// value per received event, everything else is evaluated once and
+    /// produces a single value.
+    pub fn eval_stream<'a, 'b, Ctx>(
+        &'a self,
+        ctx: &'b mut EvalContext<'a, Ctx>,
+    ) -> impl Future<Output = Result<ValueStream, Error>> + Send + Captures<&'b &'a ()>
+    where
+        Ctx: ResolverContextLike + Sync,
+    {
+        Box::pin(async move {
+            match self {
+                IR::IO(io) => eval_io_stream(io, ctx).await,
+                // every event is fresh by definition, so there is nothing to cache
+                IR::Cache(Cache { io, .. }) => eval_io_stream(io, ctx).await,
+                IR::Protect(protected, expr) => {
+                    ctx.request_ctx
+                        .auth_ctx
+                        .validate(ctx.request_ctx)
+                        .await
+                        .authorize(protected)?;
+                    expr.eval_stream(ctx).await
+                }
+                IR::RateLimit(RateLimit { name, limit, expr }) => {
+                    rate_limit::check_field(ctx.request_ctx, name, limit).await?;
+                    expr.eval_stream(ctx).await
+                }
+                IR::Path(input, path) => {
+                    let path = path.clone();
+                    let stream = input.eval_stream(ctx).await?;
+
+                    Ok(stream
+                        .map_ok(move |value| value.get_path(&path).cloned().unwrap_or_default())
+                        .boxed())
+                }
+                IR::Pipe(first, second) => {
+                    let args = first.eval(&mut ctx.clone()).await?;
+                    let ctx = &mut ctx.with_args(args);
+                    second.eval_stream(ctx).await
+                }
+                _ => {
+                    let value = self.eval(ctx).await?;
+                    Ok(stream::once(async move { Ok(value) }).boxed())
+                }
+            }
+        })
+    }
+}
+
+async fn eval_io_stream<Ctx>(io: &IO, ctx: &mut EvalContext<'_, Ctx>) -> Result<ValueStream, Error>
+where
+    Ctx: ResolverContextLike + Sync,
+{
+    ctx.authenticate().await;
+
+    match io {
+        IO::Http { req_template, oauth2, .. } => {
+            let authorization = match oauth2 {
+                Some(oauth2) => Some(oauth2::authorization(ctx.request_ctx, oauth2).await?),
+                None => None,
+            };
+            let request = req_template.to_authorized_request(ctx, authorization)?;
+            let response = execute_stream(ctx, request).await?;
+
+            Ok(events(response)
+                .and_then(|data| async move { parse_json(&data) })
+                .boxed())
+        }
+        IO::GraphQL { req_template, field_name, .. } => {
+            let request = req_template.to_request(ctx)?;
+            let response = execute_stream(ctx, request).await?;
+            let field_name = field_name.clone();
+
+            Ok(events(response)
+                .and_then(move |data| {
+                    let result = parse_json(&data).and_then(|value| {
+                        let res: async_graphql::Response = async_graphql::from_value(value)
+                            .map_err(|err| Error::DeserializeError(err.to_string()))?;
+
+                        if !res.errors.is_empty() {
+                            let messages = res
+                                .errors
+                                .into_iter()
+                                .map(|err| err.message)
+                                .collect::<Vec<_>>();
+                            return Err(Error::IOException(messages.join(", ")));
+                        }
+
+                        Ok(res.data.get_key(&field_name).cloned().unwrap_or_default())
+                    });
+
+                    async move { result }
+                })
+                .boxed())
+        }
+        IO::Grpc { req_template, .. } if req_template.operation.is_server_streaming() => {
+            let request = req_template.render(ctx)?.to_request()?;
+            let stream =
+                execute_grpc_stream(&ctx.request_ctx.runtime, &req_template.operation, request)
+                    .await?;
+
+            Ok(stream.map_err(Error::from).boxed())
+        }
+        IO::Grpc { .. } | IO::Js { .. } => {
+            let value = eval_io(io, ctx).await?;
+            Ok(stream::once(async move { Ok(value) }).boxed())
+        }
+    }
+}
+
+async fn execute_stream<Ctx: ResolverContextLike>(
+    ctx: &EvalContext<'_, Ctx>,
+    mut request: reqwest::Request,
+) -> Result<Response<BodyStream>, Error> {
+    request
+        .headers_mut()
+        .entry(ACCEPT)
+        .or_insert(HeaderValue::from_static(TEXT_EVENT_STREAM));
+
+    ctx.request_ctx
+        .runtime
+        .http
+        .execute_stream(request)
+        .await
+        .map_err(Error::from)
+}
+
+/// Returns the payload of every event in the response. Upstreams that respond
+/// with a regular body instead of `text/event-stream` produce a single event.
+fn events(response: Response<BodyStream>) -> BoxStream<'static, Result<String, Error>> {
+    let is_event_stream = response
+        .headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with(TEXT_EVENT_STREAM));
+
+    // trailers are only meaningful to gRPC upstreams
+    let body = response
+        .body
+        .try_filter_map(|frame| ready(Ok(frame.into_data())))
+        .boxed();
+
+    if is_event_stream {
+        sse::decode(body)
+            // graphql-sse protocol signals the end of the stream with `complete`
+            .try_take_while(|event| {
+                let complete = event.event.as_deref() == Some("complete");
+                async move { Ok(!complete) }
+            })
+            .map_ok(|event| event.data)
+            .map_err(Error::from)
+            .boxed()
+    } else {
+        stream::once(async move {
+            let body = body
+                .try_fold(Vec::new(), |mut body, chunk| async move {
+                    body.extend_from_slice(&chunk);
+                    Ok(body)
+                })
+                .await?;
+            Ok(String::from_utf8_lossy(&body).into_owned())
+        })
+        .boxed()
+    }
+}
+
+fn parse_json(data: &str) -> Result<ConstValue, Error> {
+    serde_json::from_str(data).map_err(|err| Error::DeserializeError(err.to_string()))
+}
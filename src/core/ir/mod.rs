@@ -1,33 +1,44 @@
 mod discriminator;
 mod error;
 mod eval;
+mod eval_cache;
 mod eval_context;
 mod eval_http;
 mod eval_io;
+mod eval_stream;
 mod resolver_context_like;
 
 pub mod model;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::ops::Deref;
 
 pub use discriminator::*;
 pub use error::*;
-pub use eval_context::EvalContext;
+pub(crate) use eval_cache::Revalidation;
+pub use eval_context::{DetachedContext, EvalContext};
+pub use eval_stream::ValueStream;
 pub use resolver_context_like::{
-    EmptyResolverContext, ResolverContext, ResolverContextLike, SelectionField,
+    DetachedResolverContext, EmptyResolverContext, ResolverContext, ResolverContextLike,
+    SelectionField,
 };
 
 /// Contains all the nested fields that are resolved with current parent
 /// resolver i.e. fields that don't have their own resolver and are resolved by
 /// the ancestor
 #[derive(Debug, Default, Clone)]
-pub struct RelatedFields(pub HashMap<String, RelatedFields>);
+pub struct RelatedFields {
+    pub fields: HashMap<String, RelatedFields>,
+    /// Fields that are requested even if they are not selected, because the
+    /// resolvers of the nested fields depend on them e.g. the key fields of
+    /// an entity that is joined with another subgraph.
+    pub required: BTreeSet<String>,
+}
 
 impl Deref for RelatedFields {
     type Target = HashMap<String, RelatedFields>;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.fields
     }
 }
 
@@ -7,10 +7,11 @@ use strum_macros::Display;
 
 use super::discriminator::Discriminator;
 use super::{EvalContext, ResolverContextLike};
-use crate::core::blueprint::DynamicValue;
+use crate::core::blueprint::{self, DynamicValue, OAuth2, Retry};
 use crate::core::config::group_by::GroupBy;
 use crate::core::graphql::{self};
 use crate::core::http::HttpFilter;
+use crate::core::mustache::Mustache;
 use crate::core::{grpc, http};
 
 #[derive(Clone, Debug, Display)]
@@ -22,12 +23,31 @@ pub enum IR {
     // TODO: Path can be implement using Pipe
     Path(Box<IR>, Vec<String>),
     ContextPath(Vec<String>),
-    Protect(Box<IR>),
+    Protect(blueprint::Protected, Box<IR>),
+    RateLimit(RateLimit),
+    Invalidate(Invalidate),
     Map(Map),
     Pipe(Box<IR>, Box<IR>),
     Discriminate(Discriminator, Box<IR>),
 }
 
+/// Limits the rate at which each client can evaluate the expression.
+#[derive(Clone, Debug)]
+pub struct RateLimit {
+    /// Name of the limited field e.g. `Query.users`.
+    pub name: String,
+    pub limit: blueprint::RateLimit,
+    pub expr: Box<IR>,
+}
+
+/// Purges the cached values with any of the tags once the expression is
+/// evaluated successfully.
+#[derive(Clone, Debug)]
+pub struct Invalidate {
+    pub tags: Vec<Mustache>,
+    pub expr: Box<IR>,
+}
+
 #[derive(Clone, Debug)]
 pub struct Map {
     pub input: Box<IR>,
@@ -42,17 +62,21 @@ pub enum IO {
         group_by: Option<GroupBy>,
         dl_id: Option<DataLoaderId>,
         http_filter: Option<HttpFilter>,
+        retry: Option<Retry>,
+        oauth2: Option<Box<OAuth2>>,
     },
     GraphQL {
         req_template: graphql::RequestTemplate,
         field_name: String,
         batch: bool,
         dl_id: Option<DataLoaderId>,
+        retry: Option<Retry>,
     },
     Grpc {
         req_template: grpc::RequestTemplate,
         group_by: Option<GroupBy>,
         dl_id: Option<DataLoaderId>,
+        retry: Option<Retry>,
     },
     Js {
         name: String,
@@ -92,17 +116,44 @@ pub trait CacheKey<Ctx> {
 #[derive(Clone, Debug)]
 pub struct Cache {
     pub max_age: NonZeroU64,
+    pub stale: Stale,
+    pub tags: Vec<Mustache>,
     pub io: Box<IO>,
 }
 
+/// Windows after the `max_age` of a cached value during which it's still
+/// served.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stale {
+    pub while_revalidate: Option<NonZeroU64>,
+    pub if_error: Option<NonZeroU64>,
+}
+
+impl Stale {
+    pub fn is_empty(&self) -> bool {
+        self.while_revalidate.is_none() && self.if_error.is_none()
+    }
+
+    /// Returns the longest window, during which the value has to be kept.
+    pub fn max_window(&self) -> u64 {
+        let window = |value: Option<NonZeroU64>| value.map_or(0, NonZeroU64::get);
+        window(self.while_revalidate).max(window(self.if_error))
+    }
+}
+
 impl Cache {
     ///
     /// Wraps an expression with the cache primitive.
     /// Performance DFS on the cache on the expression and identifies all the IO
     /// nodes. Then wraps each IO node with the cache primitive.
-    pub fn wrap(max_age: NonZeroU64, expr: IR) -> IR {
+    pub fn wrap(max_age: NonZeroU64, stale: Stale, tags: Vec<Mustache>, expr: IR) -> IR {
         expr.modify(move |expr| match expr {
-            IR::IO(io) => Some(IR::Cache(Cache { max_age, io: Box::new(io.to_owned()) })),
+            IR::IO(io) => Some(IR::Cache(Cache {
+                max_age,
+                stale,
+                tags: tags.clone(),
+                io: Box::new(io.to_owned()),
+            })),
             _ => None,
         })
     }
@@ -134,15 +185,25 @@ impl IR {
                     IR::ContextPath(path) => IR::ContextPath(path),
                     IR::Dynamic(_) => expr,
                     IR::IO(_) => expr,
-                    IR::Cache(Cache { io, max_age }) => {
+                    IR::Cache(Cache { io, max_age, stale, tags }) => {
                         let expr = *IR::IO(*io).modify_box(modifier);
                         match expr {
-                            IR::IO(io) => IR::Cache(Cache { io: Box::new(io), max_age }),
+                            IR::IO(io) => {
+                                IR::Cache(Cache { io: Box::new(io), max_age, stale, tags })
+                            }
                             expr => expr,
                         }
                     }
                     IR::Path(expr, path) => IR::Path(expr.modify_box(modifier), path),
-                    IR::Protect(expr) => IR::Protect(expr.modify_box(modifier)),
+                    IR::Protect(protected, expr) => {
+                        IR::Protect(protected, expr.modify_box(modifier))
+                    }
+                    IR::RateLimit(RateLimit { name, limit, expr }) => {
+                        IR::RateLimit(RateLimit { name, limit, expr: expr.modify_box(modifier) })
+                    }
+                    IR::Invalidate(Invalidate { tags, expr }) => {
+                        IR::Invalidate(Invalidate { tags, expr: expr.modify_box(modifier) })
+                    }
                     IR::Map(Map { input, map }) => {
                         IR::Map(Map { input: input.modify_box(modifier), map })
                     }
@@ -71,7 +71,48 @@ impl<'a> ResolverContextLike for ResolverContext<'a> {
     }
 }
 
-#[derive(Debug)]
+/// Owned copy of a resolver context, that is used by the tasks that outlive
+/// the request. The errors of the tasks aren't reported to the client.
+#[derive(Clone)]
+pub struct DetachedResolverContext {
+    value: Option<Value>,
+    args: Option<IndexMap<Name, Value>>,
+    field: Option<SelectionField>,
+    is_query: bool,
+}
+
+impl DetachedResolverContext {
+    pub fn new<Ctx: ResolverContextLike>(ctx: &Ctx) -> Self {
+        Self {
+            value: ctx.value().cloned(),
+            args: ctx.args().cloned(),
+            field: ctx.field(),
+            is_query: ctx.is_query(),
+        }
+    }
+}
+
+impl ResolverContextLike for DetachedResolverContext {
+    fn value(&self) -> Option<&Value> {
+        self.value.as_ref()
+    }
+
+    fn args(&self) -> Option<&IndexMap<Name, Value>> {
+        self.args.as_ref()
+    }
+
+    fn field(&self) -> Option<SelectionField> {
+        self.field.clone()
+    }
+
+    fn is_query(&self) -> bool {
+        self.is_query
+    }
+
+    fn add_error(&self, _: ServerError) {}
+}
+
+#[derive(Clone, Debug)]
 pub struct SelectionField {
     name: String,
     args: Vec<(String, String)>,
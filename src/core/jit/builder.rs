@@ -11,7 +11,7 @@ use async_graphql_value::{ConstValue, Value};
 use super::input_resolver::InputResolver;
 use super::model::{Directive as JitDirective, *};
 use super::BuildError;
-use crate::core::blueprint::{Blueprint, Index, QueryField};
+use crate::core::blueprint::{Blueprint, Index, QueryField, QueryLimits};
 use crate::core::counter::{Count, Counter};
 use crate::core::jit::model::OperationPlan;
 use crate::core::merge_right::MergeRight;
@@ -68,7 +68,9 @@ pub struct Builder {
     pub index: Index,
     pub arg_id: Counter<usize>,
     pub field_id: Counter<usize>,
+    pub defer_id: Counter<usize>,
     pub document: ExecutableDocument,
+    pub limits: QueryLimits,
 }
 
 // TODO: make generic over Value (Input) type
@@ -80,9 +82,76 @@ impl Builder {
             index,
             arg_id: Counter::default(),
             field_id: Counter::default(),
+            defer_id: Counter::default(),
+            limits: blueprint.server.query_limits.clone(),
         }
     }
 
+    /// Returns the directive if it's present and its `if` argument isn't
+    /// false.
+    #[inline(always)]
+    fn incremental_directive<'a>(
+        directives: &'a [Positioned<Directive>],
+        name: &str,
+        variables: &Variables<ConstValue>,
+    ) -> Option<&'a Directive> {
+        let directive = directives
+            .iter()
+            .find(|d| d.node.name.node.as_str() == name)
+            .map(|d| &d.node)?;
+        let enabled = match directive.get_argument("if").map(|pos| &pos.node) {
+            Some(Value::Boolean(bool)) => *bool,
+            Some(Value::Variable(var)) => variables
+                .get(var.as_str())
+                .and_then(|value| match value {
+                    ConstValue::Boolean(bool) => Some(*bool),
+                    _ => None,
+                })
+                .unwrap_or(true),
+            _ => true,
+        };
+
+        enabled.then_some(directive)
+    }
+
+    #[inline(always)]
+    fn label(directive: &Directive) -> Option<String> {
+        match directive.get_argument("label").map(|pos| &pos.node) {
+            Some(Value::String(label)) => Some(label.clone()),
+            _ => None,
+        }
+    }
+
+    /// Marks the fields of the fragment as deferred when the fragment has the
+    /// `@defer` directive. Nested incremental directives are delivered
+    /// together with their parent, so they are ignored.
+    #[inline(always)]
+    fn defer(
+        &self,
+        mut fields: Vec<Field<Flat, Value>>,
+        directives: &[Positioned<Directive>],
+        exts: &Option<Flat>,
+        variables: &Variables<ConstValue>,
+        incremental: bool,
+    ) -> Vec<Field<Flat, Value>> {
+        if incremental {
+            return fields;
+        }
+        if let Some(directive) = Self::incremental_directive(directives, "defer", variables) {
+            let defer = Defer {
+                id: DeferId::new(self.defer_id.next()),
+                label: Self::label(directive),
+            };
+            let parent = exts.as_ref().map(|flat| flat.as_id());
+            for field in fields.iter_mut() {
+                if field.extensions.as_ref().map(|flat| flat.as_id()) == parent {
+                    field.defer = Some(defer.clone());
+                }
+            }
+        }
+        fields
+    }
+
     #[inline(always)]
     fn include(
         &self,
@@ -132,6 +201,8 @@ impl Builder {
         type_of: &str,
         exts: Option<Flat>,
         fragments: &HashMap<&str, &FragmentDefinition>,
+        variables: &Variables<ConstValue>,
+        incremental: bool,
     ) -> Vec<Field<Flat, Value>> {
         let mut fields = vec![];
         for selection in &selection.items {
@@ -148,7 +219,7 @@ impl Builder {
                     let mut directives = Vec::with_capacity(gql_field.directives.len());
                     for directive in &gql_field.directives {
                         let directive = &directive.node;
-                        if directive.name.node == "skip" || directive.name.node == "include" {
+                        if ["skip", "include", "stream"].contains(&directive.name.node.as_str()) {
                             continue;
                         }
                         let arguments = directive
@@ -198,12 +269,40 @@ impl Builder {
                             QueryField::InputField(field_def) => field_def.of_type.clone(),
                         };
 
+                        let stream = if incremental || !type_of.is_list() {
+                            None
+                        } else {
+                            Self::incremental_directive(&gql_field.directives, "stream", variables)
+                                .map(|directive| Stream {
+                                    label: Self::label(directive),
+                                    initial_count: match directive
+                                        .get_argument("initialCount")
+                                        .map(|pos| &pos.node)
+                                    {
+                                        Some(Value::Number(count)) => {
+                                            count.as_u64().unwrap_or_default() as usize
+                                        }
+                                        Some(Value::Variable(var)) => variables
+                                            .get(var.as_str())
+                                            .and_then(|value| match value {
+                                                ConstValue::Number(count) => count.as_u64(),
+                                                _ => None,
+                                            })
+                                            .unwrap_or_default()
+                                            as usize,
+                                        _ => 0,
+                                    },
+                                })
+                        };
+
                         let id = FieldId::new(self.field_id.next());
                         let child_fields = self.iter(
                             &gql_field.selection_set.node,
                             type_of.name(),
                             Some(Flat::new(id.clone())),
                             fragments,
+                            variables,
+                            incremental || stream.is_some(),
                         );
                         let name = gql_field
                             .alias
@@ -226,6 +325,8 @@ impl Builder {
                             pos: selection.pos,
                             extensions: exts.clone(),
                             directives,
+                            defer: None,
+                            stream,
                         };
 
                         fields.push(flat_field);
@@ -238,15 +339,60 @@ impl Builder {
                     if let Some(fragment) =
                         fragments.get(fragment_spread.fragment_name.node.as_str())
                     {
-                        fields.extend(self.iter(
+                        let deferred = Self::incremental_directive(
+                            &fragment_spread.directives,
+                            "defer",
+                            variables,
+                        )
+                        .is_some();
+                        let fragment_fields = self.iter(
                             &fragment.selection_set.node,
                             fragment.type_condition.node.on.node.as_str(),
                             exts.clone(),
                             fragments,
+                            variables,
+                            incremental || deferred,
+                        );
+                        fields.extend(self.defer(
+                            fragment_fields,
+                            &fragment_spread.directives,
+                            &exts,
+                            variables,
+                            incremental,
                         ));
                     }
                 }
-                _ => {}
+                Selection::InlineFragment(Positioned { node: fragment, .. }) => {
+                    // the fragments on other types than the parent one would leak their
+                    // fields into every value of the parent
+                    let type_condition = fragment
+                        .type_condition
+                        .as_ref()
+                        .map(|condition| condition.node.on.node.as_str());
+                    if type_condition
+                        .is_some_and(|condition| !self.index.is_type_of(type_of, condition))
+                    {
+                        continue;
+                    }
+                    let deferred =
+                        Self::incremental_directive(&fragment.directives, "defer", variables)
+                            .is_some();
+                    let fragment_fields = self.iter(
+                        &fragment.selection_set.node,
+                        type_of,
+                        exts.clone(),
+                        fragments,
+                        variables,
+                        incremental || deferred,
+                    );
+                    fields.extend(self.defer(
+                        fragment_fields,
+                        &fragment.directives,
+                        &exts,
+                        variables,
+                        incremental,
+                    ));
+                }
             }
         }
 
@@ -303,11 +449,22 @@ impl Builder {
         }
 
         let operation = self.get_operation(operation_name)?;
+        self.limits
+            .check(&self.index, &self.document, operation, |name| {
+                variables.get(name)
+            })?;
 
         let name = self
             .get_type(operation.ty)
             .ok_or(BuildError::RootOperationTypeNotDefined { operation: operation.ty })?;
-        fields.extend(self.iter(&operation.selection_set.node, name, None, &fragments));
+        fields.extend(self.iter(
+            &operation.selection_set.node,
+            name,
+            None,
+            &fragments,
+            variables,
+            false,
+        ));
 
         // skip the fields depending on variables.
         fields.retain(|f| !f.skip(variables));
@@ -504,6 +661,29 @@ mod tests {
         insta::assert_debug_snapshot!(plan.into_nested());
     }
 
+    #[test]
+    fn test_inline_fragment_type_condition() {
+        let plan = plan(
+            r#"
+            query {
+                posts {
+                    id
+                    ... on Post { title }
+                    ... on User { name }
+                }
+            }
+            "#,
+            &Variables::new(),
+        );
+
+        let fields = plan
+            .as_parent()
+            .iter()
+            .map(|field| field.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(fields, vec!["posts", "id", "title"]);
+    }
+
     #[test]
     fn test_default_value() {
         let plan = plan(
@@ -645,4 +825,68 @@ mod tests {
         assert!(plan.is_query());
         insta::assert_debug_snapshot!(plan.into_nested());
     }
+
+    #[test]
+    fn test_defer() {
+        let plan = plan(
+            r#"
+            query {
+                posts {
+                    id
+                    ... @defer(label: "author") {
+                        user { id name }
+                    }
+                    ... on Post @defer(if: false) { title }
+                }
+            }
+            "#,
+            &Variables::new(),
+        );
+
+        let user = plan.as_parent().iter().find(|f| f.name == "user").unwrap();
+        let defer = user.defer.as_ref().unwrap();
+        assert_eq!(defer.label.as_deref(), Some("author"));
+        assert_eq!(plan.incremental_phases(), vec![Phase::Deferred(defer.id)]);
+
+        // fields nested in a deferred fragment are delivered with it
+        let name = plan.as_parent().iter().find(|f| f.name == "name").unwrap();
+        assert!(name.defer.is_none());
+
+        let title = plan.as_parent().iter().find(|f| f.name == "title").unwrap();
+        assert!(title.defer.is_none());
+        assert!(Phase::Initial.includes(title));
+        assert!(!Phase::Initial.includes(user));
+    }
+
+    #[test]
+    fn test_stream() {
+        let mut variables = Variables::new();
+        variables.insert("count".to_string(), ConstValue::from(2));
+
+        let plan = plan(
+            r#"
+            query($count: Int!) {
+                posts @stream(initialCount: $count, label: "posts") { id }
+                users @stream(if: false) { id }
+            }
+            "#,
+            &variables,
+        );
+
+        let posts = plan.as_parent().iter().find(|f| f.name == "posts").unwrap();
+        let stream = posts.stream.as_ref().unwrap();
+        assert_eq!(stream.initial_count, 2);
+        assert_eq!(stream.label.as_deref(), Some("posts"));
+        assert!(posts.directives.is_empty());
+        assert_eq!(
+            plan.incremental_phases(),
+            vec![Phase::Stream(posts.id.clone())]
+        );
+
+        assert_eq!(Phase::Initial.items(posts, 5), 0..2);
+        assert_eq!(Phase::Stream(posts.id.clone()).items(posts, 5), 2..5);
+
+        let users = plan.as_parent().iter().find(|f| f.name == "users").unwrap();
+        assert!(users.stream.is_none());
+    }
 }
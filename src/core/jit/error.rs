@@ -2,6 +2,7 @@ use async_graphql::parser::types::OperationType;
 use async_graphql::{ErrorExtensions, PathSegment, Pos, Positioned, ServerError};
 use thiserror::Error;
 
+use crate::core::blueprint::LimitError;
 use crate::core::lift::Lift;
 
 #[derive(Error, Debug, Clone, PartialEq, Eq)]
@@ -15,6 +16,8 @@ pub enum BuildError {
     OperationNotFound(String),
     #[error("Operation name required in request")]
     OperationNameRequired,
+    #[error("{0}")]
+    LimitError(#[from] LimitError),
 }
 
 #[derive(Error, Debug, Clone, PartialEq, Eq)]
@@ -7,13 +7,13 @@ use derive_getters::Getters;
 use futures_util::future::join_all;
 
 use super::context::Context;
-use super::{DataPath, OperationPlan, Request, Response, Store};
+use super::{DataPath, Field, Nested, OperationPlan, Phase, Request, Response, Store};
 use crate::core::ir::model::IR;
 use crate::core::jit;
 use crate::core::jit::synth::Synth;
 use crate::core::json::{JsonLike, JsonObjectLike};
 
-type SharedStore<Output, Error> = Arc<Mutex<Store<Result<Output, Positioned<Error>>>>>;
+pub type SharedStore<Output, Error> = Arc<Mutex<Store<Result<Output, Positioned<Error>>>>>;
 
 ///
 /// Default GraphQL executor that takes in a GraphQL Request and produces a
@@ -39,13 +39,25 @@ where
         request: Request<Input>,
     ) -> Store<Result<Output, Positioned<jit::Error>>> {
         let store = Arc::new(Mutex::new(Store::new()));
-        let mut ctx = ExecutorInner::new(request, store.clone(), self.plan.to_owned(), &self.exec);
-        ctx.init().await;
+        self.resolve(request, Phase::Complete, store.clone()).await;
 
         let store = mem::replace(&mut *store.lock().unwrap(), Store::new());
         store
     }
 
+    /// Resolves the fields of the phase into the shared store. Values that
+    /// are already present in the store are reused instead of being resolved
+    /// again, so subsequent phases don't repeat the work of the previous ones.
+    pub async fn resolve(
+        &self,
+        request: Request<Input>,
+        phase: Phase,
+        store: SharedStore<Output, jit::Error>,
+    ) {
+        let mut ctx = ExecutorInner::new(request, store, self.plan.to_owned(), &self.exec, phase);
+        ctx.init().await;
+    }
+
     pub async fn execute(self, synth: Synth<Output>) -> Response<Output, jit::Error> {
         Response::new(synth.synthesize())
     }
@@ -57,12 +69,14 @@ struct ExecutorInner<'a, Input, Output, Error, Exec> {
     store: SharedStore<Output, Error>,
     plan: OperationPlan<Input>,
     ir_exec: &'a Exec,
+    phase: Phase,
 }
 
 impl<'a, Input, Output, Error, Exec> ExecutorInner<'a, Input, Output, Error, Exec>
 where
-    Output: for<'i> JsonLike<'i> + Debug,
+    Output: for<'i> JsonLike<'i> + Debug + Clone,
     Input: Clone + Debug,
+    Error: Clone,
     Exec: IRExecutor<Input = Input, Output = Output, Error = Error>,
 {
     fn new(
@@ -70,36 +84,52 @@ where
         store: SharedStore<Output, Error>,
         plan: OperationPlan<Input>,
         ir_exec: &'a Exec,
+        phase: Phase,
     ) -> Self {
-        Self { request, store, plan, ir_exec }
+        Self { request, store, plan, ir_exec, phase }
     }
 
     async fn init(&mut self) {
-        join_all(self.plan.as_nested().iter().map(|field| async {
-            let mut arg_map = indexmap::IndexMap::new();
-            for arg in field.args.iter() {
-                let name = arg.name.as_str();
-                let value: Option<Input> = arg
-                    .value
-                    .clone()
-                    // TODO: default value resolution should happen in the InputResolver
-                    .or_else(|| arg.default_value.clone());
-
-                if let Some(value) = value {
-                    arg_map.insert(name, value);
-                } else if !arg.type_of.is_nullable() {
-                    // TODO: throw error here
-                    todo!()
-                }
-            }
-            // TODO: with_args should be called on inside iter_field on any level, not only
-            // for root fields
-            let ctx = Context::new(&self.request, self.plan.is_query(), field).with_args(arg_map);
-            self.execute(&ctx, DataPath::new()).await
-        }))
+        let fields = self.plan.as_nested().iter();
+        join_all(
+            fields
+                .filter(|field| self.phase.includes(*field))
+                .map(|field| async {
+                    let mut arg_map = indexmap::IndexMap::new();
+                    for arg in field.args.iter() {
+                        let name = arg.name.as_str();
+                        let value: Option<Input> = arg
+                            .value
+                            .clone()
+                            // TODO: default value resolution should happen in the InputResolver
+                            .or_else(|| arg.default_value.clone());
+
+                        if let Some(value) = value {
+                            arg_map.insert(name, value);
+                        } else if !arg.type_of.is_nullable() {
+                            // TODO: throw error here
+                            todo!()
+                        }
+                    }
+                    // TODO: with_args should be called on inside iter_field on any level, not only
+                    // for root fields
+                    let ctx =
+                        Context::new(&self.request, self.plan.is_query(), field).with_args(arg_map);
+                    self.execute(&ctx, DataPath::new()).await
+                }),
+        )
         .await;
     }
 
+    fn nested<'b>(
+        &'b self,
+        field: &'b Field<Nested<Input>, Input>,
+    ) -> impl Iterator<Item = &'b Field<Nested<Input>, Input>> {
+        field
+            .nested_iter()
+            .filter(|field| self.phase.includes(*field))
+    }
+
     async fn iter_field<'b>(
         &'b self,
         ctx: &'b Context<'b, Input, Output>,
@@ -112,12 +142,15 @@ where
         if field.type_of.is_list() {
             // Check if the value is an array
             if let Some(array) = value.as_array() {
-                join_all(field.nested_iter().map(|field| {
-                    join_all(array.iter().enumerate().map(|(index, value)| {
-                        let ctx = ctx.with_value_and_field(value, field);
-                        let data_path = data_path.clone().with_index(index);
-                        async move { self.execute(&ctx, data_path).await }
-                    }))
+                let items = self.phase.items(field, array.len());
+                join_all(self.nested(field).map(|field| {
+                    join_all(array[items.clone()].iter().zip(items.clone()).map(
+                        |(value, index)| {
+                            let ctx = ctx.with_value_and_field(value, field);
+                            let data_path = data_path.clone().with_index(index);
+                            async move { self.execute(&ctx, data_path).await }
+                        },
+                    ))
                 }))
                 .await;
             }
@@ -128,7 +161,7 @@ where
         // TODO: Validate if the value is an Object
         // Has to be an Object, we don't do anything while executing if its a Scalar
         else {
-            join_all(field.nested_iter().map(|child| {
+            join_all(self.nested(field).map(|child| {
                 let ctx = ctx.with_value_and_field(value, child);
                 let data_path = data_path.clone();
                 async move { self.execute(&ctx, data_path).await }
@@ -147,6 +180,23 @@ where
         let field = ctx.field();
 
         if let Some(ir) = &field.ir {
+            let stored = match self.phase {
+                Phase::Complete => None,
+                _ => self
+                    .store
+                    .lock()
+                    .unwrap()
+                    .get_value(&field.id, &data_path)
+                    .cloned(),
+            };
+
+            if let Some(result) = stored {
+                if let Ok(ref value) = result {
+                    self.iter_field(ctx, &data_path, value).await?;
+                }
+                return Ok(());
+            }
+
             let result = self.ir_exec.execute(ir, ctx).await;
 
             if let Ok(ref value) = result {
@@ -1,10 +1,14 @@
 use std::sync::Arc;
 
+use async_graphql::{Name, PathSegment, Positioned};
 use async_graphql_value::ConstValue;
+use futures_util::future::ready;
+use futures_util::stream::{self, BoxStream, FuturesUnordered};
+use futures_util::StreamExt;
 
 use super::context::Context;
-use super::exec::{Executor, IRExecutor};
-use super::{Error, OperationPlan, Request, Response, Result};
+use super::exec::{Executor, IRExecutor, SharedStore};
+use super::{Error, Incremental, OperationPlan, Phase, Request, Response, Result};
 use crate::core::app_context::AppContext;
 use crate::core::http::RequestContext;
 use crate::core::ir::model::IR;
@@ -35,6 +39,220 @@ impl ConstValueExecutor {
         let synth = Synth::new(plan, store, vars);
         exe.execute(synth).await
     }
+
+    /// Executes the request delivering the fragments marked with `@defer` and
+    /// the items of the lists marked with `@stream` in subsequent payloads,
+    /// each one as soon as it's resolved.
+    pub fn execute_stream(
+        self,
+        req_ctx: Arc<RequestContext>,
+        request: Request<ConstValue>,
+    ) -> BoxStream<'static, Response<ConstValue, Error>> {
+        let plan = Arc::new(self.plan);
+        let store: SharedStore<ConstValue, Error> = Default::default();
+
+        let initial = resolve(
+            plan.clone(),
+            store.clone(),
+            req_ctx.clone(),
+            request.clone(),
+            Phase::Initial,
+        );
+
+        stream::once(initial)
+            .flat_map(move |result| {
+                let mut initial = Response::new(result);
+                let phases = if initial.data.is_some() {
+                    plan.incremental_phases()
+                } else {
+                    Vec::new()
+                };
+                let total = phases.len();
+                initial.has_next = Some(total > 0);
+
+                let subsequent = phases
+                    .into_iter()
+                    .map(|phase| {
+                        let plan = plan.clone();
+                        let result = resolve(
+                            plan.clone(),
+                            store.clone(),
+                            req_ctx.clone(),
+                            request.clone(),
+                            phase.clone(),
+                        );
+                        async move {
+                            Response::from_incremental(incremental(&plan, &phase, result.await))
+                        }
+                    })
+                    .collect::<FuturesUnordered<_>>()
+                    .enumerate()
+                    .map(move |(index, mut response)| {
+                        response.has_next = Some(index + 1 < total);
+                        response
+                    });
+
+                stream::once(ready(initial)).chain(subsequent)
+            })
+            .boxed()
+    }
+}
+
+/// Resolves the phase into the shared store and synthesizes the value with
+/// everything that is resolved up to it.
+async fn resolve(
+    plan: Arc<OperationPlan<ConstValue>>,
+    store: SharedStore<ConstValue, Error>,
+    req_ctx: Arc<RequestContext>,
+    request: Request<ConstValue>,
+    phase: Phase,
+) -> std::result::Result<ConstValue, Positioned<Error>> {
+    let vars = request.variables.clone();
+    let exec = ConstValueExec::new(&req_ctx);
+    let exe = Executor::new(plan.as_ref().clone(), exec);
+    exe.resolve(request, phase.clone(), store.clone()).await;
+
+    let store = store.lock().unwrap().clone();
+    Synth::new(plan.as_ref().clone(), store, vars)
+        .phase(phase)
+        .synthesize()
+}
+
+/// Extracts the results that are delivered by the phase from the synthesized
+/// value.
+fn incremental(
+    plan: &OperationPlan<ConstValue>,
+    phase: &Phase,
+    result: std::result::Result<ConstValue, Positioned<Error>>,
+) -> Vec<Incremental<ConstValue, Positioned<Error>>> {
+    let value = match result {
+        Ok(value) => value,
+        Err(error) => {
+            return vec![Incremental {
+                data: None,
+                items: None,
+                path: Vec::new(),
+                label: None,
+                errors: vec![error],
+            }]
+        }
+    };
+
+    match phase {
+        Phase::Deferred(id) => {
+            let fields = plan
+                .as_parent()
+                .iter()
+                .filter(|field| field.defer.as_ref().is_some_and(|defer| defer.id == *id))
+                .collect::<Vec<_>>();
+            let Some(defer) = fields.first().and_then(|field| field.defer.as_ref()) else {
+                return Vec::new();
+            };
+            let ancestors = fields[0]
+                .extensions
+                .as_ref()
+                .map(|parent| plan.find_ancestors(parent.as_id()))
+                .unwrap_or_default();
+            let names = ancestors
+                .iter()
+                .map(|field| field.name.as_str())
+                .collect::<Vec<_>>();
+
+            instances(&value, &names, Vec::new(), true)
+                .into_iter()
+                .filter_map(|(path, parent)| {
+                    let ConstValue::Object(parent) = parent else {
+                        return None;
+                    };
+                    let data = fields
+                        .iter()
+                        .filter_map(|field| {
+                            let value = parent.get(field.name.as_str())?;
+                            Some((Name::new(&field.name), value.clone()))
+                        })
+                        .collect();
+
+                    Some(Incremental {
+                        data: Some(ConstValue::Object(data)),
+                        items: None,
+                        path,
+                        label: defer.label.clone(),
+                        errors: Vec::new(),
+                    })
+                })
+                .collect()
+        }
+        Phase::Stream(id) => {
+            let ancestors = plan.find_ancestors(id);
+            let Some(stream) = ancestors.last().and_then(|field| field.stream.as_ref()) else {
+                return Vec::new();
+            };
+            let names = ancestors
+                .iter()
+                .map(|field| field.name.as_str())
+                .collect::<Vec<_>>();
+
+            instances(&value, &names, Vec::new(), false)
+                .into_iter()
+                .filter_map(|(mut path, list)| {
+                    let ConstValue::List(list) = list else {
+                        return None;
+                    };
+                    let items = list.get(stream.initial_count..)?;
+                    if items.is_empty() {
+                        return None;
+                    }
+                    path.push(PathSegment::Index(stream.initial_count));
+
+                    Some(Incremental {
+                        data: None,
+                        items: Some(items.to_vec()),
+                        path,
+                        label: stream.label.clone(),
+                        errors: Vec::new(),
+                    })
+                })
+                .collect()
+        }
+        Phase::Complete | Phase::Initial => Vec::new(),
+    }
+}
+
+/// Finds all the values located at the path of field names along with their
+/// exact paths. Lists in the middle of the path are traversed item by item,
+/// lists at the end only when `flatten` is set.
+fn instances<'a>(
+    value: &'a ConstValue,
+    names: &[&str],
+    path: Vec<PathSegment>,
+    flatten: bool,
+) -> Vec<(Vec<PathSegment>, &'a ConstValue)> {
+    if let ConstValue::List(list) = value {
+        if flatten || !names.is_empty() {
+            return list
+                .iter()
+                .enumerate()
+                .flat_map(|(index, item)| {
+                    let mut path = path.clone();
+                    path.push(PathSegment::Index(index));
+                    instances(item, names, path, flatten)
+                })
+                .collect();
+        }
+    }
+
+    match (value, names.split_first()) {
+        (_, None) => vec![(path, value)],
+        (ConstValue::Object(object), Some((name, names))) => match object.get(*name) {
+            Some(child) => {
+                let mut path = path;
+                path.push(PathSegment::Field(name.to_string()));
+                instances(child, names, path, flatten)
+            }
+            None => Vec::new(),
+        },
+        _ => Vec::new(),
+    }
 }
 
 struct ConstValueExec<'a> {
@@ -63,3 +281,45 @@ impl<'ctx> IRExecutor for ConstValueExec<'ctx> {
         Ok(ir.eval(&mut ctx).await?)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use async_graphql::PathSegment;
+    use async_graphql_value::ConstValue;
+    use serde_json::json;
+
+    use super::instances;
+
+    #[test]
+    fn test_instances() {
+        let value = ConstValue::from_json(json!({
+            "posts": [
+                { "user": { "id": 1 } },
+                { "user": { "id": 2 } }
+            ]
+        }))
+        .unwrap();
+
+        let found = instances(&value, &["posts", "user"], Vec::new(), false);
+        let paths = found.into_iter().map(|(path, _)| path).collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            vec![
+                vec![
+                    PathSegment::Field("posts".to_string()),
+                    PathSegment::Index(0),
+                    PathSegment::Field("user".to_string()),
+                ],
+                vec![
+                    PathSegment::Field("posts".to_string()),
+                    PathSegment::Index(1),
+                    PathSegment::Field("user".to_string()),
+                ],
+            ]
+        );
+
+        // lists at the end of the path are only traversed when flattened
+        assert_eq!(instances(&value, &["posts"], Vec::new(), false).len(), 1);
+        assert_eq!(instances(&value, &["posts"], Vec::new(), true).len(), 2);
+    }
+}
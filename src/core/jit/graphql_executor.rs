@@ -1,10 +1,13 @@
-use std::future::Future;
+use std::future::{ready, Future};
 use std::sync::Arc;
 
+use async_graphql::parser::types::OperationType;
 use async_graphql::{Data, Executor, Response};
-use futures_util::stream::BoxStream;
+use futures_util::stream::{self, BoxStream};
+use futures_util::StreamExt;
 
 use crate::core::app_context::AppContext;
+use crate::core::async_graphql_hyper::GraphQLStreamResponse;
 use crate::core::http::RequestContext;
 use crate::core::jit;
 use crate::core::jit::ConstValueExecutor;
@@ -19,6 +22,46 @@ impl JITExecutor {
     pub fn new(app_ctx: Arc<AppContext>, req_ctx: Arc<RequestContext>) -> Self {
         Self { app_ctx, req_ctx }
     }
+
+    /// Executes the request sending the initial result first and then the
+    /// results of every `@defer` and `@stream` as they are resolved.
+    pub fn execute_incremental(
+        &self,
+        mut request: async_graphql::Request,
+    ) -> GraphQLStreamResponse {
+        if is_subscription(&mut request) {
+            return GraphQLStreamResponse::new(Executor::execute_stream(self, request, None));
+        }
+
+        let request = jit::Request::from(request);
+        match ConstValueExecutor::new(&request, self.app_ctx.clone()) {
+            Ok(exec) => GraphQLStreamResponse::new(
+                exec.execute_stream(self.req_ctx.clone(), request)
+                    .map(|response| response.into_incremental_payload()),
+            ),
+            Err(error) => {
+                GraphQLStreamResponse::new(stream::once(ready(Response::from_errors(vec![
+                    error.into_server_error()
+                ]))))
+            }
+        }
+    }
+}
+
+/// Checks if the operation selected by the request is a subscription. The
+/// other operations of the document are ignored.
+fn is_subscription(request: &mut async_graphql::Request) -> bool {
+    let operation_name = request.operation_name.clone();
+    request.parsed_query().is_ok_and(|document| {
+        let mut operations = document.operations.iter();
+        let operation = match operation_name.as_deref() {
+            Some(operation_name) => operations
+                .find(|(name, _)| name.is_some_and(|name| name.as_str() == operation_name)),
+            // without a name, the document must have a single operation
+            None => operations.next().filter(|_| operations.next().is_none()),
+        };
+        operation.is_some_and(|(_, operation)| operation.node.ty == OperationType::Subscription)
+    })
 }
 
 impl Executor for JITExecutor {
@@ -36,11 +79,42 @@ impl Executor for JITExecutor {
         }
     }
 
+    /// Subscriptions aren't compiled by the JIT engine yet, so they are
+    /// delegated to the schema while every other operation produces a single
+    /// response.
     fn execute_stream(
         &self,
-        _: async_graphql::Request,
-        _: Option<Arc<Data>>,
+        mut request: async_graphql::Request,
+        session_data: Option<Arc<Data>>,
     ) -> BoxStream<'static, Response> {
-        unimplemented!("streaming not supported")
+        if is_subscription(&mut request) {
+            let request = request.data(self.req_ctx.clone());
+            Executor::execute_stream(&self.app_ctx.schema, request, session_data)
+        } else {
+            let executor = self.clone();
+            stream::once(async move { executor.execute(request).await }).boxed()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOCUMENT: &str = "query Users { users { id } } subscription News { news { id } }";
+
+    #[test]
+    fn test_is_subscription() {
+        let mut request = async_graphql::Request::new(DOCUMENT).operation_name("News");
+        assert!(is_subscription(&mut request));
+
+        let mut request = async_graphql::Request::new(DOCUMENT).operation_name("Users");
+        assert!(!is_subscription(&mut request));
+
+        let mut request = async_graphql::Request::new(DOCUMENT);
+        assert!(!is_subscription(&mut request));
+
+        let mut request = async_graphql::Request::new("subscription { news { id } }");
+        assert!(is_subscription(&mut request));
     }
 }
@@ -115,6 +115,81 @@ pub struct Field<Extensions, Input> {
     pub pos: Pos,
     pub is_scalar: bool,
     pub directives: Vec<Directive<Input>>,
+    /// Set for the fields of a fragment marked with `@defer`
+    pub defer: Option<Defer>,
+    /// Set for the list fields marked with `@stream`
+    pub stream: Option<Stream>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DeferId(usize);
+
+impl Debug for DeferId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl DeferId {
+    pub fn new(id: usize) -> Self {
+        DeferId(id)
+    }
+}
+
+/// Fragment that is delivered in a subsequent payload
+#[derive(Clone, Debug, PartialEq)]
+pub struct Defer {
+    pub id: DeferId,
+    pub label: Option<String>,
+}
+
+/// List field whose items after `initial_count` are delivered in a subsequent
+/// payload
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stream {
+    pub label: Option<String>,
+    pub initial_count: usize,
+}
+
+/// Part of the operation that is resolved by a single pass of the executor.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Phase {
+    /// Resolves the whole operation ignoring `@defer` and `@stream`
+    #[default]
+    Complete,
+    /// Resolves everything except deferred fragments and streamed items
+    Initial,
+    /// Resolves the fields of the deferred fragment
+    Deferred(DeferId),
+    /// Resolves the remaining items of the streamed field
+    Stream(FieldId),
+}
+
+impl Phase {
+    /// Checks if the field is resolved in this phase or is an ancestor of the
+    /// resolved fields.
+    pub fn includes<Extensions, Input>(&self, field: &Field<Extensions, Input>) -> bool {
+        match (self, &field.defer) {
+            (Phase::Complete, _) | (_, None) => true,
+            (Phase::Deferred(id), Some(defer)) => defer.id == *id,
+            _ => false,
+        }
+    }
+
+    /// Range of the list items of the field resolved in this phase.
+    pub fn items<Extensions, Input>(
+        &self,
+        field: &Field<Extensions, Input>,
+        len: usize,
+    ) -> std::ops::Range<usize> {
+        match (self, &field.stream) {
+            (Phase::Complete, _) | (_, None) => 0..len,
+            (Phase::Stream(id), Some(stream)) if *id == field.id => {
+                stream.initial_count.min(len)..len
+            }
+            (_, Some(stream)) => 0..stream.initial_count.min(len),
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -167,6 +242,8 @@ impl<Input> Field<Nested<Input>, Input> {
                 .into_iter()
                 .map(|directive| directive.try_map(map))
                 .collect::<Result<_, _>>()?,
+            defer: self.defer,
+            stream: self.stream,
         })
     }
 }
@@ -196,6 +273,8 @@ impl<Input> Field<Flat, Input> {
                 .into_iter()
                 .map(|directive| directive.try_map(&map))
                 .collect::<Result<_, _>>()?,
+            defer: self.defer,
+            stream: self.stream,
         })
     }
 }
@@ -249,6 +328,8 @@ impl<Input> Field<Flat, Input> {
             extensions,
             is_scalar: self.is_scalar,
             directives: self.directives,
+            defer: self.defer,
+            stream: self.stream,
         }
     }
 }
@@ -276,6 +357,12 @@ impl<Extensions: Debug, Input: Debug> Debug for Field<Extensions, Input> {
             debug_struct.field("include", &self.include);
         }
         debug_struct.field("directives", &self.directives);
+        if self.defer.is_some() {
+            debug_struct.field("defer", &self.defer);
+        }
+        if self.stream.is_some() {
+            debug_struct.field("stream", &self.stream);
+        }
         debug_struct.finish()
     }
 }
@@ -388,6 +475,37 @@ impl<Input> OperationPlan<Input> {
     pub fn size(&self) -> usize {
         self.flat.len()
     }
+
+    /// Phases that resolve the deferred fragments and streamed items after
+    /// the initial payload.
+    pub fn incremental_phases(&self) -> Vec<Phase> {
+        let mut phases = Vec::new();
+        for field in self.flat.iter() {
+            if let Some(defer) = &field.defer {
+                let phase = Phase::Deferred(defer.id);
+                if !phases.contains(&phase) {
+                    phases.push(phase);
+                }
+            }
+            if field.stream.is_some() {
+                phases.push(Phase::Stream(field.id.clone()));
+            }
+        }
+        phases
+    }
+
+    /// Returns the field along with all of its ancestors starting from the
+    /// root.
+    pub fn find_ancestors(&self, id: &FieldId) -> Vec<&Field<Flat, Input>> {
+        let mut fields = Vec::new();
+        let mut current = self.find_field(id.clone());
+        while let Some(field) = current {
+            fields.push(field);
+            current = field.parent().and_then(|id| self.find_field(id.clone()));
+        }
+        fields.reverse();
+        fields
+    }
 }
 
 #[derive(Clone, Debug)]
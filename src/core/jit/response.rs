@@ -1,4 +1,4 @@
-use async_graphql::{Positioned, ServerError};
+use async_graphql::{PathSegment, Positioned, ServerError};
 use derive_setters::Setters;
 use serde::Serialize;
 
@@ -6,6 +6,7 @@ use crate::core::jit;
 use crate::core::lift::Lift;
 
 #[derive(Setters, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Response<Value, Error> {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub data: Option<Value>,
@@ -13,6 +14,40 @@ pub struct Response<Value, Error> {
     pub errors: Vec<Positioned<Error>>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub extensions: Vec<(String, Value)>,
+    /// Results of the deferred fragments and streamed items
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub incremental: Vec<Incremental<Value, Positioned<Error>>>,
+    /// Set when the response is delivered incrementally
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub has_next: Option<bool>,
+}
+
+/// Result of a deferred fragment or of the remaining items of a streamed list
+/// that is delivered after the initial response.
+#[derive(Debug, Serialize)]
+pub struct Incremental<Value, Error> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items: Option<Vec<Value>>,
+    pub path: Vec<PathSegment>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<Error>,
+}
+
+/// Serializable payload of an incrementally delivered response
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IncrementalPayload {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<async_graphql::Value>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<ServerError>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub incremental: Vec<Incremental<async_graphql::Value, ServerError>>,
+    pub has_next: bool,
 }
 
 impl<Value, Error> Response<Value, Error> {
@@ -22,8 +57,26 @@ impl<Value, Error> Response<Value, Error> {
                 data: Some(value),
                 errors: Vec::new(),
                 extensions: Vec::new(),
+                incremental: Vec::new(),
+                has_next: None,
+            },
+            Err(error) => Response {
+                data: None,
+                errors: vec![error],
+                extensions: Vec::new(),
+                incremental: Vec::new(),
+                has_next: None,
             },
-            Err(error) => Response { data: None, errors: vec![error], extensions: Vec::new() },
+        }
+    }
+
+    pub fn from_incremental(incremental: Vec<Incremental<Value, Positioned<Error>>>) -> Self {
+        Response {
+            data: None,
+            errors: Vec::new(),
+            extensions: Vec::new(),
+            incremental,
+            has_next: None,
         }
     }
 }
@@ -39,4 +92,29 @@ impl Response<async_graphql::Value, jit::Error> {
         }
         resp
     }
+
+    pub fn into_incremental_payload(self) -> IncrementalPayload {
+        let into_server_error = |error| Lift::<ServerError>::from(error).take();
+
+        IncrementalPayload {
+            data: self.data,
+            errors: self.errors.into_iter().map(into_server_error).collect(),
+            incremental: self
+                .incremental
+                .into_iter()
+                .map(|incremental| Incremental {
+                    data: incremental.data,
+                    items: incremental.items,
+                    path: incremental.path,
+                    label: incremental.label,
+                    errors: incremental
+                        .errors
+                        .into_iter()
+                        .map(into_server_error)
+                        .collect(),
+                })
+                .collect(),
+            has_next: self.has_next.unwrap_or_default(),
+        }
+    }
 }
@@ -34,7 +34,7 @@ impl DataPath {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Store<A> {
     data: HashMap<usize, Data<A>>,
 }
@@ -119,4 +119,20 @@ impl<A> Store<A> {
     pub fn get(&self, field_id: &FieldId) -> Option<&Data<A>> {
         self.data.get(&field_id.as_usize())
     }
+
+    /// Returns the value computed for the field at the specified path.
+    pub fn get_value(&self, field_id: &FieldId, path: &DataPath) -> Option<&A> {
+        let mut data = self.get(field_id)?;
+        for index in path.as_slice() {
+            match data {
+                Data::Multiple(map) => data = map.get(index)?,
+                _ => return None,
+            }
+        }
+
+        match data {
+            Data::Single(value) => Some(value),
+            _ => None,
+        }
+    }
 }
@@ -1,6 +1,6 @@
 use async_graphql::Positioned;
 
-use crate::core::jit::model::{Field, Nested, OperationPlan, Variable, Variables};
+use crate::core::jit::model::{Field, Nested, OperationPlan, Phase, Variable, Variables};
 use crate::core::jit::store::{Data, DataPath, Store};
 use crate::core::jit::{Error, ValidationError};
 use crate::core::json::{JsonLike, JsonObjectLike};
@@ -10,6 +10,7 @@ pub struct Synth<Value> {
     selection: Vec<Field<Nested<Value>, Value>>,
     store: Store<Result<Value, Positioned<Error>>>,
     variables: Variables<Value>,
+    phase: Phase,
 }
 
 impl<Extensions, Input> Field<Extensions, Input> {
@@ -37,7 +38,17 @@ impl<Value> Synth<Value> {
         store: Store<Result<Value, Positioned<Error>>>,
         variables: Variables<Value>,
     ) -> Self {
-        Self { selection: plan.into_nested(), store, variables }
+        Self {
+            selection: plan.into_nested(),
+            store,
+            variables,
+            phase: Phase::default(),
+        }
+    }
+
+    /// Limits the synthesized value to the fields resolved up to the phase.
+    pub fn phase(self, phase: Phase) -> Self {
+        Self { phase, ..self }
     }
 }
 
@@ -48,7 +59,7 @@ where
 {
     #[inline(always)]
     fn include<T>(&self, field: &Field<T, Value>) -> bool {
-        !field.skip(&self.variables)
+        !field.skip(&self.variables) && self.phase.includes(field)
     }
 
     #[inline(always)]
@@ -200,7 +211,8 @@ where
                 (Some(arr), _) => {
                     let mut ans = vec![];
                     if include {
-                        for (i, val) in arr.iter().enumerate() {
+                        let items = self.phase.items(node, arr.len());
+                        for (i, val) in arr.iter().enumerate().take(items.end) {
                             let val =
                                 self.iter_inner(node, val, &data_path.clone().with_index(i))?;
                             ans.push(val)
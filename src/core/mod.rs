@@ -29,12 +29,15 @@ pub mod path;
 pub mod primitive;
 pub mod print_schema;
 pub mod proto_reader;
+pub mod rate_limit;
 pub mod resource_reader;
 pub mod rest;
 pub mod runtime;
 pub mod scalar;
+pub mod schema_diff;
 pub mod schema_extension;
 mod serde_value_ext;
+pub mod subgraph;
 pub mod tracing;
 mod transform;
 pub mod try_fold;
@@ -48,6 +51,8 @@ use std::num::NonZeroU64;
 
 use async_graphql_value::ConstValue;
 pub use error::{Error, Result};
+use futures_util::stream::BoxStream;
+use futures_util::StreamExt;
 use http::Response;
 use ir::model::IoId;
 pub use mustache::Mustache;
@@ -58,12 +63,47 @@ pub trait EnvIO: Send + Sync + 'static {
     fn get(&self, key: &str) -> Option<Cow<'_, str>>;
 }
 
+/// A frame of a body that is delivered as it arrives from the upstream.
+#[derive(Debug)]
+pub enum Frame {
+    Data(hyper::body::Bytes),
+    /// Sent after the last chunk of data, gRPC upstreams use them to report the
+    /// status of a stream.
+    Trailers(hyper::HeaderMap),
+}
+
+impl Frame {
+    pub fn into_data(self) -> Option<hyper::body::Bytes> {
+        match self {
+            Frame::Data(data) => Some(data),
+            Frame::Trailers(_) => None,
+        }
+    }
+}
+
+/// A body that is delivered in frames as they arrive from the upstream.
+pub type BodyStream = BoxStream<'static, anyhow::Result<Frame>>;
+
 #[async_trait::async_trait]
 pub trait HttpIO: Sync + Send + 'static {
     async fn execute(
         &self,
         request: reqwest::Request,
     ) -> anyhow::Result<Response<hyper::body::Bytes>>;
+
+    /// Executes the request without waiting for the whole body, used for
+    /// long-lived upstream streams like Server-Sent Events. Runtimes that can't
+    /// stream responses fallback to a single chunk containing the whole body.
+    async fn execute_stream(
+        &self,
+        request: reqwest::Request,
+    ) -> anyhow::Result<Response<BodyStream>> {
+        let response = self.execute(request).await?;
+        let body =
+            futures_util::stream::once(async move { Ok(Frame::Data(response.body)) }).boxed();
+
+        Ok(Response { status: response.status, headers: response.headers, body })
+    }
 }
 
 #[async_trait::async_trait]
@@ -84,11 +124,36 @@ pub trait Cache: Send + Sync {
     ) -> Result<(), cache::Error>;
     async fn get<'a>(&'a self, key: &'a Self::Key) -> Result<Option<Self::Value>, cache::Error>;
 
+    /// Associates the entry of the key with tags until it expires, so that it
+    /// is removed when one of them is purged.
+    async fn tag<'a>(
+        &'a self,
+        key: &'a Self::Key,
+        tags: &'a [String],
+        ttl: NonZeroU64,
+    ) -> Result<(), cache::Error>;
+
+    /// Removes the entry of the key, and returns whether it was present.
+    async fn delete<'a>(&'a self, key: &'a Self::Key) -> Result<bool, cache::Error>;
+
+    /// Removes the entries associated with any of the tags, and returns their
+    /// number.
+    async fn purge<'a>(&'a self, tags: &'a [String]) -> Result<usize, cache::Error>;
+
     fn hit_rate(&self) -> Option<f64>;
 }
 
 pub type EntityCache = dyn Cache<Key = IoId, Value = ConstValue>;
 
+/// Stores the counters of the rate limits, so that they can be shared between
+/// the instances of the server.
+#[async_trait::async_trait]
+pub trait RateLimiter: Send + Sync {
+    /// Takes a token from the bucket identified by the key, and returns
+    /// `false` when the bucket is empty.
+    async fn acquire(&self, key: &str, rate: &blueprint::Rate) -> anyhow::Result<bool>;
+}
+
 #[async_trait::async_trait]
 pub trait WorkerIO<In, Out>: Send + Sync + 'static {
     /// Calls a global JS function
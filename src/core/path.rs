@@ -95,6 +95,7 @@ impl<'a, Ctx: ResolverContextLike> EvalContext<'a, Ctx> {
                     ctx.var(tail[0].as_ref())?,
                 ))),
                 "env" => Some(ValueString::String(ctx.env_var(tail[0].as_ref())?)),
+                "auth" => Some(ValueString::Value(Cow::Owned(ctx.path_auth(tail)?))),
                 _ => None,
             })
     }
@@ -0,0 +1,50 @@
+use std::sync::Arc;
+
+use hyper::body::Bytes;
+
+use crate::core::blueprint::Rate;
+use crate::core::http::Response;
+use crate::core::ir::Error;
+use crate::core::{BodyStream, HttpIO, RateLimiter};
+
+/// Caps the number of requests sent to each upstream host, failing the
+/// requests over the cap without sending them.
+pub struct RateLimitedHttp {
+    http: Arc<dyn HttpIO>,
+    limiter: Arc<dyn RateLimiter>,
+    rate: Rate,
+}
+
+impl RateLimitedHttp {
+    pub fn new(http: Arc<dyn HttpIO>, limiter: Arc<dyn RateLimiter>, rate: Rate) -> Self {
+        Self { http, limiter, rate }
+    }
+
+    async fn acquire(&self, request: &reqwest::Request) -> anyhow::Result<()> {
+        let host = request.url().host_str().unwrap_or_default();
+        let key = format!("upstream:{}", host);
+
+        if super::try_acquire(self.limiter.as_ref(), &key, &self.rate).await {
+            Ok(())
+        } else {
+            let message = format!("too many requests to the upstream {}", host);
+            Err(Error::RateLimitError(message).into())
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl HttpIO for RateLimitedHttp {
+    async fn execute(&self, request: reqwest::Request) -> anyhow::Result<Response<Bytes>> {
+        self.acquire(&request).await?;
+        self.http.execute(request).await
+    }
+
+    async fn execute_stream(
+        &self,
+        request: reqwest::Request,
+    ) -> anyhow::Result<Response<BodyStream>> {
+        self.acquire(&request).await?;
+        self.http.execute_stream(request).await
+    }
+}
@@ -0,0 +1,65 @@
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ttl_cache::TtlCache;
+
+use super::TokenBucket;
+use crate::core::blueprint::Rate;
+use crate::core::RateLimiter;
+
+// TODO: take this from the user instead of hardcoding it
+const CAPACITY: usize = 100000;
+
+/// Keeps the buckets in the memory of the server. The buckets are dropped once
+/// they have been idle for the duration of the rate, as they'd be full again.
+pub struct InMemoryRateLimiter {
+    buckets: Mutex<TtlCache<String, TokenBucket>>,
+}
+
+impl Default for InMemoryRateLimiter {
+    fn default() -> Self {
+        Self { buckets: Mutex::new(TtlCache::new(CAPACITY)) }
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis() as u64)
+}
+
+#[async_trait::async_trait]
+impl RateLimiter for InMemoryRateLimiter {
+    async fn acquire(&self, key: &str, rate: &Rate) -> anyhow::Result<bool> {
+        let now = now();
+        let mut buckets = self.buckets.lock().unwrap();
+        let mut bucket = buckets
+            .remove(key)
+            .unwrap_or_else(|| TokenBucket::new(rate, now));
+        let acquired = bucket.acquire(rate, now);
+        buckets.insert(key.to_string(), bucket, rate.duration);
+
+        Ok(acquired)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU64;
+    use std::time::Duration;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_acquire() {
+        let limiter = InMemoryRateLimiter::default();
+        let rate = Rate {
+            requests: NonZeroU64::new(1).unwrap(),
+            duration: Duration::from_secs(60),
+        };
+
+        assert!(limiter.acquire("a", &rate).await.unwrap());
+        assert!(!limiter.acquire("a", &rate).await.unwrap());
+        assert!(limiter.acquire("b", &rate).await.unwrap());
+    }
+}
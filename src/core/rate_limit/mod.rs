@@ -0,0 +1,101 @@
+mod http;
+mod in_memory;
+mod token_bucket;
+
+pub use http::RateLimitedHttp;
+pub use in_memory::InMemoryRateLimiter;
+pub use token_bucket::TokenBucket;
+
+use crate::core::blueprint::{Rate, RateLimit, RateLimitKey};
+use crate::core::http::RequestContext;
+use crate::core::ir::Error;
+use crate::core::RateLimiter;
+
+/// Takes a token from the bucket of the client that sent the request. The
+/// buckets of a client are separated by the scope of the limit.
+pub async fn acquire(req_ctx: &RequestContext, scope: &str, limit: &RateLimit) -> bool {
+    let key = format!("{}:{}", scope, client_key(req_ctx, &limit.key).await);
+    try_acquire(req_ctx.runtime.rate_limiter.as_ref(), &key, &limit.rate).await
+}
+
+/// Takes a token from the bucket of the key. The request is let through when
+/// the buckets can't be read, so that the clients aren't failed by an
+/// unavailable store.
+async fn try_acquire(limiter: &dyn RateLimiter, key: &str, rate: &Rate) -> bool {
+    match limiter.acquire(key, rate).await {
+        Ok(acquired) => acquired,
+        Err(err) => {
+            tracing::warn!("Failed to check the rate limit of {}: {}", key, err);
+            true
+        }
+    }
+}
+
+/// Fails the resolution of the field when the client is over its limit.
+pub async fn check_field(
+    req_ctx: &RequestContext,
+    name: &str,
+    limit: &RateLimit,
+) -> Result<(), Error> {
+    if acquire(req_ctx, name, limit).await {
+        Ok(())
+    } else {
+        Err(Error::RateLimitError(format!(
+            "too many requests to the field {}",
+            name
+        )))
+    }
+}
+
+async fn client_key(req_ctx: &RequestContext, key: &RateLimitKey) -> String {
+    let client = match key {
+        RateLimitKey::Ip => None,
+        RateLimitKey::Header(name) => req_ctx
+            .request_headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| format!("header:{}", value)),
+        RateLimitKey::Claim(claim) => {
+            let verification = req_ctx.auth_ctx.validate(req_ctx).await;
+            verification
+                .claims()
+                .and_then(|claims| claims.get(claim))
+                .map(|value| match value {
+                    serde_json::Value::String(value) => format!("claim:{}", value),
+                    value => format!("claim:{}", value),
+                })
+        }
+    };
+
+    // the clients whose IP address is unknown to the runtime share a bucket
+    client.unwrap_or_else(|| match req_ctx.client_ip {
+        Some(ip) => format!("ip:{}", ip),
+        None => "unknown".to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU64;
+    use std::time::Duration;
+
+    use super::*;
+
+    struct FailingRateLimiter;
+
+    #[async_trait::async_trait]
+    impl RateLimiter for FailingRateLimiter {
+        async fn acquire(&self, _: &str, _: &Rate) -> anyhow::Result<bool> {
+            anyhow::bail!("The store is unavailable")
+        }
+    }
+
+    #[tokio::test]
+    async fn test_acquire_when_limiter_fails() {
+        let rate = Rate {
+            requests: NonZeroU64::new(1).unwrap(),
+            duration: Duration::from_secs(1),
+        };
+        assert!(try_acquire(&FailingRateLimiter, "ip:127.0.0.1", &rate).await);
+    }
+}
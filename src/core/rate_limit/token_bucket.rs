@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::blueprint::Rate;
+
+/// Tokens left to a client. The bucket holds up to `requests` tokens, each
+/// request takes one, and it is refilled at a constant speed so that it is
+/// full again after the `duration`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenBucket {
+    pub tokens: f64,
+    /// Time of the last update, in milliseconds since the epoch.
+    pub updated_at: u64,
+}
+
+impl TokenBucket {
+    pub fn new(rate: &Rate, now: u64) -> Self {
+        Self { tokens: rate.requests.get() as f64, updated_at: now }
+    }
+
+    /// Refills the bucket up to the given time, and takes a token from it when
+    /// one is left.
+    pub fn acquire(&mut self, rate: &Rate, now: u64) -> bool {
+        let capacity = rate.requests.get() as f64;
+        let duration = rate.duration.as_millis().max(1) as f64;
+        let elapsed = now.saturating_sub(self.updated_at) as f64;
+
+        self.tokens = (self.tokens + elapsed * capacity / duration).min(capacity);
+        self.updated_at = self.updated_at.max(now);
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU64;
+    use std::time::Duration;
+
+    use super::*;
+
+    fn rate() -> Rate {
+        Rate {
+            requests: NonZeroU64::new(2).unwrap(),
+            duration: Duration::from_secs(1),
+        }
+    }
+
+    #[test]
+    fn test_acquire() {
+        let rate = rate();
+        let mut bucket = TokenBucket::new(&rate, 0);
+
+        assert!(bucket.acquire(&rate, 0));
+        assert!(bucket.acquire(&rate, 0));
+        assert!(!bucket.acquire(&rate, 100));
+    }
+
+    #[test]
+    fn test_refill() {
+        let rate = rate();
+        let mut bucket = TokenBucket::new(&rate, 0);
+
+        assert!(bucket.acquire(&rate, 0));
+        assert!(bucket.acquire(&rate, 0));
+        assert!(bucket.acquire(&rate, 500));
+        assert!(!bucket.acquire(&rate, 500));
+
+        // the bucket never holds more than `requests` tokens
+        assert!(bucket.acquire(&rate, 10000));
+        assert!(bucket.acquire(&rate, 10000));
+        assert!(!bucket.acquire(&rate, 10000));
+    }
+}
@@ -74,6 +74,18 @@ impl ResourceReader<Cached> {
     pub fn cached(runtime: TargetRuntime) -> Self {
         ResourceReader(Cached::init(runtime))
     }
+
+    /// Paths of the files that were read from the filesystem.
+    pub fn local_paths(&self) -> Vec<String> {
+        self.0
+            .cache
+            .lock()
+            .unwrap()
+            .keys()
+            .filter(|path| !Url::parse(path).is_ok_and(|url| url.scheme().starts_with("http")))
+            .cloned()
+            .collect()
+    }
 }
 
 impl std::fmt::Display for Resource {
@@ -234,4 +246,14 @@ mod test {
 
         assert_eq!(actual, expected);
     }
+
+    #[tokio::test]
+    async fn test_local_paths() {
+        let runtime = crate::core::runtime::test::init(None);
+        let reader = ResourceReader::cached(runtime);
+        let path = tailcall_fixtures::configs::UNION_CONFIG;
+        reader.read_file(path).await.unwrap();
+
+        assert_eq!(reader.local_paths(), vec![path.to_string()]);
+    }
 }
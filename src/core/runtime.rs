@@ -3,9 +3,11 @@ use std::sync::Arc;
 use async_graphql_value::ConstValue;
 
 use super::ir::model::IoId;
+use crate::core::blueprint::Retry;
+use crate::core::http::RetryHttp;
 use crate::core::schema_extension::SchemaExtension;
 use crate::core::worker::{Command, Event};
-use crate::core::{Cache, EnvIO, FileIO, HttpIO, WorkerIO};
+use crate::core::{Cache, EnvIO, FileIO, HttpIO, RateLimiter, WorkerIO};
 
 /// The TargetRuntime struct unifies the available runtime-specific
 /// IO implementations. This is used to reduce piping IO structs all
@@ -25,6 +27,8 @@ pub struct TargetRuntime {
     /// Cache for storing and retrieving entity data, improving performance and
     /// reducing external calls.
     pub cache: Arc<dyn Cache<Key = IoId, Value = ConstValue>>,
+    /// Backend that keeps the counters of the rate limits.
+    pub rate_limiter: Arc<dyn RateLimiter>,
     /// A list of extensions that can be used to extend the runtime's
     /// functionality or integrate additional features.
     pub extensions: Arc<Vec<SchemaExtension>>,
@@ -38,6 +42,14 @@ impl TargetRuntime {
     pub fn add_extensions(&mut self, extensions: Vec<SchemaExtension>) {
         self.extensions = Arc::new(extensions);
     }
+
+    /// Returns a runtime whose HTTP clients retry the failed requests.
+    pub fn with_retry(&self, retry: &Retry) -> TargetRuntime {
+        let mut runtime = self.clone();
+        runtime.http = Arc::new(RetryHttp::new(self.http.clone(), retry.clone()));
+        runtime.http2_only = Arc::new(RetryHttp::new(self.http2_only.clone(), retry.clone()));
+        runtime
+    }
 }
 
 #[cfg(test)]
@@ -60,6 +72,7 @@ pub mod test {
     use crate::core::blueprint::Upstream;
     use crate::core::cache::InMemoryCache;
     use crate::core::http::Response;
+    use crate::core::rate_limit::InMemoryRateLimiter;
     use crate::core::runtime::TargetRuntime;
     use crate::core::worker::{Command, Event};
     use crate::core::{blueprint, EnvIO, FileIO, HttpIO};
@@ -103,10 +116,10 @@ pub mod test {
 
             let mut client = ClientBuilder::new(builder.build().expect("Failed to build client"));
 
-            if upstream.http_cache > 0 {
+            if let Some(blueprint::HttpCache::InMemory { capacity }) = upstream.http_cache {
                 client = client.with(Cache(HttpCache {
                     mode: CacheMode::Default,
-                    manager: HttpCacheManager::new(upstream.http_cache),
+                    manager: HttpCacheManager::new(capacity),
                     options: HttpCacheOptions::default(),
                 }))
             }
@@ -186,6 +199,7 @@ pub mod test {
             env: Arc::new(env),
             file: Arc::new(file),
             cache: Arc::new(InMemoryCache::new()),
+            rate_limiter: Arc::new(InMemoryRateLimiter::default()),
             extensions: Arc::new(vec![]),
             cmd_worker: match &script {
                 Some(script) => Some(init_worker_io::<Event, Command>(script.to_owned())),
@@ -0,0 +1,586 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+use async_graphql::parser::types::{
+    BaseType, FieldDefinition, InputValueDefinition, ServiceDocument, Type, TypeDefinition,
+    TypeKind, TypeSystemDefinition,
+};
+use async_graphql::parser::{parse_schema, Positioned};
+use serde::Serialize;
+
+use crate::core::blueprint::Blueprint;
+use crate::core::print_schema::print_schema;
+
+/// How a change of the schema affects the existing clients.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Criticality {
+    /// The existing operations keep working.
+    Safe,
+    /// The existing operations keep working, but the clients could fail to
+    /// handle the new values of the responses.
+    Dangerous,
+    /// The existing operations could fail or return different results.
+    Breaking,
+}
+
+impl Display for Criticality {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Criticality::Safe => "safe",
+            Criticality::Dangerous => "dangerous",
+            Criticality::Breaking => "breaking",
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Change {
+    pub criticality: Criticality,
+    /// Path of the changed element, like `User.name` or `Query.user.id`.
+    pub path: String,
+    pub message: String,
+}
+
+impl Change {
+    fn new(criticality: Criticality, path: impl Into<String>, message: String) -> Self {
+        Self { criticality, path: path.into(), message }
+    }
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}] {}", self.criticality, self.message)
+    }
+}
+
+/// Compares the schemas that are served for the two blueprints.
+pub fn diff(old: &Blueprint, new: &Blueprint) -> anyhow::Result<Vec<Change>> {
+    diff_sdl(
+        &print_schema(old.to_schema()),
+        &print_schema(new.to_schema()),
+    )
+}
+
+/// Compares two schemas in SDL. The changes are ordered by their path.
+pub fn diff_sdl(old: &str, new: &str) -> anyhow::Result<Vec<Change>> {
+    let (old, new) = (parse_schema(old)?, parse_schema(new)?);
+    let (old, new) = (Schema::new(&old), Schema::new(&new));
+
+    let mut changes = vec![];
+    diff_roots(&old, &new, &mut changes);
+
+    // the operations never name their root type, so a renamed root type is
+    // compared with the type that replaces it
+    let renamed = old
+        .roots
+        .iter()
+        .zip(&new.roots)
+        .filter_map(|(old, new)| Some((old.as_deref()?, new.as_deref()?)))
+        .filter(|(old, new)| old != new)
+        .collect::<BTreeMap<_, _>>();
+
+    for (name, old_type) in &old.types {
+        let name = renamed.get(name.as_str()).copied().unwrap_or(name);
+        match new.types.get(name) {
+            Some(new_type) => diff_type(name, old_type, new_type, &mut changes),
+            None => changes.push(Change::new(
+                Criticality::Breaking,
+                name,
+                format!("Type `{}` was removed", name),
+            )),
+        }
+    }
+    for name in new
+        .types
+        .keys()
+        .filter(|name| !old.types.contains_key(*name))
+        .filter(|name| !renamed.values().any(|renamed| *renamed == name.as_str()))
+    {
+        changes.push(Change::new(
+            Criticality::Safe,
+            name.as_str(),
+            format!("Type `{}` was added", name),
+        ));
+    }
+
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(changes)
+}
+
+/// The parts of a schema that are compared.
+struct Schema<'a> {
+    roots: [Option<String>; 3],
+    types: BTreeMap<String, &'a TypeDefinition>,
+}
+
+impl<'a> Schema<'a> {
+    fn new(document: &'a ServiceDocument) -> Self {
+        let mut schema = Schema { roots: Default::default(), types: BTreeMap::new() };
+        for definition in &document.definitions {
+            match definition {
+                TypeSystemDefinition::Schema(definition) => {
+                    let definition = &definition.node;
+                    schema.roots = [
+                        &definition.query,
+                        &definition.mutation,
+                        &definition.subscription,
+                    ]
+                    .map(|root| root.as_ref().map(|name| name.node.to_string()));
+                }
+                TypeSystemDefinition::Type(definition) => {
+                    let definition = &definition.node;
+                    schema
+                        .types
+                        .insert(definition.name.node.to_string(), definition);
+                }
+                TypeSystemDefinition::Directive(_) => {}
+            }
+        }
+        schema
+    }
+}
+
+fn diff_roots(old: &Schema, new: &Schema, changes: &mut Vec<Change>) {
+    let operations = ["query", "mutation", "subscription"];
+    for ((operation, old), new) in operations.iter().zip(&old.roots).zip(&new.roots) {
+        if old == new {
+            continue;
+        }
+        let (criticality, message) = match (old, new) {
+            (None, Some(new)) => (
+                Criticality::Safe,
+                format!("Type `{}` was set as the {} type", new, operation),
+            ),
+            (Some(old), None) => (
+                Criticality::Breaking,
+                format!("Type `{}` was removed as the {} type", old, operation),
+            ),
+            (old, new) => (
+                Criticality::Safe,
+                format!(
+                    "The {} type was renamed from `{}` to `{}`",
+                    operation,
+                    old.as_deref().unwrap_or_default(),
+                    new.as_deref().unwrap_or_default()
+                ),
+            ),
+        };
+        changes.push(Change::new(criticality, "schema", message));
+    }
+}
+
+fn diff_type(name: &str, old: &TypeDefinition, new: &TypeDefinition, changes: &mut Vec<Change>) {
+    match (&old.kind, &new.kind) {
+        (TypeKind::Scalar, TypeKind::Scalar) => {}
+        (TypeKind::Object(old), TypeKind::Object(new)) => {
+            diff_names(
+                name,
+                "Interface",
+                &old.implements,
+                &new.implements,
+                Criticality::Dangerous,
+                changes,
+            );
+            diff_fields(name, &old.fields, &new.fields, changes);
+        }
+        (TypeKind::Interface(old), TypeKind::Interface(new)) => {
+            diff_names(
+                name,
+                "Interface",
+                &old.implements,
+                &new.implements,
+                Criticality::Dangerous,
+                changes,
+            );
+            diff_fields(name, &old.fields, &new.fields, changes);
+        }
+        (TypeKind::Union(old), TypeKind::Union(new)) => {
+            diff_names(
+                name,
+                "Member",
+                &old.members,
+                &new.members,
+                Criticality::Dangerous,
+                changes,
+            );
+        }
+        (TypeKind::Enum(old), TypeKind::Enum(new)) => {
+            let old = old.values.iter().map(|value| &value.node.value);
+            let new = new.values.iter().map(|value| &value.node.value);
+            diff_names(
+                name,
+                "Enum value",
+                &old.cloned().collect::<Vec<_>>(),
+                &new.cloned().collect::<Vec<_>>(),
+                Criticality::Dangerous,
+                changes,
+            );
+        }
+        (TypeKind::InputObject(old), TypeKind::InputObject(new)) => {
+            diff_inputs(name, "Input field", &old.fields, &new.fields, changes);
+        }
+        (old_kind, new_kind) => changes.push(Change::new(
+            Criticality::Breaking,
+            name,
+            format!(
+                "Type `{}` changed from {} to {}",
+                name,
+                kind_name(old_kind),
+                kind_name(new_kind)
+            ),
+        )),
+    }
+}
+
+fn kind_name(kind: &TypeKind) -> &'static str {
+    match kind {
+        TypeKind::Scalar => "a scalar",
+        TypeKind::Object(_) => "an object",
+        TypeKind::Interface(_) => "an interface",
+        TypeKind::Union(_) => "a union",
+        TypeKind::Enum(_) => "an enum",
+        TypeKind::InputObject(_) => "an input object",
+    }
+}
+
+/// Compares the names listed by a type, like the members of a union. The
+/// removals are breaking, and the additions have the given criticality.
+fn diff_names<T: Display + PartialEq>(
+    parent: &str,
+    label: &str,
+    old: &[Positioned<T>],
+    new: &[Positioned<T>],
+    added: Criticality,
+    changes: &mut Vec<Change>,
+) {
+    let contains = |names: &[Positioned<T>], name: &T| names.iter().any(|n| &n.node == name);
+    for name in old.iter().filter(|name| !contains(new, &name.node)) {
+        changes.push(Change::new(
+            Criticality::Breaking,
+            format!("{}.{}", parent, name.node),
+            format!("{} `{}` was removed from `{}`", label, name.node, parent),
+        ));
+    }
+    for name in new.iter().filter(|name| !contains(old, &name.node)) {
+        changes.push(Change::new(
+            added,
+            format!("{}.{}", parent, name.node),
+            format!("{} `{}` was added to `{}`", label, name.node, parent),
+        ));
+    }
+}
+
+fn diff_fields(
+    parent: &str,
+    old: &[Positioned<FieldDefinition>],
+    new: &[Positioned<FieldDefinition>],
+    changes: &mut Vec<Change>,
+) {
+    for old in old.iter().map(|field| &field.node) {
+        let path = format!("{}.{}", parent, old.name.node);
+        let Some(new) = new
+            .iter()
+            .find(|field| field.node.name.node == old.name.node)
+        else {
+            changes.push(Change::new(
+                Criticality::Breaking,
+                &path,
+                format!("Field `{}` was removed", path),
+            ));
+            continue;
+        };
+        let new = &new.node;
+
+        if old.ty.node != new.ty.node {
+            // the clients can still handle a value that is never null
+            let criticality = if is_nullable_of(&old.ty.node, &new.ty.node) {
+                Criticality::Safe
+            } else {
+                Criticality::Breaking
+            };
+            changes.push(Change::new(
+                criticality,
+                &path,
+                format!(
+                    "Field `{}` changed type from `{}` to `{}`",
+                    path, old.ty.node, new.ty.node
+                ),
+            ));
+        }
+        diff_inputs(&path, "Argument", &old.arguments, &new.arguments, changes);
+    }
+
+    for new in new.iter().map(|field| &field.node) {
+        if !old
+            .iter()
+            .any(|field| field.node.name.node == new.name.node)
+        {
+            let path = format!("{}.{}", parent, new.name.node);
+            changes.push(Change::new(
+                Criticality::Safe,
+                &path,
+                format!("Field `{}` was added", path),
+            ));
+        }
+    }
+}
+
+/// Compares the arguments of a field or the fields of an input object.
+fn diff_inputs(
+    parent: &str,
+    label: &str,
+    old: &[Positioned<InputValueDefinition>],
+    new: &[Positioned<InputValueDefinition>],
+    changes: &mut Vec<Change>,
+) {
+    for old in old.iter().map(|input| &input.node) {
+        let path = format!("{}.{}", parent, old.name.node);
+        let Some(new) = new
+            .iter()
+            .find(|input| input.node.name.node == old.name.node)
+        else {
+            changes.push(Change::new(
+                Criticality::Breaking,
+                &path,
+                format!("{} `{}` was removed", label, path),
+            ));
+            continue;
+        };
+        let new = &new.node;
+
+        if old.ty.node != new.ty.node {
+            // the existing operations can still pass a value to a nullable input
+            let criticality = if is_nullable_of(&new.ty.node, &old.ty.node) {
+                Criticality::Safe
+            } else {
+                Criticality::Breaking
+            };
+            changes.push(Change::new(
+                criticality,
+                &path,
+                format!(
+                    "{} `{}` changed type from `{}` to `{}`",
+                    label, path, old.ty.node, new.ty.node
+                ),
+            ));
+        }
+
+        let old_default = old.default_value.as_ref().map(|value| &value.node);
+        let new_default = new.default_value.as_ref().map(|value| &value.node);
+        if old_default != new_default {
+            let show = |value: Option<&async_graphql::Value>| {
+                value.map_or("none".to_string(), |value| value.to_string())
+            };
+            changes.push(Change::new(
+                Criticality::Dangerous,
+                &path,
+                format!(
+                    "{} `{}` changed default value from `{}` to `{}`",
+                    label,
+                    path,
+                    show(old_default),
+                    show(new_default)
+                ),
+            ));
+        }
+    }
+
+    for new in new.iter().map(|input| &input.node) {
+        if !old
+            .iter()
+            .any(|input| input.node.name.node == new.name.node)
+        {
+            let path = format!("{}.{}", parent, new.name.node);
+            let required = !new.ty.node.nullable && new.default_value.is_none();
+            let (criticality, message) = if required {
+                (
+                    Criticality::Breaking,
+                    format!("Required {} `{}` was added", label.to_lowercase(), path),
+                )
+            } else {
+                (Criticality::Safe, format!("{} `{}` was added", label, path))
+            };
+            changes.push(Change::new(criticality, &path, message));
+        }
+    }
+}
+
+/// Checks if `nullable` is `ty` with some of its types made nullable, so
+/// that all the values of `ty` are values of `nullable`.
+fn is_nullable_of(nullable: &Type, ty: &Type) -> bool {
+    (nullable.nullable || !ty.nullable)
+        && match (&nullable.base, &ty.base) {
+            (BaseType::Named(nullable), BaseType::Named(ty)) => nullable == ty,
+            (BaseType::List(nullable), BaseType::List(ty)) => is_nullable_of(nullable, ty),
+            _ => false,
+        }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OLD: &str = r#"
+        schema { query: Query }
+        type Query {
+            user(id: Int!): User
+            users: [User!]!
+            posts(limit: Int = 10): [Post]
+        }
+        type User { id: Int! name: String email: String }
+        type Post { id: Int! status: Status }
+        enum Status { DRAFT PUBLISHED }
+        union Item = User | Post
+        input UserInput { name: String! email: String }
+    "#;
+
+    fn changes(new: &str) -> Vec<String> {
+        diff_sdl(OLD, new)
+            .unwrap()
+            .into_iter()
+            .map(|change| change.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_no_changes() {
+        assert!(changes(OLD).is_empty());
+    }
+
+    #[test]
+    fn test_breaking_changes() {
+        let new = r#"
+            schema { query: Query }
+            type Query {
+                user(id: Int!, tenant: String!): User
+                users: [User]!
+                posts(limit: String = 10): [Post]
+            }
+            type User { id: Int! name: String }
+            type Post { id: Int! status: Status }
+            enum Status { DRAFT }
+            union Item = User | Post
+            input UserInput { name: String! email: String! }
+        "#;
+
+        assert_eq!(
+            changes(new),
+            vec![
+                "[breaking] Argument `Query.posts.limit` changed type from `Int` to `String`",
+                "[breaking] Required argument `Query.user.tenant` was added",
+                "[breaking] Field `Query.users` changed type from `[User!]!` to `[User]!`",
+                "[breaking] Enum value `PUBLISHED` was removed from `Status`",
+                "[breaking] Field `User.email` was removed",
+                "[breaking] Input field `UserInput.email` changed type from `String` to `String!`",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dangerous_changes() {
+        let new = r#"
+            schema { query: Query }
+            type Query {
+                user(id: Int!): User
+                users: [User!]!
+                posts(limit: Int = 20): [Post]
+            }
+            type User { id: Int! name: String email: String }
+            type Post { id: Int! status: Status }
+            enum Status { DRAFT PUBLISHED ARCHIVED }
+            union Item = User | Post | Comment
+            type Comment { id: Int! }
+            input UserInput { name: String! email: String }
+        "#;
+
+        assert_eq!(
+            changes(new),
+            vec![
+                "[safe] Type `Comment` was added",
+                "[dangerous] Member `Comment` was added to `Item`",
+                "[dangerous] Argument `Query.posts.limit` changed default value from `10` to `20`",
+                "[dangerous] Enum value `ARCHIVED` was added to `Status`",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_safe_changes() {
+        let new = r#"
+            schema { query: Query }
+            type Query {
+                user(id: Int, tenant: String): User!
+                users: [User!]!
+                posts(limit: Int = 10): [Post]
+            }
+            type User { id: Int! name: String! email: String age: Int }
+            type Post { id: Int! status: Status }
+            enum Status { DRAFT PUBLISHED }
+            union Item = User | Post
+            input UserInput { name: String email: String }
+        "#;
+
+        assert_eq!(
+            changes(new),
+            vec![
+                "[safe] Field `Query.user` changed type from `User` to `User!`",
+                "[safe] Argument `Query.user.id` changed type from `Int!` to `Int`",
+                "[safe] Argument `Query.user.tenant` was added",
+                "[safe] Field `User.age` was added",
+                "[safe] Field `User.name` changed type from `String` to `String!`",
+                "[safe] Input field `UserInput.name` changed type from `String!` to `String`",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_type_changes() {
+        let new = r#"
+            schema { query: Query mutation: Mutation }
+            type Query { user(id: Int!): User users: [User!]! posts(limit: Int = 10): [Post] }
+            type Mutation { createUser(input: UserInput!): User }
+            type User { id: Int! name: String email: String }
+            type Post { id: Int! status: String }
+            scalar Status
+            union Item = User | Post
+            input UserInput { name: String! email: String }
+        "#;
+
+        assert_eq!(
+            changes(new),
+            vec![
+                "[safe] Type `Mutation` was added",
+                "[breaking] Field `Post.status` changed type from `Status` to `String`",
+                "[breaking] Type `Status` changed from an enum to a scalar",
+                "[safe] Type `Mutation` was set as the mutation type",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_renamed_root_type() {
+        let new = r#"
+            schema { query: RootQuery }
+            type RootQuery {
+                user(id: Int!): User
+                users: [User!]!
+                posts(limit: Int = 10): [Post]
+                post(id: Int!): Post
+            }
+            type User { id: Int! name: String email: String }
+            type Post { id: Int! status: Status }
+            enum Status { DRAFT PUBLISHED }
+            union Item = User | Post
+            input UserInput { name: String! email: String }
+        "#;
+
+        assert_eq!(
+            changes(new),
+            vec![
+                "[safe] Field `RootQuery.post` was added",
+                "[safe] The query type was renamed from `Query` to `RootQuery`",
+            ]
+        );
+    }
+}
@@ -0,0 +1,158 @@
+use crate::core::config::{Config, GraphQL, Type};
+use crate::core::subgraph::introspection::to_graphql_args;
+
+/// Schema of an upstream GraphQL service that is composed into the gateway.
+#[derive(Debug, Clone)]
+pub struct Subgraph {
+    pub url: String,
+    pub config: Config,
+}
+
+/// Composes the configs of the subgraphs into the config of a gateway.
+///
+/// Every field is owned by the first subgraph that defines it without
+/// `@external`. The fields of an entity are resolved from their owning
+/// subgraph through the `_entities` field, using the key of the entity in that
+/// subgraph, so that a query can join the fields contributed by several
+/// subgraphs. Such a request is skipped when the field was already requested
+/// along with the parent entity.
+pub fn compose(subgraphs: Vec<Subgraph>) -> Config {
+    let mut composed = Config::default();
+
+    for Subgraph { url, config } in subgraphs {
+        composed.schema.query = composed.schema.query.or(config.schema.query);
+        composed.schema.mutation = composed.schema.mutation.or(config.schema.mutation);
+        composed.schema.subscription = composed.schema.subscription.or(config.schema.subscription);
+
+        for (name, type_of) in config.types {
+            let Type { fields, implements, key, doc, .. } = type_of;
+            let composed_type = composed
+                .types
+                .entry(name)
+                .or_insert_with(|| Type { doc, ..Default::default() });
+            composed_type.implements.extend(implements);
+
+            for (field_name, mut field) in fields {
+                if composed_type.fields.contains_key(&field_name) || field.external.is_some() {
+                    continue;
+                }
+
+                if let Some(key) = &key {
+                    if field.graphql.is_none() && !key.field_names().contains(&field_name.as_str())
+                    {
+                        field.graphql = Some(GraphQL {
+                            name: field_name.clone(),
+                            args: (!field.args.is_empty()).then(|| to_graphql_args(&field.args)),
+                            base_url: Some(url.clone()),
+                            entity: Some(key.clone()),
+                            ..Default::default()
+                        });
+                    }
+                }
+
+                composed_type.fields.insert(field_name, field);
+            }
+        }
+
+        for (name, union) in config.unions {
+            let composed_union = composed.unions.entry(name).or_default();
+            composed_union.types.extend(union.types);
+            composed_union.doc = composed_union.doc.take().or(union.doc);
+        }
+
+        for (name, enum_of) in config.enums {
+            match composed.enums.get_mut(&name) {
+                Some(composed_enum) => composed_enum.variants.extend(enum_of.variants),
+                None => {
+                    composed.enums.insert(name, enum_of);
+                }
+            }
+        }
+    }
+
+    composed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::{External, Field, Key};
+
+    fn field(type_of: &str) -> Field {
+        Field { type_of: type_of.to_string(), ..Default::default() }
+    }
+
+    fn user(fields: Vec<(&str, Field)>) -> Type {
+        Type {
+            key: Some(Key { fields: "id".to_string() }),
+            ..Default::default()
+        }
+        .fields(fields)
+    }
+
+    fn subgraph(url: &str, types: Vec<(&str, Type)>) -> Subgraph {
+        let mut config = Config::default();
+        config.schema.query = Some("Query".to_string());
+        config.types = types
+            .into_iter()
+            .map(|(name, type_of)| (name.to_string(), type_of))
+            .collect();
+        Subgraph { url: url.to_string(), config }
+    }
+
+    #[test]
+    fn test_compose() {
+        let users = subgraph(
+            "http://users",
+            vec![(
+                "User",
+                user(vec![("id", field("ID")), ("name", field("String"))]),
+            )],
+        );
+        let reviews = subgraph(
+            "http://reviews",
+            vec![(
+                "User",
+                user(vec![
+                    ("id", field("ID")),
+                    (
+                        "name",
+                        Field { external: Some(External {}), ..field("String") },
+                    ),
+                    ("reviews", field("Review")),
+                ]),
+            )],
+        );
+
+        let composed = compose(vec![users, reviews]);
+        let user = composed.find_type("User").unwrap();
+
+        assert!(user.key.is_none());
+        assert!(user.fields["id"].graphql.is_none());
+
+        let name = user.fields["name"].graphql.as_ref().unwrap();
+        assert_eq!(name.base_url.as_deref(), Some("http://users"));
+        assert_eq!(name.entity, Some(Key { fields: "id".to_string() }));
+
+        let reviews = user.fields["reviews"].graphql.as_ref().unwrap();
+        assert_eq!(reviews.name, "reviews");
+        assert_eq!(reviews.base_url.as_deref(), Some("http://reviews"));
+    }
+
+    #[test]
+    fn test_compose_roots() {
+        let users = subgraph("http://users", vec![]);
+        let mut reviews = subgraph("http://reviews", vec![]);
+        reviews.config.schema.mutation = Some("Mutation".to_string());
+        reviews.config.schema.subscription = Some("Subscription".to_string());
+
+        let composed = compose(vec![users, reviews]);
+
+        assert_eq!(composed.schema.query.as_deref(), Some("Query"));
+        assert_eq!(composed.schema.mutation.as_deref(), Some("Mutation"));
+        assert_eq!(
+            composed.schema.subscription.as_deref(),
+            Some("Subscription")
+        );
+    }
+}
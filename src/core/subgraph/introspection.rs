@@ -0,0 +1,464 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{Context, Result};
+use async_graphql::parser::types::{TypeKind as SdlTypeKind, TypeSystemDefinition};
+use async_graphql_value::ConstValue;
+use serde::Deserialize;
+
+use crate::core::config::{
+    Arg, Config, Enum, External, Field, GraphQL, Key, KeyValue, RootSchema, Type, Union, Variant,
+};
+use crate::core::scalar::Scalar;
+
+/// Query used to introspect the schema of a subgraph.
+pub const INTROSPECTION_QUERY: &str = "query { __schema { queryType { name } mutationType { name } subscriptionType { name } types { kind name description fields { name description args { name description type { ...TypeRef } } type { ...TypeRef } } inputFields { name description type { ...TypeRef } } interfaces { name } enumValues { name } possibleTypes { name } } } } fragment TypeRef on __Type { kind name ofType { kind name ofType { kind name ofType { kind name } } } }";
+
+/// Query used to read the federation SDL of a subgraph, which contains the
+/// `@key` and `@external` directives that are not part of the introspection.
+pub const SERVICE_QUERY: &str = "query { _service { sdl } }";
+
+const BUILTIN_SCALARS: &[&str] = &["String", "Int", "Float", "Boolean", "ID"];
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+enum TypeKind {
+    Scalar,
+    Object,
+    Interface,
+    Union,
+    Enum,
+    InputObject,
+    List,
+    NonNull,
+}
+
+#[derive(Debug, Deserialize)]
+struct Named {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TypeRef {
+    kind: TypeKind,
+    name: Option<String>,
+    of_type: Option<Box<TypeRef>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct InputValue {
+    name: String,
+    description: Option<String>,
+    #[serde(rename = "type")]
+    type_of: TypeRef,
+}
+
+#[derive(Debug, Deserialize)]
+struct FieldDefinition {
+    name: String,
+    description: Option<String>,
+    #[serde(default)]
+    args: Vec<InputValue>,
+    #[serde(rename = "type")]
+    type_of: TypeRef,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FullType {
+    kind: TypeKind,
+    name: String,
+    description: Option<String>,
+    fields: Option<Vec<FieldDefinition>>,
+    input_fields: Option<Vec<InputValue>>,
+    interfaces: Option<Vec<Named>>,
+    enum_values: Option<Vec<Named>>,
+    possible_types: Option<Vec<Named>>,
+}
+
+/// Schema of a subgraph as returned by the introspection query.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Introspection {
+    query_type: Named,
+    mutation_type: Option<Named>,
+    subscription_type: Option<Named>,
+    types: Vec<FullType>,
+}
+
+/// Shape of the type of a field or an argument.
+struct Shape {
+    name: String,
+    list: bool,
+    required: bool,
+    list_type_required: bool,
+}
+
+impl TypeRef {
+    fn to_shape(&self) -> Option<Shape> {
+        let (required, type_ref) = match (&self.kind, &self.of_type) {
+            (TypeKind::NonNull, Some(of_type)) => (true, of_type.as_ref()),
+            _ => (false, self),
+        };
+
+        match (&type_ref.kind, &type_ref.of_type) {
+            (TypeKind::List, Some(of_type)) => {
+                let (list_type_required, of_type) = match (&of_type.kind, &of_type.of_type) {
+                    (TypeKind::NonNull, Some(of_type)) => (true, of_type.as_ref()),
+                    _ => (false, of_type.as_ref()),
+                };
+                Some(Shape {
+                    name: of_type.name.clone()?,
+                    list: true,
+                    required,
+                    list_type_required,
+                })
+            }
+            _ => Some(Shape {
+                name: type_ref.name.clone()?,
+                list: false,
+                required,
+                list_type_required: false,
+            }),
+        }
+    }
+}
+
+/// Types that are defined by the GraphQL spec, by Tailcall or by the federation
+/// spec and therefore are not composed into the config.
+fn is_reserved(name: &str) -> bool {
+    name.starts_with('_')
+        || name.contains("__")
+        || BUILTIN_SCALARS.contains(&name)
+        || Scalar::is_predefined(name)
+}
+
+/// Federation directives of the subgraph types read from the SDL.
+#[derive(Debug, Default)]
+struct Federation {
+    keys: BTreeMap<String, Key>,
+    externals: BTreeSet<(String, String)>,
+}
+
+impl Federation {
+    fn from_sdl(sdl: &str) -> Result<Self> {
+        let document = async_graphql::parser::parse_schema(sdl)
+            .context("Failed to parse the SDL of the subgraph")?;
+        let mut federation = Federation::default();
+
+        for definition in document.definitions {
+            let TypeSystemDefinition::Type(definition) = definition else {
+                continue;
+            };
+            let definition = definition.node;
+            let type_name = definition.name.node.to_string();
+
+            let key = definition
+                .directives
+                .iter()
+                .find(|directive| directive.node.name.node == "key")
+                .and_then(|directive| directive.node.get_argument("fields"));
+            if let Some(ConstValue::String(fields)) = key.map(|fields| &fields.node) {
+                federation
+                    .keys
+                    .insert(type_name.clone(), Key { fields: fields.clone() });
+            }
+
+            if let SdlTypeKind::Object(object) = definition.kind {
+                for field in object.fields {
+                    let field = field.node;
+                    if field
+                        .directives
+                        .iter()
+                        .any(|directive| directive.node.name.node == "external")
+                    {
+                        federation
+                            .externals
+                            .insert((type_name.clone(), field.name.node.to_string()));
+                    }
+                }
+            }
+        }
+
+        Ok(federation)
+    }
+}
+
+impl Introspection {
+    /// Converts the schema of the subgraph available at the given url into a
+    /// config. The root fields are resolved with `@graphql` requests to the
+    /// subgraph and the entities are marked with the `@key` and `@external`
+    /// directives found in the federation SDL, if any.
+    pub fn into_config(self, url: &str, sdl: Option<&str>) -> Result<Config> {
+        let federation = sdl
+            .map(Federation::from_sdl)
+            .transpose()?
+            .unwrap_or_default();
+        let query = self.query_type.name;
+        let mutation = self.mutation_type.map(|mutation| mutation.name);
+        let subscription = self.subscription_type.map(|subscription| subscription.name);
+
+        let root_name = |name: &str| -> String {
+            if name == query {
+                "Query".to_string()
+            } else if mutation.as_deref() == Some(name) {
+                "Mutation".to_string()
+            } else if subscription.as_deref() == Some(name) {
+                "Subscription".to_string()
+            } else {
+                name.to_string()
+            }
+        };
+
+        let mut config = Config {
+            schema: RootSchema {
+                query: Some("Query".to_string()),
+                mutation: mutation.as_ref().map(|_| "Mutation".to_string()),
+                subscription: subscription.as_ref().map(|_| "Subscription".to_string()),
+            },
+            ..Default::default()
+        };
+
+        for full_type in self.types {
+            if is_reserved(&full_type.name) {
+                continue;
+            }
+            let is_root = full_type.name == query
+                || mutation.as_ref() == Some(&full_type.name)
+                || subscription.as_ref() == Some(&full_type.name);
+            let name = root_name(&full_type.name);
+
+            match full_type.kind {
+                TypeKind::Scalar => {
+                    config.types.insert(name, Type::default());
+                }
+                TypeKind::Enum => {
+                    let variants = full_type
+                        .enum_values
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|value| Variant { name: value.name, alias: None })
+                        .collect();
+                    config
+                        .enums
+                        .insert(name, Enum { variants, doc: full_type.description });
+                }
+                TypeKind::Union => {
+                    let types = full_type
+                        .possible_types
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|possible_type| possible_type.name)
+                        .collect();
+                    config
+                        .unions
+                        .insert(name, Union { types, doc: full_type.description });
+                }
+                TypeKind::InputObject => {
+                    let fields = full_type
+                        .input_fields
+                        .unwrap_or_default()
+                        .into_iter()
+                        .filter_map(|input| {
+                            let shape = input.type_of.to_shape()?;
+                            let field = Field {
+                                type_of: root_name(&shape.name),
+                                list: shape.list,
+                                required: shape.required,
+                                list_type_required: shape.list_type_required,
+                                doc: input.description,
+                                ..Default::default()
+                            };
+                            Some((input.name, field))
+                        })
+                        .collect();
+                    config.types.insert(
+                        name,
+                        Type { fields, doc: full_type.description, ..Default::default() },
+                    );
+                }
+                TypeKind::Object | TypeKind::Interface => {
+                    let mut fields = BTreeMap::new();
+                    for field in full_type.fields.unwrap_or_default() {
+                        let Some(shape) = field.type_of.to_shape() else {
+                            continue;
+                        };
+                        if (is_root && field.name.starts_with('_')) || shape.name.starts_with('_') {
+                            continue;
+                        }
+
+                        let args: BTreeMap<String, Arg> = field
+                            .args
+                            .into_iter()
+                            .filter_map(|arg| {
+                                let shape = arg.type_of.to_shape()?;
+                                let arg_of = Arg {
+                                    type_of: shape.name,
+                                    list: shape.list,
+                                    required: shape.required,
+                                    doc: arg.description,
+                                    ..Default::default()
+                                };
+                                Some((arg.name, arg_of))
+                            })
+                            .collect();
+
+                        let graphql = is_root.then(|| GraphQL {
+                            name: field.name.clone(),
+                            args: (!args.is_empty()).then(|| to_graphql_args(&args)),
+                            base_url: Some(url.to_string()),
+                            ..Default::default()
+                        });
+                        let external = federation
+                            .externals
+                            .contains(&(full_type.name.clone(), field.name.clone()))
+                            .then_some(External {});
+
+                        fields.insert(
+                            field.name,
+                            Field {
+                                type_of: root_name(&shape.name),
+                                list: shape.list,
+                                required: shape.required,
+                                list_type_required: shape.list_type_required,
+                                args,
+                                doc: field.description,
+                                graphql,
+                                external,
+                                ..Default::default()
+                            },
+                        );
+                    }
+
+                    let implements = full_type
+                        .interfaces
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|interface| interface.name)
+                        .collect();
+
+                    config.types.insert(
+                        name,
+                        Type {
+                            fields,
+                            doc: full_type.description,
+                            implements,
+                            key: federation.keys.get(&full_type.name).cloned(),
+                            ..Default::default()
+                        },
+                    );
+                }
+                TypeKind::List | TypeKind::NonNull => {}
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+/// Forwards the arguments of the field to the subgraph as they are.
+pub fn to_graphql_args(args: &BTreeMap<String, Arg>) -> Vec<KeyValue> {
+    args.keys()
+        .map(|name| KeyValue { key: name.clone(), value: format!("{{{{.args.{name}}}}}") })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn introspection() -> Introspection {
+        serde_json::from_value(json!({
+            "queryType": {"name": "RootQuery"},
+            "mutationType": null,
+            "subscriptionType": {"name": "RootSubscription"},
+            "types": [
+                {
+                    "kind": "OBJECT",
+                    "name": "RootQuery",
+                    "fields": [
+                        {
+                            "name": "user",
+                            "args": [{
+                                "name": "id",
+                                "type": {"kind": "NON_NULL", "ofType": {"kind": "SCALAR", "name": "ID"}}
+                            }],
+                            "type": {"kind": "OBJECT", "name": "User"}
+                        },
+                        {
+                            "name": "_service",
+                            "args": [],
+                            "type": {"kind": "NON_NULL", "ofType": {"kind": "OBJECT", "name": "_Service"}}
+                        }
+                    ]
+                },
+                {
+                    "kind": "OBJECT",
+                    "name": "RootSubscription",
+                    "fields": [
+                        {
+                            "name": "userAdded",
+                            "args": [],
+                            "type": {"kind": "OBJECT", "name": "User"}
+                        }
+                    ]
+                },
+                {
+                    "kind": "OBJECT",
+                    "name": "User",
+                    "fields": [
+                        {
+                            "name": "id",
+                            "type": {"kind": "NON_NULL", "ofType": {"kind": "SCALAR", "name": "ID"}}
+                        },
+                        {
+                            "name": "tags",
+                            "type": {
+                                "kind": "LIST",
+                                "ofType": {"kind": "NON_NULL", "ofType": {"kind": "SCALAR", "name": "String"}}
+                            }
+                        }
+                    ]
+                },
+                {"kind": "OBJECT", "name": "_Service", "fields": []},
+                {"kind": "SCALAR", "name": "String"},
+                {"kind": "OBJECT", "name": "__Schema", "fields": []}
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_into_config() {
+        let sdl = r#"type User @key(fields: "id") { id: ID! tags: [String!] }"#;
+        let config = introspection()
+            .into_config("http://users/graphql", Some(sdl))
+            .unwrap();
+
+        assert_eq!(config.schema.query.as_deref(), Some("Query"));
+        assert_eq!(config.schema.subscription.as_deref(), Some("Subscription"));
+        assert_eq!(
+            config.types.keys().collect::<Vec<_>>(),
+            vec!["Query", "Subscription", "User"]
+        );
+
+        let query = config.find_type("Query").unwrap();
+        assert_eq!(query.fields.keys().collect::<Vec<_>>(), vec!["user"]);
+        let graphql = query.fields["user"].graphql.as_ref().unwrap();
+        assert_eq!(graphql.name, "user");
+        assert_eq!(graphql.base_url.as_deref(), Some("http://users/graphql"));
+        assert_eq!(graphql.args.as_ref().unwrap()[0].value, "{{.args.id}}");
+
+        let subscription = config.find_type("Subscription").unwrap();
+        let graphql = subscription.fields["userAdded"].graphql.as_ref().unwrap();
+        assert_eq!(graphql.name, "userAdded");
+
+        let user = config.find_type("User").unwrap();
+        assert_eq!(user.key, Some(Key { fields: "id".to_string() }));
+        let tags = &user.fields["tags"];
+        assert!(tags.list && tags.list_type_required && !tags.required);
+        assert!(tags.graphql.is_none());
+    }
+}
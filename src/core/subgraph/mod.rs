@@ -0,0 +1,6 @@
+mod compose;
+mod introspection;
+mod reader;
+
+pub use compose::*;
+pub use reader::*;
@@ -0,0 +1,83 @@
+use anyhow::{Context, Result};
+use reqwest::header::{HeaderValue, CONTENT_TYPE};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use super::introspection::{Introspection, INTROSPECTION_QUERY, SERVICE_QUERY};
+use super::Subgraph;
+use crate::core::runtime::TargetRuntime;
+
+#[derive(Debug, Deserialize)]
+struct GraphQLResponse<T> {
+    data: Option<T>,
+    #[serde(default)]
+    errors: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SchemaData {
+    #[serde(rename = "__schema")]
+    schema: Introspection,
+}
+
+#[derive(Debug, Deserialize)]
+struct Service {
+    sdl: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ServiceData {
+    #[serde(rename = "_service")]
+    service: Service,
+}
+
+/// Reads the schema of upstream GraphQL services linked as subgraphs.
+#[derive(Clone)]
+pub struct SubgraphReader {
+    runtime: TargetRuntime,
+}
+
+impl SubgraphReader {
+    pub fn init(runtime: TargetRuntime) -> Self {
+        Self { runtime }
+    }
+
+    /// Introspects the subgraph available at the given url. The federation
+    /// SDL is read as well when the subgraph supports it, to find its
+    /// entities.
+    pub async fn read(&self, url: &str) -> Result<Subgraph> {
+        let introspection = self
+            .execute::<SchemaData>(url, INTROSPECTION_QUERY)
+            .await?
+            .schema;
+        let sdl = self
+            .execute::<ServiceData>(url, SERVICE_QUERY)
+            .await
+            .ok()
+            .map(|data| data.service.sdl);
+        let config = introspection.into_config(url, sdl.as_deref())?;
+
+        Ok(Subgraph { url: url.to_string(), config })
+    }
+
+    async fn execute<T: DeserializeOwned>(&self, url: &str, query: &str) -> Result<T> {
+        let mut request = reqwest::Request::new(reqwest::Method::POST, url.parse()?);
+        request
+            .headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        request
+            .body_mut()
+            .replace(serde_json::json!({ "query": query }).to_string().into());
+
+        let response = self.runtime.http.execute(request).await?;
+        let response: GraphQLResponse<T> = serde_json::from_slice(&response.body)
+            .with_context(|| format!("Failed to parse the response of the subgraph {url}"))?;
+
+        response.data.with_context(|| {
+            format!(
+                "Failed to query the subgraph {url}: {}",
+                serde_json::Value::from(response.errors)
+            )
+        })
+    }
+}
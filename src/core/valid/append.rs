@@ -76,3 +76,12 @@ impl<A0, A1, A2, A3, A4, A5, A6, A7, A8, A9, A10> Append<A10>
         (a0, a1, a2, a3, a4, a5, a6, a7, a8, a9, a10)
     }
 }
+impl<A0, A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11> Append<A11>
+    for (A0, A1, A2, A3, A4, A5, A6, A7, A8, A9, A10)
+{
+    type Out = (A0, A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11);
+    fn append(self, a11: A11) -> Self::Out {
+        let (a0, a1, a2, a3, a4, a5, a6, a7, a8, a9, a10) = self;
+        (a0, a1, a2, a3, a4, a5, a6, a7, a8, a9, a10, a11)
+    }
+}
@@ -1,7 +1,9 @@
+use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
 
 use anyhow::Result;
 use hyper::body::Bytes;
+use lambda_http::request::RequestContext;
 use lambda_http::RequestExt;
 use reqwest::Client;
 use tailcall::core::http::Response;
@@ -76,9 +78,25 @@ pub fn to_request(req: lambda_http::Request) -> anyhow::Result<hyper::Request<hy
         req2 = req2.header(key, value);
     }
 
+    if let Some(ip) = source_ip(&req) {
+        req2 = req2.extension(SocketAddr::new(ip, 0));
+    }
+
     Ok(req2.body(hyper::Body::from(req.body().to_vec()))?)
 }
 
+/// Returns the IP address of the client, that API Gateway sends in the context
+/// of the request. The load balancers only send it in the headers.
+fn source_ip(req: &lambda_http::Request) -> Option<IpAddr> {
+    let source_ip = match req.request_context_ref()? {
+        RequestContext::ApiGatewayV1(context) => context.identity.source_ip.as_ref(),
+        RequestContext::ApiGatewayV2(context) => context.http.source_ip.as_ref(),
+        RequestContext::WebSocket(context) => context.identity.source_ip.as_ref(),
+        _ => None,
+    }?;
+    source_ip.parse().ok()
+}
+
 pub async fn to_response(
     res: hyper::Response<hyper::Body>,
 ) -> Result<lambda_http::Response<lambda_http::Body>, lambda_http::http::Error> {
@@ -3,6 +3,7 @@ use std::sync::Arc;
 
 use anyhow::anyhow;
 use tailcall::core::cache::InMemoryCache;
+use tailcall::core::rate_limit::InMemoryRateLimiter;
 use tailcall::core::runtime::TargetRuntime;
 use tailcall::core::{EntityCache, EnvIO, FileIO};
 use tokio::io::AsyncReadExt;
@@ -59,6 +60,7 @@ pub fn init_runtime() -> TargetRuntime {
         file: init_file(),
         env: init_env(),
         cache: init_cache(),
+        rate_limiter: Arc::new(InMemoryRateLimiter::default()),
         extensions: Arc::new(vec![]),
         cmd_worker: None,
         worker: None,
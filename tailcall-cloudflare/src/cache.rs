@@ -1,31 +1,65 @@
+use std::collections::HashMap;
 use std::num::NonZeroU64;
 use std::rc::Rc;
 
 use async_graphql_value::ConstValue;
-use serde_json::Value;
+use tailcall::core::cache::codec;
 use tailcall::core::ir::model::IoId;
 use tailcall::core::{cache, Cache};
 use worker::kv::KvStore;
 
+/// Keeps the results of `@cache` in Workers KV, so that they are shared
+/// between the isolates. The values are encoded in CBOR. The keys of a tag are
+/// kept in a single entry, so with the eventual consistency of KV, the keys
+/// tagged concurrently from different locations can miss a purge.
 pub struct CloudflareChronoCache {
     env: Rc<worker::Env>,
+    binding: String,
+    prefix: String,
 }
 
 unsafe impl Send for CloudflareChronoCache {}
 
 unsafe impl Sync for CloudflareChronoCache {}
 
+// KV doesn't allow keys to expire in less than a minute, so the entries that
+// live less than that are not kept, rather than being served after their
+// `maxAge`.
+const MIN_EXPIRATION_TTL: u64 = 60;
+
 impl CloudflareChronoCache {
-    pub fn init(env: Rc<worker::Env>) -> Self {
-        Self { env }
+    pub fn init(env: Rc<worker::Env>, binding: String, prefix: String) -> Self {
+        Self { env, binding, prefix }
     }
     fn get_kv(&self) -> Result<KvStore, cache::Error> {
         self.env
-            .kv("TMP_KV")
+            .kv(&self.binding)
             .map_err(|e| cache::Error::Kv(e.to_string()))
     }
+    /// Key of the entry with the keys associated with a tag, and their expiry.
+    fn tag_key(&self, tag: &str) -> String {
+        format!("{}tag:{}", self.prefix, tag)
+    }
+}
+
+// the ttl is in milliseconds, while KV expects seconds
+fn expiration_ttl(ttl: u64) -> u64 {
+    ttl.div_ceil(1000).max(MIN_EXPIRATION_TTL)
+}
+
+fn is_short_lived(ttl: NonZeroU64) -> bool {
+    ttl.get() < MIN_EXPIRATION_TTL * 1000
 }
-// TODO: Needs fix
+
+async fn get_tag(kv_store: &KvStore, tag: &str) -> Result<HashMap<String, u64>, cache::Error> {
+    let keys = kv_store
+        .get(tag)
+        .json::<HashMap<String, u64>>()
+        .await
+        .map_err(|e| cache::Error::Kv(e.to_string()))?;
+    Ok(keys.unwrap_or_default())
+}
+
 #[async_trait::async_trait]
 impl Cache for CloudflareChronoCache {
     type Key = IoId;
@@ -36,11 +70,16 @@ impl Cache for CloudflareChronoCache {
         value: ConstValue,
         ttl: NonZeroU64,
     ) -> Result<(), cache::Error> {
+        if is_short_lived(ttl) {
+            return Ok(());
+        }
         let kv_store = self.get_kv()?;
-        let ttl = ttl.get();
+        let key = codec::key(&self.prefix, key.as_u64());
+        let value = codec::encode(&value)?;
+        let ttl = expiration_ttl(ttl.get());
         async_std::task::spawn_local(async move {
             kv_store
-                .put(&key.as_u64().to_string(), value.to_string())
+                .put_bytes(&key, &value)
                 .map_err(|e| cache::Error::Kv(e.to_string()))?
                 .expiration_ttl(ttl)
                 .execute()
@@ -52,14 +91,89 @@ impl Cache for CloudflareChronoCache {
 
     async fn get<'a>(&'a self, key: &'a IoId) -> Result<Option<Self::Value>, cache::Error> {
         let kv_store = self.get_kv()?;
-        let key = key.as_u64().to_string();
+        let key = codec::key(&self.prefix, key.as_u64());
         async_std::task::spawn_local(async move {
             let val = kv_store
                 .get(&key)
-                .json::<Value>()
+                .bytes()
                 .await
                 .map_err(|e| cache::Error::Kv(e.to_string()))?;
-            Ok(val.map(ConstValue::from_json).transpose()?)
+            val.map(|val| codec::decode(&val)).transpose()
+        })
+        .await
+    }
+
+    async fn tag<'a>(
+        &'a self,
+        key: &'a IoId,
+        tags: &'a [String],
+        ttl: NonZeroU64,
+    ) -> Result<(), cache::Error> {
+        if is_short_lived(ttl) {
+            return Ok(());
+        }
+        let kv_store = self.get_kv()?;
+        let key = codec::key(&self.prefix, key.as_u64());
+        let tags = tags.iter().map(|tag| self.tag_key(tag)).collect::<Vec<_>>();
+        let ttl = ttl.get();
+        async_std::task::spawn_local(async move {
+            let now = worker::Date::now().as_millis();
+            for tag in tags {
+                let mut keys = get_tag(&kv_store, &tag).await?;
+                keys.retain(|_, expiry| *expiry > now);
+                keys.insert(key.clone(), now + ttl);
+
+                // the tag lives as long as the longest lived of its keys
+                let max_expiry = keys.values().max().copied().unwrap_or(now);
+                let value = serde_json::to_string(&keys)?;
+                kv_store
+                    .put(&tag, value)
+                    .map_err(|e| cache::Error::Kv(e.to_string()))?
+                    .expiration_ttl(expiration_ttl(max_expiry - now))
+                    .execute()
+                    .await
+                    .map_err(|e| cache::Error::Kv(e.to_string()))?;
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    async fn delete<'a>(&'a self, key: &'a IoId) -> Result<bool, cache::Error> {
+        let kv_store = self.get_kv()?;
+        let key = codec::key(&self.prefix, key.as_u64());
+        async_std::task::spawn_local(async move {
+            // KV doesn't tell whether a deleted key was present
+            kv_store
+                .delete(&key)
+                .await
+                .map_err(|e| cache::Error::Kv(e.to_string()))?;
+            Ok(true)
+        })
+        .await
+    }
+
+    async fn purge<'a>(&'a self, tags: &'a [String]) -> Result<usize, cache::Error> {
+        let kv_store = self.get_kv()?;
+        let tags = tags.iter().map(|tag| self.tag_key(tag)).collect::<Vec<_>>();
+        async_std::task::spawn_local(async move {
+            let now = worker::Date::now().as_millis();
+            let mut purged = 0;
+            for tag in tags {
+                let keys = get_tag(&kv_store, &tag).await?;
+                for (key, expiry) in keys {
+                    kv_store
+                        .delete(&key)
+                        .await
+                        .map_err(|e| cache::Error::Kv(e.to_string()))?;
+                    purged += usize::from(expiry > now);
+                }
+                kv_store
+                    .delete(&tag)
+                    .await
+                    .map_err(|e| cache::Error::Kv(e.to_string()))?;
+            }
+            Ok(purged)
         })
         .await
     }
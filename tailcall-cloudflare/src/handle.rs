@@ -60,9 +60,10 @@ async fn get_app_ctx(
         }
     }
 
-    let runtime = runtime::init(env)?;
+    let runtime = runtime::init(env.clone())?;
     match showcase::create_app_ctx::<GraphQLRequest>(req, runtime, true).await? {
-        Ok(app_ctx) => {
+        Ok(mut app_ctx) => {
+            app_ctx.runtime.cache = runtime::init_cache(env, &app_ctx.blueprint.server.cache);
             let app_ctx: Arc<AppContext> = Arc::new(app_ctx);
             if let Some(file_path) = file_path {
                 *APP_CTX.write().unwrap() = Some((file_path, app_ctx.clone()));
@@ -1,3 +1,5 @@
+use std::net::{IpAddr, SocketAddr};
+
 use anyhow::{anyhow, Result};
 use async_std::task::spawn_local;
 use hyper::body::Bytes;
@@ -93,5 +95,15 @@ pub async fn to_request(mut req: worker::Request) -> Result<hyper::Request<hyper
     for (k, v) in headers {
         builder = builder.header(k, v);
     }
+    // Cloudflare sends the IP address of the client in a header
+    let client_ip = req
+        .headers()
+        .get("CF-Connecting-IP")
+        .ok()
+        .flatten()
+        .and_then(|ip| ip.parse::<IpAddr>().ok());
+    if let Some(ip) = client_ip {
+        builder = builder.extension(SocketAddr::new(ip, 0));
+    }
     Ok(builder.body(hyper::body::Body::from(body))?)
 }
@@ -7,6 +7,7 @@ mod env;
 mod file;
 pub mod handle;
 mod http;
+mod rate_limit;
 mod runtime;
 
 #[worker::event(fetch)]
@@ -0,0 +1,94 @@
+use std::num::NonZeroU64;
+use std::rc::Rc;
+use std::time::Duration;
+
+use tailcall::core::blueprint::Rate;
+use tailcall::core::rate_limit::TokenBucket;
+use tailcall::core::RateLimiter;
+use worker::{durable_object, Date, DurableObject, Env, ObjectNamespace, Request, Response, State};
+
+use crate::to_anyhow;
+
+/// Keeps the buckets in a Durable Object for each key, that are shared between
+/// the isolates. An object handles the requests of its key one at a time, so
+/// unlike KV, that allows a single write per second to a key, it keeps up with
+/// the clients sending many requests.
+pub struct CloudflareRateLimiter {
+    env: Rc<worker::Env>,
+}
+
+unsafe impl Send for CloudflareRateLimiter {}
+
+unsafe impl Sync for CloudflareRateLimiter {}
+
+impl CloudflareRateLimiter {
+    pub fn init(env: Rc<worker::Env>) -> Self {
+        Self { env }
+    }
+    fn get_namespace(&self) -> anyhow::Result<ObjectNamespace> {
+        self.env.durable_object("RATE_LIMITER").map_err(to_anyhow)
+    }
+}
+
+#[async_trait::async_trait]
+impl RateLimiter for CloudflareRateLimiter {
+    async fn acquire(&self, key: &str, rate: &Rate) -> anyhow::Result<bool> {
+        let namespace = self.get_namespace()?;
+        let key = key.to_string();
+        let url = format!(
+            "https://rate-limit/?requests={}&duration={}",
+            rate.requests,
+            rate.duration.as_millis()
+        );
+        async_std::task::spawn_local(async move {
+            let stub = namespace
+                .id_from_name(&key)
+                .and_then(|id| id.get_stub())
+                .map_err(to_anyhow)?;
+            let mut response = stub.fetch_with_str(&url).await.map_err(to_anyhow)?;
+            response.json::<bool>().await.map_err(to_anyhow)
+        })
+        .await
+    }
+}
+
+/// The bucket of a key, that is kept in memory while the object is active. The
+/// bucket starts full again once the object is evicted after being idle.
+#[durable_object]
+pub struct RateLimitBucket {
+    bucket: Option<TokenBucket>,
+}
+
+#[durable_object]
+impl DurableObject for RateLimitBucket {
+    fn new(_state: State, _env: Env) -> Self {
+        Self { bucket: None }
+    }
+
+    async fn fetch(&mut self, req: Request) -> worker::Result<Response> {
+        let Some(rate) = to_rate(&req) else {
+            return Response::error("Invalid rate", 400);
+        };
+
+        let now = Date::now().as_millis();
+        let bucket = self
+            .bucket
+            .get_or_insert_with(|| TokenBucket::new(&rate, now));
+        Response::from_json(&bucket.acquire(&rate, now))
+    }
+}
+
+/// Reads the rate sent by the limiter in the query of the request.
+fn to_rate(req: &Request) -> Option<Rate> {
+    let url = req.url().ok()?;
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .and_then(|(_, value)| value.parse::<u64>().ok())
+    };
+
+    Some(Rate {
+        requests: NonZeroU64::new(param("requests")?)?,
+        duration: Duration::from_millis(param("duration")?),
+    })
+}
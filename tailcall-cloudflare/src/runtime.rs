@@ -3,11 +3,13 @@ use std::sync::Arc;
 
 use anyhow::anyhow;
 use async_graphql_value::ConstValue;
+use tailcall::core::blueprint::CacheStore;
+use tailcall::core::cache::InMemoryCache;
 use tailcall::core::ir::model::IoId;
 use tailcall::core::runtime::TargetRuntime;
-use tailcall::core::{EnvIO, FileIO, HttpIO};
+use tailcall::core::{EnvIO, FileIO, HttpIO, RateLimiter};
 
-use crate::{cache, env, file, http};
+use crate::{cache, env, file, http, rate_limit};
 
 fn init_env(env: Rc<worker::Env>) -> Arc<dyn EnvIO> {
     Arc::new(env::CloudflareEnv::init(env))
@@ -21,10 +23,26 @@ fn init_http() -> Arc<dyn HttpIO> {
     Arc::new(http::CloudflareHttp::init())
 }
 
-fn init_cache(
+/// Initializes the store of the results of `@cache`, which is only known once
+/// the config is read.
+pub fn init_cache(
     env: Rc<worker::Env>,
+    store: &CacheStore,
 ) -> Arc<dyn tailcall::core::Cache<Key = IoId, Value = ConstValue>> {
-    Arc::new(cache::CloudflareChronoCache::init(env))
+    match store {
+        CacheStore::InMemory { capacity } => Arc::new(InMemoryCache::with_capacity(capacity.get())),
+        CacheStore::Redis { .. } => {
+            tracing::warn!("The Redis cache isn't available on Cloudflare, using memory");
+            Arc::new(InMemoryCache::new())
+        }
+        CacheStore::CloudflareKv { binding, prefix } => Arc::new(
+            cache::CloudflareChronoCache::init(env, binding.clone(), prefix.clone()),
+        ),
+    }
+}
+
+fn init_rate_limiter(env: Rc<worker::Env>) -> Arc<dyn RateLimiter> {
+    Arc::new(rate_limit::CloudflareRateLimiter::init(env))
 }
 
 pub fn init(env: Rc<worker::Env>) -> anyhow::Result<TargetRuntime> {
@@ -39,7 +57,8 @@ pub fn init(env: Rc<worker::Env>) -> anyhow::Result<TargetRuntime> {
         http2_only: http.clone(),
         env: init_env(env.clone()),
         file: init_file(env.clone(), &bucket_id)?,
-        cache: init_cache(env),
+        cache: init_cache(env.clone(), &CacheStore::default()),
+        rate_limiter: init_rate_limiter(env),
         extensions: Arc::new(vec![]),
         cmd_worker: None,
         worker: None,
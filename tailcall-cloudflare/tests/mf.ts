@@ -67,5 +67,6 @@ export const mf = new Miniflare({
   modulesRules: [{type: "CompiledWasm", include: ["**/*.wasm"], fallthrough: true}],
   bindings: {BUCKET: "MY_R2"},
   r2Buckets: ["MY_R2"],
+  durableObjects: {RATE_LIMITER: "RateLimitBucket"},
   fetchMock: mockAgent,
 })
@@ -20,8 +20,8 @@ impl Default for HttpCacheManager {
 
 #[derive(Clone, Deserialize, Serialize)]
 pub struct Store {
-    response: HttpResponse,
-    policy: CachePolicy,
+    pub(crate) response: HttpResponse,
+    pub(crate) policy: CachePolicy,
 }
 
 impl HttpCacheManager {
@@ -71,60 +71,20 @@ impl CacheManager for HttpCacheManager {
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
-
-    use http_cache::HttpVersion;
-    use reqwest::{Method, Response, ResponseBuilderExt};
-    use url::Url;
-
     use super::*;
-
-    fn convert_response(response: HttpResponse) -> Result<Response> {
-        let ret_res = http::Response::builder()
-            .status(response.status)
-            .url(response.url)
-            .version(response.version.into())
-            .body(response.body)?;
-
-        Ok(Response::from(ret_res))
-    }
-
-    async fn insert_key_into_cache(manager: &HttpCacheManager, key: &str) {
-        let request_url = "http://localhost:8080/test";
-        let url = Url::parse(request_url).unwrap();
-
-        let http_resp = HttpResponse {
-            headers: HashMap::default(),
-            body: vec![1, 2, 3],
-            status: 200,
-            url: url.clone(),
-            version: HttpVersion::Http11,
-        };
-        let resp = convert_response(http_resp.clone()).unwrap();
-        let request: reqwest::Request =
-            reqwest::Request::new(Method::GET, request_url.parse().unwrap());
-
-        let _ = manager
-            .put(
-                key.to_string(),
-                http_resp,
-                CachePolicy::new(&request, &resp),
-            )
-            .await
-            .unwrap();
-    }
+    use crate::tests::insert_key_into_cache;
 
     #[tokio::test]
     async fn test_put() {
         let manager = HttpCacheManager::default();
-        insert_key_into_cache(&manager, "test").await;
+        insert_key_into_cache(&manager, "test", vec![1, 2, 3]).await;
         assert!(manager.cache.contains_key("test"));
     }
 
     #[tokio::test]
     async fn test_get_when_key_present() {
         let manager = HttpCacheManager::default();
-        insert_key_into_cache(&manager, "test").await;
+        insert_key_into_cache(&manager, "test", vec![1, 2, 3]).await;
         let value = manager.get("test").await.unwrap();
         assert!(value.is_some());
     }
@@ -139,7 +99,7 @@ mod tests {
     #[tokio::test]
     async fn test_delete_when_key_present() {
         let manager = HttpCacheManager::default();
-        insert_key_into_cache(&manager, "test").await;
+        insert_key_into_cache(&manager, "test", vec![1, 2, 3]).await;
 
         assert!(manager.cache.iter().count() as i32 == 1);
         let _ = manager.delete("test").await;
@@ -149,7 +109,7 @@ mod tests {
     #[tokio::test]
     async fn test_clear() {
         let manager = HttpCacheManager::default();
-        insert_key_into_cache(&manager, "test").await;
+        insert_key_into_cache(&manager, "test", vec![1, 2, 3]).await;
         assert!(manager.cache.iter().count() as i32 == 1);
         let _ = manager.clear().await;
         assert!(manager.cache.iter().count() as i32 == 0);
@@ -158,9 +118,9 @@ mod tests {
     #[tokio::test]
     async fn test_lru_eviction_policy() {
         let manager = HttpCacheManager::new(2);
-        insert_key_into_cache(&manager, "test-1").await;
-        insert_key_into_cache(&manager, "test-2").await;
-        insert_key_into_cache(&manager, "test-10").await;
+        insert_key_into_cache(&manager, "test-1", vec![1, 2, 3]).await;
+        insert_key_into_cache(&manager, "test-2", vec![1, 2, 3]).await;
+        insert_key_into_cache(&manager, "test-10", vec![1, 2, 3]).await;
 
         let res = manager.get("test-1").await.unwrap();
         assert!(res.is_none());
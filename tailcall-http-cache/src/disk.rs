@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use cacache::Integrity;
+use http_cache_reqwest::{CacheManager, HttpResponse};
+use http_cache_semantics::CachePolicy;
+use moka::notification::RemovalCause;
+use moka::policy::EvictionPolicy;
+use moka::sync::Cache;
+
+use crate::cache::{Result, Store};
+
+/// Keeps the cached responses in a content-addressed store on the disk, so
+/// that they survive restarts. Once the responses take more than `max_size`
+/// bytes, the least recently used ones are evicted. The clones share the
+/// same index.
+#[derive(Clone)]
+pub struct DiskCacheManager {
+    path: PathBuf,
+    /// The entries of the store, weighed by their size on the disk.
+    index: Cache<String, Entry>,
+    contents: Contents,
+}
+
+#[derive(Clone)]
+struct Entry {
+    integrity: Integrity,
+    size: u32,
+}
+
+/// Counts the keys of each content, as the keys with the same response share
+/// its content on the disk, that is only removed along with the last of them.
+#[derive(Clone, Default)]
+struct Contents(Arc<Mutex<HashMap<String, usize>>>);
+
+impl Contents {
+    fn acquire(&self, integrity: &Integrity) {
+        *self
+            .0
+            .lock()
+            .unwrap()
+            .entry(integrity.to_string())
+            .or_default() += 1;
+    }
+
+    /// Returns whether the content isn't used by another key anymore.
+    fn release(&self, integrity: &Integrity) -> bool {
+        let mut contents = self.0.lock().unwrap();
+        let key = integrity.to_string();
+        match contents.get_mut(&key) {
+            Some(count) if *count > 1 => {
+                *count -= 1;
+                false
+            }
+            _ => {
+                contents.remove(&key);
+                true
+            }
+        }
+    }
+}
+
+impl DiskCacheManager {
+    pub fn new(path: impl Into<PathBuf>, max_size: u64) -> Self {
+        let path = path.into();
+        let dir = path.clone();
+        let contents = Contents::default();
+        let evicted = contents.clone();
+        let index = Cache::builder()
+            .eviction_policy(EvictionPolicy::lru())
+            .max_capacity(max_size)
+            .weigher(|_, entry: &Entry| entry.size)
+            .eviction_listener(move |key: Arc<String>, entry: Entry, cause| {
+                // the replaced and deleted entries are removed by the manager
+                if matches!(cause, RemovalCause::Size | RemovalCause::Expired) {
+                    let remove_content = evicted.release(&entry.integrity);
+                    remove(dir.clone(), key, entry, remove_content);
+                }
+            })
+            .build();
+
+        // the entries written by previous runs are the least recently used
+        let mut entries = cacache::list_sync(&path)
+            .filter_map(|metadata| metadata.ok())
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|metadata| metadata.time);
+        for metadata in entries {
+            let size = u32::try_from(metadata.size).unwrap_or(u32::MAX);
+            contents.acquire(&metadata.integrity);
+            index.insert(metadata.key, Entry { integrity: metadata.integrity, size });
+        }
+        index.run_pending_tasks();
+
+        Self { path, index, contents }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the total size in bytes of the cached responses.
+    pub fn size(&self) -> u64 {
+        self.index.run_pending_tasks();
+        self.index.weighted_size()
+    }
+
+    pub async fn clear(&self) -> Result<()> {
+        self.index.invalidate_all();
+        self.index.run_pending_tasks();
+        self.contents.0.lock().unwrap().clear();
+        Ok(cacache::clear(&self.path).await?)
+    }
+}
+
+/// Removes the evicted entry from the disk, along with its content when no
+/// other key uses it. The failures are ignored, as the entry can't be read
+/// anymore anyway. The eviction happens while an entry is inserted, so the
+/// files are removed on the blocking threads of the runtime when there is one.
+/// A key that is written again meanwhile can be removed too, which is read as
+/// a miss.
+fn remove(path: PathBuf, key: Arc<String>, entry: Entry, remove_content: bool) {
+    let task = move || {
+        let _ = cacache::remove_sync(&path, key.as_str());
+        if remove_content {
+            let _ = cacache::remove_hash_sync(&path, &entry.integrity);
+        }
+    };
+
+    match tokio::runtime::Handle::try_current() {
+        Ok(runtime) => {
+            runtime.spawn_blocking(task);
+        }
+        Err(_) => task(),
+    }
+}
+
+#[async_trait::async_trait]
+impl CacheManager for DiskCacheManager {
+    async fn get(&self, cache_key: &str) -> Result<Option<(HttpResponse, CachePolicy)>> {
+        if self.index.get(cache_key).is_none() {
+            return Ok(None);
+        }
+
+        let store = cacache::read(&self.path, cache_key)
+            .await
+            .ok()
+            .and_then(|bytes| bincode::deserialize::<Store>(&bytes).ok());
+        match store {
+            Some(store) => Ok(Some((store.response, store.policy))),
+            None => {
+                self.index.invalidate(cache_key);
+                Ok(None)
+            }
+        }
+    }
+
+    async fn put(
+        &self,
+        cache_key: String,
+        response: HttpResponse,
+        policy: CachePolicy,
+    ) -> Result<HttpResponse> {
+        let data = Store { response: response.clone(), policy };
+        let bytes = bincode::serialize(&data)?;
+        let size = u32::try_from(bytes.len()).unwrap_or(u32::MAX);
+        let integrity = cacache::write(&self.path, &cache_key, bytes).await?;
+
+        self.contents.acquire(&integrity);
+        if let Some(replaced) = self.index.get(&cache_key) {
+            if self.contents.release(&replaced.integrity) {
+                let _ = cacache::remove_hash(&self.path, &replaced.integrity).await;
+            }
+        }
+        self.index.insert(cache_key, Entry { integrity, size });
+        self.index.run_pending_tasks();
+        Ok(response)
+    }
+
+    async fn delete(&self, cache_key: &str) -> Result<()> {
+        if let Some(entry) = self.index.remove(cache_key) {
+            cacache::remove(&self.path, cache_key).await?;
+            if self.contents.release(&entry.integrity) {
+                cacache::remove_hash(&self.path, &entry.integrity).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::insert_key_into_cache;
+
+    #[tokio::test]
+    async fn test_get_after_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = DiskCacheManager::new(dir.path(), 1024 * 1024);
+        insert_key_into_cache(&manager, "test", vec![1, 2, 3]).await;
+        drop(manager);
+
+        let manager = DiskCacheManager::new(dir.path(), 1024 * 1024);
+        let (response, _) = manager.get("test").await.unwrap().unwrap();
+        assert_eq!(response.body, vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_get_when_key_not_present() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = DiskCacheManager::new(dir.path(), 1024 * 1024);
+        assert!(manager.get("test").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_delete() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = DiskCacheManager::new(dir.path(), 1024 * 1024);
+        insert_key_into_cache(&manager, "test", vec![1, 2, 3]).await;
+
+        manager.delete("test").await.unwrap();
+        assert!(manager.get("test").await.unwrap().is_none());
+        assert_eq!(manager.size(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_delete_shared_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = DiskCacheManager::new(dir.path(), 1024 * 1024);
+        insert_key_into_cache(&manager, "test-1", vec![1, 2, 3]).await;
+        let (response, policy) = manager.get("test-1").await.unwrap().unwrap();
+        // the same response under another key shares the content
+        manager
+            .put("test-2".to_string(), response, policy)
+            .await
+            .unwrap();
+
+        manager.delete("test-1").await.unwrap();
+        let (response, _) = manager.get("test-2").await.unwrap().unwrap();
+        assert_eq!(response.body, vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_clear() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = DiskCacheManager::new(dir.path(), 1024 * 1024);
+        insert_key_into_cache(&manager, "test", vec![1, 2, 3]).await;
+
+        manager.clear().await.unwrap();
+        assert!(manager.get("test").await.unwrap().is_none());
+        assert_eq!(cacache::list_sync(manager.path()).count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_lru_eviction_by_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = DiskCacheManager::new(dir.path().join("size"), 1024 * 1024);
+        insert_key_into_cache(&manager, "test", vec![0; 800]).await;
+        let size = manager.size();
+
+        // room for two of the responses only
+        let manager = DiskCacheManager::new(dir.path().join("lru"), size * 5 / 2);
+        insert_key_into_cache(&manager, "test-1", vec![1; 800]).await;
+        insert_key_into_cache(&manager, "test-2", vec![2; 800]).await;
+        assert!(manager.get("test-1").await.unwrap().is_some());
+        insert_key_into_cache(&manager, "test-3", vec![3; 800]).await;
+
+        assert!(manager.get("test-1").await.unwrap().is_some());
+        assert!(manager.get("test-2").await.unwrap().is_none());
+        assert!(manager.get("test-3").await.unwrap().is_some());
+        assert_eq!(manager.size(), size * 2);
+    }
+}
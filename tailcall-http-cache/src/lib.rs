@@ -1,3 +1,7 @@
 mod cache;
+mod disk;
+#[cfg(test)]
+mod tests;
 
 pub use cache::HttpCacheManager;
+pub use disk::DiskCacheManager;
@@ -0,0 +1,46 @@
+use std::collections::HashMap;
+
+use http_cache::HttpVersion;
+use http_cache_reqwest::{CacheManager, HttpResponse};
+use http_cache_semantics::CachePolicy;
+use reqwest::{Method, Response, ResponseBuilderExt};
+use url::Url;
+
+use crate::cache::Result;
+
+fn convert_response(response: HttpResponse) -> Result<Response> {
+    let ret_res = http::Response::builder()
+        .status(response.status)
+        .url(response.url)
+        .version(response.version.into())
+        .body(response.body)?;
+
+    Ok(Response::from(ret_res))
+}
+
+/// Caches a response with the given body under the key, as the middleware
+/// does for the responses of the upstreams.
+pub async fn insert_key_into_cache<M: CacheManager>(manager: &M, key: &str, body: Vec<u8>) {
+    let request_url = "http://localhost:8080/test";
+    let url = Url::parse(request_url).unwrap();
+
+    let http_resp = HttpResponse {
+        headers: HashMap::default(),
+        body,
+        status: 200,
+        url: url.clone(),
+        version: HttpVersion::Http11,
+    };
+    let resp = convert_response(http_resp.clone()).unwrap();
+    let request: reqwest::Request =
+        reqwest::Request::new(Method::GET, request_url.parse().unwrap());
+
+    let _ = manager
+        .put(
+            key.to_string(),
+            http_resp,
+            CachePolicy::new(&request, &resp),
+        )
+        .await
+        .unwrap();
+}
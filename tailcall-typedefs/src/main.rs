@@ -52,7 +52,7 @@ async fn main() {
 
 async fn mode_check() -> Result<()> {
     let json_schema = get_file_path();
-    let rt = cli::runtime::init(&Default::default());
+    let rt = cli::runtime::init(&Default::default())?;
     let file_io = rt.file;
     let content = file_io
         .read(
@@ -70,7 +70,7 @@ async fn mode_check() -> Result<()> {
 }
 
 async fn mode_fix() -> Result<()> {
-    let rt = cli::runtime::init(&Default::default());
+    let rt = cli::runtime::init(&Default::default())?;
     let file_io = rt.file;
 
     update_json(file_io.clone()).await?;
@@ -3,6 +3,7 @@ use std::sync::Arc;
 use async_graphql_value::ConstValue;
 use tailcall::core::cache::InMemoryCache;
 use tailcall::core::ir::model::IoId;
+use tailcall::core::rate_limit::InMemoryRateLimiter;
 use tailcall::core::runtime::TargetRuntime;
 use tailcall::core::{EnvIO, FileIO, HttpIO};
 
@@ -38,6 +39,7 @@ pub fn init_rt() -> TargetRuntime {
         env,
         file,
         cache,
+        rate_limiter: Arc::new(InMemoryRateLimiter::default()),
         extensions: Arc::new(vec![]),
         cmd_worker: None,
         worker: None,
@@ -110,7 +110,13 @@ impl HttpIO for Http {
         let status_code = reqwest::StatusCode::from_u16(mock_response.0.status)?;
 
         if status_code.is_client_error() || status_code.is_server_error() {
-            return Err(anyhow::format_err!("Status code error"));
+            // Keep the status in the error, as the native client does, so that
+            // retries can be decided on it.
+            let response = hyper::Response::builder().status(status_code).body("")?;
+            let error = reqwest::Response::from(response)
+                .error_for_status()
+                .unwrap_err();
+            return Err(anyhow::Error::new(error).context("Status code error"));
         }
 
         let mut response = Response { status: status_code, ..Default::default() };
@@ -16,6 +16,7 @@ use tailcall::core::app_context::AppContext;
 use tailcall::core::blueprint::Blueprint;
 use tailcall::core::cache::InMemoryCache;
 use tailcall::core::config::{ConfigModule, Source};
+use tailcall::core::rate_limit::InMemoryRateLimiter;
 use tailcall::core::runtime::TargetRuntime;
 use tailcall::core::worker::{Command, Event};
 use tailcall::core::{EnvIO, WorkerIO};
@@ -303,6 +304,7 @@ impl ExecutionSpec {
             file: Arc::new(File::new(self.clone())),
             env: Arc::new(Env::init(env)),
             cache: Arc::new(InMemoryCache::new()),
+            rate_limiter: Arc::new(InMemoryRateLimiter::default()),
             extensions: Arc::new(vec![]),
             cmd_worker: http_worker,
             worker,
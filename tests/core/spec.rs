@@ -186,17 +186,22 @@ async fn run_query_tests_on_spec(
                 headers.insert(key.to_string(), value.to_str().unwrap().to_string());
             }
 
-            let response: APIResponse = APIResponse {
-                status: response.status().clone().as_u16(),
-                headers,
-                body: Some(APIBody::Value(
-                    serde_json::from_slice(
-                        &hyper::body::to_bytes(response.into_body()).await.unwrap(),
-                    )
-                    .unwrap_or_default(),
-                )),
+            // streamed responses are made of several payloads, that are
+            // compared as a whole
+            let is_stream = headers.get("content-type").is_some_and(|content_type| {
+                content_type.starts_with("text/event-stream")
+                    || content_type.starts_with("multipart/mixed")
+            });
+            let status = response.status().as_u16();
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            let body = if is_stream {
+                APIBody::Text(String::from_utf8_lossy(&body).into_owned())
+            } else {
+                APIBody::Value(serde_json::from_slice(&body).unwrap_or_default())
             };
 
+            let response: APIResponse = APIResponse { status, headers, body: Some(body) };
+
             let snapshot_name = format!("{}_{}", spec.safe_name, i);
 
             insta::assert_json_snapshot!(snapshot_name, response);
@@ -12,9 +12,10 @@ pub mod test {
     use reqwest::Client;
     use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
     use tailcall::cli::javascript::init_worker_io;
-    use tailcall::core::blueprint::{Script, Upstream};
+    use tailcall::core::blueprint::{self, Script, Upstream};
     use tailcall::core::cache::InMemoryCache;
     use tailcall::core::http::Response;
+    use tailcall::core::rate_limit::InMemoryRateLimiter;
     use tailcall::core::runtime::TargetRuntime;
     use tailcall::core::worker::{Command, Event};
     use tailcall::core::{EnvIO, FileIO, HttpIO};
@@ -60,10 +61,10 @@ pub mod test {
 
             let mut client = ClientBuilder::new(builder.build().expect("Failed to build client"));
 
-            if upstream.http_cache > 0 {
+            if let Some(blueprint::HttpCache::InMemory { capacity }) = upstream.http_cache {
                 client = client.with(Cache(HttpCache {
                     mode: CacheMode::Default,
-                    manager: HttpCacheManager::new(upstream.http_cache),
+                    manager: HttpCacheManager::new(capacity),
                     options: HttpCacheOptions::default(),
                 }))
             }
@@ -143,6 +144,7 @@ pub mod test {
             env: Arc::new(env),
             file: Arc::new(file),
             cache: Arc::new(InMemoryCache::new()),
+            rate_limiter: Arc::new(InMemoryRateLimiter::default()),
             extensions: Arc::new(vec![]),
             cmd_worker: match &script {
                 Some(script) => Some(init_worker_io::<Event, Command>(script.to_owned())),
@@ -158,8 +160,11 @@ pub mod test {
 
 #[cfg(test)]
 mod server_spec {
+    use std::time::Duration;
+
     use reqwest::Client;
     use serde_json::json;
+    use tailcall::cli::server::reload::Reloader;
     use tailcall::cli::server::Server;
     use tailcall::core::config::reader::ConfigReader;
 
@@ -265,4 +270,108 @@ mod server_spec {
         )
         .await
     }
+
+    #[tokio::test]
+    async fn server_start_http2_mtls() {
+        let configs = &["tests/server/config/server-start-http2-mtls.graphql"];
+        let runtime = crate::test::init(None);
+        let reader = ConfigReader::init(runtime);
+        let config = reader.read_all(configs).await.unwrap();
+        let mut server = Server::new(config);
+        let server_up_receiver = server.server_up_receiver();
+
+        tokio::spawn(async move {
+            server.start().await.unwrap();
+        });
+
+        server_up_receiver
+            .await
+            .expect("Server did not start up correctly");
+
+        let url = "https://localhost:8805/graphql";
+        let query = json!({"query": "{ greet }"});
+        let cert = std::fs::read("tests/server/config/client.crt").unwrap();
+        let key = std::fs::read("tests/server/config/client.key").unwrap();
+        let identity = reqwest::Identity::from_pem(&[cert, key].concat()).unwrap();
+
+        // required since our cert is self signed
+        let client = Client::builder()
+            .use_rustls_tls()
+            .danger_accept_invalid_certs(true)
+            .identity(identity)
+            .build()
+            .unwrap();
+        let response = client.post(url).json(&query).send().await.unwrap();
+        let response_body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(response_body, json!({"data": {"greet": "Hello billing!"}}));
+
+        // the handshake fails without a client certificate
+        let client = Client::builder()
+            .use_rustls_tls()
+            .danger_accept_invalid_certs(true)
+            .build()
+            .unwrap();
+        assert!(client.post(url).json(&query).send().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn server_reload_on_change() {
+        let config = |greeting: &str| {
+            format!(
+                "schema @server(port: 8806) {{ query: Query }}\n\
+                 type Query {{ greet: String @expr(body: \"{}\") }}\n",
+                greeting
+            )
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("server-reload.graphql");
+        let path_str = path.to_string_lossy().to_string();
+        std::fs::write(&path, config("Hello World!")).unwrap();
+
+        let runtime = crate::test::init(None);
+        let reader = ConfigReader::init(runtime.clone());
+        let config_module = reader.read_all(&[path_str.clone()]).await.unwrap();
+        let reloader = Reloader::new(vec![path_str], &reader, runtime, true);
+        let mut server = Server::new(config_module).reloader(reloader);
+        let server_up_receiver = server.server_up_receiver();
+
+        tokio::spawn(async move {
+            server.start().await.unwrap();
+        });
+
+        server_up_receiver
+            .await
+            .expect("Server did not start up correctly");
+
+        let greet = || async {
+            let response = Client::new()
+                .post("http://localhost:8806/graphql")
+                .json(&json!({"query": "{ greet }"}))
+                .send()
+                .await
+                .unwrap();
+            let response_body: serde_json::Value = response.json().await.unwrap();
+            response_body["data"]["greet"].clone()
+        };
+        assert_eq!(greet().await, json!("Hello World!"));
+
+        // the modification time has to change
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        std::fs::write(&path, config("Hello again!")).unwrap();
+        let mut greeting = greet().await;
+        for _ in 0..50 {
+            if greeting != json!("Hello World!") {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            greeting = greet().await;
+        }
+        assert_eq!(greeting, json!("Hello again!"));
+
+        // an invalid config is ignored
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        std::fs::write(&path, "type Query { greet: Unknown }").unwrap();
+        tokio::time::sleep(Duration::from_millis(2500)).await;
+        assert_eq!(greet().await, json!("Hello again!"));
+    }
 }